@@ -0,0 +1,63 @@
+//! Benchmarks for reading a large directory, comparing exa’s own directory
+//! reading against collecting the paths from the standard library’s
+//! `read_dir`.
+//!
+//! As with the sorting benchmarks, the directory gets created in the
+//! `target` directory on the first run, and left there afterwards.
+
+#[macro_use] extern crate criterion;
+extern crate exa;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use criterion::Criterion;
+
+use exa::fs::Dir;
+
+
+/// How many files to put in the directory.
+const FILE_COUNT: usize = 100_000;
+
+
+/// Returns the path to the directory of files to read, creating it if it
+/// isn’t there yet.
+fn fixture() -> PathBuf {
+    let target = Path::new(env!("CARGO_MANIFEST_DIR")).join("target");
+    let path = target.join(format!("exa-bench-read-dir-{}", FILE_COUNT));
+    let done = path.join(".complete");
+    if done.exists() {
+        return path;
+    }
+
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+
+    for number in 0 .. FILE_COUNT {
+        let _ = fs::File::create(path.join(format!("file-{}", number))).unwrap();
+    }
+
+    let _ = fs::File::create(&done).unwrap();
+    path
+}
+
+
+fn exa_read_dir(c: &mut Criterion) {
+    let path = fixture();
+    let _ = c.bench_function("exa read_dir", |b| b.iter(|| {
+        Dir::read_dir(path.clone(), None).unwrap()
+    }));
+}
+
+fn std_read_dir(c: &mut Criterion) {
+    let path = fixture();
+    let _ = c.bench_function("std read_dir", |b| b.iter(|| {
+        fs::read_dir(&path).unwrap()
+                           .map(|e| e.unwrap().path())
+                           .collect::<Vec<PathBuf>>()
+    }));
+}
+
+
+criterion_group!(benches, exa_read_dir, std_read_dir);
+criterion_main!(benches);
@@ -0,0 +1,179 @@
+//! Benchmarks for sorting a large directory by each of the sort fields.
+//!
+//! The files are real ones, as a `File` needs metadata from the filesystem,
+//! so the first run creates a directory of them in the `target` directory.
+//! It gets left there afterwards, so later runs don’t have to create it all
+//! over again, and `cargo clean` removes it along with everything else.
+
+#[macro_use] extern crate criterion;
+extern crate exa;
+extern crate libc;
+
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use criterion::Criterion;
+
+use exa::fs::{Dir, DotFilter, File};
+use exa::fs::filter::{FileFilter, SortField, SortCase, DirsFirst};
+
+
+/// How many files to sort.
+const FILE_COUNT: usize = 100_000;
+
+/// One in this many of the files is a directory.
+const DIRECTORY_EVERY: usize = 10;
+
+/// How many files with numbers in their names to sort naturally.
+const NUMBERED_COUNT: usize = 50_000;
+
+/// The extensions to give files, so sorting by extension has several of
+/// each to compare, and some files with none at all.
+const EXTENSIONS: &[&str] = &[ "rs", "txt", "md", "PNG", "tar.gz", "json", "" ];
+
+
+/// The directory the fixtures go in, which is the crate’s `target`.
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("target")
+}
+
+/// Returns the path to the directory of files to sort, creating it if it
+/// isn’t there yet.
+fn fixture() -> PathBuf {
+    let path = fixtures_dir().join(format!("exa-bench-sort-{}", FILE_COUNT));
+    let done = path.join(".complete");
+    if done.exists() {
+        return path;
+    }
+
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+
+    // A simple linear congruential generator, so the names, sizes, and
+    // dates are shuffled the same way on every run.
+    let mut seed: u64 = 0x2545_F491;
+    let mut next = || { seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1); seed >> 33 };
+
+    for number in 0 .. FILE_COUNT {
+        let random = next();
+        let extension = EXTENSIONS[number % EXTENSIONS.len()];
+        let name = if extension.is_empty() { format!("file-{:08x}-{}", random, number) }
+                                      else { format!("File-{:08x}-{}.{}", random, number, extension) };
+        let file_path = path.join(name);
+
+        if number % DIRECTORY_EVERY == 0 {
+            fs::create_dir(&file_path).unwrap();
+        }
+        else {
+            // Setting the length makes sparse files, so the sizes vary
+            // without filling the disk up.
+            let file = fs::File::create(&file_path).unwrap();
+            file.set_len(random % 1_000_000).unwrap();
+        }
+
+        set_modified_time(&file_path, 1_000_000_000 + (next() % 500_000_000) as libc::time_t);
+    }
+
+    let _ = fs::File::create(&done).unwrap();
+    path
+}
+
+/// Returns the path to a directory of files whose names are mostly
+/// numbers, which is the worst case for natural sorting, creating it if it
+/// isn’t there yet. Some of the numbers have leading zeroes.
+fn numbered_fixture() -> PathBuf {
+    let path = fixtures_dir().join(format!("exa-bench-sort-numbered-{}", NUMBERED_COUNT));
+    let done = path.join(".complete");
+    if done.exists() {
+        return path;
+    }
+
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+
+    let mut seed: u64 = 0x9E37_79B9;
+    let mut next = || { seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1); seed >> 33 };
+
+    for number in 0 .. NUMBERED_COUNT {
+        let name = if number % 3 == 0 { format!("IMG_{:05}-{}.jpg", next() % 100_000, number) }
+                                 else { format!("chapter {} part {}.{}.txt", next() % 1000, number, next() % 100) };
+        let _ = fs::File::create(path.join(name)).unwrap();
+    }
+
+    let _ = fs::File::create(&done).unwrap();
+    path
+}
+
+/// Sets both the accessed and modified times of the file at the given path.
+fn set_modified_time(path: &Path, time: libc::time_t) {
+    let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    let times = libc::utimbuf { actime: time, modtime: time };
+    let result = unsafe { libc::utime(path.as_ptr(), &times) };
+    assert_eq!(result, 0, "Failed to set the modified time of {:?}", path);
+}
+
+/// Benchmarks sorting every file in the fixture directory with the given
+/// filter. The files only get read once: each iteration sorts a fresh
+/// vector of references to them.
+fn bench_sort(c: &mut Criterion, id: &str, filter: FileFilter) {
+    bench_sort_in(c, id, filter, fixture(), FILE_COUNT);
+}
+
+fn bench_sort_in(c: &mut Criterion, id: &str, filter: FileFilter, path: PathBuf, count: usize) {
+    let dir = Dir::read_dir(path, None).unwrap();
+    let files: Vec<File> = dir.files(DotFilter::JustFiles)
+                              .map(|f| f.unwrap())
+                              .collect();
+    assert_eq!(files.len(), count);
+
+    let _ = c.bench_function(id, |b| b.iter(|| {
+        let mut refs: Vec<&File> = files.iter().collect();
+        filter.sort_files(&mut refs);
+        refs
+    }));
+}
+
+fn sort_field(field: SortField) -> FileFilter {
+    FileFilter { sort_field: field, ..FileFilter::default() }
+}
+
+
+fn by_name(c: &mut Criterion) {
+    bench_sort(c, "sort by name", sort_field(SortField::Name(SortCase::Sensitive)));
+}
+
+fn by_name_insensitive(c: &mut Criterion) {
+    bench_sort(c, "sort by Name", sort_field(SortField::Name(SortCase::Insensitive)));
+}
+
+fn by_size(c: &mut Criterion) {
+    bench_sort(c, "sort by size", sort_field(SortField::Size));
+}
+
+fn by_modified(c: &mut Criterion) {
+    bench_sort(c, "sort by modified", sort_field(SortField::ModifiedDate));
+}
+
+fn by_extension(c: &mut Criterion) {
+    bench_sort(c, "sort by extension", sort_field(SortField::Extension(SortCase::Sensitive)));
+}
+
+fn by_type(c: &mut Criterion) {
+    bench_sort(c, "sort by type", sort_field(SortField::FileType));
+}
+
+fn by_name_numbered(c: &mut Criterion) {
+    let filter = sort_field(SortField::Name(SortCase::Sensitive));
+    bench_sort_in(c, "sort numbered names by name", filter, numbered_fixture(), NUMBERED_COUNT);
+}
+
+fn dirs_first(c: &mut Criterion) {
+    let filter = FileFilter { list_dirs_first: Some(DirsFirst::WithLinks), ..FileFilter::default() };
+    bench_sort(c, "sort by name, directories first", filter);
+}
+
+
+criterion_group!(benches, by_name, by_name_insensitive, by_name_numbered, by_size, by_modified, by_extension, by_type, dirs_first);
+criterion_main!(benches);
@@ -5,25 +5,61 @@ complete -c exa -s '?' -l 'help'    -d "Show list of command-line options"
 # Display options
 complete -c exa -s '1' -l 'oneline'      -d "Display one entry per line"
 complete -c exa -s 'l' -l 'long'         -d "Display extended file metadata as a table"
+complete -c exa -l 'tsv'                 -d "Display file metadata separated by tabs"
+complete -c exa -l 'json'                -d "Print a tree as nested JSON"
 complete -c exa -s 'G' -l 'grid'         -d "Display entries in a grid"
 complete -c exa -s 'x' -l 'across'       -d "Sort the grid across, rather than downwards"
+complete -c exa -l 'spacing' -d "Number of spaces between the columns of the grid" -x
 complete -c exa -s 'R' -l 'recurse'      -d "Recurse into directories"
 complete -c exa -s 'T' -l 'tree'         -d "Recurse into directories as a tree"
 complete -c exa -s 'F' -l 'classify'     -d "Display type indicator by file names"
+complete -c exa        -l 'slash-dirs'   -d "Display a slash after directory names only"
 complete -c exa        -l 'color'        -d "When to use terminal colours"
 complete -c exa        -l 'colour'       -d "When to use terminal colours"
 complete -c exa        -l 'color-scale'  -d "Highlight levels of file sizes distinctly"
 complete -c exa        -l 'colour-scale' -d "Highlight levels of file sizes distinctly"
+complete -c exa        -l 'ext-colors'   -d "Style files by extension from a file" -r
+complete -c exa        -l 'ext-colours'  -d "Style files by extension from a file" -r
+complete -c exa        -l 'paginate'     -d "Send long output through a pager"
+complete -c exa        -l 'highlight-recent' -d "Show names in bold if they changed in the last day"
+complete -c exa        -l 'color-by-access'  -d "Colour names by whether you can read and write them"
+complete -c exa        -l 'colour-by-access' -d "Colour names by whether you can read and write them"
+complete -c exa        -l 'summary'      -d "Count how many of each kind of file were listed"
+complete -c exa        -l 'summary-recurse' -d "Count the files inside directories for --summary too"
+complete -c exa        -l 'alphabet-dividers' -d "Divide names by first letter when sorting by name"
+complete -c exa        -l 'link-target'  -d "How to show symlink targets" -x -a "
+    raw\t'As they were written'
+    absolute\t'As absolute paths'
+    relative\t'Relative to the directory being listed'
+"
+complete -c exa        -l 'icons'        -d "Show icons before file names" -x -a "
+    all\t'Before every file'
+    files\t'Before files, but not directories'
+    none\t'Before nothing'
+"
 
 # Filtering and sorting options
 complete -c exa -l 'group-directories-first' -d "Sort directories before other files"
+complete -c exa -l 'dereference-command-line' -d "Follow symlinks given as arguments"
+complete -c exa -l 'glob-args' -d "Expand arguments that are glob patterns"
 complete -c exa -s 'a' -l 'all'       -d "Show and 'dot' files"
+complete -c exa -l 'almost-all'       -d "Show dot files, but not . and .."
+complete -c exa -l 'all-all'          -d "Show dot files, and . and .. too"
+complete -c exa -l 'all-level' -x     -d "How many times to apply --all" -a "0 1 2"
 complete -c exa -s 'd' -l 'list-dirs' -d "List directories like regular files"
 complete -c exa -s 'L' -l 'level'     -d "Limit the depth of recursion" -a "1 2 3 4 5 6 7 8 9"
+complete -c exa -l 'recurse-order' -x -d "Order to list directories in when recursing" -a "
+    dfs\t'Depth-first, like ls'
+    bfs\t'Breadth-first'
+"
 complete -c exa -s 'r' -l 'reverse'   -d "Reverse the sort order"
 complete -c exa -s 's' -l 'sort'   -x -d "Which field to sort by" -a "
     accessed\t'Sort by file accessed time'
+    atime\t'Sort by file accessed time'
+    ctime\t'Sort by file changed time'
     created\t'Sort by file modified time'
+    birth\t'Sort by file created time'
+    crtime\t'Sort by file created time'
     ext\t'Sort by file extension'
     Ext\t'Sort by file extension (case-insensitive)'
     extension\t'Sort by file extension'
@@ -32,24 +68,69 @@ complete -c exa -s 's' -l 'sort'   -x -d "Which field to sort by" -a "
     Filename\t'Sort by filename (case-insensitive)'
     inode\t'Sort by file inode'
     modified\t'Sort by file modified time'
+    mtime\t'Sort by file modified time'
     name\t'Sort by filename'
     Name\t'Sort by filename (case-insensitive)'
     none\t'Do not sort files at all'
+    entries\t'Sort by number of entries, recursively'
+    items\t'Sort by number of entries directly inside directories'
+    mixed\t'Sort numbers descending, then names ascending'
+    git\t'Sort by Git status, with conflicted and modified files first'
+    segments\t'Sort by number of path components'
     size\t'Sort by file size'
     type\t'Sort by file type'
 "
+complete -c exa -l 'sort-exec' -d "Sort by what a command prints for each file" -x -a "(__fish_complete_command)"
+complete -c exa -l 'sort-cmd' -d "Reorder files by the names a command prints back" -x -a "(__fish_complete_command)"
+complete -c exa -l 'locale-numbers' -d "Read numbers in names with the locale's thousands separator when sorting"
 
 complete -c exa -s 'I' -l 'ignore-glob' -d "Ignore files that match these glob patterns" -r
+complete -c exa -l 'ignore-file' -d "Ignore files that match the glob patterns in this file" -r
+complete -c exa -l 'ignore-glob-below' -d "Only ignore files deeper than this depth" -x
+complete -c exa -l 'grep' -d "Only show files whose names match this regex" -x
+complete -c exa -l 'ignore-file-like-git' -d "Ignore files mentioned in .gitignore and .ignore files"
+complete -c exa -l 'no-ignore' -d "Don't ignore any files"
+complete -c exa -l 'links-only' -d "Only show symbolic links"
+complete -c exa -l 'executable' -d "Only show files with an execute bit set"
+complete -c exa -l 'filter-mode' -x -d "How filters combine" -a "
+    and\t'Files have to pass every filter'
+    or\t'Files only have to pass one filter'
+"
+complete -c exa -l 'top' -d "Only show the first N files in each directory, once sorted" -x
+complete -c exa -l 'no-root' -d "Hide files owned by root"
+complete -c exa -l 'owner' -d "Only show files owned by this user" -x -a "(__fish_complete_users)"
+complete -c exa -l 'owner-group' -d "Only show files owned by this group" -x -a "(__fish_complete_groups)"
+complete -c exa -l 'print-sort' -d "Print the sort field being used"
+complete -c exa -l 'print-width' -d "Print the terminal width and grid layout"
+complete -c exa -l 'timings' -d "Print how long each stage of listing took"
+complete -c exa -l 'config' -d "Read sort and filter defaults from a file" -r
 
 # Long view options
 complete -c exa -s 'b' -l 'binary'   -d "List file sizes with binary prefixes"
 complete -c exa -s 'B' -l 'bytes'    -d "List file sizes in bytes, without any prefixes"
 complete -c exa -s 'g' -l 'group'    -d "List each file's group"
+complete -c exa -l 'collapse-owner'     -d "Leave the group blank when it's named after the user"
 complete -c exa -s 'h' -l 'header'   -d "Add a header row to each column"
-complete -c exa -s 'h' -l 'links'    -d "List each file's number of hard links"
+complete -c exa -s 'h' -l 'links'    -d "List each file's number of hard links" -x -a "
+    always\t'For every file'
+    multiple\t'Only when there is more than one'
+"
+complete -c exa        -l 'blank-symlink-meta' -d "Leave the sizes and timestamps of symlinks blank"
 complete -c exa -s 'g' -l 'group'    -d "List each file's inode number"
 complete -c exa -s 'm' -l 'modified' -d "Use the modified timestamp field"
 complete -c exa -s 'S' -l 'blocks'   -d "List each file's number of filesystem blocks"
+complete -c exa        -l 'show-sparse' -d "Show whether each file is sparse"
+complete -c exa        -l 'show-time-anomalies' -d "Mark files modified before they were created"
+complete -c exa        -l 'flags'      -d "List each file's BSD flags"
+complete -c exa        -l 'entries'     -d "Count the entries inside each directory, recursively"
+complete -c exa        -l 'dir-entries' -d "Show how many entries each directory has in place of its size"
+complete -c exa        -l 'content-type' -d "Show whether each file looks like text or binary"
+complete -c exa        -l 'wc'           -d "List the number of lines in each text file"
+complete -c exa        -l 'symlink-groups' -d "Number symlinks by the file they lead to"
+complete -c exa        -l 'age-column'   -d "List how old each file is"
+complete -c exa        -l 'context'      -d "List the directory each file is in"
+complete -c exa        -l 'size-percent' -d "List each file's share of the total size"
+complete -c exa        -l 'age-thresholds' -d "The durations that end each age bucket" -x
 complete -c exa -s 't' -l 'time'  -x -d "Which timestamp field to list" -a "
     accessed\t'Display accessed time'
     created\t'Display created time'
@@ -57,6 +138,10 @@ complete -c exa -s 't' -l 'time'  -x -d "Which timestamp field to list" -a "
 "
 complete -c exa -s 'u' -l 'accessed'      -d "Use the accessed timestamp field"
 complete -c exa -s 'U' -l 'created'       -d "Use the created timestamp field"
+complete -c exa        -l 'align'      -x -d "Override the alignments of columns"
+complete -c exa        -l 'columns'    -x -d "Which columns to show, in order"
+complete -c exa        -l 'compact'       -d "Only show permissions and sizes"
+complete -c exa        -l 'compact-long'  -d "Drop columns and shorten dates until the table fits"
 complete -c exa        -l 'time-style' -x -d "How to format timestamps" -a "
     default\t'Use the default time style'
     iso\t'Display brief ISO timestamps'
@@ -66,4 +151,16 @@ complete -c exa        -l 'time-style' -x -d "How to format timestamps" -a "
 
 # Optional extras
 complete -c exa -s 'g' -l 'git'      -d "List each file's Git status, if tracked"
+complete -c exa -l 'changed-only'    -d "Only show files changed since the last Git commit"
+complete -c exa -l 'git-since'      -x -d "List each file's Git status compared to a ref"
+complete -c exa -l 'git-since-only' -x -d "Only show files changed since a ref"
+complete -c exa -l 'git-last-commit'    -d "List the commit that last changed each file"
+complete -c exa -l 'git-only-dirty'     -d "Only show files with staged or unstaged changes"
+complete -c exa -l 'tracked-only'       -d "Only show files tracked by Git"
+complete -c exa -l 'untracked-only'     -d "Only show files not tracked by Git"
+complete -c exa -l 'git-dim-ignored'    -d "Dim the names of files ignored by Git"
+complete -c exa -l 'git-remote'         -d "List the URL of each repository's origin remote"
+complete -c exa -l 'git-remote-name'    -d "List the URL of this remote instead" -x
+complete -c exa -l 'git-recurse-submodules' -d "Show the statuses of the files inside submodules"
 complete -c exa -s '@' -l 'extended' -d "List each file's extended attributes and sizes"
+complete -c exa        -l 'acl'      -d "List each file's access control list entries"
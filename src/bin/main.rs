@@ -1,5 +1,5 @@
 extern crate exa;
-use exa::Exa;
+use exa::{Exa, PagedOutput};
 
 use std::ffi::OsString;
 use std::env::args_os;
@@ -9,9 +9,20 @@ use std::process::exit;
 
 fn main() {
     let args: Vec<OsString> = args_os().skip(1).collect();
-    match Exa::new(args.iter(), &mut stdout()) {
+    let mut output = PagedOutput::stdout();
+
+    match Exa::new(args.iter(), &mut output) {
         Ok(mut exa) => {
-            match exa.run() {
+            if let Some(pager) = exa.options.pager.take() {
+                exa.writer.page_with(pager);
+            }
+
+            // Whatever got rendered before an error still gets sent to the
+            // pager, with the error reported after it.
+            let result = exa.run();
+            let finished = exa.writer.finish();
+
+            match result.and_then(|status| finished.map(|_| status)) {
                 Ok(exit_status) => exit(exit_status),
                 Err(e) => {
                     match e.kind() {
@@ -1,3 +1,10 @@
+//! exa is a replacement for `ls`, and this is its library crate.
+//!
+//! The `exa` binary is a thin wrapper around the `Exa` type here, which
+//! parses the command-line options and prints the listing. The code that
+//! reads, filters, and sorts files lives in the public `fs` module, so other
+//! programs can list directories the same way exa does.
+
 #![warn(trivial_casts, trivial_numeric_casts)]
 #![warn(unused_results)]
 
@@ -9,35 +16,50 @@ extern crate locale;
 extern crate natord;
 extern crate num_cpus;
 extern crate number_prefix;
+extern crate regex;
 extern crate scoped_threadpool;
 extern crate term_grid;
 extern crate unicode_width;
 extern crate users;
 extern crate zoneinfo_compiled;
 extern crate term_size;
+extern crate toml;
 
 #[cfg(feature="git")] extern crate git2;
 
 #[macro_use]
 extern crate lazy_static;
 
+#[cfg(test)] extern crate json as json_parser;
+
 
 use std::env::var_os;
 use std::ffi::{OsStr, OsString};
-use std::io::{stderr, Write, Result as IOResult};
-use std::path::{Component, PathBuf};
+use std::cmp::max;
+use std::io::{stderr, Write, Error as IOError, ErrorKind, Result as IOResult};
+use std::path::{Component, Path, PathBuf};
 
 use ansi_term::{ANSIStrings, Style};
 
 use fs::{Dir, File};
+use fs::dir_action::RecurseOrder;
+use fs::fields::RepoState;
+use fs::filter::{SortField, TrackedFilter};
 use options::{Options, Vars};
 pub use options::Misfire;
-use output::{escape, lines, grid, grid_details, details, View, Mode};
+pub use output::pager::PagedOutput;
+use output::{escape, lines, grid, grid_details, details, tsv, json, View, Mode};
+use output::summary::{self, Summary, SummaryScope};
+use timings::Stage;
 
-mod fs;
+pub mod fs;
 mod info;
 mod options;
 mod output;
+mod timings;
+
+#[cfg(test)]
+mod scratch;
 
 
 /// The main program wrapper.
@@ -75,26 +97,127 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
     }
 
     pub fn run(&mut self) -> IOResult<i32> {
-        let mut files = Vec::new();
-        let mut dirs = Vec::new();
         let mut exit_status = 0;
 
+        if self.options.timings {
+            timings::enable();
+        }
+
+        if self.options.print_sort {
+            writeln!(stderr(), "{}", self.options.filter.sort_description())?;
+        }
+
         // List the current directory by default, like ls.
         if self.args.is_empty() {
             self.args = vec![ OsStr::new(".") ];
         }
 
-        for file_path in &self.args {
-            match File::new(PathBuf::from(file_path), None, None) {
+        // The arguments that couldn’t be listed get complained about as
+        // they’re found, and kept for the views that report them too.
+        let mut failed = Vec::new();
+
+        let paths = if self.options.glob_args { self.expand_globs(&mut exit_status, &mut failed)? }
+                                         else { self.args.iter().map(PathBuf::from).collect() };
+
+        let (mut files, dirs) = self.classify_arguments(&paths, &mut exit_status, &mut failed)?;
+
+        // Like ls, the files given as arguments get listed first, together,
+        // then each directory gets its own section. The sections only get
+        // headers when more than one path was given, so listing a single
+        // directory shows nothing but its files.
+        self.options.filter.filter_argument_files(&mut files);
+        self.options.filter.sort_files(&mut files);
+
+        let no_files = files.is_empty();
+        let is_only_dir = paths.len() == 1;
+
+        if !self.print_files(None, files, failed, 0)? {
+            exit_status = max(exit_status, 1);
+        }
+
+        let dirs = dirs.into_iter().map(Ok).collect();
+        let exit_status = self.print_dirs(dirs, 0, no_files, is_only_dir, exit_status)?;
+
+        for pattern in self.unmatched_ignore_patterns() {
+            writeln!(stderr(), "{:?}: no files match this ignore pattern", pattern)?;
+        }
+
+        if self.options.timings {
+            writeln!(stderr(), "{}", timings::report())?;
+        }
+
+        Ok(exit_status)
+    }
+
+    /// The `--ignore-glob` patterns that didn’t match a single file in any
+    /// of the directories that got listed, and so are probably typos. These
+    /// only get complained about in strict mode, as listing a directory
+    /// that just happens not to have any of the files is fine otherwise.
+    fn unmatched_ignore_patterns(&self) -> Vec<&str> {
+        if self.options.strict { self.options.filter.ignore_patterns.unmatched() }
+                          else { Vec::new() }
+    }
+
+    /// Sorts the paths given as arguments into the files to list together
+    /// and the directories to list one at a time, after following any
+    /// symlinks that should be followed. Directories get listed as files
+    /// when the view lists them that way. Paths that can’t be read get
+    /// complained about, added to the failed ones, and make exa exit with a
+    /// status of 2.
+    fn classify_arguments(&self, paths: &[PathBuf], exit_status: &mut i32, failed: &mut Vec<(PathBuf, IOError)>) -> IOResult<(Vec<File<'static>>, Vec<Dir>)> {
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+
+        for file_path in paths {
+            let file = File::new(file_path.clone(), None, None);
+
+            // Links to directories get listed as directories when they’re
+            // dereferenced, but a broken link is only an error for that one
+            // argument, like a missing file.
+            let file = if self.options.dereference_args { file.and_then(File::dereference) }
+                                                     else { file };
+
+            match file {
                 Err(e) => {
-                    exit_status = 2;
+                    *exit_status = 2;
                     writeln!(stderr(), "{:?}: {}", file_path, e)?;
+                    failed.push((file_path.clone(), e));
                 },
                 Ok(f) => {
                     if f.is_directory() && !self.options.dir_action.treat_dirs_as_files() {
-                        match f.to_dir(self.options.should_scan_for_git()) {
-                            Ok(d) => dirs.push(d),
-                            Err(e) => writeln!(stderr(), "{:?}: {}", file_path, e)?,
+                        match self.options.filter.read_dir(&f, self.options.git_scan()) {
+                            Ok(d) => {
+                                if self.options.filter.changed_only && !d.has_git_repo() {
+                                    writeln!(stderr(), "{:?}: not in a Git repository, so --changed-only is showing every file", file_path)?;
+                                }
+
+                                if self.options.filter.tracked_filter != TrackedFilter::Any && !d.has_git_repo() {
+                                    writeln!(stderr(), "{:?}: not in a Git repository, so every file is being shown, tracked or not", file_path)?;
+                                }
+
+                                if self.options.filter.dirty_only && !d.has_git_repo() {
+                                    if self.options.strict {
+                                        *exit_status = 2;
+                                        writeln!(stderr(), "{:?}: not in a Git repository, so --git-only-dirty has nothing to show", file_path)?;
+                                        continue;
+                                    }
+
+                                    writeln!(stderr(), "{:?}: not in a Git repository, so --git-only-dirty is showing no files", file_path)?;
+                                }
+
+                                if let Some(ref reference) = self.options.git.since {
+                                    if self.options.should_scan_for_git() && d.has_git_repo() && !d.has_git_since() {
+                                        writeln!(stderr(), "{:?}: could not compare against Git reference {:?}", file_path, reference)?;
+                                    }
+                                }
+
+                                dirs.push(d);
+                            },
+                            Err(e) => {
+                                *exit_status = 2;
+                                writeln!(stderr(), "{:?}: {}", file_path, e)?;
+                                failed.push((file_path.clone(), e));
+                            },
                         }
                     }
                     else {
@@ -104,20 +227,66 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
             }
         }
 
-        // We want to print a directory’s name before we list it, *except* in
-        // the case where it’s the only directory, *except* if there are any
-        // files to print as well. (It’s a double negative)
 
-        let no_files = files.is_empty();
-        let is_only_dir = dirs.len() == 1 && no_files;
+        Ok((files, dirs))
+    }
 
-        self.options.filter.filter_argument_files(&mut files);
-        self.print_files(None, files)?;
+    /// Expands any arguments that are glob patterns, rather than the paths
+    /// of files that exist, into the paths of the files they match, for
+    /// when the shell hasn’t done it already. As in the shell, patterns
+    /// have to start with a dot to match dotfiles. A pattern that matches
+    /// nothing is an error, like a missing file, and gets added to the
+    /// failed arguments.
+    fn expand_globs(&self, exit_status: &mut i32, failed: &mut Vec<(PathBuf, IOError)>) -> IOResult<Vec<PathBuf>> {
+        let options = glob::MatchOptions {
+            case_sensitive:              true,
+            require_literal_separator:   true,
+            require_literal_leading_dot: true,
+        };
+
+        let mut paths = Vec::new();
+        for arg in &self.args {
+            let pattern = match arg.to_str() {
+                Some(p) if is_glob_pattern(p) && Path::new(p).symlink_metadata().is_err() => p,
+                _ => { paths.push(PathBuf::from(arg)); continue },
+            };
+
+            let matches: Vec<PathBuf> = match glob::glob_with(pattern, &options) {
+                Ok(m)   => m.filter_map(Result::ok).collect(),
+                Err(e)  => {
+                    let message = format!("invalid glob pattern: {}", e);
+                    *exit_status = 2;
+                    writeln!(stderr(), "{:?}: {}", pattern, message)?;
+                    failed.push((PathBuf::from(pattern), IOError::new(ErrorKind::InvalidInput, message)));
+                    continue;
+                },
+            };
 
-        self.print_dirs(dirs, no_files, is_only_dir, exit_status)
+            if matches.is_empty() {
+                *exit_status = 2;
+                writeln!(stderr(), "{:?}: no files match this pattern", pattern)?;
+                failed.push((PathBuf::from(pattern), IOError::new(ErrorKind::NotFound, "no files match this pattern")));
+            }
+
+            paths.extend(matches);
+        }
+
+        Ok(paths)
     }
 
-    fn print_dirs(&mut self, dir_files: Vec<Dir>, mut first: bool, is_only_dir: bool, exit_status: i32) -> IOResult<i32> {
+    /// Lists each directory in its own section. Directories found while
+    /// recursing that couldn’t be read still get a section, holding a
+    /// marker instead of their files, and make exa exit with a status of 1
+    /// after listing everything else.
+    ///
+    /// The depth is how far down from the directories given as arguments
+    /// these ones are, which decides whether the ignore patterns apply.
+    fn print_dirs(&mut self, dir_files: Vec<Result<Dir, (PathBuf, IOError)>>, depth: usize, mut first: bool, is_only_dir: bool, mut exit_status: i32) -> IOResult<i32> {
+
+        // When recursing breadth-first, the directories found inside these
+        // ones get saved up until they’ve all been listed.
+        let mut next_level = Vec::new();
+
         for dir in dir_files {
 
             // Put a gap between directories, or between the list of files and
@@ -129,11 +298,19 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
                 write!(self.writer, "\n")?;
             }
 
-            if !is_only_dir {
-                let mut bits = Vec::new();
-                escape(dir.path.display().to_string(), &mut bits, Style::default(), Style::default());
-                writeln!(self.writer, "{}:", ANSIStrings(&bits))?;
-            }
+            let dir = match dir {
+                Ok(d)  => d,
+                Err((path, e)) => {
+                    if !is_only_dir {
+                        self.print_dir_header(&path, None, None)?;
+                    }
+
+                    exit_status = max(exit_status, 1);
+                    writeln!(self.writer, "{}", self.options.view.colours.broken_arrow.paint(details::unreadable_marker(&e)))?;
+                    writeln!(stderr(), "{}: {}", path.display(), e)?;
+                    continue;
+                },
+            };
 
             let mut children = Vec::new();
             for file in dir.files(self.options.filter.dot_filter) {
@@ -143,52 +320,518 @@ impl<'args, 'w, W: Write + 'w> Exa<'args, 'w, W> {
                 }
             };
 
-            self.options.filter.filter_child_files(&mut children);
+            self.options.filter.filter_child_files(&mut children, depth);
             self.options.filter.sort_files(&mut children);
+            self.options.filter.keep_top(&mut children);
+
+            if !is_only_dir {
+
+                // A repository in the middle of a merge or rebase explains
+                // why its files are conflicted, so the directories given as
+                // arguments say so. Any under them would only repeat it.
+                let operation = if depth == 0 { dir.git_operation() } else { None };
+                let totals = if self.options.view.header_totals { Some(summary::totals(&children)) } else { None };
+                self.print_dir_header(&dir.path, operation, totals)?;
+            }
 
             if let Some(recurse_opts) = self.options.dir_action.recurse_options() {
-                let depth = dir.path.components().filter(|&c| c != Component::CurDir).count() + 1;
-                if !recurse_opts.tree && !recurse_opts.is_too_deep(depth) {
-
-                    let mut child_dirs = Vec::new();
-                    for child_dir in children.iter().filter(|f| f.is_directory()) {
-                        match child_dir.to_dir(false) {
-                            Ok(d)  => child_dirs.push(d),
-                            Err(e) => writeln!(stderr(), "{}: {}", child_dir.path.display(), e)?,
-                        }
+                let level = dir.path.components().filter(|&c| c != Component::CurDir).count() + 1;
+                if !recurse_opts.tree && !recurse_opts.is_too_deep(level) {
+
+                    let child_dirs: Vec<_> = children.iter().filter(|f| f.is_directory())
+                                                     .map(|f| self.options.filter.read_dir(f, self.options.child_git_scan()).map_err(|e| (f.path.clone(), e)))
+                                                     .collect();
+
+                    if !self.print_files(Some(&dir), children, Vec::new(), depth + 1)? {
+                        exit_status = max(exit_status, 1);
                     }
 
-                    self.print_files(Some(&dir), children)?;
-                    match self.print_dirs(child_dirs, false, false, exit_status) {
-                        Ok(_) => (),
-                        Err(e) => return Err(e),
+                    match recurse_opts.order {
+                        RecurseOrder::DepthFirst   => exit_status = self.print_dirs(child_dirs, depth + 1, false, false, exit_status)?,
+                        RecurseOrder::BreadthFirst => next_level.extend(child_dirs),
                     }
                     continue;
                 }
             }
 
-            self.print_files(Some(&dir), children)?;
+            if !self.print_files(Some(&dir), children, Vec::new(), depth + 1)? {
+                exit_status = max(exit_status, 1);
+            }
+        }
+
+        if !next_level.is_empty() {
+            return self.print_dirs(next_level, depth + 1, false, false, exit_status);
         }
 
         Ok(exit_status)
     }
 
+    /// Prints the path above a directory’s files. The path gets painted like
+    /// a directory’s name, so the start of each section stands out when
+    /// scrolling through a long listing.
+    fn print_dir_header(&mut self, path: &Path, operation: Option<RepoState>, totals: Option<String>) -> IOResult<()> {
+        let colours = &self.options.view.colours;
+        let mut bits = Vec::new();
+        escape(path.display().to_string(), &mut bits, colours.filetypes.directory, colours.control_char);
+        bits.push(Style::default().paint(":"));
+
+        if let Some(state) = operation {
+            bits.push(Style::default().paint(" "));
+            bits.push(colours.punctuation.paint(format!("({})", state.description())));
+        }
+
+        if let Some(totals) = totals {
+            bits.push(Style::default().paint(" "));
+            bits.push(colours.punctuation.paint(totals));
+        }
+
+        writeln!(self.writer, "{}", ANSIStrings(&bits))
+    }
+
     /// Prints the list of files using whichever view is selected.
     /// For various annoying logistical reasons, each one handles
     /// printing differently...
-    fn print_files(&mut self, dir: Option<&Dir>, files: Vec<File>) -> IOResult<()> {
-        if !files.is_empty() {
-            let View { ref mode, ref colours, ref style } = self.options.view;
-
-            match *mode {
-                Mode::Lines                  => lines::Render { files, colours, style }.render(self.writer),
-                Mode::Grid(ref opts)         => grid::Render { files, colours, style, opts }.render(self.writer),
-                Mode::Details(ref opts)      => details::Render { dir, files, colours, style, opts, filter: &self.options.filter, recurse: self.options.dir_action.recurse_options() }.render(self.writer),
-                Mode::GridDetails(ref grid, ref details) => grid_details::Render { dir, files, colours, style, grid, details, filter: &self.options.filter }.render(self.writer),
+    ///
+    /// The depth is how many directories down from the arguments the files
+    /// are, counting the arguments’ own directories, so it’s 0 for the
+    /// arguments themselves.
+    ///
+    /// The failed arguments are the ones that couldn’t be listed, which the
+    /// JSON view reports in its document, so it gets printed even when none
+    /// of the arguments could be.
+    ///
+    /// Returns whether every directory in a tree view could be read.
+    fn print_files(&mut self, dir: Option<&Dir>, files: Vec<File>, failed: Vec<(PathBuf, IOError)>, depth: usize) -> IOResult<bool> {
+        let is_json = match self.options.view.mode { Mode::Json => true, _ => false };
+
+        if !files.is_empty() || (is_json && !failed.is_empty()) {
+            let View { ref mode, ref colours, ref style, summary, alphabet_dividers, .. } = self.options.view;
+
+            // The files get moved into the view, so they have to be
+            // counted before it gets rendered.
+            let summary = match summary {
+                Some(SummaryScope::Listed)     => Some(Summary::of_files(&files)),
+                Some(SummaryScope::Recursive)  => Some(Summary::of_files_recursively(&files, &self.options.filter, self.options.child_git_scan(), depth)),
+                None                           => None,
+            };
+
+            // Dividers only make sense when the files are sorted by name.
+            let dividers = match self.options.filter.sort_field {
+                SortField::Name(case) if alphabet_dividers && self.options.filter.sort_exec.is_none()  => Some(case),
+                _                                           => None,
+            };
+
+            // The closure can’t borrow all of `self` while the view is
+            // borrowed, so it gets given the parts it needs.
+            let writer = &mut *self.writer;
+            let filter = &self.options.filter;
+            let recurse = self.options.dir_action.recurse_options();
+            let git = self.options.child_git_scan();
+
+            // Only the tree and JSON views read directories while rendering.
+            let unreadable = timings::time(Stage::Render, || -> IOResult<_> { Ok(match *mode {
+                Mode::Lines                  => { lines::Render { files, colours, style, dividers }.render(writer)?; Vec::new() },
+                Mode::Grid(ref opts)         => { grid::Render { files, colours, style, opts }.render(writer)?; Vec::new() },
+                Mode::Details(ref opts)      => details::Render { dir, files, colours, style, opts, filter, recurse, git, dividers, depth }.render(writer)?,
+                Mode::GridDetails(ref grid, ref details) => { grid_details::Render { dir, files, colours, style, grid, details, filter, depth }.render(writer)?; Vec::new() },
+                Mode::Tsv(ref opts)          => { tsv::Render { dir, files, opts, depth }.render(writer)?; Vec::new() },
+                Mode::Json                   => json::Render { files, failed, filter, recurse, git }.render(writer)?,
+            })})?;
+
+            if let Some(summary) = summary {
+                writeln!(writer, "{}", ANSIStrings(&summary.render(colours)))?;
             }
+
+            for &(ref path, ref e) in &unreadable {
+                writeln!(stderr(), "{}: {}", path.display(), e)?;
+            }
+
+            Ok(unreadable.is_empty())
         }
         else {
-            Ok(())
+            Ok(true)
+        }
+    }
+}
+
+
+/// Whether this argument has any of the characters that make it a glob
+/// pattern in it.
+fn is_glob_pattern(arg: &str) -> bool {
+    arg.contains(|c| c == '*' || c == '?' || c == '[')
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs as std_fs;
+    use output::Colours;
+    use scratch::ScratchDir;
+
+    /// Makes a directory with the given subdirectories and files in it,
+    /// lists it with the given arguments, and returns what got printed,
+    /// with the directory’s path replaced by `DIR`, and its name on its own
+    /// replaced by `NAME`.
+    fn run(name: &str, dirs: &[&str], files: &[&str], flags: &[&str]) -> String {
+        let scratch = ScratchDir::new(&format!("run-{}", name));
+        let path = &scratch.path;
+
+        for dir in dirs {
+            std_fs::create_dir_all(path.join(dir)).unwrap();
+        }
+
+        for file in files {
+            let _ = std_fs::File::create(path.join(file)).unwrap();
+        }
+
+        let mut args: Vec<&str> = flags.to_vec();
+        let dir = path.display().to_string();
+        args.push(&dir);
+
+        let (output, _) = list(&args);
+        let name = path.file_name().unwrap().to_string_lossy();
+        output.replace(&dir, "DIR").replace(&*name, "NAME")
+    }
+
+    /// Runs exa with the given arguments, returning what got printed and
+    /// the exit status.
+    fn list(args: &[&str]) -> (String, i32) {
+        let args: Vec<OsString> = args.iter().map(OsString::from).collect();
+        let vars: Option<OsString> = None;
+        let (options, frees) = Options::parse(&args, vars).unwrap();
+
+        let mut output = Vec::new();
+        let status = Exa { options, writer: &mut output, args: frees }.run().unwrap();
+        (String::from_utf8(output).unwrap(), status)
+    }
+
+    /// Lists two directories, one with a log file in it and the other
+    /// without, with some ignore patterns, returning the ones that were
+    /// reported as not matching anything.
+    fn unmatched(name: &str, strict: bool) -> Vec<String> {
+        let scratch = ScratchDir::new(&format!("unmatched-{}", name));
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("logs")).unwrap();
+        std_fs::create_dir_all(path.join("src")).unwrap();
+        let _ = std_fs::File::create(path.join("logs/debug.log")).unwrap();
+        let _ = std_fs::File::create(path.join("src/main.rs")).unwrap();
+
+        let args: Vec<OsString> = vec![ "-I*.log|*.lgo".into(), path.join("logs").into(), path.join("src").into() ];
+        let vars: Option<OsString> = None;
+        let (mut options, frees) = Options::parse(&args, vars).unwrap();
+        options.strict = strict;
+
+        let mut output = Vec::new();
+        let mut exa = Exa { options, writer: &mut output, args: frees };
+        let _ = exa.run().unwrap();
+        exa.unmatched_ignore_patterns().into_iter().map(String::from).collect()
+    }
+
+    #[test]
+    fn unmatched_ignore_patterns() {
+        assert_eq!(unmatched("strict", true), vec![ "*.lgo".to_string() ]);
+    }
+
+    #[test]
+    fn unmatched_ignore_patterns_are_quiet() {
+        assert_eq!(unmatched("quiet", false), Vec::<String>::new());
+    }
+
+    /// Lists a directory with one subdirectory in it, recursing into it.
+    fn recurse(name: &str, colour: &str) -> String {
+        run(name, &[ "sub" ], &[ "top", "sub/inner" ], &[ "--long", "--header", "--recurse", "--columns=size,name", colour ])
+    }
+
+    #[test]
+    fn header_in_every_section() {
+        assert_eq!(recurse("plain", "--colour=never"),
+                   "Size Name\n   - sub\n   0 top\n\nDIR/sub:\nSize Name\n   0 inner\n");
+    }
+
+    #[test]
+    fn styled_paths() {
+        let output = recurse("styled", "--colour=always");
+        assert!(output.contains(&format!("\n{}:\n", Colours::colourful(false).filetypes.directory.paint("DIR/sub"))));
+    }
+
+    /// The headers of the sections printed while recursing through three
+    /// levels of directories in the given order.
+    fn sections(order: &str) -> Vec<String> {
+        let dirs = [ "a/b/c", "a/d", "e/f" ];
+        let output = run(&format!("sections-{}", order), &dirs, &[], &[ "--oneline", "--recurse", "--colour=never", order ]);
+        output.lines().filter(|l| l.ends_with(':')).map(String::from).collect()
+    }
+
+    #[test]
+    fn depth_first() {
+        assert_eq!(sections("--recurse-order=dfs"),
+                   vec![ "DIR/a:", "DIR/a/b:", "DIR/a/b/c:", "DIR/a/d:", "DIR/e:", "DIR/e/f:" ]);
+    }
+
+    #[test]
+    fn breadth_first() {
+        assert_eq!(sections("--recurse-order=bfs"),
+                   vec![ "DIR/a:", "DIR/e:", "DIR/a/b:", "DIR/a/d:", "DIR/e/f:", "DIR/a/b/c:" ]);
+    }
+
+    #[test]
+    fn breadth_first_reversed() {
+        let dirs = [ "a/b", "a/c", "d/e" ];
+        let output = run("sections-reversed", &dirs, &[], &[ "--oneline", "--recurse", "--reverse", "--colour=never", "--recurse-order=bfs" ]);
+        let sections: Vec<&str> = output.lines().filter(|l| l.ends_with(':')).collect();
+        assert_eq!(sections, vec![ "DIR/d:", "DIR/a:", "DIR/d/e:", "DIR/a/c:", "DIR/a/b:" ]);
+    }
+
+    #[test]
+    fn top_ten() {
+        let files: Vec<String> = (0 .. 25).map(|i| format!("file{:02}", i)).collect();
+        let files: Vec<&str> = files.iter().map(|f| &f[..]).collect();
+        let output = run("top-ten", &[], &files, &[ "--oneline", "--reverse", "--top=10" ]);
+        assert_eq!(output.lines().count(), 10);
+        assert_eq!(output.lines().next(), Some("file24"));
+        assert_eq!(output.lines().last(), Some("file15"));
+    }
+
+    #[test]
+    fn top_in_each_directory_with_summary() {
+        let output = run("top-summary", &[ "a", "b" ], &[ "a/1", "a/2", "a/3", "b/1" ], &[ "--oneline", "--recurse", "--top=1", "--summary", "--colour=never" ]);
+        assert_eq!(output, "a\n1 dir\n\nDIR/a:\n1\n1 other\n");
+    }
+
+    #[test]
+    fn header_totals() {
+        let scratch = ScratchDir::new("run-header-totals");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("src/nested")).unwrap();
+        std_fs::create_dir_all(path.join("docs")).unwrap();
+        std_fs::File::create(path.join("src/main.rs")).unwrap().set_len(1536).unwrap();
+        std_fs::File::create(path.join("docs/README")).unwrap().set_len(100).unwrap();
+
+        let src = path.join("src").display().to_string();
+        let docs = path.join("docs").display().to_string();
+        let (with, _) = list(&[ "--oneline", "--colour=never", "--header", &src, &docs ]);
+        let (without, _) = list(&[ "--oneline", "--colour=never", &src, &docs ]);
+
+        let dir = path.display().to_string();
+        assert_eq!(with.replace(&dir, "DIR"), "DIR/src: 2 items, 1.5 KiB\nmain.rs\nnested\n\nDIR/docs: 1 item, 100 bytes\nREADME\n");
+        assert_eq!(without.replace(&dir, "DIR"), "DIR/src:\nmain.rs\nnested\n\nDIR/docs:\nREADME\n");
+    }
+
+    /// Lists two files and two directories, given in a jumbled-up order,
+    /// with the given arguments.
+    fn mixed(name: &str, flags: &[&str]) -> String {
+        let scratch = ScratchDir::new(&format!("run-mixed-{}", name));
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("beta")).unwrap();
+        std_fs::create_dir_all(path.join("alpha")).unwrap();
+        let _ = std_fs::File::create(path.join("beta/inner")).unwrap();
+        let _ = std_fs::File::create(path.join("zed.txt")).unwrap();
+        let _ = std_fs::File::create(path.join("apple.txt")).unwrap();
+
+        let paths: Vec<String> = [ "zed.txt", "beta", "apple.txt", "alpha" ].iter().map(|p| path.join(p).display().to_string()).collect();
+        let mut args = flags.to_vec();
+        args.extend(paths.iter().map(|p| &p[..]));
+
+        let (output, _) = list(&args);
+        output.replace(&path.display().to_string(), "DIR")
+    }
+
+    #[test]
+    fn files_before_directories() {
+        assert_eq!(mixed("lines", &[ "--oneline", "--colour=never" ]),
+                   "DIR/apple.txt\nDIR/zed.txt\n\nDIR/beta:\ninner\n\nDIR/alpha:\n");
+    }
+
+    #[test]
+    fn files_before_directories_in_tables() {
+        let long = mixed("long", &[ "--long", "--columns=name", "--colour=never" ]);
+        assert_eq!(long, "DIR/apple.txt\nDIR/zed.txt\n\nDIR/beta:\ninner\n\nDIR/alpha:\n");
+
+        let tsv = mixed("tsv", &[ "--tsv", "--columns=name", "--colour=never" ]);
+        assert_eq!(tsv, "apple.txt\nzed.txt\n\nDIR/beta:\ninner\n\nDIR/alpha:\n");
+    }
+
+    #[test]
+    fn header_when_only_one_path_can_be_listed() {
+        let scratch = ScratchDir::new("run-missing-and-dir");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("dir")).unwrap();
+        let _ = std_fs::File::create(path.join("dir/file")).unwrap();
+
+        let dir = path.join("dir").display().to_string();
+        let missing = path.join("missing").display().to_string();
+        let (output, status) = list(&[ "--oneline", "--colour=never", &missing, &dir ]);
+
+        assert_eq!(output.replace(&path.display().to_string(), "DIR"), "DIR/dir:\nfile\n");
+        assert_eq!(status, 2);
+    }
+
+    #[test]
+    fn json_errors_for_missing_argument() {
+        let scratch = ScratchDir::new("run-json-missing");
+        let missing = scratch.join("missing").display().to_string();
+        let (output, status) = list(&[ "--tree", "--json", &missing ]);
+
+        let document = json_parser::parse(&output).unwrap();
+        assert!(document["files"].is_empty());
+        assert_eq!(document["errors"].len(), 1);
+
+        let error = &document["errors"][0];
+        assert_eq!(error["path"].as_str(), Some(&*missing));
+        assert_eq!(error["code"].as_i32(), Some(libc::ENOENT));
+        assert!(error["message"].as_str().unwrap().contains("No such file or directory"), "{}", error["message"]);
+        assert_eq!(status, 2);
+    }
+
+    #[test]
+    fn context_column_in_recurse_mode() {
+        let output = run("context", &[ "a/b" ], &[ "top", "a/mid", "a/b/deep" ],
+                         &[ "--recurse", "--long", "--context", "--columns=context,name", "--colour=never" ]);
+        assert_eq!(output, "NAME a\nNAME top\n\nDIR/a:\nNAME/a b\nNAME/a mid\n\nDIR/a/b:\nNAME/a/b deep\n");
+    }
+
+    #[test]
+    fn context_column_for_arguments() {
+        let scratch = ScratchDir::new("run-context-args");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("a")).unwrap();
+        let _ = std_fs::File::create(path.join("a/mid")).unwrap();
+
+        let mid = path.join("a/mid").display().to_string();
+        let (output, _) = list(&[ "--tsv", "--columns=context,name", &mid ]);
+
+        assert_eq!(output.replace(&path.display().to_string(), "DIR"), "DIR/a\tmid\n");
+    }
+
+    #[test]
+    fn tsv_with_header() {
+        let output = run("tsv", &[ "dir" ], &[ "tab\there", "back\\slash" ], &[ "--tsv", "--header", "--columns=size,name" ]);
+        assert_eq!(output, "Size\tName\n0\tback\\\\slash\n-\tdir\n0\ttab\\there\n");
+    }
+
+    #[test]
+    fn ignore_glob_below_recurse() {
+        let output = run("ignore-below-recurse", &[ "a/b" ], &[ "top.log", "a/mid.log", "a/b/deep.log", "a/b/kept.txt" ],
+                         &[ "--recurse", "--oneline", "--colour=never", "--ignore-glob=*.log", "--ignore-glob-below=1" ]);
+        assert_eq!(output, "a\ntop.log\n\nDIR/a:\nb\nmid.log\n\nDIR/a/b:\nkept.txt\n");
+    }
+
+    #[test]
+    fn ignore_glob_below_tree() {
+        let output = run("ignore-below-tree", &[ "a/b" ], &[ "top.log", "a/mid.log", "a/b/deep.log", "a/b/kept.txt" ],
+                         &[ "--tree", "--colour=never", "--ignore-glob=*.log", "--ignore-glob-below=0" ]);
+        assert_eq!(output, "DIR\n├── a\n│  └── b\n│     └── kept.txt\n└── top.log\n");
+    }
+
+    #[test]
+    fn no_ignore() {
+        let output = run("no-ignore", &[], &[ "main.rs", "lib.rs", "README.md" ],
+                         &[ "--oneline", "--colour=never", "--ignore-glob=*.rs", "--no-ignore" ]);
+        assert_eq!(output, "README.md\nlib.rs\nmain.rs\n");
+    }
+
+    #[test]
+    fn git_ignore_files_in_tree() {
+        use std::io::Write;
+
+        let scratch = ScratchDir::new("git-ignore-tree");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("a/b")).unwrap();
+        for file in &[ "top.log", "kept.txt", "a/inner.tmp", "a/b/deep.log", "a/b/keep.log" ] {
+            let _ = std_fs::File::create(path.join(file)).unwrap();
+        }
+
+        std_fs::File::create(path.join(".gitignore")).unwrap().write_all(b"*.log\n").unwrap();
+        std_fs::File::create(path.join("a/.ignore")).unwrap().write_all(b"*.tmp\n!keep.log\n").unwrap();
+
+        let dir = path.display().to_string();
+        let (output, _) = list(&[ "--tree", "--colour=never", "--ignore-file-like-git", &dir ]);
+        assert_eq!(output.replace(&dir, "DIR"), "DIR\n├── a\n│  └── b\n│     └── keep.log\n└── kept.txt\n");
+    }
+
+    #[test]
+    fn glob_argument() {
+        let scratch = ScratchDir::new("glob-args");
+        let path = &scratch.path;
+        for file in &[ "main.rs", "lib.rs", ".hidden.rs", "Cargo.toml" ] {
+            let _ = std_fs::File::create(path.join(file)).unwrap();
+        }
+
+        let dir = path.display().to_string();
+        let pattern = format!("{}/*.rs", dir);
+        let (output, status) = list(&[ "--glob-args", "--oneline", "--colour=never", &pattern ]);
+        let (missing, missing_status) = list(&[ "--glob-args", "--oneline", "--colour=never", &format!("{}/*.py", dir) ]);
+        let (literal, literal_status) = list(&[ "--oneline", "--colour=never", &pattern ]);
+
+        assert_eq!((output.replace(&dir, "DIR"), status), ("DIR/lib.rs\nDIR/main.rs\n".into(), 0));
+        assert_eq!((missing, missing_status), ("".into(), 2));
+        assert_eq!((literal, literal_status), ("".into(), 2));
+    }
+
+    /// Lists a directory with an unreadable subdirectory between two
+    /// readable ones, returning what got printed and the exit status.
+    /// Nothing is unreadable to root, so this returns `None` then.
+    fn list_unreadable(name: &str, flags: &[&str]) -> Option<(String, i32)> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if unsafe { libc::geteuid() } == 0 {
+            return None;
+        }
+
+        let scratch = ScratchDir::new(&format!("unreadable-{}", name));
+        let path = &scratch.path;
+        for dir in &[ "a", "locked/inner", "z" ] {
+            std_fs::create_dir_all(path.join(dir)).unwrap();
+        }
+
+        std_fs::set_permissions(path.join("locked"), std_fs::Permissions::from_mode(0o000)).unwrap();
+
+        let dir = path.display().to_string();
+        let mut args = flags.to_vec();
+        args.push(&dir);
+        let (output, status) = list(&args);
+
+        std_fs::set_permissions(path.join("locked"), std_fs::Permissions::from_mode(0o755)).unwrap();
+        Some((output.replace(&dir, "DIR"), status))
+    }
+
+    #[test]
+    fn unreadable_in_tree() {
+        if let Some((output, status)) = list_unreadable("tree", &[ "--tree", "--colour=never" ]) {
+            assert_eq!(output, "DIR\n├── a\n├── locked\n│  └── [permission denied]\n└── z\n");
+            assert_eq!(status, 1);
+        }
+    }
+
+    #[test]
+    fn unreadable_in_recurse() {
+        if let Some((output, status)) = list_unreadable("recurse", &[ "--recurse", "--oneline", "--colour=never" ]) {
+            assert_eq!(output, "a\nlocked\nz\n\nDIR/a:\n\nDIR/locked:\n[permission denied]\n\nDIR/z:\n");
+            assert_eq!(status, 1);
+        }
+    }
+
+    #[test]
+    fn unreadable_in_json() {
+        if let Some((output, status)) = list_unreadable("json", &[ "--tree", "--json" ]) {
+            let document = json_parser::parse(&output).unwrap();
+            assert_eq!(document["errors"].len(), 1);
+            assert_eq!(document["errors"][0]["path"].as_str(), Some("DIR/locked"));
+            assert_eq!(document["errors"][0]["code"].as_i32(), Some(libc::EACCES));
+            assert_eq!(status, 1);
+        }
+    }
+
+    #[test]
+    fn timings_report() {
+        let output = run("timings", &[ "a" ], &[ "b", "a/c" ], &[ "--long", "--tree", "--colour=never", "--timings" ]);
+        assert!(output.contains("c"));
+
+        let report = super::timings::report();
+        for label in &[ "read directories", "stat files", "scan Git repositories", "sort files", "render output" ] {
+            assert!(report.contains(&format!("\n  {}: ", label)), "{:?} is missing from {:?}", label, report);
         }
     }
 }
@@ -0,0 +1,146 @@
+//! Working out what the user running exa can do to a file, going by its
+//! permission bits and who owns it.
+//!
+//! This follows the same rules the kernel does when a file gets opened: the
+//! owner’s bits apply to the owner, the group’s bits to anyone in the group,
+//! and the other bits to everyone else, with root able to do anything. It
+//! doesn’t call `access(2)` for every file, so it can’t know about access
+//! control lists, which can grant or take away more than the bits say.
+
+use std::ptr;
+
+use libc;
+
+use fs::fields as f;
+
+
+/// The user and groups that exa is running as, which decide which of a
+/// file’s permission bits apply.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Identity {
+
+    /// The effective user ID.
+    pub uid: f::uid_t,
+
+    /// The effective group ID, along with every supplementary group.
+    pub gids: Vec<f::gid_t>,
+}
+
+impl Identity {
+
+    /// Looks up the identity of the current process. If the list of
+    /// supplementary groups can’t be read, only the effective group counts.
+    pub fn current() -> Identity {
+        let uid = unsafe { libc::geteuid() };
+        let mut gids = vec![ unsafe { libc::getegid() } ];
+
+        let count = unsafe { libc::getgroups(0, ptr::null_mut()) };
+        if count > 0 {
+            let mut groups: Vec<libc::gid_t> = vec![ 0; count as usize ];
+            let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+            if count > 0 {
+                gids.extend(groups.into_iter().take(count as usize));
+            }
+        }
+
+        Identity { uid, gids }
+    }
+
+    /// What this identity can do to a file with the given permissions,
+    /// owner, and group. A file that can be written to but not read counts
+    /// as having no access, as there’s not much that can be done with it.
+    pub fn access(&self, permissions: &f::Permissions, user: &f::User, group: &f::Group) -> f::Access {
+        if self.uid == 0 {
+            return f::Access::Full;
+        }
+
+        let (read, write) = if user.0 == self.uid            { (permissions.user_read,  permissions.user_write) }
+                       else if self.gids.contains(&group.0)  { (permissions.group_read, permissions.group_write) }
+                                                        else { (permissions.other_read, permissions.other_write) };
+
+        match (read, write) {
+            (true,  true)   => f::Access::Full,
+            (true,  false)  => f::Access::ReadOnly,
+            (false, _)      => f::Access::Denied,
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Permissions from an octal mode, such as `0o640`.
+    fn permissions(mode: u32) -> f::Permissions {
+        let has_bit = |bit| mode & bit == bit;
+
+        f::Permissions {
+            user_read:      has_bit(0o400),
+            user_write:     has_bit(0o200),
+            user_execute:   has_bit(0o100),
+            group_read:     has_bit(0o040),
+            group_write:    has_bit(0o020),
+            group_execute:  has_bit(0o010),
+            other_read:     has_bit(0o004),
+            other_write:    has_bit(0o002),
+            other_execute:  has_bit(0o001),
+            sticky:         false,
+            setgid:         false,
+            setuid:         false,
+        }
+    }
+
+    fn me() -> Identity {
+        Identity { uid: 1000, gids: vec![ 1000, 27 ] }
+    }
+
+    fn access(identity: &Identity, mode: u32, uid: f::uid_t, gid: f::gid_t) -> f::Access {
+        identity.access(&permissions(mode), &f::User(uid), &f::Group(gid))
+    }
+
+    #[test]
+    fn own_file() {
+        assert_eq!(f::Access::Full,     access(&me(), 0o644, 1000, 1000));
+        assert_eq!(f::Access::ReadOnly, access(&me(), 0o444, 1000, 1000));
+        assert_eq!(f::Access::Denied,   access(&me(), 0o077, 1000, 1000));
+    }
+
+    #[test]
+    fn owner_bits_win_over_others() {
+        // The owner’s bits apply, even though everyone else can write.
+        assert_eq!(f::Access::ReadOnly, access(&me(), 0o466, 1000, 1000));
+    }
+
+    #[test]
+    fn group_member() {
+        assert_eq!(f::Access::Full,     access(&me(), 0o660, 0, 27));
+        assert_eq!(f::Access::ReadOnly, access(&me(), 0o640, 0, 27));
+        assert_eq!(f::Access::Denied,   access(&me(), 0o606, 0, 27));
+    }
+
+    #[test]
+    fn supplementary_group() {
+        let identity = Identity { uid: 1000, gids: vec![ 1000, 27, 100 ] };
+        assert_eq!(f::Access::ReadOnly, access(&identity, 0o640, 0, 100));
+    }
+
+    #[test]
+    fn someone_else() {
+        assert_eq!(f::Access::Full,     access(&me(), 0o666, 0, 0));
+        assert_eq!(f::Access::ReadOnly, access(&me(), 0o644, 0, 0));
+        assert_eq!(f::Access::Denied,   access(&me(), 0o640, 0, 0));
+    }
+
+    #[test]
+    fn write_only() {
+        assert_eq!(f::Access::Denied, access(&me(), 0o200, 1000, 1000));
+    }
+
+    #[test]
+    fn root_can_do_anything() {
+        let root = Identity { uid: 0, gids: vec![ 0 ] };
+        assert_eq!(f::Access::Full, access(&root, 0o000, 1000, 1000));
+    }
+}
@@ -0,0 +1,196 @@
+//! Sniffing the start of a file to guess whether it’s text or binary.
+//!
+//! This is the same sort of guess that `file` or `grep` make: a file with
+//! a NUL byte near the start is binary, and so is one that isn’t valid
+//! UTF-8. Only the first kibibyte gets read, so a text file with binary
+//! data further on will still count as text.
+//!
+//! Counting a file’s lines is different, as the whole file has to be read
+//! anyway: any NUL byte in it makes it binary, and then its lines don’t
+//! get counted at all.
+
+use std::fs;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use std::str;
+
+use fs::fields as f;
+
+
+/// How many bytes to read from the start of each file.
+pub const SNIFF_SIZE: usize = 1024;
+
+/// The largest file to count the lines of. Anything bigger gets skipped, as
+/// reading all of it would slow the listing down too much.
+pub const LINE_COUNT_LIMIT: u64 = 16 * 1024 * 1024;
+
+
+/// Reads the start of the file at the given path and guesses what’s in it.
+/// Files that can’t be opened or read count as unreadable.
+pub fn read_content_type(path: &Path) -> f::ContentType {
+    let file = match fs::File::open(path) {
+        Ok(f)   => f,
+        Err(_)  => return f::ContentType::Unreadable,
+    };
+
+    let mut bytes = Vec::with_capacity(SNIFF_SIZE);
+    match file.take(SNIFF_SIZE as u64).read_to_end(&mut bytes) {
+        Ok(_)   => sniff(&bytes),
+        Err(_)  => f::ContentType::Unreadable,
+    }
+}
+
+/// Reads the whole file at the given path to count its lines. Files that
+/// are binary, or that can’t be opened or read, have no count.
+pub fn read_line_count(path: &Path) -> f::LineCount {
+    match fs::File::open(path).ok().and_then(count_lines) {
+        Some(count)  => f::LineCount::Some(count),
+        None         => f::LineCount::None,
+    }
+}
+
+/// Counts the newlines in everything the reader has to give, a buffer at a
+/// time, returning `None` if there’s a NUL byte anywhere in there or if
+/// reading fails.
+fn count_lines<R: Read>(mut reader: R) -> Option<u64> {
+    let mut buffer = [0; 8192];
+    let mut count = 0;
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0)   => return Some(count),
+            Ok(n)   => n,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_)  => return None,
+        };
+
+        let bytes = &buffer[.. read];
+        if bytes.contains(&0) {
+            return None;
+        }
+
+        count += bytes.iter().filter(|&&b| b == b'\n').count() as u64;
+    }
+}
+
+/// Guesses whether the given bytes from the start of a file are text or
+/// binary. A multi-byte character cut off at the end of the bytes doesn’t
+/// count against them, as it’s most likely where the sniffing stopped
+/// rather than where the file ends.
+pub fn sniff(bytes: &[u8]) -> f::ContentType {
+    if bytes.is_empty() {
+        return f::ContentType::Empty;
+    }
+
+    if bytes.contains(&0) {
+        return f::ContentType::Binary;
+    }
+
+    match str::from_utf8(bytes) {
+        Ok(_)                                                 => f::ContentType::Text,
+        Err(e) if is_cut_off_char(&bytes[e.valid_up_to() ..])  => f::ContentType::Text,
+        Err(_)                                                => f::ContentType::Binary,
+    }
+}
+
+/// Whether these bytes are the start of a multi-byte UTF-8 character, with
+/// the rest of it missing.
+fn is_cut_off_char(bytes: &[u8]) -> bool {
+    let length = match bytes.first() {
+        Some(&b) if b >= 0xC2 && b <= 0xDF  => 2,
+        Some(&b) if b >= 0xE0 && b <= 0xEF  => 3,
+        Some(&b) if b >= 0xF0 && b <= 0xF4  => 4,
+        _                                   => return false,
+    };
+
+    bytes.len() < length && bytes[1 ..].iter().all(|&b| b >= 0x80 && b <= 0xBF)
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        assert_eq!(sniff(b""), f::ContentType::Empty);
+    }
+
+    #[test]
+    fn ascii() {
+        assert_eq!(sniff(b"fn main() {}\n"), f::ContentType::Text);
+    }
+
+    #[test]
+    fn utf8() {
+        assert_eq!(sniff("naïve café ☕\n".as_bytes()), f::ContentType::Text);
+    }
+
+    #[test]
+    fn nul_byte() {
+        assert_eq!(sniff(b"text\0more text"), f::ContentType::Binary);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert_eq!(sniff(b"\x7FELF\x02\x01\x01\xFF"), f::ContentType::Binary);
+    }
+
+    #[test]
+    fn lone_continuation_byte() {
+        assert_eq!(sniff(b"abc\x80"), f::ContentType::Binary);
+    }
+
+    #[test]
+    fn cut_off_at_the_end() {
+        let mut bytes = vec![ b'a'; SNIFF_SIZE - 2 ];
+        bytes.extend("☕".as_bytes()[.. 2].iter());
+        assert_eq!(sniff(&bytes), f::ContentType::Text);
+    }
+
+    #[test]
+    fn cut_off_in_the_middle() {
+        let mut bytes = "☕".as_bytes()[.. 2].to_vec();
+        bytes.extend(b"abc".iter());
+        assert_eq!(sniff(&bytes), f::ContentType::Binary);
+    }
+
+    #[test]
+    fn too_long_to_be_cut_off() {
+        assert_eq!(sniff(b"abc\xE2\x98\x95\x95"), f::ContentType::Binary);
+    }
+
+    #[test]
+    fn no_lines() {
+        assert_eq!(count_lines(&b""[..]), Some(0));
+    }
+
+    #[test]
+    fn some_lines() {
+        assert_eq!(count_lines(&b"one\ntwo\nthree\n"[..]), Some(3));
+    }
+
+    #[test]
+    fn unfinished_last_line() {
+        assert_eq!(count_lines(&b"one\ntwo"[..]), Some(1));
+    }
+
+    #[test]
+    fn lines_over_many_buffers() {
+        let bytes = b"line\n".iter().cycle().take(5 * 10_000).cloned().collect::<Vec<u8>>();
+        assert_eq!(count_lines(&bytes[..]), Some(10_000));
+    }
+
+    #[test]
+    fn binary_lines() {
+        assert_eq!(count_lines(&b"one\n\0two\n"[..]), None);
+    }
+
+    #[test]
+    fn nul_byte_far_in() {
+        let mut bytes = vec![ b'\n'; 20_000 ];
+        bytes.push(0);
+        assert_eq!(count_lines(&bytes[..]), None);
+    }
+}
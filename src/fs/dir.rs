@@ -1,10 +1,16 @@
+use std::collections::HashMap;
+use std::env;
 use std::io::{self, Result as IOResult};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::slice::Iter as SliceIter;
+use std::sync::Arc;
 
-use fs::feature::Git;
+use fs::feature::{Git, GitOptions};
 use fs::{File, fields};
+use fs::entries::EntryCounts;
+use fs::gitignore::IgnoreFiles;
+use timings::{self, Stage};
 
 
 /// A **Dir** provides a cached list of the file paths in a directory that's
@@ -24,6 +30,24 @@ pub struct Dir {
     /// Holds a `Git` object if scanning for Git repositories is switched on,
     /// and this directory happens to contain one.
     git: Option<Git>,
+
+    /// The Git statuses of the files in this directory, looked up all at
+    /// once when the directory was read, if it has a repository.
+    git_statuses: HashMap<PathBuf, fields::Git>,
+
+    /// The path that was read, made absolute and without any symlinks, the
+    /// way the paths in the repository’s statuses are. This only gets
+    /// worked out when there’s a repository to look files up in.
+    canonical_path: PathBuf,
+
+    /// The ignore files that apply to the files in this directory, if
+    /// they’ve been read.
+    ignore_files: Option<IgnoreFiles>,
+
+    /// The recursive entry counts of the directories in and under this
+    /// one, shared with the directories inside it that get read as part of
+    /// the same listing.
+    entry_counts: Arc<EntryCounts>,
 }
 
 impl Dir {
@@ -36,13 +60,49 @@ impl Dir {
     /// The `read_dir` iterator doesn’t actually yield the `.` and `..`
     /// entries, so if the user wants to see them, we’ll have to add them
     /// ourselves after the files have been read.
-    pub fn read_dir(path: PathBuf, git: bool) -> IOResult<Dir> {
-        let contents: Vec<PathBuf> = try!(fs::read_dir(&path)?
-                                                 .map(|result| result.map(|entry| entry.path()))
-                                                 .collect());
+    ///
+    /// A Git repository on or above the directory only gets scanned for if
+    /// there are options to scan it with.
+    pub fn read_dir(path: PathBuf, git: Option<&GitOptions>) -> IOResult<Dir> {
+        let contents = timings::time(Stage::ReadDir, || read_paths(&path))?;
+        let git = git.and_then(|options| timings::time(Stage::GitScan, || Git::scan(&path, options)).ok());
+        let canonical_path = if git.is_some() { canonicalise(&path) }
+                                         else { path.clone() };
+        let git_statuses = match git {
+            Some(ref git)  => git.statuses_for_dir(&canonical_path),
+            None           => HashMap::new(),
+        };
+
+        let entry_counts = Arc::new(EntryCounts::default());
+        Ok(Dir { contents, path, git, git_statuses, canonical_path, ignore_files: None, entry_counts })
+    }
 
-        let git = if git { Git::scan(&path).ok() } else { None };
-        Ok(Dir { contents, path, git })
+    /// Makes this directory share the cache of entry counts of the given
+    /// directory, which it’s inside, so none of the counts worked out while
+    /// listing that one have to be worked out again.
+    pub fn share_entry_counts(&mut self, parent: &Dir) {
+        self.entry_counts = parent.entry_counts.clone();
+    }
+
+    /// The cache of recursive entry counts for the files in this directory.
+    pub fn entry_counts(&self) -> &EntryCounts {
+        &self.entry_counts
+    }
+
+    /// Reads the ignore files that apply to the files in this directory.
+    /// When the ignore files for the directory this one is in have already
+    /// been read, only the ones in this directory itself have to be.
+    pub fn read_ignore_files(&mut self, parent: Option<&IgnoreFiles>) {
+        self.ignore_files = Some(match parent {
+            Some(parent)  => parent.below(&self.path),
+            None          => IgnoreFiles::read_for(&self.path),
+        });
+    }
+
+    /// The ignore files that apply to the files in this directory, if
+    /// they’ve been read.
+    pub fn ignore_files(&self) -> Option<&IgnoreFiles> {
+        self.ignore_files.as_ref()
     }
 
     /// Produce an iterator of IO results of trying to read all the files in
@@ -72,13 +132,137 @@ impl Dir {
     }
 
     /// Get a string describing the Git status of the given file.
+    ///
+    /// Files directly inside this directory have already had their statuses
+    /// looked up, so only other paths, such as `.` and `..`, need the
+    /// repository to be searched again.
     pub fn git_status(&self, path: &Path, prefix_lookup: bool) -> fields::Git {
+        if self.git.is_some() && path.file_name().is_some() && path.parent() == Some(self.path.as_path()) {
+            return self.git_statuses.get(&self.git_path(path)).cloned().unwrap_or_else(fields::Git::empty);
+        }
+
         match (&self.git, prefix_lookup) {
-            (&Some(ref git), false)  => git.status(path),
-            (&Some(ref git), true)   => git.dir_status(path),
+            (&Some(ref git), false)  => git.status(&self.git_path(path)),
+            (&Some(ref git), true)   => git.dir_status(&self.git_path(path)),
             (&None, _)               => fields::Git::empty()
         }
     }
+
+    /// The path of the given file the way the paths in the repository’s
+    /// statuses are. Files directly inside this directory get joined onto
+    /// its canonical path, which has already been worked out, so only other
+    /// paths, such as `.` and `..`, have to be canonicalised again.
+    ///
+    /// Only the directories leading up to a file get canonicalised, as
+    /// doing it to the whole path would give a symlink its target’s status.
+    fn git_path(&self, path: &Path) -> PathBuf {
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) if parent == self.path  => self.canonical_path.join(name),
+            (Some(parent), Some(name))                         => canonicalise(parent).join(name),
+            _                                                  => canonicalise(path),
+        }
+    }
+
+    /// Whether the file with the given path is ignored by this directory’s
+    /// repository, if it has one.
+    pub fn is_git_ignored(&self, path: &Path) -> bool {
+        match self.git {
+            Some(ref git)  => git.is_ignored(&self.git_path(path)),
+            None           => false,
+        }
+    }
+
+    /// Whether the file with the given path is tracked by this directory’s
+    /// repository. Files are never tracked without a repository.
+    pub fn is_git_tracked(&self, path: &Path) -> bool {
+        match self.git {
+            Some(ref git)  => git.is_tracked(&self.git_path(path)),
+            None           => false,
+        }
+    }
+
+    /// Whether the file with the given path is untracked by this
+    /// directory’s repository, or is a directory with an untracked file
+    /// inside it. Files are never untracked without a repository.
+    pub fn has_git_untracked(&self, path: &Path) -> bool {
+        match self.git {
+            Some(ref git)  => git.has_untracked(&self.git_path(path)),
+            None           => false,
+        }
+    }
+
+    /// Return whether this directory’s repository was compared against a
+    /// reference, meaning the files have statuses since that reference.
+    pub fn has_git_since(&self) -> bool {
+        self.git.as_ref().map(Git::has_since).unwrap_or(false)
+    }
+
+    /// Return whether the commits that last changed this directory’s files
+    /// were looked up in its repository.
+    pub fn has_git_last_commits(&self) -> bool {
+        self.git.as_ref().map(Git::has_last_commits).unwrap_or(false)
+    }
+
+    /// The commit that last changed the file with the given path, if this
+    /// directory has a repository and the commits were looked up in it.
+    pub fn git_last_commit(&self, path: &Path) -> Option<fields::GitCommit> {
+        self.git.as_ref().and_then(|git| git.last_commit(&self.git_path(path)))
+    }
+
+    /// The operation this directory’s repository is in the middle of, such
+    /// as a merge that stopped because of conflicts, if it has a repository
+    /// and it’s in the middle of one.
+    pub fn git_operation(&self) -> Option<fields::RepoState> {
+        self.git.as_ref().and_then(Git::operation_in_progress)
+    }
+
+    /// Get the status of the given file compared to the reference.
+    pub fn git_since_status(&self, path: &Path, prefix_lookup: bool) -> fields::GitStatus {
+        match (&self.git, prefix_lookup) {
+            (&Some(ref git), false)  => git.since_status(&self.git_path(path)),
+            (&Some(ref git), true)   => git.dir_since_status(&self.git_path(path)),
+            (&None, _)               => fields::GitStatus::NotModified,
+        }
+    }
+}
+
+
+/// Makes the given path absolute, by joining it onto the current directory,
+/// and resolves any symlinks in it. A path that can’t be resolved, such as
+/// one that doesn’t exist, is only made absolute.
+fn canonicalise(path: &Path) -> PathBuf {
+    let path = match env::current_dir() {
+        Ok(dir)  => dir.join(path),
+        Err(_)   => path.to_path_buf(),
+    };
+
+    path.canonicalize().unwrap_or(path)
+}
+
+/// Reads the paths of every entry in the directory at the given path. On
+/// Linux, this uses the faster batched reading in the `getdents` module,
+/// falling back to the standard library if the kernel doesn’t support it.
+#[cfg(target_os="linux")]
+fn read_paths(path: &Path) -> IOResult<Vec<PathBuf>> {
+    use fs::getdents;
+    use libc;
+
+    match getdents::read_dir_paths(path) {
+        Err(ref e) if e.raw_os_error() == Some(libc::ENOSYS) => std_read_paths(path),
+        result => result,
+    }
+}
+
+#[cfg(not(target_os="linux"))]
+fn read_paths(path: &Path) -> IOResult<Vec<PathBuf>> {
+    std_read_paths(path)
+}
+
+/// Reads the paths of every entry in the directory one at a time, using
+/// the standard library.
+fn std_read_paths(path: &Path) -> IOResult<Vec<PathBuf>> {
+    fs::read_dir(path)?.map(|result| result.map(|entry| entry.path()))
+                       .collect()
 }
 
 
@@ -188,7 +372,7 @@ impl Default for DotFilter {
 impl DotFilter {
 
     /// Whether this filter should show dotfiles in a listing.
-    fn shows_dotfiles(&self) -> bool {
+    pub fn shows_dotfiles(&self) -> bool {
         match *self {
             DotFilter::JustFiles       => false,
             DotFilter::Dotfiles        => true,
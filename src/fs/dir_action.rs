@@ -68,6 +68,30 @@ pub struct RecurseOptions {
     /// The maximum number of times that recursion should descend to, if one
     /// is specified.
     pub max_depth: Option<usize>,
+
+    /// The order to list the directories found while recursing in.
+    pub order: RecurseOrder,
+}
+
+/// The order the directories found while recursing get listed in. Either
+/// way, the directories inside each one are listed in the order they’re
+/// sorted in.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum RecurseOrder {
+
+    /// List each directory’s subdirectories straight after it, before
+    /// moving on to its next sibling. This is what `ls -R` does.
+    DepthFirst,
+
+    /// List every directory at one depth before any of the directories
+    /// inside them.
+    BreadthFirst,
+}
+
+impl Default for RecurseOrder {
+    fn default() -> RecurseOrder {
+        RecurseOrder::DepthFirst
+    }
 }
 
 impl RecurseOptions {
@@ -0,0 +1,179 @@
+//! Counting the entries inside a directory, either just the ones directly
+//! inside it, or every one underneath it, recursively.
+//!
+//! Walking a whole tree of directories is slow, so each directory’s count
+//! gets cached for as long as the listing it’s part of: sorting by the
+//! count and displaying it in a column, or listing a directory and then
+//! recursing into its children, would otherwise walk the same directories
+//! over and over again.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+
+/// While walking, directories are identified by their device and inode
+/// numbers, rather than their paths, as the same directory can be reached
+/// through several paths.
+type DirId = (u64, u64);
+
+
+/// The recursive counts of the directories that have been walked so far.
+///
+/// Each directory being listed has one of these, which gets shared with
+/// the directories inside it when they get listed too, so the counts last
+/// as long as the listing does, and no longer: once it’s over, they could
+/// be out of date.
+#[derive(Default)]
+pub struct EntryCounts {
+    counts: Mutex<HashMap<PathBuf, u64>>,
+}
+
+
+/// Counts the entries inside the directory with the given path and
+/// metadata, and inside all of its subdirectories, not including the `.`
+/// and `..` entries. Returns `None` if the directory can’t be read.
+///
+/// Symlinks are counted as entries, but never followed. Subdirectories that
+/// can’t be read count as entries themselves, but add nothing else to the
+/// total, and a directory that has already been visited during the walk
+/// (such as one mounted inside itself) isn’t counted twice.
+///
+/// The counts of the directory and everything inside it get kept in the
+/// given cache, and looked up there first.
+pub fn recursive_count(path: &Path, metadata: &fs::Metadata, cache: &EntryCounts) -> Option<u64> {
+    let mut visited = HashSet::new();
+    count_dir(path, (metadata.dev(), metadata.ino()), &mut visited, cache)
+}
+
+/// Counts the entries in one directory, descending into its subdirectories,
+/// and caches the result.
+fn count_dir(path: &Path, id: DirId, visited: &mut HashSet<DirId>, cache: &EntryCounts) -> Option<u64> {
+    if let Some(&count) = cache.counts.lock().unwrap().get(path) {
+        return Some(count);
+    }
+
+    if !visited.insert(id) {
+        return Some(0);
+    }
+
+    let entries = match fs::read_dir(path) {
+        Ok(es)  => es,
+        Err(_)  => return None,
+    };
+
+    let mut count = 0;
+    for entry in entries {
+        let entry = match entry {
+            Ok(e)   => e,
+            Err(_)  => continue,
+        };
+
+        count += 1;
+
+        // `symlink_metadata` doesn’t follow links, which is what stops the
+        // walk from escaping the directory, or going round in circles.
+        let child_path = entry.path();
+        if let Ok(child) = fs::symlink_metadata(&child_path) {
+            if child.is_dir() {
+                count += count_dir(&child_path, (child.dev(), child.ino()), visited, cache).unwrap_or(0);
+            }
+        }
+    }
+
+    let _ = cache.counts.lock().unwrap().insert(path.to_path_buf(), count);
+    Some(count)
+}
+
+
+/// Counts the entries directly inside the directory with the given path,
+/// not including the `.` and `..` entries, and not including dotfiles
+/// unless they’re being shown. Returns `None` if the directory can’t be
+/// read.
+///
+/// This only reads the one directory, so it’s cheap enough not to need
+/// caching.
+pub fn shallow_count(path: &Path, dotfiles: bool) -> Option<u64> {
+    let entries = match fs::read_dir(path) {
+        Ok(es)  => es,
+        Err(_)  => return None,
+    };
+
+    let count = entries.filter_map(Result::ok)
+                       .filter(|e| dotfiles || !e.file_name().to_string_lossy().starts_with('.'))
+                       .count();
+
+    Some(count as u64)
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::{recursive_count, shallow_count, EntryCounts};
+    use scratch::ScratchDir;
+    use std::fs::{self, File};
+    use std::os::unix::fs::symlink;
+    use std::path::Path;
+
+    fn count(path: &Path) -> Option<u64> {
+        recursive_count(path, &fs::metadata(path).unwrap(), &EntryCounts::default())
+    }
+
+    #[test]
+    fn nested() {
+        let scratch = ScratchDir::new("entries-nested");
+        let path = &scratch.path;
+        fs::create_dir_all(path.join("a/b")).unwrap();
+        let _ = File::create(path.join("one")).unwrap();
+        let _ = File::create(path.join("a/two")).unwrap();
+        let _ = File::create(path.join("a/b/three")).unwrap();
+        let _ = File::create(path.join("a/b/.four")).unwrap();
+
+        assert_eq!(Some(2), count(&path.join("a/b")));
+        assert_eq!(Some(4), count(&path.join("a")));
+        assert_eq!(Some(6), count(&path));
+    }
+
+    #[test]
+    fn links_are_not_followed() {
+        let scratch = ScratchDir::new("entries-links");
+        let path = &scratch.path;
+        fs::create_dir_all(path.join("dir")).unwrap();
+        let _ = File::create(path.join("dir/file")).unwrap();
+        symlink(&path, path.join("dir/loop")).unwrap();
+
+        assert_eq!(Some(3), count(&path));
+    }
+
+    #[test]
+    fn cached_per_listing() {
+        let scratch = ScratchDir::new("entries-cached");
+        let path = &scratch.path;
+        let _ = File::create(path.join("one")).unwrap();
+
+        let cache = EntryCounts::default();
+        let metadata = fs::metadata(&path).unwrap();
+        assert_eq!(Some(1), recursive_count(&path, &metadata, &cache));
+
+        let _ = File::create(path.join("two")).unwrap();
+        assert_eq!(Some(1), recursive_count(&path, &metadata, &cache));
+        assert_eq!(Some(2), recursive_count(&path, &metadata, &EntryCounts::default()));
+    }
+
+    #[test]
+    fn shallow() {
+        let scratch = ScratchDir::new("entries-shallow");
+        let path = &scratch.path;
+        fs::create_dir_all(path.join("a/b")).unwrap();
+        let _ = File::create(path.join("one")).unwrap();
+        let _ = File::create(path.join(".two")).unwrap();
+        let _ = File::create(path.join("a/three")).unwrap();
+
+        assert_eq!(Some(2), shallow_count(&path, false));
+        assert_eq!(Some(3), shallow_count(&path, true));
+        assert_eq!(None,    shallow_count(&path.join("missing"), true));
+    }
+}
@@ -0,0 +1,330 @@
+//! POSIX access control list support for Linux systems.
+//!
+//! Linux stores a file’s ACLs as extended attributes, so whether a file has
+//! one can be worked out from the list of attribute names that gets read for
+//! the `@` indicator anyway. Listing the entries means reading the value of
+//! that attribute and decoding it.
+#![allow(trivial_casts)]  // for ARM
+
+use std::io;
+use std::path::Path;
+
+use fs::feature::xattr::{self, Attribute};
+
+pub const ENABLED: bool = xattr::ENABLED && cfg!(target_os="linux");
+
+/// The attribute holding the ACL that gets checked when accessing a file.
+const ACCESS_ATTRIBUTE: &str = "system.posix_acl_access";
+
+/// The attribute holding the ACL that new files in a directory inherit.
+const DEFAULT_ATTRIBUTE: &str = "system.posix_acl_default";
+
+/// The only version of the attribute format there is.
+const VERSION: u32 = 2;
+
+
+/// Whether this extended attribute holds an ACL, rather than being one the
+/// user would recognise as an extended attribute. These get shown with a
+/// `+` instead of an `@`.
+pub fn is_acl_attribute(attr: &Attribute) -> bool {
+    ENABLED && (attr.name == ACCESS_ATTRIBUTE || attr.name == DEFAULT_ATTRIBUTE)
+}
+
+/// Whether a file with these attributes has an ACL beyond its permission
+/// bits. The kernel only stores the attribute when there’s something in it
+/// that the bits can’t express, so its presence is enough.
+pub fn has_extended_acl(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(is_acl_attribute)
+}
+
+
+/// One entry in a file’s access control list.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Entry {
+
+    /// Whether this entry is in the list new files inherit, rather than the
+    /// one that gets checked for this file.
+    pub default: bool,
+
+    /// Who this entry grants permissions to.
+    pub tag: Tag,
+
+    /// The permissions granted.
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+/// Who an ACL entry grants permissions to.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Tag {
+
+    /// The file’s owner.
+    UserObj,
+
+    /// A particular user, by their ID.
+    User(u32),
+
+    /// The file’s group.
+    GroupObj,
+
+    /// A particular group, by its ID.
+    Group(u32),
+
+    /// The most that any named user or group, or the file’s group, can be
+    /// granted.
+    Mask,
+
+    /// Everyone else.
+    Other,
+}
+
+impl Entry {
+
+    /// Decodes the value of an ACL attribute, returning `None` if it isn’t
+    /// in the format the kernel uses. Each entry is a 16-bit tag, a 16-bit
+    /// permission set, and a 32-bit user or group ID, after a 32-bit version
+    /// number, all little-endian.
+    pub fn parse(bytes: &[u8], default: bool) -> Option<Vec<Entry>> {
+        if bytes.len() < 4 || (bytes.len() - 4) % 8 != 0 || read_u32(&bytes[0 .. 4]) != VERSION {
+            return None;
+        }
+
+        let mut entries = Vec::new();
+        for chunk in bytes[4 ..].chunks(8) {
+            let id = read_u32(&chunk[4 .. 8]);
+            let tag = match read_u16(&chunk[0 .. 2]) {
+                0x01 => Tag::UserObj,
+                0x02 => Tag::User(id),
+                0x04 => Tag::GroupObj,
+                0x08 => Tag::Group(id),
+                0x10 => Tag::Mask,
+                0x20 => Tag::Other,
+                _    => return None,
+            };
+
+            let perms = read_u16(&chunk[2 .. 4]);
+            entries.push(Entry {
+                default,
+                tag,
+                read:    perms & 4 != 0,
+                write:   perms & 2 != 0,
+                execute: perms & 1 != 0,
+            });
+        }
+
+        Some(entries)
+    }
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    u16::from(bytes[0]) | u16::from(bytes[1]) << 8
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from(bytes[0]) | u32::from(bytes[1]) << 8 | u32::from(bytes[2]) << 16 | u32::from(bytes[3]) << 24
+}
+
+
+/// Reads the entries of the file’s ACL, followed by the entries of its
+/// default ACL if it’s a directory with one. A file without an ACL gives
+/// no entries, as its permission bits say everything there is to say.
+#[cfg(target_os = "linux")]
+pub fn entries(path: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for &(name, default) in &[ (ACCESS_ATTRIBUTE, false), (DEFAULT_ATTRIBUTE, true) ] {
+        if let Some(bytes) = read_attribute(path, name)? {
+            match Entry::parse(&bytes, default) {
+                Some(es) => entries.extend(es),
+                None     => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid ACL")),
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn entries(_: &Path) -> io::Result<Vec<Entry>> {
+    Ok(vec![])
+}
+
+/// Reads the value of one extended attribute, returning `None` if the file
+/// doesn’t have it.
+#[cfg(target_os = "linux")]
+fn read_attribute(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::ptr;
+    use libc::{self, size_t, ssize_t, c_char, c_void};
+
+    extern "C" {
+        fn getxattr(
+            path: *const c_char, name: *const c_char,
+            value: *mut c_void, size: size_t
+        ) -> ssize_t;
+    }
+
+    let c_path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(cstring) => cstring,
+        Err(_) => return Err(io::Error::new(io::ErrorKind::Other, "Error: path somehow contained a NUL?")),
+    };
+    let c_name = CString::new(name).unwrap();
+
+    let size = unsafe { getxattr(c_path.as_ptr(), c_name.as_ptr(), ptr::null_mut(), 0) };
+    if size < 0 {
+        let error = io::Error::last_os_error();
+        return match error.raw_os_error() {
+            Some(libc::ENODATA) | Some(libc::ENOTSUP) => Ok(None),
+            _ => Err(error),
+        };
+    }
+
+    let mut buf = vec![0u8; size as usize];
+    let read = unsafe { getxattr(c_path.as_ptr(), c_name.as_ptr(), buf.as_mut_ptr() as *mut c_void, buf.len()) };
+    if read < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    buf.truncate(read as usize);
+    Ok(Some(buf))
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn attr(name: &str) -> Attribute {
+        Attribute { name: name.into(), size: 28 }
+    }
+
+    /// Encodes entries in the kernel’s format, as `(tag, perms, id)`.
+    fn encode(entries: &[(u16, u16, u32)]) -> Vec<u8> {
+        let mut bytes = vec![ 2, 0, 0, 0 ];
+        for &(tag, perms, id) in entries {
+            bytes.extend(&[ tag as u8, (tag >> 8) as u8, perms as u8, (perms >> 8) as u8 ]);
+            bytes.extend(&[ id as u8, (id >> 8) as u8, (id >> 16) as u8, (id >> 24) as u8 ]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn no_attributes() {
+        assert_eq!(false, has_extended_acl(&[]));
+    }
+
+    #[test]
+    fn other_attributes() {
+        assert_eq!(false, has_extended_acl(&[ attr("user.comment"), attr("security.selinux") ]));
+    }
+
+    #[test]
+    fn access_acl() {
+        assert_eq!(ENABLED, has_extended_acl(&[ attr("user.comment"), attr("system.posix_acl_access") ]));
+    }
+
+    #[test]
+    fn default_acl() {
+        assert_eq!(ENABLED, has_extended_acl(&[ attr("system.posix_acl_default") ]));
+    }
+
+    #[test]
+    fn parse_entries() {
+        let bytes = encode(&[ (0x01, 6, u32::max_value()), (0x02, 4, 1000), (0x04, 5, u32::max_value()),
+                              (0x08, 7, 2000), (0x10, 7, u32::max_value()), (0x20, 0, u32::max_value()) ]);
+
+        let tags: Vec<Tag> = Entry::parse(&bytes, false).unwrap().iter().map(|e| e.tag).collect();
+        assert_eq!(tags, vec![ Tag::UserObj, Tag::User(1000), Tag::GroupObj, Tag::Group(2000), Tag::Mask, Tag::Other ]);
+    }
+
+    #[test]
+    fn parse_permissions() {
+        let bytes = encode(&[ (0x02, 5, 1000) ]);
+        let entry = Entry { default: true, tag: Tag::User(1000), read: true, write: false, execute: true };
+        assert_eq!(Entry::parse(&bytes, true), Some(vec![ entry ]));
+    }
+
+    #[test]
+    fn parse_wrong_version() {
+        let mut bytes = encode(&[ (0x01, 6, 0) ]);
+        bytes[0] = 1;
+        assert_eq!(Entry::parse(&bytes, false), None);
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let bytes = encode(&[ (0x01, 6, 0) ]);
+        assert_eq!(Entry::parse(&bytes[.. 10], false), None);
+    }
+
+    #[test]
+    fn parse_unknown_tag() {
+        let bytes = encode(&[ (0x40, 6, 0) ]);
+        assert_eq!(Entry::parse(&bytes, false), None);
+    }
+}
+
+
+#[cfg(all(test, target_os = "linux"))]
+mod test_files {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::ffi::CString;
+    use std::fs;
+    use std::os::unix::ffi::OsStrExt;
+    use libc;
+    use fs::feature::xattr::FileAttributes;
+
+    extern "C" {
+        fn setxattr(
+            path: *const libc::c_char, name: *const libc::c_char,
+            value: *const libc::c_void, size: libc::size_t, flags: libc::c_int
+        ) -> libc::c_int;
+    }
+
+    /// Gives the file at the path an ACL granting user 1000 read access,
+    /// returning false if the filesystem doesn’t support ACLs.
+    fn set_acl(path: &Path) -> bool {
+        // user::rw-, user:1000:r--, group::r--, mask::r--, other::r--
+        let value: &[u8] = &[ 2, 0, 0, 0,
+                              0x01, 0, 6, 0,  0xFF, 0xFF, 0xFF, 0xFF,
+                              0x02, 0, 4, 0,  0xE8, 0x03, 0, 0,
+                              0x04, 0, 4, 0,  0xFF, 0xFF, 0xFF, 0xFF,
+                              0x10, 0, 4, 0,  0xFF, 0xFF, 0xFF, 0xFF,
+                              0x20, 0, 4, 0,  0xFF, 0xFF, 0xFF, 0xFF ];
+
+        let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+        let c_name = CString::new(ACCESS_ATTRIBUTE).unwrap();
+        let result = unsafe {
+            setxattr(c_path.as_ptr(), c_name.as_ptr(), value.as_ptr() as *const libc::c_void, value.len(), 0)
+        };
+        result == 0
+    }
+
+    #[test]
+    fn file_with_acl() {
+        let scratch = ScratchDir::new("acl");
+        let path = scratch.join("file");
+        let _ = fs::File::create(&path).unwrap();
+
+        if set_acl(&path) {
+            let attrs = path.attributes().unwrap();
+            assert_eq!(ENABLED, has_extended_acl(&attrs));
+
+            let entries = entries(&path).unwrap();
+            assert!(entries.iter().any(|e| e.tag == Tag::User(1000) && e.read && !e.write));
+        }
+    }
+
+    #[test]
+    fn file_without_acl() {
+        let scratch = ScratchDir::new("no-acl");
+        let path = scratch.join("file");
+        let _ = fs::File::create(&path).unwrap();
+
+        assert_eq!(entries(&path).unwrap(), vec![]);
+    }
+}
@@ -1,37 +1,173 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
 
 use git2;
 
 use fs::fields as f;
+use fs::feature::GitOptions;
 
 
 /// Container of Git statuses for all the files in this folder's Git repository.
+///
+/// The paths of the files that get looked up have to be absolute, with no
+/// symlinks in the directories leading up to them, like the paths in the
+/// statuses; `Dir` works these out for the files it contains.
 pub struct Git {
     statuses: Vec<(PathBuf, git2::Status)>,
+
+    /// The files that have been added with `git add --intent-to-add`, which
+    /// are in the index, but without any of their contents.
+    intent_to_add: Vec<PathBuf>,
+
+    /// The directories with at least one file in the index somewhere
+    /// inside them. Only files have index entries, so these get collected
+    /// from the files’ paths.
+    tracked_dirs: HashSet<PathBuf>,
+
+    /// The statuses of the files that differ between the working tree and
+    /// the reference being compared against, if one was given and it could
+    /// be found in the repository.
+    since: Option<Vec<(PathBuf, git2::Delta)>>,
+
+    /// The operation the repository was in the middle of when it was
+    /// scanned, if any.
+    state: Option<f::RepoState>,
+
+    /// The commit that last changed each file in the directory that was
+    /// scanned, if they were asked for. Finding these means walking back
+    /// through the history, so only that one directory’s files get them.
+    last_commits: Option<HashMap<PathBuf, f::GitCommit>>,
 }
 
 impl Git {
 
     /// Discover a Git repository on or above this directory, scanning it for
     /// the files' statuses if one is found.
-    pub fn scan(path: &Path) -> Result<Git, git2::Error> {
+    pub fn scan(path: &Path, options: &GitOptions) -> Result<Git, git2::Error> {
         let repo = git2::Repository::discover(path)?;
+
+        // Inside a linked worktree (one made by `git worktree add`), the
+        // repository’s workdir is the worktree’s own directory rather than
+        // the main checkout’s, but the path it gets reported as can still
+        // differ from the paths we’re handed through symlinks or relative
+        // components. Canonicalising it here, as the directory being listed
+        // canonicalises its own path, means the two sets of paths can be
+        // compared.
         let workdir = match repo.workdir() {
-            Some(w) => w,
-            None => return Ok(Git { statuses: vec![] }),  // bare repo
+            Some(w) => w.canonicalize().unwrap_or_else(|_| w.to_path_buf()),
+            None => return Ok(Git { statuses: vec![], intent_to_add: vec![], tracked_dirs: HashSet::new(), since: None, state: None, last_commits: None }),  // bare repo
+        };
+
+        let index = repo.index().ok();
+        let mut statuses = Vec::new();
+        let mut intent_to_add = Vec::new();
+
+        for entry in repo.statuses(None)?.iter() {
+            let relative = Path::new(entry.path().unwrap());
+            let status = entry.status();
+
+            // Whether a file is only intended to be added depends on which
+            // version of libgit2 is reporting it, so the index entry itself
+            // gets checked for the flag instead.
+            if status.intersects(git2::STATUS_INDEX_NEW | git2::STATUS_WT_NEW) {
+                if let Some(ref index) = index {
+                    if is_intent_to_add(index, relative) {
+                        intent_to_add.push(workdir.join(relative));
+                    }
+                }
+            }
+
+            statuses.push((workdir.join(relative), status));
+        }
+
+        let mut tracked_dirs = HashSet::new();
+        if let Some(ref index) = index {
+            for entry in index.iter() {
+                let mut dir = Path::new(OsStr::from_bytes(&entry.path)).parent();
+
+                // Once a directory’s been added, so have all the ones above it.
+                while let Some(d) = dir {
+                    if d == Path::new("") || !tracked_dirs.insert(workdir.join(d)) {
+                        break;
+                    }
+
+                    dir = d.parent();
+                }
+            }
+        }
+
+        // A submodule with changes inside it only shows up as modified in
+        // its parent’s statuses, so finding out what the changes are means
+        // opening its repository too. Submodules that haven’t been checked
+        // out can’t be opened, and keep whatever status they already have.
+        if options.recurse_submodules {
+            for submodule in repo.submodules().unwrap_or_default() {
+                let status = match submodule.open() {
+                    Ok(sub_repo)  => submodule_status(&sub_repo),
+                    Err(_)        => continue,
+                };
+
+                let path = workdir.join(submodule.path());
+                match statuses.iter_mut().find(|p| p.0 == path) {
+                    Some(entry)  => entry.1 = entry.1 | status,
+                    None         => if !status.is_empty() { statuses.push((path, status)) },
+                }
+            }
+        }
+
+        // A reference that can’t be found isn’t an error for the whole
+        // repository: the files still get their usual statuses.
+        let since = match options.since {
+            Some(ref reference) => diff_since(&repo, &workdir, reference).ok(),
+            None                => None,
         };
 
-        let statuses = repo.statuses(None)?.iter()
-                                                .map(|e| (workdir.join(Path::new(e.path().unwrap())), e.status()))
-                                                .collect();
+        // A repository without any commits yet has no history to search,
+        // which isn’t an error either.
+        let last_commits = if options.last_commits { Some(last_commits(&repo, &workdir, path).unwrap_or_default()) }
+                                              else { None };
+
+        let state = repo_state(repo.state());
+        Ok(Git { statuses, intent_to_add, tracked_dirs, since, state, last_commits })
+    }
+
+    /// Whether the working tree was compared against a reference.
+    pub fn has_since(&self) -> bool {
+        self.since.is_some()
+    }
+
+    /// Whether the commits that last changed the files were looked up.
+    pub fn has_last_commits(&self) -> bool {
+        self.last_commits.is_some()
+    }
 
-        Ok(Git { statuses: statuses })
+    /// The commit that last changed the file at the given path, if it’s in
+    /// the directory that was scanned and is tracked.
+    pub fn last_commit(&self, path: &Path) -> Option<f::GitCommit> {
+        match self.last_commits {
+            Some(ref commits)  => commits.get(path).cloned(),
+            None               => None,
+        }
+    }
+
+    /// The operation the repository is in the middle of, such as a merge or
+    /// a rebase, if it’s in the middle of one.
+    pub fn operation_in_progress(&self) -> Option<f::RepoState> {
+        self.state
     }
 
     /// Get the status for the file at the given path, if present.
     pub fn status(&self, path: &Path) -> f::Git {
         let status = self.statuses.iter()
                                   .find(|p| p.0.as_path() == path);
+        // None of an intent-to-add file’s contents have been staged yet, so
+        // all of them count as unstaged modifications.
+        if self.intent_to_add.iter().any(|p| p == path) {
+            return f::Git { staged: f::GitStatus::IntentToAdd, unstaged: f::GitStatus::Modified };
+        }
+
         match status {
             Some(&(_, s)) => f::Git { staged: index_status(s),           unstaged: working_tree_status(s) },
             None          => f::Git { staged: f::GitStatus::NotModified, unstaged: f::GitStatus::NotModified }
@@ -41,18 +177,291 @@ impl Git {
     /// Get the combined status for all the files whose paths begin with the
     /// path that gets passed in. This is used for getting the status of
     /// directories, which don't really have an 'official' status.
+    ///
+    /// Ignored files are left out, so a directory with nothing in it but
+    /// build output doesn’t look like it has changed.
     pub fn dir_status(&self, dir: &Path) -> f::Git {
         let s = self.statuses.iter()
                              .filter(|p| p.0.starts_with(dir))
+                             .filter(|p| !p.1.contains(git2::STATUS_IGNORED))
                              .fold(git2::Status::empty(), |a, b| a | b.1);
 
         f::Git { staged: index_status(s), unstaged: working_tree_status(s) }
     }
+
+    /// Get the statuses for every file immediately inside the given
+    /// directory in one pass over the repository’s statuses, rather than
+    /// going through them once per file. A file’s status is combined with
+    /// those of everything inside it, as with `dir_status`, so directories
+    /// get their usual statuses too.
+    ///
+    /// The map’s keys are the directory’s path joined with each file’s
+    /// name. Files without any changes are left out.
+    pub fn statuses_for_dir(&self, dir: &Path) -> HashMap<PathBuf, f::Git> {
+        let mut combined: HashMap<PathBuf, git2::Status> = HashMap::new();
+
+        for &(ref path, status) in &self.statuses {
+            if status.contains(git2::STATUS_IGNORED) {
+                continue;
+            }
+
+            let name = match path.strip_prefix(dir).ok().and_then(|p| p.components().next()) {
+                Some(component) => component.as_os_str(),
+                None            => continue,
+            };
+
+            let entry = combined.entry(dir.join(name)).or_insert_with(git2::Status::empty);
+            *entry = *entry | status;
+        }
+
+        let mut statuses: HashMap<PathBuf, f::Git> = combined.into_iter()
+            .map(|(path, s)| (path, f::Git { staged: index_status(s), unstaged: working_tree_status(s) }))
+            .collect();
+
+        for path in &self.intent_to_add {
+            if path.parent() == Some(dir) {
+                if let Some(name) = path.file_name() {
+                    let _ = statuses.insert(dir.join(name), f::Git { staged: f::GitStatus::IntentToAdd, unstaged: f::GitStatus::Modified });
+                }
+            }
+        }
+
+        statuses
+    }
+
+    /// Whether the file at the given path is ignored by Git, either itself
+    /// or because it’s inside an ignored directory. Ignored files are
+    /// included in the statuses, rather than being looked up separately.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.statuses.iter()
+                     .filter(|p| p.1.contains(git2::STATUS_IGNORED))
+                     .any(|p| path.starts_with(&p.0))
+    }
+
+    /// Whether the file at the given path is tracked by Git, meaning it’s
+    /// in the index, rather than being untracked or ignored. A directory
+    /// counts as tracked if there’s a tracked file anywhere inside it.
+    ///
+    /// Files that haven’t changed don’t have statuses, so any file without
+    /// one that isn’t inside an ignored directory is tracked. Directories
+    /// never have statuses of their own, so they get looked up separately.
+    pub fn is_tracked(&self, path: &Path) -> bool {
+        if self.intent_to_add.iter().any(|p| p == path) || self.tracked_dirs.contains(path) {
+            return true;
+        }
+
+        match self.statuses.iter().find(|p| p.0 == path) {
+            Some(&(_, s))  => !s.intersects(git2::STATUS_WT_NEW | git2::STATUS_IGNORED),
+            None           => !self.is_ignored(path) && !path.is_dir(),
+        }
+    }
+
+    /// Whether the file at the given path is untracked, meaning it’s
+    /// neither in the index nor ignored, or, for a directory, whether
+    /// there’s an untracked file anywhere inside it. Files that have only
+    /// been marked as intended to be added count as tracked.
+    pub fn has_untracked(&self, path: &Path) -> bool {
+        self.statuses.iter()
+                     .filter(|p| p.1.contains(git2::STATUS_WT_NEW))
+                     .filter(|p| !self.intent_to_add.contains(&p.0))
+                     .any(|p| p.0.starts_with(path))
+    }
+
+    /// Get the status for the file at the given path compared to the
+    /// reference, if present.
+    pub fn since_status(&self, path: &Path) -> f::GitStatus {
+        let delta = self.since.iter().flat_map(|s| s.iter())
+                                     .find(|p| p.0.as_path() == path);
+        match delta {
+            Some(&(_, d)) => delta_status(d),
+            None          => f::GitStatus::NotModified,
+        }
+    }
+
+    /// Get the status compared to the reference for a directory, which
+    /// counts as modified if any of the files inside it have changed.
+    pub fn dir_since_status(&self, dir: &Path) -> f::GitStatus {
+        let changed = self.since.iter().flat_map(|s| s.iter())
+                                       .any(|p| p.0.starts_with(dir));
+        if changed { f::GitStatus::Modified }
+              else { f::GitStatus::NotModified }
+    }
+}
+
+/// The URL of the remote with the given name, if the directory at the given
+/// path is the top of a repository that has one. Directories further down
+/// inside a repository don’t count, so only the repository itself gets it.
+pub fn remote_url(path: &Path, name: &str) -> Option<String> {
+    let repo = match git2::Repository::open(path) {
+        Ok(r)   => r,
+        Err(_)  => return None,
+    };
+
+    let remote = match repo.find_remote(name) {
+        Ok(r)   => r,
+        Err(_)  => return None,
+    };
+
+    remote.url().map(String::from)
+}
+
+/// The combined status of every file in a submodule’s repository, and in
+/// any submodules nested inside that, leaving out ignored files.
+fn submodule_status(repo: &git2::Repository) -> git2::Status {
+    let mut status = match repo.statuses(None) {
+        Ok(statuses) => statuses.iter().map(|e| e.status())
+                                       .filter(|s| !s.contains(git2::STATUS_IGNORED))
+                                       .fold(git2::Status::empty(), |a, b| a | b),
+        Err(_) => git2::Status::empty(),
+    };
+
+    for submodule in repo.submodules().unwrap_or_default() {
+        if let Ok(sub_repo) = submodule.open() {
+            status = status | submodule_status(&sub_repo);
+        }
+    }
+
+    status
+}
+
+/// The flag in an index entry’s extended flags that marks it as only
+/// intended to be added (`GIT_IDXENTRY_INTENT_TO_ADD` in libgit2).
+const INTENT_TO_ADD: u16 = 1 << 13;
+
+/// Whether the file at the given path, relative to the working tree, has
+/// an entry in the index with the intent-to-add flag set.
+fn is_intent_to_add(index: &git2::Index, path: &Path) -> bool {
+    match index.get_path(path, 0) {
+        Some(entry) => entry.flags_extended & INTENT_TO_ADD != 0,
+        None        => false,
+    }
+}
+
+/// Compare the tree of the given reference to the working tree, including
+/// any changes in the index, and collect the files that differ, with the
+/// same canonical paths as the statuses.
+fn diff_since(repo: &git2::Repository, workdir: &Path, reference: &str) -> Result<Vec<(PathBuf, git2::Delta)>, git2::Error> {
+    let object = repo.revparse_single(reference)?.peel(git2::ObjectType::Tree)?;
+    let tree = match object.as_tree() {
+        Some(t) => t,
+        None    => return Err(git2::Error::from_str("Reference does not point to a tree")),
+    };
+
+    let mut options = git2::DiffOptions::new();
+    let _ = options.include_untracked(true).recurse_untracked_dirs(true);
+
+    let diff = repo.diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))?;
+    let mut deltas = Vec::new();
+    for delta in diff.deltas() {
+        let (new_file, old_file) = (delta.new_file(), delta.old_file());
+        if let Some(path) = new_file.path().or_else(|| old_file.path()) {
+            deltas.push((workdir.join(path), delta.status()));
+        }
+    }
+
+    Ok(deltas)
+}
+
+/// Finds the commit that last changed each of the files directly inside the
+/// given directory, by walking back from the current commit until each file
+/// has been found in the same state it’s in now, with its parent having had
+/// it in some other state. Only the first parent of merges gets compared.
+fn last_commits(repo: &git2::Repository, workdir: &Path, dir: &Path) -> Result<HashMap<PathBuf, f::GitCommit>, git2::Error> {
+    let mut last_commits = HashMap::new();
+
+    let relative = match dir.canonicalize().ok().and_then(|d| d.strip_prefix(workdir).ok().map(Path::to_path_buf)) {
+        Some(r)  => r,
+        None     => return Ok(last_commits),
+    };
+
+    let head = match repo.head()?.target() {
+        Some(oid)  => repo.find_commit(oid)?,
+        None       => return Ok(last_commits),
+    };
+    let mut wanted = match subtree_id(&head, &relative) {
+        Some(id)  => tree_entries(repo, id),
+        None      => return Ok(last_commits),
+    };
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(git2::SORT_TOPOLOGICAL | git2::SORT_TIME);
+
+    for oid in revwalk {
+        if wanted.is_empty() {
+            break;
+        }
+
+        // Most commits don’t change anything in the directory at all, which
+        // comparing the IDs of the directory’s trees finds out cheaply.
+        let commit = repo.find_commit(oid?)?;
+        let parent_id = commit.parent(0).ok().and_then(|p| subtree_id(&p, &relative));
+        let entries = match subtree_id(&commit, &relative) {
+            Some(id) if Some(id) != parent_id  => tree_entries(repo, id),
+            _                                  => continue,
+        };
+
+        let parent_entries = parent_id.map(|id| tree_entries(repo, id)).unwrap_or_default();
+        let changed: Vec<String> = wanted.iter()
+                                         .filter(|&(name, id)| entries.get(name) == Some(id) && parent_entries.get(name) != Some(id))
+                                         .map(|(name, _)| name.clone())
+                                         .collect();
+
+        for name in changed {
+            let _ = wanted.remove(&name);
+            let _ = last_commits.insert(workdir.join(&relative).join(name), f::GitCommit {
+                id:     commit.id().to_string().chars().take(7).collect(),
+                author: commit.author().name().unwrap_or("").to_string(),
+                time:   f::Time { seconds: commit.time().seconds(), nanoseconds: 0 },
+            });
+        }
+    }
+
+    Ok(last_commits)
+}
+
+/// The ID of the tree of the directory at the given path, relative to the
+/// top of the repository, as it was in the given commit, if it was there.
+fn subtree_id(commit: &git2::Commit, dir: &Path) -> Option<git2::Oid> {
+    let tree = match commit.tree() {
+        Ok(t)   => t,
+        Err(_)  => return None,
+    };
+
+    if dir == Path::new("") { Some(tree.id()) }
+                       else { tree.get_path(dir).ok().map(|e| e.id()) }
+}
+
+/// The names of the entries in the tree with the given ID, along with the
+/// IDs of the objects they point to. Names that aren’t valid UTF-8 get
+/// left out, as libgit2 can’t give them back as strings.
+fn tree_entries(repo: &git2::Repository, id: git2::Oid) -> HashMap<String, git2::Oid> {
+    match repo.find_tree(id) {
+        Ok(tree)  => tree.iter().filter_map(|e| e.name().map(|n| (n.to_string(), e.id()))).collect(),
+        Err(_)    => HashMap::new(),
+    }
+}
+
+/// Converts the state libgit2 reports a repository as being in, with the
+/// different kinds of rebase and sequence all counting as the same thing.
+fn repo_state(state: git2::RepositoryState) -> Option<f::RepoState> {
+    use git2::RepositoryState::*;
+
+    match state {
+        Clean                                             => None,
+        Merge                                             => Some(f::RepoState::Merge),
+        Revert | RevertSequence                           => Some(f::RepoState::Revert),
+        CherryPick | CherryPickSequence                   => Some(f::RepoState::CherryPick),
+        Bisect                                            => Some(f::RepoState::Bisect),
+        Rebase | RebaseInteractive | RebaseMerge          => Some(f::RepoState::Rebase),
+        ApplyMailbox | ApplyMailboxOrRebase               => Some(f::RepoState::ApplyMailbox),
+    }
 }
 
 /// The character to display if the file has been modified, but not staged.
 fn working_tree_status(status: git2::Status) -> f::GitStatus {
     match status {
+        s if s.contains(git2::STATUS_CONFLICTED)     => f::GitStatus::Conflicted,
         s if s.contains(git2::STATUS_WT_NEW)         => f::GitStatus::New,
         s if s.contains(git2::STATUS_WT_MODIFIED)    => f::GitStatus::Modified,
         s if s.contains(git2::STATUS_WT_DELETED)     => f::GitStatus::Deleted,
@@ -62,10 +471,23 @@ fn working_tree_status(status: git2::Status) -> f::GitStatus {
     }
 }
 
+/// The character to display if the file differs from the reference.
+fn delta_status(delta: git2::Delta) -> f::GitStatus {
+    match delta {
+        git2::Delta::Added | git2::Delta::Untracked  => f::GitStatus::New,
+        git2::Delta::Deleted                         => f::GitStatus::Deleted,
+        git2::Delta::Renamed                         => f::GitStatus::Renamed,
+        git2::Delta::Typechange                      => f::GitStatus::TypeChange,
+        git2::Delta::Unmodified | git2::Delta::Ignored  => f::GitStatus::NotModified,
+        _                                            => f::GitStatus::Modified,
+    }
+}
+
 /// The character to display if the file has been modified, and the change
 /// has been staged.
 fn index_status(status: git2::Status) -> f::GitStatus {
     match status {
+        s if s.contains(git2::STATUS_CONFLICTED)        => f::GitStatus::Conflicted,
         s if s.contains(git2::STATUS_INDEX_NEW)         => f::GitStatus::New,
         s if s.contains(git2::STATUS_INDEX_MODIFIED)    => f::GitStatus::Modified,
         s if s.contains(git2::STATUS_INDEX_DELETED)     => f::GitStatus::Deleted,
@@ -74,3 +496,322 @@ fn index_status(status: git2::Status) -> f::GitStatus {
         _                                               => f::GitStatus::NotModified,
     }
 }
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs;
+    use std::io::Write;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git").args(args).current_dir(dir)
+                                        .env("GIT_AUTHOR_NAME", "exa").env("GIT_AUTHOR_EMAIL", "exa@example.com")
+                                        .env("GIT_COMMITTER_NAME", "exa").env("GIT_COMMITTER_EMAIL", "exa@example.com")
+                                        .status().unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn intent_to_add() {
+        let scratch = ScratchDir::new("git-intent-to-add");
+        let path = &scratch.path;
+        let _ = fs::File::create(path.join("intended")).unwrap();
+        let _ = fs::File::create(path.join("staged")).unwrap();
+
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "--intent-to-add", "intended" ]);
+        git(&path, &[ "add", "staged" ]);
+
+        let git = Git::scan(&path, &GitOptions::default()).unwrap();
+        assert_eq!(f::GitStatus::IntentToAdd, git.status(&path.join("intended")).staged);
+        assert_eq!(f::GitStatus::New,         git.status(&path.join("staged")).staged);
+    }
+
+    #[test]
+    fn staged_and_unstaged() {
+        let scratch = ScratchDir::new("git-staged-and-unstaged");
+        let path = &scratch.path;
+        fs::File::create(path.join("file")).unwrap().write_all(b"committed").unwrap();
+
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "file" ]);
+        git(&path, &[ "commit", "-q", "-m", "File" ]);
+        fs::File::create(path.join("file")).unwrap().write_all(b"staged").unwrap();
+        git(&path, &[ "add", "file" ]);
+        fs::File::create(path.join("file")).unwrap().write_all(b"unstaged").unwrap();
+
+        let git = Git::scan(&path, &GitOptions::default()).unwrap();
+        let status = git.status(&path.join("file"));
+        assert_eq!(f::GitStatus::Modified, status.staged);
+        assert_eq!(f::GitStatus::Modified, status.unstaged);
+    }
+
+    #[test]
+    fn linked_worktree() {
+        let scratch = ScratchDir::new("git-worktree");
+        let main = scratch.join("main");
+        let wt = scratch.join("wt");
+        fs::create_dir(&main).unwrap();
+        fs::File::create(main.join("file")).unwrap().write_all(b"committed").unwrap();
+
+        git(&main, &[ "init", "-q" ]);
+        git(&main, &[ "add", "file" ]);
+        git(&main, &[ "commit", "-q", "-m", "File" ]);
+        git(&main, &[ "worktree", "add", "-q", wt.to_str().unwrap() ]);
+        fs::File::create(wt.join("file")).unwrap().write_all(b"modified").unwrap();
+
+        let git = Git::scan(&wt, &GitOptions::default()).unwrap();
+        assert_eq!(f::GitStatus::Modified,    git.status(&wt.join("file")).unstaged);
+        assert_eq!(f::GitStatus::NotModified, git.status(&main.join("file")).unstaged);
+    }
+
+    #[test]
+    fn tracked_files() {
+        let scratch = repo_with_ignored_file("git-tracked");
+        let path = &scratch.path;
+        fs::create_dir(path.join("untracked-dir")).unwrap();
+        let _ = fs::File::create(path.join("new")).unwrap();
+        let _ = fs::File::create(path.join("untracked-dir/new")).unwrap();
+        let _ = fs::File::create(path.join("staged")).unwrap();
+        git(&path, &[ "add", "staged" ]);
+
+        let git = Git::scan(&path, &GitOptions::default()).unwrap();
+        assert!(git.is_tracked(&path.join("dir/clean")));
+        assert!(git.is_tracked(&path.join("dir")));
+        assert!(git.is_tracked(&path.join("staged")));
+        assert!(!git.is_tracked(&path.join("new")));
+        assert!(!git.is_tracked(&path.join("dir/ignored")));
+        assert!(!git.is_tracked(&path.join("untracked-dir")));
+        assert!(!git.is_tracked(&path.join("untracked-dir/new")));
+    }
+
+    #[test]
+    fn untracked_files() {
+        let scratch = repo_with_ignored_file("git-untracked");
+        let path = &scratch.path;
+        fs::create_dir(path.join("untracked-dir")).unwrap();
+        let _ = fs::File::create(path.join("dir/new")).unwrap();
+        let _ = fs::File::create(path.join("untracked-dir/new")).unwrap();
+
+        let git = Git::scan(&path, &GitOptions::default()).unwrap();
+        assert!(git.has_untracked(&path.join("dir/new")));
+        assert!(git.has_untracked(&path.join("dir")));
+        assert!(git.has_untracked(&path.join("untracked-dir")));
+        assert!(!git.has_untracked(&path.join("dir/clean")));
+        assert!(!git.has_untracked(&path.join("dir/ignored")));
+    }
+
+    /// Makes a repository with a committed `dir/clean` file and an ignored
+    /// `dir/ignored` file.
+    fn repo_with_ignored_file(name: &str) -> ScratchDir {
+        let scratch = ScratchDir::new(name);
+        let path = scratch.path.clone();
+        fs::create_dir_all(path.join("dir")).unwrap();
+        fs::File::create(path.join(".gitignore")).unwrap().write_all(b"ignored\n").unwrap();
+        fs::File::create(path.join("dir/clean")).unwrap().write_all(b"clean").unwrap();
+        let _ = fs::File::create(path.join("dir/ignored")).unwrap();
+
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", ".gitignore", "dir/clean" ]);
+        git(&path, &[ "commit", "-q", "-m", "Files" ]);
+        scratch
+    }
+
+    #[test]
+    fn dir_status_skips_ignored() {
+        let scratch = repo_with_ignored_file("git-dir-ignored");
+        let path = &scratch.path;
+
+        let git = Git::scan(&path, &GitOptions::default()).unwrap();
+        assert!(git.is_ignored(&path.join("dir/ignored")));
+
+        let status = git.dir_status(&path.join("dir"));
+        assert_eq!(f::GitStatus::NotModified, status.staged);
+        assert_eq!(f::GitStatus::NotModified, status.unstaged);
+    }
+
+    #[test]
+    fn dir_status_with_ignored_and_modified() {
+        let scratch = repo_with_ignored_file("git-dir-ignored-modified");
+        let path = &scratch.path;
+        fs::File::create(path.join("dir/clean")).unwrap().write_all(b"changed").unwrap();
+
+        let git = Git::scan(&path, &GitOptions::default()).unwrap();
+        assert_eq!(f::GitStatus::Modified, git.dir_status(&path.join("dir")).unstaged);
+    }
+
+    #[test]
+    fn statuses_for_dir() {
+        let scratch = repo_with_ignored_file("git-statuses-for-dir");
+        let path = &scratch.path;
+        fs::create_dir(path.join("untracked-dir")).unwrap();
+        fs::File::create(path.join("dir/clean")).unwrap().write_all(b"changed").unwrap();
+        let _ = fs::File::create(path.join("untracked-dir/new")).unwrap();
+        let _ = fs::File::create(path.join("staged")).unwrap();
+        let _ = fs::File::create(path.join("intended")).unwrap();
+        git(&path, &[ "add", "staged" ]);
+        git(&path, &[ "add", "--intent-to-add", "intended" ]);
+
+        let git = Git::scan(&path, &GitOptions::default()).unwrap();
+        let statuses = git.statuses_for_dir(&path);
+
+        let status = |name: &str| statuses.get(&path.join(name));
+        assert_eq!(status("dir"),            Some(&f::Git { staged: f::GitStatus::NotModified, unstaged: f::GitStatus::Modified }));
+        assert_eq!(status("untracked-dir"),  Some(&f::Git { staged: f::GitStatus::NotModified, unstaged: f::GitStatus::New }));
+        assert_eq!(status("staged"),         Some(&f::Git { staged: f::GitStatus::New,         unstaged: f::GitStatus::NotModified }));
+        assert_eq!(status("intended"),       Some(&f::Git { staged: f::GitStatus::IntentToAdd, unstaged: f::GitStatus::Modified }));
+        assert_eq!(status(".gitignore"),     None);
+        assert_eq!(statuses.len(), 4);
+
+        // Each one should be the same as looking it up on its own.
+        for (child, s) in &statuses {
+            let expected = if child.is_dir() { git.dir_status(child) } else { git.status(child) };
+            assert_eq!(&expected, s);
+        }
+    }
+
+    #[test]
+    fn last_commits() {
+        let scratch = ScratchDir::new("git-last-commits");
+        let path = &scratch.path;
+        fs::File::create(path.join("unchanged")).unwrap().write_all(b"first").unwrap();
+        fs::File::create(path.join("changed")).unwrap().write_all(b"first").unwrap();
+
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "unchanged", "changed" ]);
+        git(&path, &[ "commit", "-q", "-m", "First" ]);
+        fs::File::create(path.join("changed")).unwrap().write_all(b"second").unwrap();
+        git(&path, &[ "commit", "-q", "-a", "-m", "Second" ]);
+        let _ = fs::File::create(path.join("untracked")).unwrap();
+
+        let short_hash = |rev: &str| {
+            let output = Command::new("git").args(&[ "rev-parse", "--short=7", rev ]).current_dir(&path).output().unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+
+        let options = GitOptions { last_commits: true, ..GitOptions::default() };
+        let git = Git::scan(&path, &options).unwrap();
+        let id = |name: &str| git.last_commit(&path.join(name)).map(|c| c.id);
+
+        assert!(git.has_last_commits());
+        assert_eq!(id("unchanged"), Some(short_hash("HEAD~1")));
+        assert_eq!(id("changed"),   Some(short_hash("HEAD")));
+        assert_eq!(id("untracked"), None);
+        assert_eq!(git.last_commit(&path.join("changed")).unwrap().author, "exa");
+    }
+
+    #[test]
+    fn remote_urls() {
+        let scratch = ScratchDir::new("git-remote");
+        let path = &scratch.path;
+        fs::create_dir_all(path.join("with-origin/inner")).unwrap();
+        fs::create_dir_all(path.join("without-origin")).unwrap();
+
+        git(&path.join("with-origin"), &[ "init", "-q" ]);
+        git(&path.join("with-origin"), &[ "remote", "add", "origin", "https://example.com/exa.git" ]);
+        git(&path.join("with-origin"), &[ "remote", "add", "mirror", "https://mirror.example.com/exa.git" ]);
+        git(&path.join("without-origin"), &[ "init", "-q" ]);
+
+        assert_eq!(Some("https://example.com/exa.git".into()),        remote_url(&path.join("with-origin"), "origin"));
+        assert_eq!(Some("https://mirror.example.com/exa.git".into()), remote_url(&path.join("with-origin"), "mirror"));
+        assert_eq!(None, remote_url(&path.join("with-origin/inner"), "origin"));
+        assert_eq!(None, remote_url(&path.join("without-origin"), "origin"));
+        assert_eq!(None, remote_url(&path, "origin"));
+    }
+
+    /// Makes a repository with a submodule in `sub`, which has a committed
+    /// `file` in it.
+    fn repo_with_submodule(name: &str) -> ScratchDir {
+        let scratch = ScratchDir::new(name);
+        let path = scratch.path.clone();
+        fs::create_dir_all(path.join("origin")).unwrap();
+        fs::create_dir_all(path.join("parent")).unwrap();
+
+        let origin = path.join("origin");
+        fs::File::create(origin.join("file")).unwrap().write_all(b"original").unwrap();
+        git(&origin, &[ "init", "-q" ]);
+        git(&origin, &[ "add", "file" ]);
+        git(&origin, &[ "commit", "-q", "-m", "File" ]);
+
+        let parent = path.join("parent");
+        git(&parent, &[ "init", "-q" ]);
+        git(&parent, &[ "-c", "protocol.file.allow=always", "submodule", "add", "-q", origin.to_str().unwrap(), "sub" ]);
+        git(&parent, &[ "commit", "-q", "-m", "Submodule" ]);
+        scratch
+    }
+
+    #[test]
+    fn submodule_with_modified_file() {
+        let scratch = repo_with_submodule("git-submodule-modified");
+        let path = &scratch.path;
+        let parent = path.join("parent");
+        fs::File::create(parent.join("sub/file")).unwrap().write_all(b"changed").unwrap();
+
+        let options = GitOptions { recurse_submodules: true, ..GitOptions::default() };
+        let git = Git::scan(&parent, &options).unwrap();
+        assert_eq!(f::GitStatus::Modified, git.dir_status(&parent.join("sub")).unstaged);
+    }
+
+    #[test]
+    fn submodule_with_new_file() {
+        let scratch = repo_with_submodule("git-submodule-new");
+        let path = &scratch.path;
+        let parent = path.join("parent");
+        let _ = fs::File::create(parent.join("sub/another")).unwrap();
+
+        let options = GitOptions { recurse_submodules: true, ..GitOptions::default() };
+        let git = Git::scan(&parent, &options).unwrap();
+        assert_eq!(f::GitStatus::New, git.dir_status(&parent.join("sub")).unstaged);
+    }
+
+    /// Makes a repository that’s stopped in the middle of merging a branch,
+    /// with a conflict in its `file`.
+    fn repo_with_conflict(name: &str) -> ScratchDir {
+        let scratch = ScratchDir::new(name);
+        let path = scratch.path.clone();
+
+        fs::File::create(path.join("file")).unwrap().write_all(b"base").unwrap();
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "file" ]);
+        git(&path, &[ "commit", "-q", "-m", "Base" ]);
+
+        git(&path, &[ "checkout", "-q", "-b", "other" ]);
+        fs::File::create(path.join("file")).unwrap().write_all(b"theirs").unwrap();
+        git(&path, &[ "commit", "-q", "-am", "Theirs" ]);
+        git(&path, &[ "checkout", "-q", "-" ]);
+        fs::File::create(path.join("file")).unwrap().write_all(b"ours").unwrap();
+        git(&path, &[ "commit", "-q", "-am", "Ours" ]);
+
+        let merge = Command::new("git").args(&[ "merge", "-q", "other" ]).current_dir(&path).output().unwrap();
+        assert!(!merge.status.success(), "merge should have stopped at the conflict");
+        scratch
+    }
+
+    #[test]
+    fn merge_in_progress() {
+        let scratch = repo_with_conflict("git-merge-in-progress");
+        let path = &scratch.path;
+
+        let merging = Git::scan(&path, &GitOptions::default()).unwrap();
+        assert_eq!(Some(f::RepoState::Merge), merging.operation_in_progress());
+        assert_eq!(f::GitStatus::Conflicted, merging.status(&path.join("file")).unstaged);
+
+        git(&path, &[ "merge", "--abort" ]);
+        let aborted = Git::scan(&path, &GitOptions::default()).unwrap();
+        assert_eq!(None, aborted.operation_in_progress());
+    }
+
+    #[test]
+    fn rebases_count_the_same() {
+        use git2::RepositoryState;
+
+        assert_eq!(None, repo_state(RepositoryState::Clean));
+        assert_eq!(Some(f::RepoState::Rebase), repo_state(RepositoryState::RebaseInteractive));
+        assert_eq!(Some(f::RepoState::Rebase), repo_state(RepositoryState::RebaseMerge));
+        assert_eq!(Some(f::RepoState::CherryPick), repo_state(RepositoryState::CherryPickSequence));
+    }
+}
@@ -1,21 +1,76 @@
 // Extended attribute support
 pub mod xattr;
 
+// Access control list support
+pub mod acl;
+
 // Git support
 
+/// What to find out about a Git repository when one gets scanned, beyond
+/// the status of each file.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct GitOptions {
+
+    /// A reference, such as a branch name, tag, or commit hash, to compare
+    /// the working tree against, as well as the last commit.
+    pub since: Option<String>,
+
+    /// Whether to open the repositories of any submodules, and give each
+    /// submodule the combined status of the files inside it, rather than
+    /// just whether it’s changed. This means scanning every submodule as
+    /// well as the repository itself, so it’s off unless asked for.
+    pub recurse_submodules: bool,
+
+    /// Whether to find the commit that last changed each file in the
+    /// directory being scanned, which means walking back through the
+    /// repository’s history.
+    pub last_commits: bool,
+}
+
 #[cfg(feature="git")] mod git;
-#[cfg(feature="git")] pub use self::git::Git;
+#[cfg(feature="git")] pub use self::git::{Git, remote_url};
 
 #[cfg(not(feature="git"))] pub struct Git;
-#[cfg(not(feature="git"))] use std::path::Path;
+#[cfg(not(feature="git"))] use std::collections::HashMap;
+#[cfg(not(feature="git"))] use std::path::{Path, PathBuf};
 #[cfg(not(feature="git"))] use fs::fields;
 
+#[cfg(not(feature="git"))]
+pub fn remote_url(_: &Path, _: &str) -> Option<String> {
+    None
+}
+
 #[cfg(not(feature="git"))]
 impl Git {
-    pub fn scan(_: &Path) -> Result<Git, ()> {
+    pub fn scan(_: &Path, _: &GitOptions) -> Result<Git, ()> {
         Err(())
     }
 
+    pub fn has_since(&self) -> bool {
+        false
+    }
+
+    pub fn operation_in_progress(&self) -> Option<fields::RepoState> {
+        None
+    }
+
+    pub fn has_last_commits(&self) -> bool {
+        false
+    }
+
+    pub fn last_commit(&self, path: &Path) -> Option<fields::GitCommit> {
+        let _ = self.status(path);
+        None
+    }
+
+    pub fn since_status(&self, path: &Path) -> fields::GitStatus {
+        self.status(path).staged
+    }
+
+    pub fn dir_since_status(&self, path: &Path) -> fields::GitStatus {
+        self.since_status(path)
+    }
+
     pub fn status(&self, _: &Path) -> fields::Git {
         panic!("Tried to access a Git repo without Git support!");
     }
@@ -23,4 +78,21 @@ impl Git {
     pub fn dir_status(&self, path: &Path) -> fields::Git {
         self.status(path)
     }
+
+    pub fn statuses_for_dir(&self, path: &Path) -> HashMap<PathBuf, fields::Git> {
+        let _ = self.status(path);
+        HashMap::new()
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.status(path).is_modified()
+    }
+
+    pub fn is_tracked(&self, path: &Path) -> bool {
+        self.status(path).is_modified()
+    }
+
+    pub fn has_untracked(&self, path: &Path) -> bool {
+        self.status(path).is_modified()
+    }
 }
@@ -14,6 +14,8 @@
 // C-style `blkcnt_t` types don’t follow Rust’s rules!
 #![allow(non_camel_case_types)]
 
+use std::time::SystemTime;
+
 
 /// The type of a file’s block count.
 pub type blkcnt_t = u64;
@@ -42,7 +44,7 @@ pub type uid_t = u32;
 /// regular file. (See the `filetype` module for those checks.)
 ///
 /// Its ordering is used when sorting by type.
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum Type {
     Directory, File, Link, Pipe, Socket, CharDevice, BlockDevice, Special,
 }
@@ -76,13 +78,14 @@ pub struct Permissions {
     pub setuid:         bool,
 }
 
-/// The three pieces of information that are displayed as a single column in
-/// the details view. These values are fused together to make the output a
+/// The pieces of information that are displayed as a single column in the
+/// details view. These values are fused together to make the output a
 /// little more compressed.
 pub struct PermissionsPlus {
     pub file_type:   Type,
     pub permissions: Permissions,
     pub xattrs:      bool,
+    pub acl:         bool,
 }
 
 
@@ -119,6 +122,197 @@ pub enum Blocks {
 }
 
 
+/// The number of entries inside a directory, counting everything inside its
+/// subdirectories too.
+pub enum EntryCount {
+
+    /// This directory contains this many entries.
+    Some(u64),
+
+    /// This file isn’t a directory, or it couldn’t be read.
+    None,
+}
+
+
+/// The number of entries directly inside a directory, shown in place of its
+/// size.
+pub enum ItemCount {
+
+    /// This directory contains this many entries.
+    Some(u64),
+
+    /// This directory couldn’t be read.
+    Unreadable,
+
+    /// This file isn’t a directory.
+    NotADirectory,
+}
+
+
+/// Whether a file is **sparse**: whether it has fewer blocks allocated to it
+/// on disk than its size says it should need, because parts of it are holes
+/// that read back as zeroes without being stored anywhere.
+pub enum Sparseness {
+
+    /// This file takes up less room on disk than its size, with this
+    /// many bytes actually allocated to it.
+    Sparse { allocated: u64 },
+
+    /// This file takes up at least as much room as its size, or isn’t a
+    /// regular file, so can’t be sparse.
+    Dense,
+}
+
+
+/// Whether a file’s **timestamps** look wrong: whether it was last modified
+/// before it was created, which happens when a file gets copied with its
+/// old modified time kept, or when the clock was wrong at some point.
+#[derive(PartialEq, Debug)]
+pub enum TimeAnomaly {
+
+    /// This file’s modified time is earlier than its birth time.
+    ModifiedBeforeBirth,
+
+    /// This file was modified at or after the time it was created.
+    Consistent,
+
+    /// This platform or filesystem doesn’t record when files were created,
+    /// so there’s nothing to compare against.
+    Unknown,
+}
+
+impl TimeAnomaly {
+
+    /// Compares a file’s modified time against its birth time.
+    pub fn between(modified: SystemTime, birth: SystemTime) -> TimeAnomaly {
+        if modified < birth { TimeAnomaly::ModifiedBeforeBirth }
+                       else { TimeAnomaly::Consistent }
+    }
+}
+
+
+/// The **file flags** that macOS and the BSDs let users and the system set
+/// on files, such as making them immutable, or hiding them from the Finder.
+/// Other platforms don’t have these, so their files never have any set.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct FileFlags(pub u32);
+
+/// Flags that stop a file being changed, by its owner or by anyone.
+const UF_IMMUTABLE: u32 = 0x0000_0002;
+const SF_IMMUTABLE: u32 = 0x0002_0000;
+
+/// The flags that get named, with the names `chflags` and `ls -lO` use for
+/// them. These bits mean the same thing on every platform that has them;
+/// ones that differ between platforms aren’t named at all.
+const FLAG_NAMES: &[(u32, &str)] = &[
+    (0x0000_0001,   "nodump"),
+    (UF_IMMUTABLE,  "uchg"),
+    (0x0000_0004,   "uappnd"),
+    (0x0000_0008,   "opaque"),
+    (0x0000_8000,   "hidden"),
+    (0x0001_0000,   "arch"),
+    (SF_IMMUTABLE,  "schg"),
+    (0x0004_0000,   "sappnd"),
+];
+
+impl FileFlags {
+
+    /// The names of the flags that are set, in the order `ls` lists them.
+    pub fn names(&self) -> Vec<&'static str> {
+        FLAG_NAMES.iter().filter(|&&(bit, _)| self.0 & bit != 0)
+                         .map(|&(_, name)| name)
+                         .collect()
+    }
+
+    /// Whether this file can’t be changed, either because its owner has
+    /// said so or because the system has.
+    pub fn is_immutable(&self) -> bool {
+        self.0 & (UF_IMMUTABLE | SF_IMMUTABLE) != 0
+    }
+}
+
+
+/// What a file’s **content** looks like, going by the first few bytes of
+/// it. This is only a guess, and can be wrong about files that are mostly
+/// text with binary data further in, or the other way around.
+#[derive(PartialEq, Debug)]
+pub enum ContentType {
+
+    /// This file is valid UTF-8 text with no NUL bytes.
+    Text,
+
+    /// This file has NUL bytes or invalid UTF-8 in it.
+    Binary,
+
+    /// This file has nothing in it.
+    Empty,
+
+    /// This file couldn’t be opened or read, such as because of its
+    /// permissions.
+    Unreadable,
+
+    /// This isn’t a regular file, so has no content to look at.
+    NotAFile,
+}
+
+
+/// The number of lines in a text file, counted the same way as `wc -l`.
+pub enum LineCount {
+
+    /// This file is text, with this many newlines in it.
+    Some(u64),
+
+    /// This isn’t a regular file, or it’s binary, or it couldn’t be read,
+    /// or it’s too big to be worth reading all of.
+    None,
+}
+
+
+/// Roughly how long ago one of a file’s timestamps was, for the age column.
+/// Each bucket covers the time from the end of the one before it, up to
+/// its threshold.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Age {
+    Now,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Old,
+}
+
+impl Age {
+
+    /// The label this bucket gets shown as.
+    pub fn label(&self) -> &'static str {
+        match *self {
+            Age::Now    => "now",
+            Age::Hour   => "hour",
+            Age::Day    => "day",
+            Age::Week   => "week",
+            Age::Month  => "month",
+            Age::Old    => "old",
+        }
+    }
+}
+
+
+/// What the user running exa can do to a file, going by its permission bits
+/// and who owns it.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Access {
+
+    /// The file can be read and written to.
+    Full,
+
+    /// The file can be read, but not written to.
+    ReadOnly,
+
+    /// The file can’t be read.
+    Denied,
+}
+
+
 /// The ID of the user that owns a file. This will only ever be a number;
 /// looking up the username is done in the `display` module.
 pub struct User(pub uid_t);
@@ -176,6 +370,7 @@ pub struct Time {
 /// A file’s status in a Git repository. Whether a file is in a repository or
 /// not is handled by the Git module, rather than having a “null” variant in
 /// this enum.
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub enum GitStatus {
 
     /// This file hasn’t changed since the last commit.
@@ -196,11 +391,85 @@ pub enum GitStatus {
 
     /// A file that’s had its type (such as the file permissions) changed.
     TypeChange,
+
+    /// A new file that’s been added with `git add --intent-to-add`, so Git
+    /// knows about it, but none of its contents have been staged.
+    IntentToAdd,
+
+    /// A file with conflicts from a merge that haven’t been resolved yet.
+    Conflicted,
+}
+
+/// A file’s status compared to a given Git reference, rather than to the
+/// last commit. This covers every change to the file, committed or not, and
+/// staged or not, since that reference.
+pub struct GitSince(pub GitStatus);
+
+impl GitSince {
+
+    /// Whether the file differs from the reference at all.
+    pub fn is_modified(&self) -> bool {
+        match self.0 {
+            GitStatus::NotModified  => false,
+            _                       => true,
+        }
+    }
+}
+
+/// An operation that a repository is in the middle of, such as a merge that
+/// stopped because of conflicts, which explains why files are conflicted.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum RepoState {
+    Merge,
+    Revert,
+    CherryPick,
+    Bisect,
+    Rebase,
+    ApplyMailbox,
 }
 
+impl RepoState {
+
+    /// A description of the operation, such as “merge in progress”.
+    pub fn description(&self) -> &'static str {
+        match *self {
+            RepoState::Merge         => "merge in progress",
+            RepoState::Revert        => "revert in progress",
+            RepoState::CherryPick    => "cherry-pick in progress",
+            RepoState::Bisect        => "bisect in progress",
+            RepoState::Rebase        => "rebase in progress",
+            RepoState::ApplyMailbox  => "am in progress",
+        }
+    }
+}
+
+/// The URL of one of the remotes of the repository a directory is the top
+/// of, if it is one, and the repository has a remote with that name.
+pub struct GitRemote(pub Option<String>);
+
+/// The commit that last changed a file in a Git repository: the most recent
+/// one to leave the file the way it is in the current commit.
+#[derive(Clone)]
+pub struct GitCommit {
+
+    /// The commit’s hash, shortened to its first seven characters.
+    pub id: String,
+
+    /// The name of the commit’s author.
+    pub author: String,
+
+    /// When the commit was made.
+    pub time: Time,
+}
+
+/// The commit that last changed a file, if it’s tracked, and the commits
+/// were looked up for the directory it’s in.
+pub struct GitLastCommit(pub Option<GitCommit>);
+
 /// A file’s complete Git status. It’s possible to make changes to a file, add
 /// it to the staging area, then make *more* changes, so we need to list each
 /// file’s status for both of these.
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub struct Git {
     pub staged:   GitStatus,
     pub unstaged: GitStatus,
@@ -212,4 +481,13 @@ impl Git {
     pub fn empty() -> Git {
         Git { staged: GitStatus::NotModified, unstaged: GitStatus::NotModified }
     }
+
+    /// Whether anything at all has been done to the file since the last
+    /// commit, whether the change has been staged or not.
+    pub fn is_modified(&self) -> bool {
+        match (&self.staged, &self.unstaged) {
+            (&GitStatus::NotModified, &GitStatus::NotModified) => false,
+            _                                                  => true,
+        }
+    }
 }
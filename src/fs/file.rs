@@ -4,10 +4,16 @@ use std::fs;
 use std::io::Error as IOError;
 use std::io::Result as IOResult;
 use std::os::unix::fs::{MetadataExt, PermissionsExt, FileTypeExt};
-use std::path::{Path, PathBuf};
-
-use fs::dir::Dir;
+use std::env::current_dir;
+use std::path::{Component, Path, PathBuf};
+
+use fs::access::Identity;
+use fs::dir::{Dir, DotFilter};
+use fs::content;
+use fs::entries;
+use fs::feature::{self, GitOptions};
 use fs::fields as f;
+use timings::{self, Stage};
 
 
 /// A **File** is a wrapper around one of Rust's Path objects, along with
@@ -62,13 +68,28 @@ impl<'dir> File<'dir> {
           FN: Into<Option<String>>
     {
         let parent_dir = parent_dir.into();
-        let metadata   = fs::symlink_metadata(&path)?;
+        let metadata   = timings::time(Stage::Stat, || fs::symlink_metadata(&path))?;
         let name       = filename.into().unwrap_or_else(|| File::filename(&path));
         let ext        = File::ext(&path);
 
         Ok(File { path, parent_dir, metadata, ext, name })
     }
 
+    /// If this file is a symbolic link, replaces its metadata with that of
+    /// the file the link points to, so it gets treated as that file from
+    /// then on, while keeping its own path and name. Returns an `Err` if the
+    /// link is broken.
+    ///
+    /// This is what happens to links given on the command-line when the
+    /// user asks for them to be dereferenced.
+    pub fn dereference(mut self) -> IOResult<File<'dir>> {
+        if self.is_link() {
+            self.metadata = fs::metadata(&self.path)?;
+        }
+
+        Ok(self)
+    }
+
     /// A file’s name is derived from its string. This needs to handle directories
     /// such as `/` or `..`, which have no `file_name` component. So instead, just
     /// use the last component as the name.
@@ -103,14 +124,24 @@ impl<'dir> File<'dir> {
         self.metadata.is_dir()
     }
 
+    /// Whether this file is a directory, or a symlink that leads to one.
+    pub fn points_to_directory(&self) -> bool {
+        self.is_directory() || (self.is_link() && fs::metadata(&self.path).map(|m| m.is_dir()).unwrap_or(false))
+    }
+
     /// If this file is a directory on the filesystem, then clone its
     /// `PathBuf` for use in one of our own `Dir` objects, and read a list of
     /// its contents.
     ///
     /// Returns an IO error upon failure, but this shouldn't be used to check
     /// if a `File` is a directory or not! For that, just use `is_directory()`.
-    pub fn to_dir(&self, scan_for_git: bool) -> IOResult<Dir> {
-        Dir::read_dir(self.path.clone(), scan_for_git)
+    pub fn to_dir(&self, git: Option<&GitOptions>) -> IOResult<Dir> {
+        let mut dir = Dir::read_dir(self.path.clone(), git)?;
+        if let Some(parent) = self.parent_dir {
+            dir.share_entry_counts(parent);
+        }
+
+        Ok(dir)
     }
 
     /// Whether this file is a regular file on the filesystem - that is, not a
@@ -127,6 +158,13 @@ impl<'dir> File<'dir> {
         self.is_file() && (self.metadata.permissions().mode() & bit) == bit
     }
 
+    /// Whether this file is a regular file with any of its execute bits
+    /// set, whether it’s for the file’s user, its group, or anyone else.
+    pub fn has_execute_bit(&self) -> bool {
+        let bits = modes::USER_EXECUTE | modes::GROUP_EXECUTE | modes::OTHER_EXECUTE;
+        self.is_file() && (self.metadata.permissions().mode() & bits) != 0
+    }
+
     /// Whether this file is a symlink on the filesystem.
     pub fn is_link(&self) -> bool {
         self.metadata.file_type().is_symlink()
@@ -182,6 +220,21 @@ impl<'dir> File<'dir> {
     /// existed. If this file cannot be read at all, returns the error that
     /// we got when we tried to read it.
     pub fn link_target(&self) -> FileTarget<'dir> {
+        self.link_target_as(TargetPath::Raw)
+    }
+
+    /// The canonical path of the file this symlink ends up at, after
+    /// following it and any other links along the way, or `None` if it’s
+    /// broken. Links that go round in a loop count as broken too, as the OS
+    /// gives up on resolving them after a certain number of links.
+    pub fn canonical_target(&self) -> Option<PathBuf> {
+        fs::canonicalize(&self.path).ok()
+    }
+
+    /// Follows this symlink like `link_target`, but with the path of a
+    /// working link’s target expressed the given way. Broken links keep
+    /// the path they were written with, as there’s nothing to resolve.
+    pub fn link_target_as(&self, target_path: TargetPath) -> FileTarget<'dir> {
 
         // We need to be careful to treat the path actually pointed to by
         // this file -- which could be absolute or relative -- to the path
@@ -197,6 +250,12 @@ impl<'dir> File<'dir> {
         // Use plain `metadata` instead of `symlink_metadata` - we *want* to
         // follow links.
         if let Ok(metadata) = fs::metadata(&absolute_path) {
+            let path = match target_path {
+                TargetPath::Raw       => path,
+                TargetPath::Absolute  => resolve(&absolute_path),
+                TargetPath::Relative  => self.relative_to_listing(&resolve(&absolute_path)),
+            };
+
             let ext  = File::ext(&path);
             let name = File::filename(&path);
             FileTarget::Ok(File { parent_dir: None, path, ext, metadata, name })
@@ -206,6 +265,22 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// Re-expresses the given absolute path relative to the directory this
+    /// file is being listed in, or, for a file given as an argument, the
+    /// directory exa is being run from. The path is left absolute if that
+    /// directory can’t be resolved.
+    fn relative_to_listing(&self, path: &Path) -> PathBuf {
+        let base = match self.parent_dir {
+            Some(dir)  => fs::canonicalize(&dir.path),
+            None       => current_dir(),
+        };
+
+        match base {
+            Ok(base)  => relative_path(&base, path),
+            Err(_)    => path.to_path_buf(),
+        }
+    }
+
     /// This file's number of hard links.
     ///
     /// It also reports whether this is both a regular file, and a file with
@@ -227,6 +302,84 @@ impl<'dir> File<'dir> {
         f::Inode(self.metadata.ino())
     }
 
+    /// Whether this file is sparse, which is worked out by comparing its size
+    /// against the number of 512-byte blocks allocated to it. (`st_blocks`
+    /// is always measured in 512-byte units, whatever the filesystem’s
+    /// actual block size is.)
+    ///
+    /// Some filesystems can also share data between files that have been
+    /// cloned or reflinked, but there’s no portable way to find out about
+    /// that, so it isn’t reported here.
+    pub fn sparseness(&self) -> f::Sparseness {
+        let allocated = self.allocated_bytes();
+
+        if self.is_file() && is_sparse(self.metadata.len(), allocated, self.metadata.blksize()) {
+            f::Sparseness::Sparse { allocated }
+        }
+        else {
+            f::Sparseness::Dense
+        }
+    }
+
+    /// Whether this file looks like it contains text or binary data, going
+    /// by the first kibibyte of it.
+    ///
+    /// This opens and reads the file, so it should only be used when the
+    /// user wants it.
+    pub fn content_type(&self) -> f::ContentType {
+        if self.is_file() { content::read_content_type(&self.path) }
+                     else { f::ContentType::NotAFile }
+    }
+
+    /// The number of lines in this file, if it’s a regular text file that
+    /// isn’t too big to read all of.
+    ///
+    /// This reads the whole file, so it should only be used when the user
+    /// wants it.
+    pub fn line_count(&self) -> f::LineCount {
+        if self.is_file() && self.metadata.len() <= content::LINE_COUNT_LIMIT {
+            content::read_line_count(&self.path)
+        }
+        else {
+            f::LineCount::None
+        }
+    }
+
+    /// The number of entries inside this directory and all of its
+    /// subdirectories, if it’s a directory that can be read.
+    ///
+    /// This walks the whole tree below the directory the first time it gets
+    /// asked for during a listing, so it should only be used when the user
+    /// wants it.
+    pub fn entry_count(&self) -> f::EntryCount {
+        if !self.is_directory() {
+            return f::EntryCount::None;
+        }
+
+        let count = match self.parent_dir {
+            Some(dir)  => entries::recursive_count(&self.path, &self.metadata, dir.entry_counts()),
+            None       => entries::recursive_count(&self.path, &self.metadata, &entries::EntryCounts::default()),
+        };
+
+        match count {
+            Some(count)  => f::EntryCount::Some(count),
+            None         => f::EntryCount::None,
+        }
+    }
+
+    /// The number of entries directly inside this directory, leaving out
+    /// dotfiles if the dot filter would hide them.
+    pub fn item_count(&self, dot_filter: DotFilter) -> f::ItemCount {
+        if !self.is_directory() {
+            return f::ItemCount::NotADirectory;
+        }
+
+        match entries::shallow_count(&self.path, dot_filter.shows_dotfiles()) {
+            Some(count)  => f::ItemCount::Some(count),
+            None         => f::ItemCount::Unreadable,
+        }
+    }
+
     /// This file's number of filesystem blocks.
     ///
     /// (Not the size of each block, which we don't actually report on)
@@ -249,6 +402,12 @@ impl<'dir> File<'dir> {
         f::Group(self.metadata.gid())
     }
 
+    /// What the given identity can do to this file, going by its
+    /// permissions and who owns it.
+    pub fn access(&self, identity: &Identity) -> f::Access {
+        identity.access(&self.permissions(), &self.user(), &self.group())
+    }
+
     /// This file’s size, if it’s a regular file.
     ///
     /// For directories, no size is given. Although they do have a size on
@@ -273,6 +432,20 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// How much space this file takes up on disk, if it’s a regular file.
+    /// This can be less than its size if it’s sparse, or more, because the
+    /// space gets allocated in whole blocks.
+    pub fn allocated_size(&self) -> f::Size {
+        if self.is_file() { f::Size::Some(self.allocated_bytes()) }
+                     else { f::Size::None }
+    }
+
+    /// The number of bytes in the blocks allocated to this file, which
+    /// `st_blocks` counts in 512-byte units.
+    fn allocated_bytes(&self) -> u64 {
+        self.metadata.blocks() * 512
+    }
+
     /// This file’s last modified timestamp.
     pub fn modified_time(&self) -> f::Time {
         f::Time {
@@ -297,35 +470,44 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// The flags set on this file. Only macOS and the BSDs have these; on
+    /// every other platform, files never have any.
+    pub fn flags(&self) -> f::FileFlags {
+        f::FileFlags(st_flags(&self.metadata))
+    }
+
+    /// Whether this file was modified before it was created. Only some
+    /// platforms keep track of when files were created; on the others,
+    /// there’s no way to tell.
+    pub fn time_anomaly(&self) -> f::TimeAnomaly {
+        match (self.metadata.modified(), self.metadata.created()) {
+            (Ok(modified), Ok(birth))  => f::TimeAnomaly::between(modified, birth),
+            _                          => f::TimeAnomaly::Unknown,
+        }
+    }
+
     /// This file’s ‘type’.
     ///
     /// This is used a the leftmost character of the permissions column.
     /// The file type can usually be guessed from the colour of the file, but
     /// ls puts this character there.
+    ///
+    /// The type is read straight from the format bits of the file’s mode, so
+    /// every kind of file gets classified, including the ones the standard
+    /// library has no method for.
     pub fn type_char(&self) -> f::Type {
-        if self.is_file() {
-            f::Type::File
-        }
-        else if self.is_directory() {
-            f::Type::Directory
-        }
-        else if self.is_pipe() {
-            f::Type::Pipe
-        }
-        else if self.is_link() {
-            f::Type::Link
-        }
-        else if self.is_char_device() {
-            f::Type::CharDevice
-        }
-        else if self.is_block_device() {
-            f::Type::BlockDevice
-        }
-        else if self.is_socket() {
-            f::Type::Socket
-        }
-        else {
-            f::Type::Special
+        match self.metadata.mode() & modes::TYPE_MASK {
+            modes::REGULAR       => f::Type::File,
+            modes::DIRECTORY     => f::Type::Directory,
+            modes::FIFO          => f::Type::Pipe,
+            modes::SYMLINK       => f::Type::Link,
+            modes::CHAR_DEVICE   => f::Type::CharDevice,
+            modes::BLOCK_DEVICE  => f::Type::BlockDevice,
+            modes::SOCKET        => f::Type::Socket,
+
+            // Solaris doors and BSD whiteouts don’t get a letter of their
+            // own, and neither does anything else unexpected.
+            _                    => f::Type::Special,
         }
     }
 
@@ -376,18 +558,65 @@ impl<'dir> File<'dir> {
     /// directory, so will not work if this file has just been passed in on
     /// the command line.
     pub fn git_status(&self) -> f::Git {
-        use std::env::current_dir;
-
         match self.parent_dir {
             None    => f::Git { staged: f::GitStatus::NotModified, unstaged: f::GitStatus::NotModified },
-            Some(d) => {
-                let cwd = match current_dir() {
-                    Err(_)  => Path::new(".").join(&self.path),
-                    Ok(dir) => dir.join(&self.path),
-                };
+            Some(d) => d.git_status(&self.path, self.is_directory()),
+        }
+    }
 
-                d.git_status(&cwd, self.is_directory())
-            },
+    /// The URL of the remote with the given name, if this is a directory at
+    /// the top of a Git repository with that remote. Unlike the statuses,
+    /// this gets looked up from the directory itself, so it works for
+    /// directories of repositories that aren’t in one themselves.
+    pub fn git_remote(&self, name: &str) -> f::GitRemote {
+        if self.is_directory() { f::GitRemote(feature::remote_url(&self.path, name)) }
+                          else { f::GitRemote(None) }
+    }
+
+    /// The commit that last changed this file, if it’s tracked in the
+    /// repository of the directory it’s in, and that directory’s commits
+    /// were looked up.
+    pub fn git_last_commit(&self) -> f::GitLastCommit {
+        match self.parent_dir {
+            None    => f::GitLastCommit(None),
+            Some(d) => f::GitLastCommit(d.git_last_commit(&self.path)),
+        }
+    }
+
+    /// Whether this file is ignored by Git. As with `git_status`, this
+    /// requires the parent directory to have been scanned for a repository.
+    pub fn is_git_ignored(&self) -> bool {
+        match self.parent_dir {
+            None    => false,
+            Some(d) => d.is_git_ignored(&self.path),
+        }
+    }
+
+    /// Whether this file is tracked by Git, or is a directory with a
+    /// tracked file inside it.
+    pub fn is_git_tracked(&self) -> bool {
+        match self.parent_dir {
+            None    => false,
+            Some(d) => d.is_git_tracked(&self.path),
+        }
+    }
+
+    /// Whether this file is untracked by Git, or is a directory with an
+    /// untracked file inside it.
+    pub fn has_git_untracked(&self) -> bool {
+        match self.parent_dir {
+            None    => false,
+            Some(d) => d.has_git_untracked(&self.path),
+        }
+    }
+
+    /// This file's Git status compared to the reference that its parent
+    /// directory's repository was compared against. As with `git_status`,
+    /// this doesn't work for files passed in on the command line.
+    pub fn git_since_status(&self) -> f::GitSince {
+        match self.parent_dir {
+            None    => f::GitSince(f::GitStatus::NotModified),
+            Some(d) => f::GitSince(d.git_since_status(&self.path, self.is_directory())),
         }
     }
 }
@@ -400,6 +629,63 @@ impl<'a> AsRef<File<'a>> for File<'a> {
 }
 
 
+/// How to show the path of the file a working symlink points to.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TargetPath {
+
+    /// Show the path exactly as it was written in the link.
+    Raw,
+
+    /// Show the absolute path of the target, with any `..` components and
+    /// symlinked directories on the way resolved.
+    Absolute,
+
+    /// Show the resolved path of the target, relative to the directory
+    /// being listed.
+    Relative,
+}
+
+impl Default for TargetPath {
+    fn default() -> TargetPath {
+        TargetPath::Raw
+    }
+}
+
+/// Resolves every directory in the given absolute path, but not its last
+/// component, so a link to another link still points to that link.
+fn resolve(path: &Path) -> PathBuf {
+    let resolved = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name))  => fs::canonicalize(parent).map(|p| p.join(name)),
+        _                           => fs::canonicalize(path),
+    };
+
+    resolved.unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The path to get from the `base` directory to `path`, both of which
+/// should be absolute and resolved, such as `../lib/file`.
+fn relative_path(base: &Path, path: &Path) -> PathBuf {
+    let base: Vec<Component> = base.components().collect();
+    let path: Vec<Component> = path.components().collect();
+    let common = base.iter().zip(path.iter()).take_while(|&(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common .. base.len() {
+        relative.push("..");
+    }
+
+    for component in &path[common ..] {
+        relative.push(component.as_os_str());
+    }
+
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+
+    relative
+}
+
+
 /// The result of following a symlink.
 pub enum FileTarget<'dir> {
 
@@ -433,6 +719,16 @@ impl<'dir> FileTarget<'dir> {
 }
 
 
+/// Whether a file of the given size, with the given number of bytes
+/// allocated to it, counts as sparse. The holes have to add up to at least
+/// one of the filesystem’s blocks, and to at least an eighth of the file, so
+/// files whose last few bytes are stored alongside their metadata, or that
+/// a filesystem has compressed a little, don’t get counted.
+fn is_sparse(size: u64, allocated: u64, block_size: u64) -> bool {
+    allocated < size && size - allocated >= block_size && size - allocated >= size / 8
+}
+
+
 /// More readable aliases for the permission bits exposed by libc.
 #[allow(trivial_numeric_casts)]
 mod modes {
@@ -457,8 +753,55 @@ mod modes {
     pub const STICKY: Mode        = libc::S_ISVTX as Mode;
     pub const SETGID: Mode        = libc::S_ISGID as Mode;
     pub const SETUID: Mode        = libc::S_ISUID as Mode;
+
+    pub const TYPE_MASK: Mode     = libc::S_IFMT as Mode;
+    pub const REGULAR: Mode       = libc::S_IFREG as Mode;
+    pub const DIRECTORY: Mode     = libc::S_IFDIR as Mode;
+    pub const FIFO: Mode          = libc::S_IFIFO as Mode;
+    pub const SYMLINK: Mode       = libc::S_IFLNK as Mode;
+    pub const CHAR_DEVICE: Mode   = libc::S_IFCHR as Mode;
+    pub const BLOCK_DEVICE: Mode  = libc::S_IFBLK as Mode;
+    pub const SOCKET: Mode        = libc::S_IFSOCK as Mode;
+}
+
+
+
+/// Reads the flags out of a file’s metadata. Each platform that has them
+/// has its own extension trait to get at them, and the rest have nothing.
+#[cfg(target_os = "macos")]
+fn st_flags(metadata: &fs::Metadata) -> u32 {
+    use std::os::macos::fs::MetadataExt;
+    metadata.st_flags()
+}
+
+#[cfg(target_os = "freebsd")]
+fn st_flags(metadata: &fs::Metadata) -> u32 {
+    use std::os::freebsd::fs::MetadataExt;
+    metadata.st_flags()
+}
+
+#[cfg(target_os = "dragonfly")]
+fn st_flags(metadata: &fs::Metadata) -> u32 {
+    use std::os::dragonfly::fs::MetadataExt;
+    metadata.st_flags()
+}
+
+#[cfg(target_os = "netbsd")]
+fn st_flags(metadata: &fs::Metadata) -> u32 {
+    use std::os::netbsd::fs::MetadataExt;
+    metadata.st_flags()
+}
+
+#[cfg(target_os = "openbsd")]
+fn st_flags(metadata: &fs::Metadata) -> u32 {
+    use std::os::openbsd::fs::MetadataExt;
+    metadata.st_flags()
 }
 
+#[cfg(not(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd")))]
+fn st_flags(_metadata: &fs::Metadata) -> u32 {
+    0
+}
 
 #[cfg(test)]
 mod ext_test {
@@ -517,3 +860,334 @@ mod filename_test {
         assert_eq!("/", File::filename(Path::new("/")))
     }
 }
+
+
+#[cfg(test)]
+mod sparseness_test {
+    use super::{File, is_sparse};
+    use fs::fields as f;
+
+    use scratch::ScratchDir;
+    use std::fs::File as StdFile;
+    use std::io::{Seek, SeekFrom, Write};
+
+    #[test]
+    fn sparse_and_dense() {
+        let scratch = ScratchDir::new("sparseness");
+        let sparse_path = scratch.join("sparse");
+        let dense_path  = scratch.join("dense");
+
+        // Seeking past the end before writing leaves a hole behind.
+        let mut sparse = StdFile::create(&sparse_path).unwrap();
+        let _ = sparse.seek(SeekFrom::Start(4 * 1024 * 1024)).unwrap();
+        sparse.write_all(b"end").unwrap();
+
+        let mut dense = StdFile::create(&dense_path).unwrap();
+        dense.write_all(&vec![ b'x'; 4 * 1024 * 1024 + 3 ]).unwrap();
+        dense.sync_all().unwrap();
+
+        let is_sparse = |path| match File::new(path, None, None).unwrap().sparseness() {
+            f::Sparseness::Sparse { .. }  => true,
+            f::Sparseness::Dense          => false,
+        };
+
+        assert_eq!(true,  is_sparse(sparse_path.clone()));
+        assert_eq!(false, is_sparse(dense_path.clone()));
+    }
+
+    #[test]
+    fn allocation_is_kept() {
+        let scratch = ScratchDir::new("sparseness-allocated");
+        let path = scratch.join("sparse");
+
+        let mut sparse = StdFile::create(&path).unwrap();
+        let _ = sparse.seek(SeekFrom::Start(4 * 1024 * 1024)).unwrap();
+        sparse.write_all(b"end").unwrap();
+        sparse.sync_all().unwrap();
+
+        match File::new(path.clone(), None, None).unwrap().sparseness() {
+            f::Sparseness::Sparse { allocated }  => assert!(allocated > 0 && allocated < 1024 * 1024),
+            f::Sparseness::Dense                 => panic!("file should be sparse"),
+        }
+    }
+
+    #[test]
+    fn inline_tail_is_dense() {
+        assert_eq!(false, is_sparse(100, 0, 4096));
+    }
+
+    #[test]
+    fn small_hole_is_dense() {
+        assert_eq!(false, is_sparse(1024 * 1024, 1024 * 1024 - 4096, 4096));
+    }
+
+    #[test]
+    fn big_hole_is_sparse() {
+        assert_eq!(true, is_sparse(1024 * 1024, 4096, 4096));
+    }
+
+    #[test]
+    fn fully_allocated_is_dense() {
+        assert_eq!(false, is_sparse(8192, 8192, 4096));
+        assert_eq!(false, is_sparse(8192, 12288, 4096));
+    }
+}
+
+
+#[cfg(test)]
+mod target_path_test {
+    use super::{File, FileTarget, TargetPath, relative_path};
+    use fs::Dir;
+
+    use scratch::ScratchDir;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn relative_paths() {
+        assert_eq!(PathBuf::from("c"),        relative_path(Path::new("/a/b"), Path::new("/a/b/c")));
+        assert_eq!(PathBuf::from("../d"),     relative_path(Path::new("/a/b"), Path::new("/a/d")));
+        assert_eq!(PathBuf::from("../../e"),  relative_path(Path::new("/a/b"), Path::new("/e")));
+        assert_eq!(PathBuf::from("."),        relative_path(Path::new("/a/b"), Path::new("/a/b")));
+        assert_eq!(PathBuf::from(".."),       relative_path(Path::new("/a/b"), Path::new("/a")));
+    }
+
+    #[test]
+    fn links_with_parent_components() {
+        let scratch = ScratchDir::new("target-paths");
+        let root = fs::canonicalize(&scratch.path).unwrap();
+        fs::create_dir_all(root.join("links/deep")).unwrap();
+        fs::create_dir_all(root.join("share")).unwrap();
+        let _ = fs::File::create(root.join("share/target")).unwrap();
+        symlink("../../links/../share/target", root.join("links/deep/link")).unwrap();
+        symlink("../nowhere/../target", root.join("links/deep/broken")).unwrap();
+
+        let dir = Dir::read_dir(root.join("links/deep"), None).unwrap();
+        let target = |name: &str, mode| {
+            let file = File::new(root.join("links/deep").join(name), Some(&dir), None).unwrap();
+            match file.link_target_as(mode) {
+                FileTarget::Ok(target)   => target.path,
+                FileTarget::Broken(path) => path,
+                FileTarget::Err(e)       => panic!("{}", e),
+            }
+        };
+
+        assert_eq!(target("link", TargetPath::Raw),      PathBuf::from("../../links/../share/target"));
+        assert_eq!(target("link", TargetPath::Absolute), root.join("share/target"));
+        assert_eq!(target("link", TargetPath::Relative), PathBuf::from("../../share/target"));
+
+        // Broken links always keep the path they were written with.
+        assert_eq!(target("broken", TargetPath::Raw),      PathBuf::from("../nowhere/../target"));
+        assert_eq!(target("broken", TargetPath::Absolute), PathBuf::from("../nowhere/../target"));
+        assert_eq!(target("broken", TargetPath::Relative), PathBuf::from("../nowhere/../target"));
+    }
+}
+
+
+#[cfg(test)]
+mod type_test {
+    use super::File;
+    use fs::fields as f;
+
+    use scratch::ScratchDir;
+    use std::fs;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixListener;
+    use std::process::Command;
+
+    fn type_of(path: &::std::path::Path) -> f::Type {
+        File::new(path.to_path_buf(), None, None).unwrap().type_char()
+    }
+
+    #[test]
+    fn regular_files_and_directories() {
+        let dir = ScratchDir::new("type-plain");
+        let _ = fs::File::create(dir.join("file")).unwrap();
+
+        assert_eq!(type_of(&dir.path), f::Type::Directory);
+        assert_eq!(type_of(&dir.join("file")), f::Type::File);
+    }
+
+    #[test]
+    fn fifos_and_sockets() {
+        let dir = ScratchDir::new("type-specials");
+
+        let status = Command::new("mkfifo").arg(dir.join("fifo")).status().unwrap();
+        assert!(status.success());
+        assert_eq!(type_of(&dir.join("fifo")), f::Type::Pipe);
+
+        // The socket only has to exist while it’s being looked at.
+        let listener = UnixListener::bind(dir.join("socket")).unwrap();
+        assert_eq!(type_of(&dir.join("socket")), f::Type::Socket);
+        drop(listener);
+    }
+
+    #[test]
+    fn devices() {
+        assert_eq!(type_of("/dev/null".as_ref()), f::Type::CharDevice);
+
+        // Block devices can’t be made without being root, so use whichever
+        // one is lying around, if there is one.
+        let block = fs::read_dir("/dev").unwrap().filter_map(Result::ok)
+                       .find(|e| e.file_type().map(|t| t.is_block_device()).unwrap_or(false));
+
+        if let Some(entry) = block {
+            assert_eq!(type_of(&entry.path()), f::Type::BlockDevice);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod dereference_test {
+    use super::File;
+
+    use scratch::ScratchDir;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn link_to_directory() {
+        let scratch = ScratchDir::new("dereference");
+        let dir = scratch.join("dir");
+        let link = scratch.join("link");
+        let broken = scratch.join("broken");
+        fs::create_dir(&dir).unwrap();
+        symlink(&dir, &link).unwrap();
+        symlink(scratch.join("nowhere"), &broken).unwrap();
+
+        let file = File::new(link.clone(), None, None).unwrap();
+        assert!(file.is_link());
+        assert!(!file.is_directory());
+
+        let file = file.dereference().unwrap();
+        assert!(!file.is_link());
+        assert!(file.is_directory());
+        assert_eq!(file.path, link);
+
+        assert!(File::new(broken.clone(), None, None).unwrap().dereference().is_err());
+    }
+}
+
+
+#[cfg(test)]
+mod time_anomaly_test {
+    use fs::fields as f;
+
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn modified_before_birth() {
+        let birth = UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        let modified = birth - Duration::from_secs(60 * 60 * 24);
+        assert_eq!(f::TimeAnomaly::ModifiedBeforeBirth, f::TimeAnomaly::between(modified, birth));
+    }
+
+    #[test]
+    fn modified_after_birth() {
+        let birth = UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        let modified = birth + Duration::from_secs(5);
+        assert_eq!(f::TimeAnomaly::Consistent, f::TimeAnomaly::between(modified, birth));
+    }
+
+    #[test]
+    fn modified_at_birth() {
+        let birth = UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+        assert_eq!(f::TimeAnomaly::Consistent, f::TimeAnomaly::between(birth, birth));
+    }
+}
+
+
+#[cfg(test)]
+mod flags_test {
+    use fs::fields as f;
+    use super::File;
+
+    use scratch::ScratchDir;
+    use std::fs;
+
+    #[test]
+    fn names() {
+        assert_eq!(f::FileFlags(0x0000_8002).names(), vec![ "uchg", "hidden" ]);
+        assert!(f::FileFlags(0).names().is_empty());
+    }
+
+    #[test]
+    fn immutable() {
+        assert!(f::FileFlags(0x0000_0002).is_immutable());
+        assert!(f::FileFlags(0x0002_0000).is_immutable());
+        assert!(!f::FileFlags(0x0000_8000).is_immutable());
+    }
+
+    #[test]
+    #[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd"))]
+    fn hidden() {
+        use std::process::Command;
+
+        let scratch = ScratchDir::new("flags-hidden");
+        let path = scratch.join("file");
+        let _ = fs::File::create(&path).unwrap();
+        assert!(Command::new("chflags").arg("hidden").arg(&path).status().unwrap().success());
+
+        let file = File::new(path.clone(), None, None).unwrap();
+        assert_eq!(file.flags().names(), vec![ "hidden" ]);
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "macos", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd")))]
+    fn never_any_elsewhere() {
+        let scratch = ScratchDir::new("flags-none");
+        let path = scratch.join("file");
+        let _ = fs::File::create(&path).unwrap();
+
+        let file = File::new(path.clone(), None, None).unwrap();
+        assert_eq!(file.flags(), f::FileFlags(0));
+    }
+}
+
+
+#[cfg(test)]
+mod line_count_test {
+    use super::File;
+    use fs::fields as f;
+
+    use scratch::ScratchDir;
+    use std::fs::File as StdFile;
+    use std::io::Write;
+
+    fn line_count(name: &str, contents: &[u8]) -> Option<u64> {
+        let scratch = ScratchDir::new(&format!("line-count-{}", name));
+        let path = scratch.join("file");
+        StdFile::create(&path).unwrap().write_all(contents).unwrap();
+
+        match File::new(path, None, None).unwrap().line_count() {
+            f::LineCount::Some(count)  => Some(count),
+            f::LineCount::None         => None,
+        }
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(Some(3), line_count("text", b"fn main() {\n    println!(\"hi\");\n}\n"));
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Some(0), line_count("empty", b""));
+    }
+
+    #[test]
+    fn binary() {
+        assert_eq!(None, line_count("binary", b"\x7FELF\x02\x01\x01\x00\n\n"));
+    }
+
+    #[test]
+    fn directory() {
+        let scratch = ScratchDir::new("line-count-directory");
+        match File::new(scratch.path.clone(), None, None).unwrap().line_count() {
+            f::LineCount::Some(_)  => panic!("directories have no lines"),
+            f::LineCount::None     => {},
+        }
+    }
+}
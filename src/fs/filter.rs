@@ -1,14 +1,27 @@
 //! Filtering and sorting the list of files before displaying them.
 
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Write, Result as IOResult};
 use std::iter::FromIterator;
 use std::os::unix::fs::MetadataExt;
+use std::path::{Component, Path};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::thread;
+use std::time::SystemTime;
 
 use glob;
 use natord;
+use regex::{self, Regex, RegexBuilder};
 
-use fs::File;
+use fs::{Dir, File};
 use fs::DotFilter;
+use fs::feature::GitOptions;
+use fs::fields as f;
+use fs::gitignore::IgnoreFiles;
+use timings::{self, Stage};
 
 
 /// The **file filter** processes a list of files before displaying them to
@@ -29,12 +42,21 @@ use fs::DotFilter;
 pub struct FileFilter {
 
     /// Whether directories should be listed first, and other types of file
-    /// second. Some users prefer it like this.
-    pub list_dirs_first: bool,
+    /// second, and if so, whether symlinks to directories count as them.
+    /// Some users prefer it like this.
+    pub list_dirs_first: Option<DirsFirst>,
 
     /// The metadata field to sort by.
     pub sort_field: SortField,
 
+    /// A command to run for each file to get the key it gets sorted by,
+    /// which takes the place of the sort field if one has been given.
+    pub sort_exec: Option<SortExec>,
+
+    /// A command that gets handed the names of the files, once they’ve been
+    /// sorted, and prints them back in the order they should be listed in.
+    pub sort_command: Option<SortCommand>,
+
     /// Whether to reverse the sorting order. This would sort the largest
     /// files first, or files starting with Z, or the most-recently-changed
     /// ones, depending on the sort field.
@@ -79,43 +101,297 @@ pub struct FileFilter {
     /// Glob patterns to ignore. Any file name that matches *any* of these
     /// patterns won’t be displayed in the list.
     pub ignore_patterns: IgnorePatterns,
+
+    /// A regular expression that file names have to match to be shown, if
+    /// one has been given.
+    pub name_regex: Option<NameRegex>,
+
+    /// Whether to hide the files matched by the `.gitignore` and `.ignore`
+    /// files in each directory and the ones above it. These get read
+    /// directly, so they work outside of Git repositories too.
+    pub git_ignore: bool,
+
+    /// Whether to only show files that have been changed since the last Git
+    /// commit, staged or not. Directories are shown if anything inside them
+    /// has changed. Files outside of a repository are always shown, as
+    /// there’s nothing to compare them against.
+    pub changed_only: bool,
+
+    /// Whether to only show files that differ from the Git reference that
+    /// their repository was compared against. As with `changed_only`,
+    /// directories are shown if anything inside them differs, and files
+    /// are only ruled out if there’s a comparison to go by.
+    pub git_since_only: bool,
+
+    /// Whether to only show the files Git considers dirty, meaning they have
+    /// changes that are staged or not. Unlike `changed_only`, files outside
+    /// of a repository are hidden rather than shown, as none of them can be
+    /// dirty. Directories are still shown if anything inside them is dirty,
+    /// so a tree keeps the path down to each file.
+    pub dirty_only: bool,
+
+    /// Whether to only show files that Git is tracking, or only the ones it
+    /// isn’t. As with `changed_only`, files outside of a repository are
+    /// always shown.
+    pub tracked_filter: TrackedFilter,
+
+    /// Which files to hide because of who owns them.
+    pub owner_filter: OwnerFilter,
+
+    /// Whether to only show symbolic links, whether or not they’re broken,
+    /// hiding every other type of file.
+    pub links_only: bool,
+
+    /// Whether to only show regular files with an execute bit set, hiding
+    /// every other type of file, including directories.
+    pub executable_only: bool,
+
+    /// The number of files to keep from each directory once they’ve been
+    /// sorted, if they’re being cut short.
+    pub top: Option<usize>,
+
+    /// The separator the user’s locale puts between each group of three
+    /// digits in a number, if numbers in file names are being read with it
+    /// when sorting, so `1,000` sorts the same as `1000`.
+    pub thousands_separator: Option<String>,
+
+    /// Whether a file has to pass every filter above that’s turned on to be
+    /// shown, or just one of them.
+    pub filter_mode: FilterMode,
 }
 
 
+impl Default for FileFilter {
+
+    /// The filter that gets used when no options are given: hide dotfiles,
+    /// ignore nothing else, and sort by name.
+    fn default() -> FileFilter {
+        FileFilter {
+            list_dirs_first: None,
+            sort_field:      SortField::default(),
+            sort_exec:       None,
+            sort_command:    None,
+            reverse:         false,
+            dot_filter:      DotFilter::default(),
+            ignore_patterns: IgnorePatterns::empty(),
+            name_regex:      None,
+            git_ignore:      false,
+            changed_only:    false,
+            git_since_only:  false,
+            dirty_only:      false,
+            tracked_filter:  TrackedFilter::Any,
+            owner_filter:    OwnerFilter::default(),
+            links_only:      false,
+            executable_only: false,
+            top:             None,
+            thousands_separator: None,
+            filter_mode:     FilterMode::And,
+        }
+    }
+}
+
 impl FileFilter {
     /// Remove every file in the given vector that does *not* pass the
-    /// filter predicate for files found inside a directory.
-    pub fn filter_child_files(&self, files: &mut Vec<File>) {
-        files.retain(|f| !self.ignore_patterns.is_ignored(&f.name));
+    /// filter predicate for files found inside a directory. The depth is
+    /// how many directories down from the one being listed the files are,
+    /// with 0 for the files directly inside it.
+    ///
+    /// The files should all be from the same directory, which is where the
+    /// ignore files come from: the ones it already has, if it was read with
+    /// `read_dir`, or else ones read from scratch.
+    pub fn filter_child_files(&self, files: &mut Vec<File>, depth: usize) {
+        let read;
+        let ignore_files = match files.first().and_then(|f| f.parent_dir) {
+            Some(dir) if self.git_ignore  => match dir.ignore_files() {
+                Some(ignore_files)  => Some(ignore_files),
+                None                => { read = IgnoreFiles::read_for(&dir.path); Some(&read) },
+            },
+            _                             => None,
+        };
+
+        files.retain(|f| {
+            let arguments = self.argument_verdicts(f);
+            let pattern = active(!self.ignore_patterns.is_empty(), || !self.ignore_patterns.is_ignored_at(f, depth));
+            let ignored = ignore_files.as_ref().map(|i| !i.is_ignored(&f.name, f.is_directory()));
+            let git = self.git_verdicts(f);
+
+            let verdicts = arguments.iter().chain(Some(&pattern)).chain(Some(&ignored)).chain(git.iter());
+            self.filter_mode.combine(verdicts.filter_map(|v| *v))
+        });
+    }
+
+    /// Reads the directory that the given file is, along with the ignore
+    /// files for its contents if they’re being checked. These get built on
+    /// top of the ones for the directory the file is in when it has them,
+    /// so however deep a tree goes, each ignore file only gets read once.
+    pub fn read_dir(&self, file: &File, git: Option<&GitOptions>) -> IOResult<Dir> {
+        let mut dir = file.to_dir(git)?;
+        if self.git_ignore {
+            dir.read_ignore_files(file.parent_dir.and_then(Dir::ignore_files));
+        }
+
+        Ok(dir)
+    }
+
+    /// The results of the filters that apply to every file, whether it was
+    /// found in a directory or given as an argument, with `None` for each
+    /// filter that isn’t turned on.
+    fn argument_verdicts(&self, file: &File) -> [Option<bool>; 4] {
+        [
+            active(self.name_regex.is_some(),        || self.name_regex.as_ref().map_or(true, |r| r.matches(&file.name))),
+            active(!self.owner_filter.is_empty(),    || !self.owner_filter.is_hidden(file)),
+            active(self.links_only,                  || file.is_link()),
+            active(self.executable_only,             || file.has_execute_bit()),
+        ]
+    }
+
+    /// Whether the filter needs the Git statuses of the files it gets given,
+    /// meaning their directories should be scanned for repositories.
+    pub fn needs_git(&self) -> bool {
+        self.changed_only || self.git_since_only || self.dirty_only || self.tracked_filter != TrackedFilter::Any
+            || self.sort_field == SortField::GitStatus
+    }
+
+    /// The results of the filters that go by the given file’s Git status,
+    /// with `None` for each one that isn’t turned on. Apart from
+    /// `dirty_only`, these only rule anything out for files in a directory
+    /// with a repository, and none of them rule out files without a
+    /// directory at all.
+    fn git_verdicts(&self, file: &File) -> [Option<bool>; 4] {
+        let dir = file.parent_dir;
+        let in_repo = dir.map_or(false, |d| d.has_git_repo());
+        let has_since = dir.map_or(false, |d| d.has_git_since());
+
+        [
+            active(self.changed_only,   || !in_repo || file.git_status().is_modified()),
+            active(self.git_since_only, || !has_since || file.git_since_status().is_modified()),
+            active(self.dirty_only,     || dir.is_none() || (in_repo && file.git_status().is_modified())),
+            active(self.tracked_filter != TrackedFilter::Any, || !in_repo || self.tracked_filter.shows(file)),
+        ]
     }
 
     /// Remove every file in the given vector that does *not* pass the
     /// filter predicate for file names specified on the command-line.
     ///
     /// The rules are different for these types of files than the other
-    /// type because the ignore rules can be used with globbing. For
-    /// example, running `exa -I='*.tmp' .vimrc` shouldn’t filter out the
-    /// dotfile, because it’s been directly specified. But running
-    /// `exa -I='*.ogg' music/*` should filter out the ogg files obtained
-    /// from the glob, even though the globbing is done by the shell!
+    /// type because they’ve been asked for by name. Running
+    /// `exa -I='*.log' error.log` should show the log file, the same way
+    /// running `exa .vimrc` shows the dotfile, so neither the ignore
+    /// patterns nor the ignore files apply to them: those only hide the
+    /// files found by reading directories.
     pub fn filter_argument_files(&self, files: &mut Vec<File>) {
-        files.retain(|f| !self.ignore_patterns.is_ignored(&f.name));
+        files.retain(|f| self.filter_mode.combine(self.argument_verdicts(f).iter().filter_map(|v| *v)));
+    }
+
+    /// Cuts the given list of files down to the first few, if there’s a
+    /// limit on how many to show. This should happen after they’ve been
+    /// sorted, so the ones that are kept are the ones at the top.
+    pub fn keep_top<T>(&self, files: &mut Vec<T>) {
+        if let Some(count) = self.top {
+            files.truncate(count);
+        }
+    }
+
+    /// Describes how files get sorted, such as `sort: Name(Insensitive)
+    /// reverse=true`, for `--print-sort` to print.
+    pub fn sort_description(&self) -> String {
+        match self.sort_exec {
+            Some(ref exec)  => format!("sort: Exec({:?}) reverse={}", exec.command, self.reverse),
+            None            => format!("sort: {} reverse={}", self.sort_field, self.reverse),
+        }
     }
 
     /// Sort the files in the given vector based on the sort field option.
     pub fn sort_files<'a, F>(&self, files: &mut Vec<F>)
     where F: AsRef<File<'a>> {
+        timings::time(Stage::Sort, || self.sort_files_untimed(files))
+    }
+
+    fn sort_files_untimed<'a, F>(&self, files: &mut Vec<F>)
+    where F: AsRef<File<'a>> {
+
+        // Extract each file’s sort key once, up-front, rather than doing it
+        // inside the comparator: a case-insensitive sort would otherwise
+        // lowercase the same names over and over again. The files can’t be
+        // moved while their keys borrow from them, so it’s a vector of
+        // *indices* that gets sorted, and the files get shuffled into that
+        // order afterwards.
+        let order = {
+            let keys: Vec<SortKey> = files.iter().map(|f| self.sort_key(f.as_ref())).collect();
+            let mut order: Vec<usize> = (0 .. keys.len()).collect();
+            order.sort_by(|&a, &b| keys[a].compare(&keys[b]));
+
+            // The sort command gets the names in the order they’ve just
+            // been sorted into, which is the order any names it leaves out
+            // get kept in.
+            if let Some(ref command) = self.sort_command {
+                let names: Vec<&str> = order.iter().map(|&i| &files[i].as_ref().name[..]).collect();
+                order = command.reorder(&names).into_iter().map(|i| order[i]).collect();
+            }
+
+            order
+        };
 
-        files.sort_by(|a, b| self.sort_field.compare_files(a.as_ref(), b.as_ref()));
+        let mut slots: Vec<Option<F>> = files.drain(..).map(Some).collect();
+        files.extend(order.into_iter().map(|i| slots[i].take().expect("file sorted twice")));
 
         if self.reverse {
             files.reverse();
         }
 
-        if self.list_dirs_first {
-            // This relies on the fact that `sort_by` is *stable*: it will keep
-            // adjacent elements next to each other.
-            files.sort_by(|a, b| b.as_ref().is_directory().cmp(&a.as_ref().is_directory()));
+        if let Some(dirs_first) = self.list_dirs_first {
+            // This relies on the fact that `partition` is *stable*: it will
+            // keep the files in each group in the order they were sorted in.
+            let (mut dirs, others): (Vec<F>, Vec<F>) = files.drain(..).partition(|f| dirs_first.groups(f.as_ref()));
+            dirs.extend(others);
+            *files = dirs;
+        }
+    }
+
+    /// Extracts the part of the given file that gets compared when sorting,
+    /// from the sort command if there is one, or the sort field otherwise.
+    fn sort_key<'f>(&self, file: &'f File) -> SortKey<'f> {
+        match self.sort_exec {
+            Some(ref exec)  => exec.sort_key(file),
+            None            => self.sort_field.sort_key(file, self.thousands_separator.as_ref().map(|s| &s[..])),
+        }
+    }
+}
+
+
+/// The result of a filter that might not be turned on: `None` if it isn’t,
+/// or whether the file passes it if it is. The check only gets run when
+/// it’s needed, as some of them have to look things up.
+fn active<F: FnOnce() -> bool>(turned_on: bool, passes: F) -> Option<bool> {
+    if turned_on { Some(passes()) }
+            else { None }
+}
+
+
+/// How the results of each filter that’s turned on get combined into
+/// whether a file should be shown.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum FilterMode {
+
+    /// A file has to pass every filter. This is the default.
+    And,
+
+    /// A file only has to pass one of the filters, so each one adds files
+    /// to the list rather than taking them away.
+    Or,
+}
+
+impl FilterMode {
+
+    /// Whether a file with the given filter results gets shown. Files are
+    /// always shown when there are no filters at all, whichever the mode.
+    fn combine<I: Iterator<Item=bool>>(&self, mut verdicts: I) -> bool {
+        match *self {
+            FilterMode::And  => verdicts.all(|v| v),
+            FilterMode::Or   => match verdicts.next() {
+                None | Some(true)  => true,
+                Some(false)        => verdicts.any(|v| v),
+            },
         }
     }
 }
@@ -159,20 +435,67 @@ pub enum SortField {
     /// http://unix.stackexchange.com/a/8842
     AccessedDate,
 
-    /// The time this file was changed or created (the “ctime”).
-    ///
-    /// Contrary to the name, this field is used to mark the time when a
-    /// file’s metadata changed -- its permissions, owners, or link count.
+    /// The time this file was created, which the filesystem has to have
+    /// kept track of. Files without a creation time, either because the
+    /// platform or the filesystem doesn’t record one, sort last.
+    CreatedDate,
+
+    /// The time this file’s metadata last changed (the “ctime”) -- its
+    /// permissions, owners, or link count.
     ///
     /// In original Unix, this was, however, meant as creation time.
     /// https://www.bell-labs.com/usr/dmr/www/cacm.html
-    CreatedDate,
+    ChangedDate,
+
+    /// The number of entries inside a directory, counting everything inside
+    /// its subdirectories too. Files that aren’t directories count as having
+    /// none, so they come first.
+    ///
+    /// This has to walk through every directory being sorted, so it’s a lot
+    /// slower than the other fields.
+    EntryCount,
+
+    /// The number of entries directly inside a directory, counting
+    /// dotfiles only if the dot filter shows them. Files that aren’t
+    /// directories, and directories that can’t be read, count as having
+    /// none.
+    ///
+    /// Unlike `EntryCount`, this only reads each directory being sorted,
+    /// rather than everything underneath it.
+    ItemCount(DotFilter),
 
     /// The type of the file: directories, links, pipes, regular, files, etc.
     ///
     /// Files are ordered according to the `PartialOrd` implementation of
     /// `fs::fields::Type`, so changing that will change this.
     FileType,
+
+    /// Files whose names start with a digit come first, with the numbers
+    /// sorted descending so the highest is at the top, followed by every
+    /// other file sorted by name ascending. This suits directories of
+    /// numbered releases or log files that sit next to named ones.
+    Mixed,
+
+    /// The file’s Git status, with the ones that most need looking at
+    /// first: conflicted, then modified, new, renamed, and type-changed
+    /// files, then clean ones, and finally ignored ones. Staged and unstaged
+    /// changes both count, with a file ranked by whichever is more severe.
+    ///
+    /// Every file is clean unless its directory has been scanned for a
+    /// repository, which `needs_git` makes sure happens.
+    GitStatus,
+
+    /// The number of components in the file’s path, as it was given or
+    /// found, with paths that have the same number compared to break ties.
+    /// Files inside the same directory all have the same number, so this
+    /// mostly matters for files given as arguments.
+    Segments,
+}
+
+impl Default for SortField {
+    fn default() -> SortField {
+        SortField::Name(SortCase::Sensitive)
+    }
 }
 
 /// Whether a field should be sorted case-sensitively or case-insensitively.
@@ -188,137 +511,2020 @@ pub enum SortCase {
     Insensitive,
 }
 
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            SortField::Unsorted         => write!(f, "Unsorted"),
+            SortField::Name(case)       => write!(f, "Name({})", case),
+            SortField::Extension(case)  => write!(f, "Extension({})", case),
+            SortField::Size             => write!(f, "Size"),
+            SortField::FileInode        => write!(f, "FileInode"),
+            SortField::ModifiedDate     => write!(f, "ModifiedDate"),
+            SortField::AccessedDate     => write!(f, "AccessedDate"),
+            SortField::CreatedDate      => write!(f, "CreatedDate"),
+            SortField::ChangedDate      => write!(f, "ChangedDate"),
+            SortField::EntryCount       => write!(f, "EntryCount"),
+            SortField::ItemCount(_)     => write!(f, "ItemCount"),
+            SortField::FileType         => write!(f, "FileType"),
+            SortField::Mixed            => write!(f, "Mixed"),
+            SortField::GitStatus        => write!(f, "GitStatus"),
+            SortField::Segments         => write!(f, "Segments"),
+        }
+    }
+}
+
+impl fmt::Display for SortCase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            SortCase::Sensitive    => write!(f, "Sensitive"),
+            SortCase::Insensitive  => write!(f, "Insensitive"),
+        }
+    }
+}
+
 impl SortField {
 
     /// Compares two files to determine the order they should be listed in,
     /// depending on the search field.
     ///
-    /// The `natord` crate is used here to provide a more *natural* sorting
-    /// order than just sorting character-by-character. This splits filenames
+    /// Names are sorted in the *natural* order the `natord` crate gives,
+    /// rather than just character-by-character. This splits filenames
     /// into groups between letters and numbers, and then sorts those blocks
     /// together, so `file10` will sort after `file9`, instead of before it
     /// because of the `1`.
     pub fn compare_files(&self, a: &File, b: &File) -> Ordering {
+        self.sort_key(a, None).compare(&self.sort_key(b, None))
+    }
+
+    /// Extracts the part of the given file that this field compares, so it
+    /// only has to be worked out once per file, instead of once per
+    /// comparison. Numbers in names are read with the thousands separator,
+    /// if one is given.
+    fn sort_key<'f>(&self, file: &'f File, separator: Option<&str>) -> SortKey<'f> {
         use self::SortCase::{Sensitive, Insensitive};
 
-        match *self {
-            SortField::Unsorted  => Ordering::Equal,
+        let name = sort_name(file);
+        let natural = |name: &str| NaturalKey::with_separator(name, separator);
 
-            SortField::Name(Sensitive)    => natord::compare(&a.name, &b.name),
-            SortField::Name(Insensitive)  => natord::compare_ignore_case(&a.name, &b.name),
+        let value = match *self {
+            SortField::Unsorted  => SortValue::Nothing,
 
-            SortField::Size          => a.metadata.len().cmp(&b.metadata.len()),
-            SortField::FileInode     => a.metadata.ino().cmp(&b.metadata.ino()),
-            SortField::ModifiedDate  => a.metadata.mtime().cmp(&b.metadata.mtime()),
-            SortField::AccessedDate  => a.metadata.atime().cmp(&b.metadata.atime()),
-            SortField::CreatedDate   => a.metadata.ctime().cmp(&b.metadata.ctime()),
+            SortField::Name(Sensitive)    => SortValue::Name(natural(name)),
+            SortField::Name(Insensitive)  => SortValue::Name(natural(&lowercase(name))),
 
-            SortField::FileType => match a.type_char().cmp(&b.type_char()) { // todo: this recomputes
-                Ordering::Equal  => natord::compare(&*a.name, &*b.name),
-                order            => order,
-            },
+            SortField::Size          => SortValue::Unsigned(file.metadata.len()),
+            SortField::FileInode     => SortValue::Unsigned(file.metadata.ino()),
+            SortField::ModifiedDate  => SortValue::Signed(file.metadata.mtime()),
+            SortField::AccessedDate  => SortValue::Signed(file.metadata.atime()),
+            SortField::CreatedDate   => SortValue::Created(file.metadata.created().ok()),
+            SortField::ChangedDate   => SortValue::Signed(file.metadata.ctime()),
 
-            SortField::Extension(Sensitive) => match a.ext.cmp(&b.ext) {
-                Ordering::Equal  => natord::compare(&*a.name, &*b.name),
-                order            => order,
+            SortField::EntryCount => match file.entry_count() {
+                f::EntryCount::Some(count)  => SortValue::Unsigned(count),
+                f::EntryCount::None         => SortValue::Unsigned(0),
             },
 
-            SortField::Extension(Insensitive) => match a.ext.cmp(&b.ext) {
-                Ordering::Equal  => natord::compare_ignore_case(&*a.name, &*b.name),
-                order            => order,
+            SortField::ItemCount(dots) => match file.item_count(dots) {
+                f::ItemCount::Some(count)  => SortValue::Unsigned(count),
+                _                          => SortValue::Unsigned(0),
             },
+
+            SortField::FileType => SortValue::Type(file.type_char(), natural(name)),
+
+            SortField::Mixed => SortValue::Mixed(starts_with_digit(name), natural(name)),
+
+            SortField::GitStatus => SortValue::Unsigned(git_rank(&file.git_status(), file.is_git_ignored())),
+
+            SortField::Segments => SortValue::Segments(segment_count(&file.path), &file.path),
+
+            SortField::Extension(Sensitive)    => SortValue::Extension(file.ext.as_ref().map(|e| &e[..]), natural(name)),
+            SortField::Extension(Insensitive)  => SortValue::Extension(file.ext.as_ref().map(|e| &e[..]), natural(&lowercase(name))),
+        };
+
+        SortKey { value, name, path: &file.path }
+    }
+}
+
+/// The name a file gets sorted by. A directory’s name can end in a slash
+/// when it’s been written that way for display, which would otherwise put
+/// `foo/` after `foo-bar` even though `foo` comes first, so any trailing
+/// slashes are left off. The root directory keeps its one slash.
+fn sort_name<'f>(file: &'f File) -> &'f str {
+    let trimmed = file.name.trim_right_matches('/');
+    if trimmed.is_empty() || !file.is_directory() { &file.name }
+                                              else { trimmed }
+}
+
+/// Where a file with the given Git status comes when sorting by it, with
+/// lower ranks first. Deleted files count as modified, and files added with
+/// `--intent-to-add` count as new. Files only count as ignored if nothing
+/// about them has changed.
+fn git_rank(git: &f::Git, ignored: bool) -> u64 {
+    fn rank(status: &f::GitStatus) -> u64 {
+        match *status {
+            f::GitStatus::Conflicted   => 0,
+            f::GitStatus::Modified     => 1,
+            f::GitStatus::Deleted      => 1,
+            f::GitStatus::New          => 2,
+            f::GitStatus::IntentToAdd  => 2,
+            f::GitStatus::Renamed      => 3,
+            f::GitStatus::TypeChange   => 4,
+            f::GitStatus::NotModified  => 5,
         }
     }
+
+    if ignored && !git.is_modified() { 6 }
+                                else { rank(&git.staged).min(rank(&git.unstaged)) }
 }
 
+/// The number of names in a path, leaving out any `.` or `/` components,
+/// so that `./a/b.txt` has as many as `a/b.txt`.
+fn segment_count(path: &Path) -> usize {
+    path.components().filter(|c| match *c { Component::Normal(_) => true, _ => false }).count()
+}
 
-/// The **ignore patterns** are a list of globs that are tested against
-/// each filename, and if any of them match, that file isn’t displayed.
-/// This lets a user hide, say, text files by ignoring `*.txt`.
-#[derive(PartialEq, Default, Debug, Clone)]
-pub struct IgnorePatterns {
-    patterns: Vec<glob::Pattern>,
+/// Lowercases a file name in the same way `natord::compare_ignore_case`
+/// does, character by character, so comparing the result naturally gives
+/// exactly the same order.
+fn lowercase(name: &str) -> String {
+    name.chars().flat_map(|c| c.to_lowercase()).collect()
 }
 
-impl FromIterator<glob::Pattern> for IgnorePatterns {
-    fn from_iter<I: IntoIterator<Item = glob::Pattern>>(iter: I) -> Self {
-        IgnorePatterns { patterns: iter.into_iter().collect() }
+/// Whether a file name starts with a digit, putting it in the numbers
+/// group when sorting with `SortField::Mixed`.
+fn starts_with_digit(name: &str) -> bool {
+    name.chars().next().map(|c| c.is_digit(10)).unwrap_or(false)
+}
+
+
+/// An external command that gets run once for each file being sorted, with
+/// the file’s path as its last argument, to print the key that the file
+/// gets sorted by. The keys are compared naturally, like names, and files
+/// that the command fails for come last.
+///
+/// The command gets run by `sh`, so it can be a pipeline, and it can do
+/// anything the user running exa can. It’s handed the paths of whatever
+/// files are in the directories being listed, so it has to cope with names
+/// chosen by whoever can write to them.
+#[derive(PartialEq, Debug, Clone)]
+pub struct SortExec {
+    pub command: String,
+}
+
+impl SortExec {
+
+    /// Runs the command for the file at the given path, returning what it
+    /// printed without the newlines at the end, or `None` if it couldn’t be
+    /// run or failed. Anything it prints to stderr gets passed through.
+    pub fn key_for(&self, path: &Path) -> Option<String> {
+        let output = Command::new("sh")
+                             .arg("-c").arg(format!("{} \"$@\"", self.command))
+                             .arg("sh").arg(path)
+                             .stdin(Stdio::null())
+                             .stderr(Stdio::inherit())
+                             .output();
+
+        match output {
+            Ok(ref output) if output.status.success()  => Some(String::from_utf8_lossy(&output.stdout).trim_right_matches('\n').to_string()),
+            _                                          => None,
+        }
+    }
+
+    fn sort_key<'f>(&self, file: &'f File) -> SortKey<'f> {
+        let value = SortValue::Command(self.key_for(&file.path).map(|k| NaturalKey::new(&k)));
+        SortKey { value, name: sort_name(file), path: &file.path }
     }
 }
 
-impl IgnorePatterns {
 
-    /// Create a new list from the input glob strings, turning the inputs that
-    /// are valid glob patterns into an IgnorePatterns. The inputs that don’t
-    /// parse correctly are returned separately.
-    pub fn parse_from_iter<'a, I: IntoIterator<Item = &'a str>>(iter: I) -> (Self, Vec<glob::PatternError>) {
-        let iter = iter.into_iter();
+/// An external command that gets run once for each list of files being
+/// sorted, with their names written to its standard input, one per line,
+/// to print them back in the order they should be in. Any names it doesn’t
+/// print come after the ones it does, in the order they were in already.
+///
+/// As with `SortExec`, the command gets run by `sh`, and it gets handed
+/// names chosen by whoever can write to the directories being listed.
+#[derive(PartialEq, Debug, Clone)]
+pub struct SortCommand {
+    pub command: String,
+}
 
-        // Almost all glob patterns are valid, so it’s worth pre-allocating
-        // the vector with enough space for all of them.
-        let mut patterns = match iter.size_hint() {
-            (_, Some(count))  => Vec::with_capacity(count),
-             _                => Vec::new(),
-        };
+impl SortCommand {
 
-        // Similarly, assume there won’t be any errors.
-        let mut errors = Vec::new();
+    /// Runs the command with the given names, returning the positions of
+    /// the names in the order it printed them, followed by the positions
+    /// of any it left out. If it couldn’t be run or failed, the names stay
+    /// in the order they’re in. Names that it prints more than once, or
+    /// that weren’t given to it, are skipped.
+    pub fn reorder(&self, names: &[&str]) -> Vec<usize> {
+        let mut positions: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, name) in names.iter().enumerate().rev() {
+            positions.entry(*name).or_insert_with(Vec::new).push(index);
+        }
 
-        for input in iter {
-            match glob::Pattern::new(input) {
-                Ok(pat) => patterns.push(pat),
-                Err(e)  => errors.push(e),
+        let mut order = Vec::with_capacity(names.len());
+        for line in self.run(names).unwrap_or_default().lines() {
+            if let Some(index) = positions.get_mut(line).and_then(|p| p.pop()) {
+                order.push(index);
             }
         }
 
-        (IgnorePatterns { patterns }, errors)
+        let mut printed = vec![ false; names.len() ];
+        for &index in &order {
+            printed[index] = true;
+        }
+
+        order.extend((0 .. names.len()).filter(|&i| !printed[i]));
+        order
     }
 
-    /// Create a new empty set of patterns that matches nothing.
-    pub fn empty() -> IgnorePatterns {
-        IgnorePatterns { patterns: Vec::new() }
+    /// Runs the command, writing the names to it and returning everything
+    /// it printed, or `None` if it couldn’t be run or failed. The names get
+    /// written from another thread, so a command that starts printing
+    /// before it’s read all of them can’t get stuck waiting for exa to read
+    /// its output.
+    fn run(&self, names: &[&str]) -> Option<String> {
+        let mut child = match Command::new("sh").arg("-c").arg(&self.command)
+                                                .stdin(Stdio::piped())
+                                                .stdout(Stdio::piped())
+                                                .stderr(Stdio::inherit())
+                                                .spawn() {
+            Ok(c)   => c,
+            Err(_)  => return None,
+        };
+
+        let mut input = String::new();
+        for name in names {
+            input.push_str(name);
+            input.push('\n');
+        }
+
+        let writer = child.stdin.take().map(|mut stdin| {
+            thread::spawn(move || { let _ = stdin.write_all(input.as_bytes()); })
+        });
+
+        let output = child.wait_with_output();
+        if let Some(writer) = writer {
+            let _ = writer.join();
+        }
+
+        match output {
+            Ok(ref output) if output.status.success()  => Some(String::from_utf8_lossy(&output.stdout).into_owned()),
+            _                                          => None,
+        }
     }
+}
 
-    /// Test whether the given file should be hidden from the results.
-    fn is_ignored(&self, file: &str) -> bool {
-        self.patterns.iter().any(|p| p.matches(file))
+
+/// Everything about a file that gets compared when sorting it, extracted
+/// once before sorting begins.
+struct SortKey<'f> {
+
+    /// The value of the field being sorted by.
+    value: SortValue<'f>,
+
+    /// The file’s name and path, which are used to break ties between files
+    /// with equal values, so the order files end up in never depends on the
+    /// order they were read in.
+    name: &'f str,
+    path: &'f Path,
+}
+
+/// The value that gets compared when sorting files by a particular field.
+///
+/// Any names held here have already been lowercased when sorting
+/// case-insensitively, so they’re always compared case-sensitively.
+enum SortValue<'f> {
+
+    /// No sorting is done, so every file is equal.
+    Nothing,
+
+    /// A file name.
+    Name(NaturalKey),
+
+    /// A file’s size or inode number.
+    Unsigned(u64),
+
+    /// One of a file’s timestamps.
+    Signed(i64),
+
+    /// A file’s type, followed by its name to break ties.
+    Type(f::Type, NaturalKey),
+
+    /// A file’s extension, followed by its name to break ties.
+    Extension(Option<&'f str>, NaturalKey),
+
+    /// Whether a file’s name starts with a number, followed by the name,
+    /// which gets compared in the opposite direction when it does.
+    Mixed(bool, NaturalKey),
+
+    /// The key that the sort command printed, if it didn’t fail.
+    Command(Option<NaturalKey>),
+
+    /// How many components a file’s path has, followed by the path to
+    /// break ties.
+    Segments(usize, &'f Path),
+
+    /// When a file was created, if the filesystem knows.
+    Created(Option<SystemTime>),
+}
+
+impl<'f> SortKey<'f> {
+
+    /// Compares the values of the two keys. If they’re equal, then the files
+    /// are compared by their names, first naturally, then exactly, then by
+    /// their paths, so that no two different files ever compare as equal
+    /// and the result is the same whatever order the files started in.
+    ///
+    /// The exception is when files aren’t being sorted at all, where the
+    /// whole point is to keep the order they were read in.
+    fn compare(&self, other: &SortKey) -> Ordering {
+        if let SortValue::Nothing = self.value {
+            return Ordering::Equal;
+        }
+
+        self.value.compare(&other.value)
+            .then_with(|| natord::compare(self.name, other.name))
+            .then_with(|| self.name.cmp(other.name))
+            .then_with(|| self.path.cmp(other.path))
     }
 }
 
+impl<'f> SortValue<'f> {
+    fn compare(&self, other: &SortValue) -> Ordering {
+        match (self, other) {
+            (&SortValue::Name(ref a),      &SortValue::Name(ref b))      => a.cmp(b),
+            (&SortValue::Unsigned(a),      &SortValue::Unsigned(b))      => a.cmp(&b),
+            (&SortValue::Signed(a),        &SortValue::Signed(b))        => a.cmp(&b),
 
+            (&SortValue::Type(ref at, ref an), &SortValue::Type(ref bt, ref bn)) => match at.cmp(bt) {
+                Ordering::Equal  => an.cmp(bn),
+                order            => order,
+            },
 
-#[cfg(test)]
-mod test_ignores {
-    use super::*;
+            (&SortValue::Extension(ae, ref an), &SortValue::Extension(be, ref bn)) => match ae.cmp(&be) {
+                Ordering::Equal  => an.cmp(bn),
+                order            => order,
+            },
 
-    #[test]
-    fn empty_matches_nothing() {
-        let pats = IgnorePatterns::empty();
-        assert_eq!(false, pats.is_ignored("nothing"));
-        assert_eq!(false, pats.is_ignored("test.mp3"));
+            (&SortValue::Mixed(true, ref an),  &SortValue::Mixed(true, ref bn))  => bn.cmp(an),
+            (&SortValue::Mixed(false, ref an), &SortValue::Mixed(false, ref bn)) => an.cmp(bn),
+            (&SortValue::Mixed(a, _),          &SortValue::Mixed(b, _))          => b.cmp(&a),
+
+            (&SortValue::Command(Some(ref a)), &SortValue::Command(Some(ref b))) => a.cmp(b),
+            (&SortValue::Command(Some(_)),     &SortValue::Command(None))        => Ordering::Less,
+            (&SortValue::Command(None),        &SortValue::Command(Some(_)))     => Ordering::Greater,
+
+            (&SortValue::Segments(a, ap), &SortValue::Segments(b, bp)) => a.cmp(&b).then_with(|| ap.cmp(bp)),
+
+            (&SortValue::Created(Some(a)),  &SortValue::Created(Some(b)))   => a.cmp(&b),
+            (&SortValue::Created(Some(_)),  &SortValue::Created(None))      => Ordering::Less,
+            (&SortValue::Created(None),     &SortValue::Created(Some(_)))   => Ordering::Greater,
+
+            // Values are only ever compared with other values from the same
+            // sort field, so this covers `Nothing` and nothing else.
+            _ => Ordering::Equal,
+        }
     }
+}
 
-    #[test]
-    fn ignores_a_glob() {
-        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ "*.mp3" ]);
-        assert!(fails.is_empty());
-        assert_eq!(false, pats.is_ignored("nothing"));
-        assert_eq!(true,  pats.is_ignored("test.mp3"));
+
+/// A name re-encoded as bytes that sort in the same order as comparing the
+/// names themselves with `natord::compare`, so the name only has to be
+/// split up into numbers and other characters once per file, and each
+/// comparison is then just a comparison of bytes.
+///
+/// Other characters are kept as their UTF-8 bytes, which sort in the same
+/// order as the characters, and whitespace is skipped. Runs of digits sort
+/// by their length, then their digits, so they become a `1` byte followed
+/// by the length and the digits. Runs with a leading zero sort like the
+/// digits after a decimal point instead, so they’re kept as they are, with
+/// a zero byte on the end so shorter runs come first. Because `0` sorts
+/// before `1`, those always come before the others, just as in `natord`;
+/// and as both start with a byte between `0` and `9`, comparing a run of
+/// digits with any other character works the same way too.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct NaturalKey(Vec<u8>);
+
+impl NaturalKey {
+    fn new(name: &str) -> NaturalKey {
+        NaturalKey::with_separator(name, None)
     }
 
-    #[test]
-    fn ignores_an_exact_filename() {
-        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ "nothing" ]);
-        assert!(fails.is_empty());
-        assert_eq!(true,  pats.is_ignored("nothing"));
-        assert_eq!(false, pats.is_ignored("test.mp3"));
+    /// Re-encodes the name, reading a separator followed by three more
+    /// digits as part of the run of digits before it, so `1,000` gets the
+    /// same key as `1000`. A separator followed by any other number of
+    /// digits is left alone, so `v1.2` stays as it is even where `.` is the
+    /// separator.
+    fn with_separator(name: &str, separator: Option<&str>) -> NaturalKey {
+        let separator = separator.and_then(|s| if s.is_empty() { None } else { Some(s) });
+        let mut bytes = Vec::with_capacity(name.len() + 8);
+        let mut chars = name.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            if c.is_digit(10) {
+                let mut digits = vec![ c as u8 ];
+                let mut end = start + 1;
+
+                loop {
+                    if let Some(&(_, d)) = chars.peek() {
+                        if d.is_digit(10) {
+                            digits.push(d as u8);
+                            end += 1;
+                            let _ = chars.next();
+                            continue;
+                        }
+                    }
+
+                    let group_length = match separator.and_then(|s| thousands_group(&name[end ..], s)) {
+                        Some(length)  => length,
+                        None          => break,
+                    };
+
+                    for _ in name[end .. end + group_length].chars() {
+                        let _ = chars.next();
+                    }
+
+                    end += group_length;
+                    digits.extend_from_slice(&name.as_bytes()[end - 3 .. end]);
+                }
+
+                if c == '0' {
+                    bytes.extend_from_slice(&digits);
+                    bytes.push(0);
+                }
+                else {
+                    let length = digits.len() as u32;
+                    bytes.push(b'1');
+                    bytes.extend_from_slice(&[ (length >> 24) as u8, (length >> 16) as u8, (length >> 8) as u8, length as u8 ]);
+                    bytes.extend_from_slice(&digits);
+                }
+            }
+            else if !c.is_whitespace() {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+
+        NaturalKey(bytes)
     }
+}
 
-    #[test]
-    fn ignores_both() {
-        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ "nothing", "*.mp3" ]);
-        assert!(fails.is_empty());
-        assert_eq!(true, pats.is_ignored("nothing"));
-        assert_eq!(true, pats.is_ignored("test.mp3"));
+/// If the given text starts with the separator and then a group of exactly
+/// three digits, the length of the two together, in bytes.
+fn thousands_group(text: &str, separator: &str) -> Option<usize> {
+    if !text.starts_with(separator) {
+        return None;
+    }
+
+    let digits = text[separator.len() ..].bytes().take_while(|&b| b >= b'0' && b <= b'9').count();
+    if digits == 3 { Some(separator.len() + 3) }
+              else { None }
+}
+
+
+/// The **tracked filter** shows only the files that Git is tracking, or only
+/// the ones it isn’t. Neither includes ignored files, and directories are
+/// shown if anything inside them would be, so a tree keeps the path down
+/// to each file.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TrackedFilter {
+
+    /// Show every file, tracked or not.
+    Any,
+
+    /// Only show files that are in the index.
+    Tracked,
+
+    /// Only show files that aren’t in the index.
+    Untracked,
+}
+
+impl TrackedFilter {
+
+    /// Whether the given file passes this filter. Its parent directory
+    /// should have a repository.
+    fn shows(&self, file: &File) -> bool {
+        match *self {
+            TrackedFilter::Any        => true,
+            TrackedFilter::Tracked    => file.is_git_tracked(),
+            TrackedFilter::Untracked  => file.has_git_untracked(),
+        }
+    }
+}
+
+
+/// Which files get listed before the others when directories are listed
+/// first.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum DirsFirst {
+
+    /// Directories, and symlinks that lead to directories, like `ls` does.
+    WithLinks,
+
+    /// Only real directories, leaving symlinks to them with the files, so a
+    /// directory full of links doesn’t end up with all of them at the top.
+    Real,
+}
+
+impl DirsFirst {
+
+    /// Whether the given file goes in the group listed first.
+    fn groups(&self, file: &File) -> bool {
+        match *self {
+            DirsFirst::WithLinks  => file.points_to_directory(),
+            DirsFirst::Real       => file.is_directory(),
+        }
+    }
+}
+
+
+/// The **owner filter** hides files based on which user and group own them,
+/// such as hiding everything owned by root when looking through a system
+/// directory for files that belong to people, or only showing one person’s
+/// files on a shared server.
+#[derive(PartialEq, Default, Debug, Clone)]
+pub struct OwnerFilter {
+
+    /// The IDs of the users whose files get hidden.
+    pub hidden_users: Vec<f::uid_t>,
+
+    /// The ID of the only user whose files get shown, if there is one.
+    pub only_user: Option<f::uid_t>,
+
+    /// The ID of the only group whose files get shown, if there is one.
+    pub only_group: Option<f::gid_t>,
+}
+
+impl OwnerFilter {
+
+    /// A filter that hides the files owned by root.
+    pub fn no_root() -> OwnerFilter {
+        OwnerFilter { hidden_users: vec![ 0 ], ..OwnerFilter::default() }
+    }
+
+    /// Whether this filter doesn’t hide anyone’s files.
+    pub fn is_empty(&self) -> bool {
+        self.hidden_users.is_empty() && self.only_user.is_none() && self.only_group.is_none()
+    }
+
+    /// Whether the given file should be hidden because of its owner.
+    pub fn is_hidden(&self, file: &File) -> bool {
+        let user = file.user().0;
+
+        self.hidden_users.contains(&user)
+            || self.only_user.map_or(false, |u| u != user)
+            || self.only_group.map_or(false, |g| g != file.group().0)
+    }
+}
+
+
+/// The **ignore patterns** are a list of globs that are tested against
+/// each filename, and if any of them match, that file isn’t displayed.
+/// This lets a user hide, say, text files by ignoring `*.txt`.
+///
+/// Patterns with a slash in them get tested against the file’s path from
+/// the directory being listed instead, like in a `.gitignore` file. A slash
+/// at the start anchors a pattern there, so `/node_modules` only matches
+/// at the top, while `**/node_modules` matches at any depth, and plain
+/// `node_modules` matches any file with that name, wherever it is. In path
+/// patterns, `*` and `?` don’t match slashes, but `**` does.
+///
+/// The patterns can also be limited to files deeper than a certain depth
+/// when recursing, so matching files near the top still get shown. Files
+/// directly inside a directory being listed are at depth 0, and files
+/// given as arguments count as being at depth 0 too.
+///
+/// Each pattern keeps count of how many files it’s matched, so the ones
+/// that never matched anything, which are probably typos, can be reported
+/// once everything’s been listed.
+#[derive(PartialEq, Default, Debug, Clone)]
+pub struct IgnorePatterns {
+    patterns: Vec<glob::Pattern>,
+    below: Option<usize>,
+
+    /// How many files each pattern has matched so far.
+    hits: HitCounts,
+
+    /// How many of the patterns, from the start of the list, get reported
+    /// if they never match anything. Patterns read from files can be there
+    /// for files that just aren’t around, so only the ones given on the
+    /// command-line are worth complaining about.
+    reported: usize,
+}
+
+impl FromIterator<glob::Pattern> for IgnorePatterns {
+    fn from_iter<I: IntoIterator<Item = glob::Pattern>>(iter: I) -> Self {
+        IgnorePatterns::new(iter.into_iter().collect())
+    }
+}
+
+/// A count of matched files for each pattern. The patterns get shared
+/// between the threads that render the details view, so the counts are
+/// atomic, and get compared and copied by their current values.
+#[derive(Default, Debug)]
+struct HitCounts(Vec<AtomicUsize>);
+
+impl HitCounts {
+    fn new(count: usize) -> HitCounts {
+        HitCounts((0 .. count).map(|_| AtomicUsize::new(0)).collect())
+    }
+
+    fn values(&self) -> Vec<usize> {
+        self.0.iter().map(|h| h.load(AtomicOrdering::Relaxed)).collect()
+    }
+}
+
+impl Clone for HitCounts {
+    fn clone(&self) -> HitCounts {
+        HitCounts(self.values().into_iter().map(AtomicUsize::new).collect())
+    }
+}
+
+impl PartialEq for HitCounts {
+    fn eq(&self, other: &HitCounts) -> bool {
+        self.values() == other.values()
+    }
+}
+
+impl IgnorePatterns {
+
+    /// Create a new list from the input glob strings, turning the inputs that
+    /// are valid glob patterns into an IgnorePatterns. The inputs that don’t
+    /// parse correctly are returned separately.
+    pub fn parse_from_iter<'a, I: IntoIterator<Item = &'a str>>(iter: I) -> (Self, Vec<glob::PatternError>) {
+        let iter = iter.into_iter();
+
+        // Almost all glob patterns are valid, so it’s worth pre-allocating
+        // the vector with enough space for all of them.
+        let mut patterns = match iter.size_hint() {
+            (_, Some(count))  => Vec::with_capacity(count),
+             _                => Vec::new(),
+        };
+
+        // Similarly, assume there won’t be any errors.
+        let mut errors = Vec::new();
+
+        for input in iter {
+            match glob::Pattern::new(input) {
+                Ok(pat) => patterns.push(pat),
+                Err(e)  => errors.push(e),
+            }
+        }
+
+        (IgnorePatterns::new(patterns), errors)
+    }
+
+    fn new(patterns: Vec<glob::Pattern>) -> IgnorePatterns {
+        let count = patterns.len();
+        IgnorePatterns { patterns, below: None, hits: HitCounts::new(count), reported: count }
+    }
+
+    /// Create a new empty set of patterns that matches nothing.
+    pub fn empty() -> IgnorePatterns {
+        IgnorePatterns::new(Vec::new())
+    }
+
+    /// Whether there are no patterns, so nothing is ever ignored.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Limit these patterns to files deeper than the given depth, leaving
+    /// any files at or above it alone.
+    pub fn only_below(self, depth: usize) -> IgnorePatterns {
+        IgnorePatterns { below: Some(depth), ..self }
+    }
+
+    /// Only report the given number of patterns, from the start of the
+    /// list, if they never match anything.
+    pub fn report_only_first(self, count: usize) -> IgnorePatterns {
+        IgnorePatterns { reported: count, ..self }
+    }
+
+    /// The patterns that are reported if they never match anything, and
+    /// haven’t matched any files yet.
+    pub fn unmatched(&self) -> Vec<&str> {
+        self.patterns.iter().zip(self.hits.values())
+                     .take(self.reported)
+                     .filter(|&(_, hits)| hits == 0)
+                     .map(|(p, _)| p.as_str())
+                     .collect()
+    }
+
+    /// Test whether the given file should be hidden from the results.
+    ///
+    /// The `.` and `..` entries are never ignored: whether they get shown
+    /// is up to the `DotFilter`. Without this, a glob such as `.*`, which is
+    /// the obvious way to hide every dotfile, would match them too, because
+    /// the `*` is allowed to match nothing.
+    pub fn is_ignored(&self, file: &str) -> bool {
+        if file == "." || file == ".." {
+            return false;
+        }
+
+        self.patterns.iter().any(|p| p.matches(file))
+    }
+
+    /// Test whether the given file, found at the given depth, should be
+    /// hidden from the results. This is the same as `is_ignored` for
+    /// patterns without slashes in them, unless the patterns have been
+    /// limited to files below a certain depth.
+    pub fn is_ignored_at(&self, file: &File, depth: usize) -> bool {
+        if let Some(below) = self.below {
+            if depth <= below {
+                return false;
+            }
+        }
+
+        if file.name == "." || file.name == ".." {
+            return false;
+        }
+
+        // Every pattern gets tried, rather than stopping at the first
+        // match, so each one’s count of matches is right.
+        let relative = relative_path(&file.path, depth);
+        let mut ignored = false;
+        for (p, hits) in self.patterns.iter().zip(&self.hits.0) {
+            let matched = if !p.as_str().contains('/') {
+                p.matches(&file.name)
+            }
+            else if p.as_str().starts_with('/') {
+                p.matches_with(&format!("/{}", relative), &PATH_MATCH_OPTIONS)
+            }
+            else {
+                p.matches_with(&relative, &PATH_MATCH_OPTIONS)
+            };
+
+            if matched {
+                let _ = hits.fetch_add(1, AtomicOrdering::Relaxed);
+                ignored = true;
+            }
+        }
+
+        ignored
+    }
+}
+
+/// The options for matching patterns against paths: the wildcards don’t
+/// match slashes, which is what lets `**` match any number of directories,
+/// as the glob crate only treats it specially with this on.
+const PATH_MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive:              true,
+    require_literal_separator:   true,
+    require_literal_leading_dot: false,
+};
+
+/// The path of a file, found at the given depth, from the directory being
+/// listed: its last `depth + 1` components, joined with slashes.
+fn relative_path(path: &Path, depth: usize) -> String {
+    let components: Vec<_> = path.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    let start = components.len().saturating_sub(depth + 1);
+    components[start ..].join("/")
+}
+
+
+/// A **name regex** is a regular expression that each file name gets
+/// tested against, with only the files that match it being displayed. It
+/// goes by the file’s name alone, rather than its whole path.
+///
+/// It’s case-insensitive unless the expression has an upper-case letter in
+/// it, so `readme` matches `README.md` but `README` doesn’t match
+/// `readme.txt`.
+#[derive(Debug, Clone)]
+pub struct NameRegex {
+    regex: Regex,
+}
+
+impl PartialEq for NameRegex {
+    fn eq(&self, other: &NameRegex) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+    }
+}
+
+impl NameRegex {
+
+    /// Compiles the given expression, returning the error from the regex
+    /// crate if it isn’t valid.
+    pub fn new(pattern: &str) -> Result<NameRegex, regex::Error> {
+        let regex = RegexBuilder::new(pattern)
+                        .case_insensitive(!has_upper_case(pattern))
+                        .build()?;
+
+        Ok(NameRegex { regex })
+    }
+
+    /// Test whether the given file name matches the expression. As with the
+    /// ignore patterns, the `.` and `..` entries always get through: whether
+    /// they’re shown is up to the `DotFilter`.
+    pub fn matches(&self, file: &str) -> bool {
+        file == "." || file == ".." || self.regex.is_match(file)
+    }
+}
+
+/// Whether the given expression has an upper-case letter in it, not
+/// counting the ones that are part of an escape such as `\S` or `\W`.
+fn has_upper_case(pattern: &str) -> bool {
+    let mut escaped = false;
+
+    for c in pattern.chars() {
+        if escaped {
+            escaped = false;
+        }
+        else if c == '\\' {
+            escaped = true;
+        }
+        else if c.is_uppercase() {
+            return true;
+        }
+    }
+
+    false
+}
+
+
+
+#[cfg(test)]
+mod test_name_regex {
+    use super::*;
+
+    #[test]
+    fn readme() {
+        let regex = NameRegex::new("^README").unwrap();
+        assert!(regex.matches("README.md"));
+        assert!(!regex.matches("readme.txt"));
+        assert!(!regex.matches("NOT_README"));
+    }
+
+    #[test]
+    fn lower_case_is_insensitive() {
+        let regex = NameRegex::new("^readme").unwrap();
+        assert!(regex.matches("README.md"));
+        assert!(regex.matches("readme.txt"));
+    }
+
+    #[test]
+    fn escapes_are_not_upper_case() {
+        let regex = NameRegex::new("^readme\\S").unwrap();
+        assert!(regex.matches("README.md"));
+    }
+
+    #[test]
+    fn dots_always_match() {
+        let regex = NameRegex::new("^README").unwrap();
+        assert!(regex.matches("."));
+        assert!(regex.matches(".."));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(NameRegex::new("(unclosed").is_err());
+    }
+
+    #[test]
+    fn filters_children() {
+        use scratch::ScratchDir;
+        use std::fs;
+        use fs::{Dir, DotFilter};
+
+        let scratch = ScratchDir::new("grep-children");
+        let path = &scratch.path;
+        for name in &[ "README.md", "README", "main.rs", "readme.txt" ] {
+            let _ = fs::File::create(path.join(name)).unwrap();
+        }
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        let filter = FileFilter { name_regex: NameRegex::new("^README").ok(), ..FileFilter::default() };
+        filter.filter_child_files(&mut files, 0);
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &*f.name).collect();
+        assert_eq!(names, vec![ "README", "README.md" ]);
+    }
+}
+
+
+#[cfg(test)]
+mod test_ignores {
+    use super::*;
+
+    #[test]
+    fn empty_matches_nothing() {
+        let pats = IgnorePatterns::empty();
+        assert_eq!(false, pats.is_ignored("nothing"));
+        assert_eq!(false, pats.is_ignored("test.mp3"));
+    }
+
+    #[test]
+    fn ignores_a_glob() {
+        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ "*.mp3" ]);
+        assert!(fails.is_empty());
+        assert_eq!(false, pats.is_ignored("nothing"));
+        assert_eq!(true,  pats.is_ignored("test.mp3"));
+    }
+
+    #[test]
+    fn ignores_an_exact_filename() {
+        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ "nothing" ]);
+        assert!(fails.is_empty());
+        assert_eq!(true,  pats.is_ignored("nothing"));
+        assert_eq!(false, pats.is_ignored("test.mp3"));
+    }
+
+    #[test]
+    fn ignores_both() {
+        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ "nothing", "*.mp3" ]);
+        assert!(fails.is_empty());
+        assert_eq!(true, pats.is_ignored("nothing"));
+        assert_eq!(true, pats.is_ignored("test.mp3"));
+    }
+
+    #[test]
+    fn ignores_dotfiles() {
+        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ ".*" ]);
+        assert!(fails.is_empty());
+        assert_eq!(true,  pats.is_ignored(".bashrc"));
+        assert_eq!(true,  pats.is_ignored(".git"));
+        assert_eq!(true,  pats.is_ignored("..."));
+        assert_eq!(false, pats.is_ignored("bashrc"));
+        assert_eq!(false, pats.is_ignored("file.txt"));
+    }
+
+    #[test]
+    fn subtree_patterns() {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+
+        let scratch = ScratchDir::new("ignore-subtree");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("target")).unwrap();
+        std_fs::create_dir_all(path.join("a/b/c/target")).unwrap();
+        std_fs::create_dir_all(path.join("a/b/c/notarget")).unwrap();
+
+        let file = |relative: &str| File::new(path.join(relative), None, None).unwrap();
+
+        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ "**/target" ]);
+        assert!(fails.is_empty());
+        assert_eq!(true,  pats.is_ignored_at(&file("target"), 0));
+        assert_eq!(true,  pats.is_ignored_at(&file("a/b/c/target"), 3));
+        assert_eq!(false, pats.is_ignored_at(&file("a/b/c/notarget"), 3));
+        assert_eq!(false, pats.is_ignored_at(&file("a/b/c"), 2));
+
+        let (pats, _) = IgnorePatterns::parse_from_iter(vec![ "/target" ]);
+        assert_eq!(true,  pats.is_ignored_at(&file("target"), 0));
+        assert_eq!(false, pats.is_ignored_at(&file("a/b/c/target"), 3));
+
+        let (pats, _) = IgnorePatterns::parse_from_iter(vec![ "target" ]);
+        assert_eq!(true,  pats.is_ignored_at(&file("target"), 0));
+        assert_eq!(true,  pats.is_ignored_at(&file("a/b/c/target"), 3));
+
+        // Single wildcards stay within one directory.
+        let (pats, _) = IgnorePatterns::parse_from_iter(vec![ "a/*/target" ]);
+        assert_eq!(false, pats.is_ignored_at(&file("a/b/c/target"), 3));
+        let (pats, _) = IgnorePatterns::parse_from_iter(vec![ "a/**/target" ]);
+        assert_eq!(true,  pats.is_ignored_at(&file("a/b/c/target"), 3));
+    }
+
+    #[test]
+    fn never_ignores_dot_entries() {
+        let (pats, fails) = IgnorePatterns::parse_from_iter(vec![ ".*", ".", "..", "*" ]);
+        assert!(fails.is_empty());
+        assert_eq!(false, pats.is_ignored("."));
+        assert_eq!(false, pats.is_ignored(".."));
+    }
+
+    #[test]
+    fn dot_entries_follow_the_dot_filter() {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+        use fs::Dir;
+
+        let scratch = ScratchDir::new("ignore-dotfiles");
+        let path = &scratch.path;
+        for name in &[ ".hidden", "visible" ] {
+            let _ = std_fs::File::create(path.join(name)).unwrap();
+        }
+
+        let (ignore_patterns, _) = IgnorePatterns::parse_from_iter(vec![ ".*" ]);
+        let filter = FileFilter { ignore_patterns, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+
+        let names = |dot_filter| {
+            let mut files: Vec<File> = dir.files(dot_filter).map(Result::unwrap).collect();
+            filter.filter_child_files(&mut files, 0);
+            filter.sort_files(&mut files);
+            files.iter().map(|f| f.name.clone()).collect::<Vec<String>>()
+        };
+
+        assert_eq!(names(DotFilter::JustFiles),       vec![ "visible" ]);
+        assert_eq!(names(DotFilter::Dotfiles),        vec![ "visible" ]);
+        assert_eq!(names(DotFilter::DotfilesAndDots), vec![ ".", "..", "visible" ]);
+    }
+
+    #[test]
+    fn arguments_are_not_ignored() {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+        use fs::Dir;
+
+        let scratch = ScratchDir::new("ignore-arguments");
+        let path = &scratch.path;
+        for name in &[ "error.log", "main.rs" ] {
+            let _ = std_fs::File::create(path.join(name)).unwrap();
+        }
+
+        let (ignore_patterns, _) = IgnorePatterns::parse_from_iter(vec![ "*.log" ]);
+        let filter = FileFilter { ignore_patterns, ..FileFilter::default() };
+
+        let mut arguments = vec![ File::new(path.join("error.log"), None, None).unwrap() ];
+        filter.filter_argument_files(&mut arguments);
+        assert_eq!(arguments.iter().map(|f| f.name.clone()).collect::<Vec<String>>(), vec![ "error.log" ]);
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let mut children: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+        filter.filter_child_files(&mut children, 0);
+        assert_eq!(children.iter().map(|f| f.name.clone()).collect::<Vec<String>>(), vec![ "main.rs" ]);
+    }
+}
+
+
+#[cfg(test)]
+mod test_owners {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::ffi::CString;
+    use std::fs as std_fs;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+    use libc;
+    use fs::Dir;
+
+    /// Gives the file at the given path to the given user, which only works
+    /// when running as root.
+    fn chown(path: &Path, uid: f::uid_t) -> bool {
+        let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+        unsafe { libc::chown(c_path.as_ptr(), uid, libc::gid_t::max_value()) == 0 }
+    }
+
+    #[test]
+    fn hides_nothing_by_default() {
+        let file = File::new(PathBuf::from("/"), None, None).unwrap();
+        assert_eq!(false, OwnerFilter::default().is_hidden(&file));
+    }
+
+    #[test]
+    fn hides_root_owned_files() {
+        let scratch = ScratchDir::new("no-root");
+        let path = &scratch.path;
+        let _ = std_fs::File::create(path.join("root-owned")).unwrap();
+        let _ = std_fs::File::create(path.join("user-owned")).unwrap();
+
+        // Files can only be given away when running as root, and can only
+        // be made root-owned that way too, so there’s nothing to test
+        // otherwise.
+        if chown(&path.join("root-owned"), 0) && chown(&path.join("user-owned"), 1000) {
+            let filter = FileFilter { owner_filter: OwnerFilter::no_root(), ..FileFilter::default() };
+            let dir = Dir::read_dir(path.clone(), None).unwrap();
+
+            let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+            filter.filter_child_files(&mut files, 0);
+
+            let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+            assert_eq!(names, vec![ "user-owned" ]);
+        }
+    }
+
+    #[test]
+    fn only_shows_one_owner() {
+        let scratch = ScratchDir::new("owner");
+        let path = &scratch.path;
+        let _ = std_fs::File::create(path.join("alice")).unwrap();
+        let _ = std_fs::File::create(path.join("bob")).unwrap();
+
+        // As above, this needs to be running as root to give files away.
+        if chown(&path.join("alice"), 1001) && chown(&path.join("bob"), 1002) {
+            let dir = Dir::read_dir(path.clone(), None).unwrap();
+            let files = || -> Vec<File> { dir.files(DotFilter::JustFiles).map(Result::unwrap).collect() };
+            let names = |files: &[File]| -> Vec<String> { files.iter().map(|f| f.name.clone()).collect() };
+
+            let by_user = FileFilter { owner_filter: OwnerFilter { only_user: Some(1001), ..OwnerFilter::default() }, ..FileFilter::default() };
+            let mut user_files = files();
+            by_user.filter_child_files(&mut user_files, 0);
+            assert_eq!(names(&user_files), vec![ "alice" ]);
+
+            let by_group = FileFilter { owner_filter: OwnerFilter { only_group: Some(0), ..OwnerFilter::default() }, ..FileFilter::default() };
+            let mut group_files = files();
+            by_group.filter_child_files(&mut group_files, 0);
+            by_group.sort_files(&mut group_files);
+            assert_eq!(names(&group_files), vec![ "alice", "bob" ]);
+        }
+    }
+
+    #[test]
+    fn hides_other_groups() {
+        let file = File::new(PathBuf::from("/"), None, None).unwrap();
+        let filter = OwnerFilter { only_group: Some(file.group().0 + 1), ..OwnerFilter::default() };
+        assert!(filter.is_hidden(&file));
+
+        let filter = OwnerFilter { only_group: Some(file.group().0), ..OwnerFilter::default() };
+        assert!(!filter.is_hidden(&file));
+    }
+
+    #[test]
+    fn hides_root_owned_arguments() {
+        let mut files = vec![ File::new(PathBuf::from("/"), None, None).unwrap() ];
+        let filter = FileFilter { owner_filter: OwnerFilter::no_root(), ..FileFilter::default() };
+        filter.filter_argument_files(&mut files);
+        assert!(files.is_empty());
+    }
+}
+
+
+#[cfg(test)]
+mod test_git_ranks {
+    use super::git_rank;
+    use fs::fields as f;
+    use fs::fields::GitStatus::*;
+
+    fn git(staged: f::GitStatus, unstaged: f::GitStatus) -> f::Git {
+        f::Git { staged, unstaged }
+    }
+
+    #[test]
+    fn severity_order() {
+        let ranks = [ git_rank(&git(Conflicted,  Conflicted),  false),
+                      git_rank(&git(NotModified, Modified),    false),
+                      git_rank(&git(New,         NotModified), false),
+                      git_rank(&git(Renamed,     NotModified), false),
+                      git_rank(&git(NotModified, TypeChange),  false),
+                      git_rank(&git(NotModified, NotModified), false),
+                      git_rank(&git(NotModified, NotModified), true) ];
+
+        for pair in ranks.windows(2) {
+            assert!(pair[0] < pair[1], "{:?}", ranks);
+        }
+    }
+
+    #[test]
+    fn staged_and_unstaged_combined() {
+        assert_eq!(git_rank(&git(New, Modified), false), git_rank(&git(NotModified, Modified), false));
+        assert_eq!(git_rank(&git(Renamed, Conflicted), false), git_rank(&git(Conflicted, NotModified), false));
+    }
+
+    #[test]
+    fn deleted_and_intent_to_add() {
+        assert_eq!(git_rank(&git(Deleted, NotModified), false), git_rank(&git(Modified, NotModified), false));
+        assert_eq!(git_rank(&git(IntentToAdd, Modified), false), git_rank(&git(NotModified, Modified), false));
+    }
+
+    #[test]
+    fn ignored_only_when_clean() {
+        assert_eq!(git_rank(&git(NotModified, New), true), git_rank(&git(NotModified, New), false));
+    }
+}
+
+
+#[cfg(test)]
+mod test_natural_keys {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use fs::Dir;
+
+    /// Names that exercise every part of `natord`’s algorithm: runs of
+    /// digits of different lengths, leading zeroes, whitespace splitting
+    /// digits apart, digits next to other characters, and non-ASCII.
+    static NAMES: &[&str] = &[
+        "", "a", "a0", "a1", "a1a", "a1b", "a2", "a10", "a20", "A10",
+        "x2-g8", "x2-y7", "x2-y8", "x8-y8", "1.001", "1.002", "1.010",
+        "1.02", "1.1", "1.3", "015", "12", "0", "00", "007", "7", "1 2",
+        "12 ", " 12", "1  3", "file9", "file10", "file 10", "file010",
+        "99999999999999999999", "100000000000000000000", "é1", "é01",
+        "-1", "1-", "\u{3000}2", "２", "a b", "ab",
+    ];
+
+    #[test]
+    fn same_order_as_natord() {
+        for a in NAMES {
+            for b in NAMES {
+                let order = NaturalKey::new(a).cmp(&NaturalKey::new(b));
+                assert_eq!(natord::compare(a, b), order, "comparing {:?} with {:?}", a, b);
+            }
+        }
+    }
+
+    fn with_separator(name: &str, separator: &str) -> NaturalKey {
+        NaturalKey::with_separator(name, Some(separator))
+    }
+
+    #[test]
+    fn thousands_separators() {
+        assert!(with_separator("1,000", ",") == NaturalKey::new("1000"));
+        assert!(with_separator("file 1,234,567.txt", ",") == NaturalKey::new("file 1234567.txt"));
+        assert!(with_separator("9,999", ",") < with_separator("10,000", ","));
+        assert!(with_separator("10,000", ",") < NaturalKey::new("10001"));
+    }
+
+    #[test]
+    fn other_locales_separators() {
+        assert!(with_separator("1.000.000", ".") == NaturalKey::new("1000000"));
+        assert!(with_separator("1\u{202f}000", "\u{202f}") == NaturalKey::new("1000"));
+        assert!(with_separator("1,000", ".") != NaturalKey::new("1000"));
+    }
+
+    #[test]
+    fn separators_need_three_digits() {
+        assert!(with_separator("v1.2", ".") == NaturalKey::new("v1.2"));
+        assert!(with_separator("1,0000", ",") == NaturalKey::new("1,0000"));
+        assert!(with_separator("1,", ",") == NaturalKey::new("1,"));
+    }
+
+    #[test]
+    fn sorted_with_separators() {
+        let scratch = ScratchDir::new("locale-numbers");
+        let path = &scratch.path;
+        for name in &[ "file 900", "file 1,000", "file 1001", "file 10,000" ] {
+            let _ = std_fs::File::create(path.join(name)).unwrap();
+        }
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        let filter = FileFilter { thousands_separator: Some(",".into()), ..FileFilter::default() };
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "file 900", "file 1,000", "file 1001", "file 10,000" ]);
+    }
+}
+
+
+#[cfg(test)]
+mod test_links_only {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use std::os::unix::fs::symlink;
+    use fs::Dir;
+
+    #[test]
+    fn only_symlinks() {
+        let scratch = ScratchDir::new("links-only");
+        let path = &scratch.path;
+        std_fs::create_dir(path.join("dir")).unwrap();
+        let _ = std_fs::File::create(path.join("file")).unwrap();
+        symlink("file", path.join("file-link")).unwrap();
+        symlink("dir", path.join("dir-link")).unwrap();
+        symlink("nowhere", path.join("broken-link")).unwrap();
+
+        let filter = FileFilter { links_only: true, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+        filter.filter_child_files(&mut files, 0);
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "broken-link", "dir-link", "file-link" ]);
+    }
+}
+
+
+#[cfg(test)]
+mod test_executable_only {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use std::os::unix::fs::PermissionsExt;
+    use fs::Dir;
+
+    #[test]
+    fn only_executables() {
+        let scratch = ScratchDir::new("executable-only");
+        let path = &scratch.path;
+        std_fs::create_dir(path.join("dir")).unwrap();
+        let _ = std_fs::File::create(path.join("plain")).unwrap();
+        let _ = std_fs::File::create(path.join("script")).unwrap();
+        let _ = std_fs::File::create(path.join("others-only")).unwrap();
+        std_fs::set_permissions(path.join("script"), std_fs::Permissions::from_mode(0o755)).unwrap();
+        std_fs::set_permissions(path.join("others-only"), std_fs::Permissions::from_mode(0o641)).unwrap();
+
+        let filter = FileFilter { executable_only: true, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+        filter.filter_child_files(&mut files, 0);
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "others-only", "script" ]);
+    }
+}
+
+
+#[cfg(test)]
+mod test_filter_modes {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use std::os::unix::fs::symlink;
+    use fs::Dir;
+
+    /// Lists a directory of text files and links with both `*.txt` ignored
+    /// and only links shown, combining the two filters in the given way.
+    fn names(filter_mode: FilterMode, name: &str) -> Vec<String> {
+        let scratch = ScratchDir::new(name);
+        let path = &scratch.path;
+        let _ = std_fs::File::create(path.join("file")).unwrap();
+        let _ = std_fs::File::create(path.join("file.txt")).unwrap();
+        symlink("file", path.join("link")).unwrap();
+        symlink("file.txt", path.join("link.txt")).unwrap();
+
+        let (ignore_patterns, _) = IgnorePatterns::parse_from_iter(vec![ "*.txt" ]);
+        let filter = FileFilter { ignore_patterns, links_only: true, filter_mode, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+        filter.filter_child_files(&mut files, 0);
+        filter.sort_files(&mut files);
+        files.iter().map(|f| f.name.clone()).collect()
+    }
+
+    #[test]
+    fn and_passes_every_filter() {
+        assert_eq!(names(FilterMode::And, "filter-mode-and"), vec![ "link" ]);
+    }
+
+    #[test]
+    fn or_passes_any_filter() {
+        assert_eq!(names(FilterMode::Or, "filter-mode-or"), vec![ "file", "link", "link.txt" ]);
+    }
+
+    #[test]
+    fn or_without_filters_passes_everything() {
+        assert!(FilterMode::Or.combine(None.into_iter()));
+        assert!(FilterMode::And.combine(None.into_iter()));
+    }
+}
+
+
+#[cfg(all(test, feature="git"))]
+mod test_changed_only {
+    use super::*;
+    use fs::Dir;
+    use fs::feature::GitOptions;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use std::io::Write;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git").args(args).current_dir(dir)
+                                        .env("GIT_AUTHOR_NAME", "exa").env("GIT_AUTHOR_EMAIL", "exa@example.com")
+                                        .env("GIT_COMMITTER_NAME", "exa").env("GIT_COMMITTER_EMAIL", "exa@example.com")
+                                        .status().unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn write(path: &Path, contents: &[u8]) {
+        std_fs::File::create(path).unwrap().write_all(contents).unwrap();
+    }
+
+    #[test]
+    fn only_the_modified_file() {
+        let scratch = ScratchDir::new("changed-only");
+        let path = &scratch.path;
+
+        write(&path.join("clean"), b"clean");
+        write(&path.join("modified"), b"before");
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "clean", "modified" ]);
+        git(&path, &[ "commit", "-q", "-m", "Files" ]);
+        write(&path.join("modified"), b"after");
+
+        let filter = FileFilter { changed_only: true, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), Some(&GitOptions::default())).unwrap();
+        assert!(dir.has_git_repo());
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "modified" ]);
+    }
+
+    #[test]
+    fn only_the_files_changed_since_a_ref() {
+        let scratch = ScratchDir::new("git-since-only");
+        let path = &scratch.path;
+
+        write(&path.join("old"), b"old");
+        write(&path.join("changed"), b"before");
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "old", "changed" ]);
+        git(&path, &[ "commit", "-q", "-m", "First" ]);
+        git(&path, &[ "tag", "first" ]);
+
+        write(&path.join("changed"), b"after");
+        write(&path.join("added"), b"added");
+        git(&path, &[ "add", "changed", "added" ]);
+        git(&path, &[ "commit", "-q", "-m", "Second" ]);
+        write(&path.join("untracked"), b"untracked");
+
+        let filter = FileFilter { git_since_only: true, ..FileFilter::default() };
+        let options = GitOptions { since: Some("first".into()), ..GitOptions::default() };
+        let dir = Dir::read_dir(path.clone(), Some(&options)).unwrap();
+        assert!(dir.has_git_since());
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "added", "changed", "untracked" ]);
+    }
+
+    #[test]
+    fn unknown_ref() {
+        let scratch = ScratchDir::new("git-since-unknown");
+        let path = &scratch.path;
+
+        write(&path.join("file"), b"file");
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "file" ]);
+        git(&path, &[ "commit", "-q", "-m", "File" ]);
+
+        let filter = FileFilter { git_since_only: true, ..FileFilter::default() };
+        let options = GitOptions { since: Some("no-such-ref".into()), ..GitOptions::default() };
+        let dir = Dir::read_dir(path.clone(), Some(&options)).unwrap();
+        assert!(dir.has_git_repo());
+        assert!(!dir.has_git_since());
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn no_repository() {
+        let scratch = ScratchDir::new("changed-only-no-repo");
+        let path = &scratch.path;
+        write(&path.join("file"), b"file");
+
+        let filter = FileFilter { changed_only: true, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn only_the_dirty_file() {
+        let scratch = ScratchDir::new("git-only-dirty");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("sub")).unwrap();
+
+        write(&path.join("clean"), b"clean");
+        write(&path.join("sub/modified"), b"before");
+        write(&path.join(".gitignore"), b"ignored\n");
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "clean", "sub/modified", ".gitignore" ]);
+        git(&path, &[ "commit", "-q", "-m", "Files" ]);
+        write(&path.join("sub/modified"), b"after");
+        write(&path.join("ignored"), b"ignored");
+
+        let filter = FileFilter { dirty_only: true, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), Some(&GitOptions::default())).unwrap();
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "sub" ]);
+
+        let sub = files[0].to_dir(Some(&GitOptions::default())).unwrap();
+        let mut files: Vec<File> = sub.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "modified" ]);
+    }
+
+    #[test]
+    fn sorted_by_git_status() {
+        let scratch = ScratchDir::new("sort-git");
+        let path = &scratch.path;
+
+        write(&path.join(".gitignore"), b"a-ignored\n");
+        write(&path.join("b-clean"), b"clean");
+        write(&path.join("d-modified"), b"before");
+        write(&path.join("e-conflicted"), b"base");
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", ".gitignore", "b-clean", "d-modified", "e-conflicted" ]);
+        git(&path, &[ "commit", "-q", "-m", "Base" ]);
+
+        git(&path, &[ "checkout", "-q", "-b", "other" ]);
+        write(&path.join("e-conflicted"), b"theirs");
+        git(&path, &[ "commit", "-q", "-am", "Theirs" ]);
+        git(&path, &[ "checkout", "-q", "-" ]);
+        write(&path.join("e-conflicted"), b"ours");
+        git(&path, &[ "commit", "-q", "-am", "Ours" ]);
+
+        // The merge is meant to fail, leaving the conflict behind.
+        let merge = Command::new("git").args(&[ "merge", "-q", "other" ]).current_dir(&path).output().unwrap();
+        assert!(!merge.status.success());
+
+        write(&path.join("a-ignored"), b"ignored");
+        write(&path.join("c-new"), b"new");
+        write(&path.join("d-modified"), b"after");
+
+        let filter = FileFilter { sort_field: SortField::GitStatus, ..FileFilter::default() };
+        assert!(filter.needs_git());
+
+        let dir = Dir::read_dir(path.clone(), Some(&GitOptions::default())).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "e-conflicted", "d-modified", "c-new", "b-clean", "a-ignored" ]);
+    }
+
+    fn tracked_names(tracked_filter: TrackedFilter, name: &str) -> Vec<String> {
+        let scratch = ScratchDir::new(name);
+        let path = &scratch.path;
+
+        write(&path.join("committed"), b"committed");
+        git(&path, &[ "init", "-q" ]);
+        git(&path, &[ "add", "committed" ]);
+        git(&path, &[ "commit", "-q", "-m", "File" ]);
+        write(&path.join("new"), b"new");
+
+        let filter = FileFilter { tracked_filter, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), Some(&GitOptions::default())).unwrap();
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+        files.iter().map(|f| f.name.clone()).collect()
+    }
+
+    #[test]
+    fn only_tracked() {
+        assert_eq!(tracked_names(TrackedFilter::Tracked, "tracked-only"), vec![ "committed" ]);
+    }
+
+    #[test]
+    fn only_untracked() {
+        assert_eq!(tracked_names(TrackedFilter::Untracked, "untracked-only"), vec![ "new" ]);
+    }
+
+    #[test]
+    fn tracked_or_not_without_a_repository() {
+        let scratch = ScratchDir::new("tracked-only-no-repo");
+        let path = &scratch.path;
+        write(&path.join("file"), b"file");
+
+        let filter = FileFilter { tracked_filter: TrackedFilter::Tracked, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), Some(&GitOptions::default())).unwrap();
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn nothing_dirty_without_a_repository() {
+        let scratch = ScratchDir::new("git-only-dirty-no-repo");
+        let path = &scratch.path;
+        write(&path.join("file"), b"file");
+
+        let filter = FileFilter { dirty_only: true, ..FileFilter::default() };
+        let dir = Dir::read_dir(path.clone(), Some(&GitOptions::default())).unwrap();
+        assert!(!dir.has_git_repo());
+
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(|f| f.unwrap()).collect();
+        filter.filter_child_files(&mut files, 0);
+        assert!(files.is_empty());
+    }
+}
+
+
+#[cfg(test)]
+mod test_sorting {
+    use super::*;
+    use std::fs as std_fs;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use fs::Dir;
+    use scratch::ScratchDir;
+
+    /// A scratch directory full of files.
+    fn scratch_with_files(name: &str, count: usize) -> ScratchDir {
+        let scratch = ScratchDir::new(&format!("{}-{}", name, count));
+
+        // Mix up the case, numbers, extensions, and sizes, so that every
+        // sort field has plenty of ties and near-ties to deal with.
+        let exts = [ "", ".txt", ".TXT", ".rs", ".Mp3", ".tar.gz" ];
+        for i in 0 .. count {
+            let stem = if i % 3 == 0 { "File" } else { "file" };
+            let name = format!("{}{}{}", stem, (count - i) % 97, exts[i % exts.len()]);
+            let name = format!("{}_{}", name, i);
+            let mut file = std_fs::File::create(scratch.join(&name)).unwrap();
+            file.write_all(&vec![b'x'; i % 13]).unwrap();
+        }
+
+        for i in 0 .. count / 100 {
+            std_fs::create_dir(scratch.join(format!("dir{}", i))).unwrap();
+        }
+
+        scratch
+    }
+
+    /// The comparison each sort field performed before sort keys existed,
+    /// kept here to make sure the keys give exactly the same order.
+    fn reference_compare(field: SortField, a: &File, b: &File) -> Ordering {
+        use self::SortCase::{Sensitive, Insensitive};
+
+        let order = match field {
+            SortField::Name(Sensitive)    => natord::compare(&a.name, &b.name),
+            SortField::Name(Insensitive)  => natord::compare_ignore_case(&a.name, &b.name),
+            SortField::Size               => a.metadata.len().cmp(&b.metadata.len()),
+            SortField::FileType           => a.type_char().cmp(&b.type_char()).then_with(|| natord::compare(&a.name, &b.name)),
+            SortField::Extension(Sensitive)    => a.ext.cmp(&b.ext).then_with(|| natord::compare(&a.name, &b.name)),
+            SortField::Extension(Insensitive)  => a.ext.cmp(&b.ext).then_with(|| natord::compare_ignore_case(&a.name, &b.name)),
+            _ => unreachable!(),
+        };
+
+        order.then_with(|| natord::compare(&a.name, &b.name))
+             .then_with(|| a.name.cmp(&b.name))
+             .then_with(|| a.path.cmp(&b.path))
+    }
+
+    #[test]
+    fn large_directory() {
+        use self::SortCase::{Sensitive, Insensitive};
+
+        let scratch = scratch_with_files("large-directory-sort", 5000);
+        let dir = Dir::read_dir(scratch.path.clone(), None).unwrap();
+
+        let fields = [ SortField::Name(Sensitive), SortField::Name(Insensitive),
+                       SortField::Extension(Sensitive), SortField::Extension(Insensitive),
+                       SortField::Size, SortField::FileType ];
+
+        for &field in fields.iter() {
+            for &list_dirs_first in [ None, Some(DirsFirst::WithLinks), Some(DirsFirst::Real) ].iter() {
+                let filter = FileFilter {
+                    list_dirs_first,
+                    sort_field: field,
+                    ..FileFilter::default()
+                };
+
+                let files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+                let mut expected: Vec<&File> = files.iter().collect();
+                expected.sort_by(|a, b| reference_compare(field, a, b));
+                if list_dirs_first.is_some() {
+                    expected.sort_by(|a, b| b.is_directory().cmp(&a.is_directory()));
+                }
+
+                let mut actual: Vec<&File> = files.iter().collect();
+                filter.sort_files(&mut actual);
+
+                let expected: Vec<&str> = expected.iter().map(|f| &*f.name).collect();
+                let actual:   Vec<&str> = actual.iter().map(|f| &*f.name).collect();
+                assert_eq!(expected, actual, "{:?} (dirs first: {:?})", field, list_dirs_first);
+            }
+        }
+    }
+
+
+    /// Shuffles the items using a simple linear congruential generator, so
+    /// the tests get a thoroughly mixed-up order without needing a source
+    /// of randomness, and the same seed always gives the same order.
+    fn shuffle<T>(items: &mut Vec<T>, seed: u64) {
+        let mut state = seed;
+        for i in (1 .. items.len()).rev() {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let j = (state >> 33) as usize % (i + 1);
+            items.swap(i, j);
+        }
+    }
+
+    /// Sorts the same set of files twice, starting from two different
+    /// shuffled orders, and checks that both come out in the same order.
+    /// The files are picked so that every field has ties to break: names
+    /// that only differ in case or in ways `natord` ignores, files with the
+    /// same size, type, and extension, and even two files with the same
+    /// name in different directories.
+    fn assert_double_shuffle(field: SortField) {
+        let scratch = ScratchDir::new(&format!("double-shuffle-{:?}", field).replace(|c: char| !c.is_alphanumeric(), "-"));
+        let names = [ "a", "A", "b.txt", "B.txt", "b.TXT", "file1", "file01", "file 1",
+                      "file10", "c.rs", "C.RS", "d", "D", "e.tar.gz", "E.tar.gz" ];
+
+        let sub = scratch.join("sub");
+        std_fs::create_dir(&sub).unwrap();
+        for name in names.iter() {
+            let _ = std_fs::File::create(scratch.join(name)).unwrap();
+            let _ = std_fs::File::create(sub.join(name)).unwrap();
+        }
+        std_fs::create_dir(scratch.join("dir")).unwrap();
+        std_fs::create_dir(scratch.join("Dir")).unwrap();
+
+        let top = Dir::read_dir(scratch.path.clone(), None).unwrap();
+        let sub = Dir::read_dir(sub, None).unwrap();
+        let files: Vec<File> = top.files(DotFilter::JustFiles)
+                                  .chain(sub.files(DotFilter::JustFiles))
+                                  .map(Result::unwrap)
+                                  .collect();
+
+        for &reverse in [ false, true ].iter() {
+            for &list_dirs_first in [ None, Some(DirsFirst::WithLinks) ].iter() {
+                let filter = FileFilter { sort_field: field, reverse, list_dirs_first, ..FileFilter::default() };
+
+                let mut one: Vec<&File> = files.iter().collect();
+                let mut two: Vec<&File> = files.iter().collect();
+                shuffle(&mut one, 1);
+                shuffle(&mut two, 2);
+                assert!(one.iter().map(|f| &f.path).ne(two.iter().map(|f| &f.path)));
+
+                filter.sort_files(&mut one);
+                filter.sort_files(&mut two);
+
+                let one: Vec<&PathBuf> = one.iter().map(|f| &f.path).collect();
+                let two: Vec<&PathBuf> = two.iter().map(|f| &f.path).collect();
+                assert_eq!(one, two, "{:?} (reverse: {}, dirs first: {:?})", field, reverse, list_dirs_first);
+            }
+        }
+    }
+
+    mod double_shuffle {
+        use super::*;
+        use super::super::SortCase::{Sensitive, Insensitive};
+
+        #[test] fn name()             { assert_double_shuffle(SortField::Name(Sensitive)) }
+        #[test] fn name_insensitive() { assert_double_shuffle(SortField::Name(Insensitive)) }
+        #[test] fn ext()              { assert_double_shuffle(SortField::Extension(Sensitive)) }
+        #[test] fn ext_insensitive()  { assert_double_shuffle(SortField::Extension(Insensitive)) }
+        #[test] fn size()             { assert_double_shuffle(SortField::Size) }
+        #[test] fn inode()            { assert_double_shuffle(SortField::FileInode) }
+        #[test] fn modified()         { assert_double_shuffle(SortField::ModifiedDate) }
+        #[test] fn accessed()         { assert_double_shuffle(SortField::AccessedDate) }
+        #[test] fn created()          { assert_double_shuffle(SortField::CreatedDate) }
+        #[test] fn changed()          { assert_double_shuffle(SortField::ChangedDate) }
+        #[test] fn entry_count()      { assert_double_shuffle(SortField::EntryCount) }
+        #[test] fn item_count()       { assert_double_shuffle(SortField::ItemCount(DotFilter::JustFiles)) }
+        #[test] fn file_type()        { assert_double_shuffle(SortField::FileType) }
+        #[test] fn mixed()            { assert_double_shuffle(SortField::Mixed) }
+        #[test] fn segments()         { assert_double_shuffle(SortField::Segments) }
+    }
+
+    #[test]
+    fn by_entry_count() {
+        let scratch = ScratchDir::new("entry-count");
+        for path in &[ "big/nested/deeper", "medium/nested", "small" ] {
+            std_fs::create_dir_all(scratch.join(path)).unwrap();
+        }
+        for path in &[ "big/one", "big/nested/two", "big/nested/deeper/three", "medium/nested/one", "small/one", "file" ] {
+            let _ = std_fs::File::create(scratch.join(path)).unwrap();
+        }
+
+        let dir = Dir::read_dir(scratch.path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        let filter = FileFilter { sort_field: SortField::EntryCount, ..FileFilter::default() };
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "file", "small", "medium", "big" ]);
+    }
+
+    #[test]
+    fn by_item_count() {
+        let scratch = ScratchDir::new("item-count");
+        for path in &[ "big/nested/deeper", "small" ] {
+            std_fs::create_dir_all(scratch.join(path)).unwrap();
+        }
+        for path in &[ "big/one", "big/nested/deeper/two", "big/nested/deeper/three", "small/one", "small/.two", "small/.three", "file" ] {
+            let _ = std_fs::File::create(scratch.join(path)).unwrap();
+        }
+
+        let dir = Dir::read_dir(scratch.path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        let filter = FileFilter { sort_field: SortField::ItemCount(DotFilter::JustFiles), ..FileFilter::default() };
+        filter.sort_files(&mut files);
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "file", "small", "big" ]);
+
+        let filter = FileFilter { sort_field: SortField::ItemCount(DotFilter::Dotfiles), ..FileFilter::default() };
+        filter.sort_files(&mut files);
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "file", "big", "small" ]);
+    }
+
+    #[test]
+    fn numbers_descend_and_names_ascend() {
+        let scratch = ScratchDir::new("mixed");
+        for name in &[ "banana", "2", "apple", "10", "1" ] {
+            let _ = std_fs::File::create(scratch.join(name)).unwrap();
+        }
+
+        let dir = Dir::read_dir(scratch.path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        let filter = FileFilter { sort_field: SortField::Mixed, ..FileFilter::default() };
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "10", "2", "1", "apple", "banana" ]);
+    }
+
+    #[test]
+    fn fewer_segments_first() {
+        let scratch = ScratchDir::new("segments");
+        std_fs::create_dir_all(scratch.join("a/b")).unwrap();
+        for path in &[ "a/b/c.txt", "a/b.txt", "a/a.txt", "z.txt" ] {
+            let _ = std_fs::File::create(scratch.join(path)).unwrap();
+        }
+
+        let mut files: Vec<File> = [ "a/b/c.txt", "a/b.txt", "z.txt", "a/a.txt" ].iter()
+            .map(|path| File::new(scratch.join(path), None, None).unwrap())
+            .collect();
+
+        let filter = FileFilter { sort_field: SortField::Segments, ..FileFilter::default() };
+        filter.sort_files(&mut files);
+
+        let paths: Vec<&Path> = files.iter().map(|f| f.path.strip_prefix(&scratch.path).unwrap()).collect();
+        assert_eq!(paths, vec![ Path::new("z.txt"), Path::new("a/a.txt"), Path::new("a/b.txt"), Path::new("a/b/c.txt") ]);
+    }
+
+    #[test]
+    fn created_is_not_changed() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::thread;
+        use std::time::Duration;
+
+        let scratch = ScratchDir::new("created-changed");
+        for name in &[ "older", "newer" ] {
+            let _ = std_fs::File::create(scratch.join(name)).unwrap();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // Changing the older file’s permissions a second later gives it the
+        // newer change time, while its birth time stays the same.
+        thread::sleep(Duration::from_millis(1100));
+        std_fs::set_permissions(scratch.join("older"), std_fs::Permissions::from_mode(0o600)).unwrap();
+
+        let sorted = |field| {
+            let mut files: Vec<File> = [ "newer", "older" ].iter()
+                .map(|name| File::new(scratch.join(name), None, None).unwrap())
+                .collect();
+            FileFilter { sort_field: field, ..FileFilter::default() }.sort_files(&mut files);
+            files.into_iter().map(|f| f.name).collect::<Vec<String>>()
+        };
+
+        assert_eq!(sorted(SortField::ChangedDate), vec![ "newer", "older" ]);
+
+        // Not every filesystem keeps track of birth times.
+        if std_fs::metadata(scratch.join("older")).unwrap().created().is_ok() {
+            assert_eq!(sorted(SortField::CreatedDate), vec![ "older", "newer" ]);
+        }
+    }
+
+    #[test]
+    fn no_creation_time_sorts_last() {
+        let now = SystemTime::now();
+        assert_eq!(SortValue::Created(None).compare(&SortValue::Created(Some(now))), Ordering::Greater);
+        assert_eq!(SortValue::Created(Some(now)).compare(&SortValue::Created(None)), Ordering::Less);
+    }
+
+    #[test]
+    fn sorted_by_command() {
+        let scratch = ScratchDir::new("sort-exec");
+        for &(name, contents) in &[ ("a", "30\n"), ("b", "4\n"), ("c", "100\n") ] {
+            let mut file = std_fs::File::create(scratch.join(name)).unwrap();
+            file.write_all(contents.as_bytes()).unwrap();
+        }
+        std_fs::create_dir(scratch.join("dir")).unwrap();
+
+        let dir = Dir::read_dir(scratch.path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        // `cat` prints each file’s contents, and fails for the directory.
+        let sort_exec = Some(SortExec { command: "cat 2>/dev/null".into() });
+        let filter = FileFilter { sort_exec, ..FileFilter::default() };
+        filter.sort_files(&mut files);
+
+        let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+        assert_eq!(names, vec![ "b", "a", "c", "dir" ]);
+    }
+
+    /// Sorts the files `a` to `d` with the given sort command, returning
+    /// their names in the order they end up in.
+    fn sorted_by_sort_command(name: &str, command: &str) -> Vec<String> {
+        let scratch = ScratchDir::new(name);
+        for name in &[ "a", "b", "c", "d" ] {
+            let _ = std_fs::File::create(scratch.join(name)).unwrap();
+        }
+
+        let dir = Dir::read_dir(scratch.path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        let sort_command = Some(SortCommand { command: command.into() });
+        let filter = FileFilter { sort_command, ..FileFilter::default() };
+        filter.sort_files(&mut files);
+
+        files.iter().map(|f| f.name.clone()).collect()
+    }
+
+    #[test]
+    fn sorted_by_reversing_command() {
+        assert_eq!(sorted_by_sort_command("sort-cmd-reverse", "sort -r"), vec![ "d", "c", "b", "a" ]);
+    }
+
+    #[test]
+    fn sort_command_leaving_names_out() {
+        assert_eq!(sorted_by_sort_command("sort-cmd-missing", "grep -v -e b -e c | sort -r"), vec![ "d", "a", "b", "c" ]);
+    }
+
+    #[test]
+    fn sort_command_failing() {
+        assert_eq!(sorted_by_sort_command("sort-cmd-failing", "sort -r; false"), vec![ "a", "b", "c", "d" ]);
+    }
+
+    #[test]
+    fn sort_command_with_duplicate_names() {
+        let command = SortCommand { command: "printf 'x\\ny\\nx\\nx\\nz\\n'".into() };
+        assert_eq!(command.reorder(&[ "y", "x", "y", "x" ]), vec![ 1, 0, 3, 2 ]);
+    }
+
+    #[test]
+    fn command_output_trimmed() {
+        let scratch = ScratchDir::new("sort-exec-key");
+        let _ = std_fs::File::create(scratch.join("file")).unwrap();
+
+        let exec = SortExec { command: "printf 'key\\n\\n'; true".into() };
+        assert_eq!(Some("key".to_string()), exec.key_for(&scratch.join("file")));
+
+        let exec = SortExec { command: "false".into() };
+        assert_eq!(None, exec.key_for(&scratch.join("file")));
+    }
+
+    #[test]
+    fn trailing_slashes_ignored() {
+        let scratch = ScratchDir::new("trailing-slash");
+        std_fs::create_dir(scratch.join("foo")).unwrap();
+        let _ = std_fs::File::create(scratch.join("foo-bar")).unwrap();
+        let _ = std_fs::File::create(scratch.join("fon")).unwrap();
+
+        let mut files = vec![
+            File::new(scratch.join("foo-bar"), None, None).unwrap(),
+            File::new(scratch.join("foo"), None, "foo/".to_string()).unwrap(),
+            File::new(scratch.join("fon"), None, None).unwrap(),
+        ];
+
+        for &field in [ SortField::Name(SortCase::Sensitive), SortField::Name(SortCase::Insensitive), SortField::Mixed ].iter() {
+            let filter = FileFilter { sort_field: field, ..FileFilter::default() };
+            filter.sort_files(&mut files);
+
+            let names: Vec<&str> = files.iter().map(|f| &f.name[..]).collect();
+            assert_eq!(names, vec![ "fon", "foo/", "foo-bar" ], "{:?}", field);
+        }
+    }
+
+    #[test]
+    fn trailing_slash_on_a_file_kept() {
+        let scratch = ScratchDir::new("trailing-slash-file");
+        let _ = std_fs::File::create(scratch.join("foo")).unwrap();
+        let file = File::new(scratch.join("foo"), None, "foo/".to_string()).unwrap();
+        assert_eq!(sort_name(&file), "foo/");
+    }
+
+    fn dirs_first_names(dirs_first: DirsFirst) -> Vec<String> {
+        use std::os::unix::fs::symlink;
+
+        let scratch = ScratchDir::new(&format!("dirs-first-{:?}", dirs_first));
+        std_fs::create_dir(scratch.join("real")).unwrap();
+        symlink("real", scratch.join("link")).unwrap();
+        symlink("nowhere", scratch.join("broken")).unwrap();
+        let _ = std_fs::File::create(scratch.join("file")).unwrap();
+
+        let dir = Dir::read_dir(scratch.path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        let filter = FileFilter { list_dirs_first: Some(dirs_first), ..FileFilter::default() };
+        filter.sort_files(&mut files);
+        files.iter().map(|f| f.name.clone()).collect()
+    }
+
+    #[test]
+    fn dirs_first_with_links() {
+        assert_eq!(dirs_first_names(DirsFirst::WithLinks), vec![ "link", "real", "broken", "file" ]);
+    }
+
+    #[test]
+    fn dirs_first_real_only() {
+        assert_eq!(dirs_first_names(DirsFirst::Real), vec![ "real", "broken", "file", "link" ]);
     }
 }
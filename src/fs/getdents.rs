@@ -0,0 +1,149 @@
+//! Reading directories in batches with the `getdents64` system call.
+//!
+//! The standard library’s `read_dir` reads entries one at a time through
+//! `readdir`, allocating a `DirEntry` for each one and looking at it
+//! separately. For directories with many thousands of entries, asking the
+//! kernel for as many as fit in a large buffer at once, and only picking out
+//! their names, is noticeably faster. The entries come back in the same order
+//! either way, as `readdir` uses this call itself.
+
+use std::ffi::OsStr;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use libc;
+
+
+/// How many bytes of entries to ask the kernel for at once.
+const BUFFER_SIZE: usize = 32 * 1024;
+
+/// The offset of the `d_reclen` field in a `linux_dirent64` record, after
+/// the 64-bit inode number and offset.
+const RECLEN_OFFSET: usize = 16;
+
+/// The offset of the `d_name` field, after the record length and the
+/// one-byte file type.
+const NAME_OFFSET: usize = 19;
+
+
+/// Reads the paths of every entry in the directory at the given path, not
+/// including `.` and `..`, in the order the kernel returns them.
+pub fn read_dir_paths(path: &Path) -> io::Result<Vec<PathBuf>> {
+    read_dir_paths_with(path, BUFFER_SIZE)
+}
+
+/// Reads the entries using a buffer of the given size, which has to be big
+/// enough to hold at least one entry.
+fn read_dir_paths_with(path: &Path, buffer_size: usize) -> io::Result<Vec<PathBuf>> {
+    let dir = Descriptor::open(path)?;
+    let mut buffer = vec![0u8; buffer_size];
+    let mut paths = Vec::new();
+
+    loop {
+        let read = unsafe {
+            libc::syscall(libc::SYS_getdents64, dir.0, buffer.as_mut_ptr(), buffer.len())
+        };
+
+        if read < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        else if read == 0 {
+            return Ok(paths);
+        }
+
+        let mut offset = 0;
+        while offset < read as usize {
+            let record = &buffer[offset ..];
+            let (first, second) = (usize::from(record[RECLEN_OFFSET]), usize::from(record[RECLEN_OFFSET + 1]));
+            let length = if cfg!(target_endian="little") { first | second << 8 }
+                                                    else { first << 8 | second };
+
+            // The name is null-terminated, with padding after it to keep
+            // the records aligned.
+            let name = &record[NAME_OFFSET .. length];
+            let name = match name.iter().position(|&b| b == 0) {
+                Some(end) => &name[.. end],
+                None      => name,
+            };
+
+            if name != b"." && name != b".." {
+                paths.push(path.join(OsStr::from_bytes(name)));
+            }
+
+            offset += length;
+        }
+    }
+}
+
+
+/// An open file descriptor, which gets closed when it goes out of scope.
+struct Descriptor(libc::c_int);
+
+impl Descriptor {
+    fn open(path: &Path) -> io::Result<Descriptor> {
+        let mut bytes = path.as_os_str().as_bytes().to_vec();
+        if bytes.contains(&0) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "path contains a null byte"));
+        }
+
+        bytes.push(0);
+        let fd = unsafe {
+            libc::open(bytes.as_ptr() as *const libc::c_char, libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC)
+        };
+
+        if fd < 0 { Err(io::Error::last_os_error()) }
+             else { Ok(Descriptor(fd)) }
+    }
+}
+
+impl Drop for Descriptor {
+    fn drop(&mut self) {
+        let _ = unsafe { libc::close(self.0) };
+    }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs;
+
+    fn std_paths(path: &Path) -> Vec<PathBuf> {
+        fs::read_dir(path).unwrap().map(|e| e.unwrap().path()).collect()
+    }
+
+    #[test]
+    fn same_as_read_dir() {
+        let scratch = ScratchDir::new("getdents");
+        let path = &scratch.path;
+        for number in 0 .. 2000 {
+            let _ = fs::File::create(path.join(format!("file-{}-with-a-longer-name", number))).unwrap();
+        }
+        fs::create_dir(path.join(".hidden")).unwrap();
+        let _ = fs::File::create(path.join("ünïcödé")).unwrap();
+
+        // A small buffer means the directory takes many batches to read.
+        assert_eq!(std_paths(&path), read_dir_paths_with(&path, 512).unwrap());
+        assert_eq!(std_paths(&path), read_dir_paths(&path).unwrap());
+    }
+
+    #[test]
+    fn empty() {
+        let scratch = ScratchDir::new("getdents-empty");
+        assert_eq!(Vec::<PathBuf>::new(), read_dir_paths(&scratch.path).unwrap());
+    }
+
+    #[test]
+    fn not_a_directory() {
+        let scratch = ScratchDir::new("getdents-file");
+        let path = scratch.join("file");
+        let _ = fs::File::create(&path).unwrap();
+
+        let fast = read_dir_paths(&path).unwrap_err();
+        let slow = fs::read_dir(&path).unwrap_err();
+        assert_eq!(slow.raw_os_error(), fast.raw_os_error());
+    }
+}
@@ -0,0 +1,336 @@
+//! Reading `.gitignore` files, and matching files against them, without
+//! going through Git.
+//!
+//! This means the patterns still work in directories that aren’t in a
+//! repository, and when exa has been built without Git support. Ripgrep’s
+//! `.ignore` files are read as well, using the same rules, and take
+//! priority over the `.gitignore` next to them.
+//!
+//! The patterns follow Git’s rules: a `!` at the start re-includes files
+//! that an earlier pattern ignored, a `/` at the end only matches
+//! directories, and a pattern with a `/` anywhere else only matches paths
+//! relative to the directory the file is in, rather than names at any
+//! depth underneath it.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use glob;
+
+
+/// The names of the ignore files that get read, lowest priority first.
+const FILE_NAMES: &[&str] = &[ ".gitignore", ".ignore" ];
+
+/// Ignore patterns are case-sensitive, and their wildcards don’t match
+/// slashes, so `*` only ever matches inside one path component.
+const MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive:              true,
+    require_literal_separator:   true,
+    require_literal_leading_dot: false,
+};
+
+
+/// The ignore files that apply to the files in one directory: the ones in
+/// the directory itself, and the ones in the directories above it, up to
+/// and including the top of the Git repository it’s in, if there is one.
+///
+/// The files get shared with the directories underneath, which only have
+/// to read their own ignore files on top of them.
+#[derive(PartialEq, Debug, Clone)]
+pub struct IgnoreFiles {
+
+    /// The directory whose files get checked, with its full path.
+    dir: PathBuf,
+
+    /// The files that were found, with the ones from the topmost directory
+    /// first, so the ones closer to the files get checked first.
+    files: Vec<Arc<IgnoreFile>>,
+}
+
+impl IgnoreFiles {
+
+    /// Reads the ignore files for the given directory, and every directory
+    /// above it. Any that can’t be read are skipped over, as if they didn’t
+    /// exist.
+    pub fn read_for(dir: &Path) -> IgnoreFiles {
+        let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let mut levels = Vec::new();
+        let mut next = Some(dir.as_path());
+
+        while let Some(current) = next {
+            levels.push(read_in(current));
+
+            // Git doesn’t look any further up than the top of the repository,
+            // so neither do we.
+            if is_repository(current) {
+                break;
+            }
+
+            next = current.parent();
+        }
+
+        let files = levels.into_iter().rev().flat_map(|level| level).collect();
+        IgnoreFiles { dir, files }
+    }
+
+    /// The ignore files for the subdirectory at the given path, which is
+    /// inside this directory: these ones, plus any in the subdirectory
+    /// itself, which are the only ones that need reading. A subdirectory
+    /// with its own repository starts again from nothing, as it’s not
+    /// part of this one.
+    pub fn below(&self, path: &Path) -> IgnoreFiles {
+        let dir = match path.file_name() {
+            Some(name)  => self.dir.join(name),
+            None        => return IgnoreFiles::read_for(path),
+        };
+
+        let mut files = if is_repository(&dir) { Vec::new() }
+                                              else { self.files.clone() };
+        files.extend(read_in(&dir));
+        IgnoreFiles { dir, files }
+    }
+
+    /// Whether the file with the given name in this directory is ignored.
+    /// The patterns in the closest file with one that matches decide.
+    pub fn is_ignored(&self, name: &str, is_dir: bool) -> bool {
+        let path = self.dir.join(name);
+
+        for file in self.files.iter().rev() {
+            if let Some(ignored) = file.check(&path, is_dir) {
+                return ignored;
+            }
+        }
+
+        false
+    }
+}
+
+/// Reads the ignore files in one directory, lowest priority first.
+fn read_in(dir: &Path) -> Vec<Arc<IgnoreFile>> {
+    FILE_NAMES.iter()
+              .filter_map(|name| read_file(&dir.join(name)))
+              .map(|contents| Arc::new(IgnoreFile::parse(dir.to_path_buf(), &contents)))
+              .collect()
+}
+
+/// Whether the directory is the top of a Git repository.
+fn is_repository(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+fn read_file(path: &Path) -> Option<String> {
+    let mut contents = String::new();
+    match fs::File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+        Ok(_)   => Some(contents),
+        Err(_)  => None,
+    }
+}
+
+
+/// The patterns from one ignore file.
+#[derive(PartialEq, Debug)]
+pub struct IgnoreFile {
+
+    /// The directory the file is in, which anchored patterns are relative to.
+    base: PathBuf,
+
+    /// The patterns, in the order they were written.
+    rules: Vec<Rule>,
+}
+
+impl IgnoreFile {
+
+    /// Parses the contents of an ignore file found in the given directory.
+    /// Blank lines and comments are skipped, as are any patterns that
+    /// aren’t valid globs.
+    pub fn parse(base: PathBuf, contents: &str) -> IgnoreFile {
+        let rules = contents.lines().filter_map(Rule::parse).collect();
+        IgnoreFile { base, rules }
+    }
+
+    /// Checks the path against these patterns, returning whether it’s
+    /// ignored if any of them match, or `None` if none of them do, or the
+    /// path isn’t underneath this file’s directory. The last pattern that
+    /// matches wins, so a negated pattern can re-include a file.
+    pub fn check(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = match path.strip_prefix(&self.base) {
+            Ok(r) if r != Path::new("")  => r,
+            _                            => return None,
+        };
+
+        self.rules.iter().rev()
+                  .find(|r| r.matches(relative, is_dir))
+                  .map(|r| !r.negated)
+    }
+}
+
+
+/// One line of an ignore file.
+#[derive(PartialEq, Debug)]
+struct Rule {
+
+    /// The glob to match against.
+    pattern: glob::Pattern,
+
+    /// Whether this pattern started with a `!`, meaning the files it
+    /// matches should be shown again.
+    negated: bool,
+
+    /// Whether this pattern ended with a `/`, meaning it only matches
+    /// directories.
+    dir_only: bool,
+
+    /// Whether this pattern has a `/` in it other than at the end, meaning
+    /// it gets matched against the whole path relative to the ignore file,
+    /// rather than just the file’s name.
+    anchored: bool,
+}
+
+impl Rule {
+
+    /// Parses one line of an ignore file, if it has a pattern on it. A
+    /// backslash can be used to start a pattern with a `#` or `!` that
+    /// doesn’t mean anything special.
+    fn parse(line: &str) -> Option<Rule> {
+        let line = line.trim_right();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = if line.starts_with('!') { (true, &line[1..]) }
+                                                  else { (false, line) };
+
+        let line = if line.starts_with("\\#") || line.starts_with("\\!") { &line[1..] }
+                                                                    else { line };
+
+        let (dir_only, line) = if line.ends_with('/') { (true, &line[.. line.len() - 1]) }
+                                                 else { (false, line) };
+
+        let anchored = line.contains('/');
+        let line = if line.starts_with('/') { &line[1..] } else { line };
+
+        if line.is_empty() {
+            return None;
+        }
+
+        glob::Pattern::new(line).ok().map(|pattern| Rule { pattern, negated, dir_only, anchored })
+    }
+
+    /// Whether this rule matches the given path, relative to the directory
+    /// of the ignore file it came from.
+    fn matches(&self, relative: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            false
+        }
+        else if self.anchored {
+            self.pattern.matches_path_with(relative, &MATCH_OPTIONS)
+        }
+        else {
+            match relative.file_name() {
+                Some(name)  => self.pattern.matches_with(&name.to_string_lossy(), &MATCH_OPTIONS),
+                None        => false,
+            }
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(contents: &str, path: &str, is_dir: bool) -> Option<bool> {
+        IgnoreFile::parse(PathBuf::from("/repo"), contents).check(&Path::new("/repo").join(path), is_dir)
+    }
+
+    #[test]
+    fn name_anywhere() {
+        assert_eq!(Some(true), check("*.log", "debug.log", false));
+        assert_eq!(Some(true), check("*.log", "a/b/debug.log", false));
+        assert_eq!(None,       check("*.log", "debug.txt", false));
+    }
+
+    #[test]
+    fn comments_and_blanks() {
+        assert_eq!(None, check("# *.log\n\n   \n", "debug.log", false));
+        assert_eq!(Some(true), check("\\#notes", "#notes", false));
+    }
+
+    #[test]
+    fn trailing_spaces() {
+        assert_eq!(Some(true), check("*.log   ", "debug.log", false));
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(Some(false), check("*.log\n!keep.log", "keep.log", false));
+        assert_eq!(Some(true),  check("*.log\n!keep.log", "drop.log", false));
+        assert_eq!(Some(true),  check("!keep.log\n*.log", "keep.log", false));
+        assert_eq!(Some(true),  check("\\!bang", "!bang", false));
+    }
+
+    #[test]
+    fn directories_only() {
+        assert_eq!(Some(true), check("build/", "build", true));
+        assert_eq!(None,       check("build/", "build", false));
+        assert_eq!(Some(true), check("build/", "a/build", true));
+    }
+
+    #[test]
+    fn anchored_to_the_top() {
+        assert_eq!(Some(true), check("/target", "target", true));
+        assert_eq!(None,       check("/target", "a/target", true));
+    }
+
+    #[test]
+    fn anchored_by_a_middle_slash() {
+        assert_eq!(Some(true), check("doc/*.html", "doc/index.html", false));
+        assert_eq!(None,       check("doc/*.html", "a/doc/index.html", false));
+        assert_eq!(None,       check("doc/*.html", "doc/api/index.html", false));
+    }
+
+    #[test]
+    fn double_stars() {
+        assert_eq!(Some(true), check("**/logs", "logs", true));
+        assert_eq!(Some(true), check("**/logs", "a/b/logs", true));
+        assert_eq!(Some(true), check("a/**/z", "a/z", false));
+        assert_eq!(Some(true), check("a/**/z", "a/b/c/z", false));
+    }
+
+    #[test]
+    fn outside_the_directory() {
+        let file = IgnoreFile::parse(PathBuf::from("/repo/sub"), "*");
+        assert_eq!(None, file.check(Path::new("/repo/other"), false));
+        assert_eq!(None, file.check(Path::new("/repo/sub"), true));
+    }
+
+    #[test]
+    fn invalid_glob_skipped() {
+        assert_eq!(Some(true), check("[\n*.log", "debug.log", false));
+    }
+
+    #[test]
+    fn subdirectories_build_on_their_parents() {
+        use scratch::ScratchDir;
+        use std::io::Write;
+
+        let scratch = ScratchDir::new("gitignore-below");
+        fs::create_dir_all(scratch.join(".git")).unwrap();
+        fs::create_dir_all(scratch.join("sub/repo/.git")).unwrap();
+        fs::File::create(scratch.join(".gitignore")).unwrap().write_all(b"*.log\n").unwrap();
+        fs::File::create(scratch.join("sub/.ignore")).unwrap().write_all(b"!keep.log\n").unwrap();
+
+        let top = IgnoreFiles::read_for(&scratch.path);
+        let sub = top.below(&scratch.join("sub"));
+        assert_eq!(sub, IgnoreFiles::read_for(&scratch.join("sub")));
+        assert!(sub.is_ignored("debug.log", false));
+        assert!(!sub.is_ignored("keep.log", false));
+
+        // Another repository doesn’t get the ignore files above it.
+        let repo = sub.below(&scratch.join("sub/repo"));
+        assert!(!repo.is_ignored("debug.log", false));
+    }
+}
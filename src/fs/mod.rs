@@ -1,10 +1,65 @@
+//! Reading files and directories from the filesystem, along with their
+//! metadata and Git statuses, and filtering and sorting them.
+//!
+//! This is the part of exa that other programs can use to list directories
+//! the way exa does, without going through its command-line options or
+//! output code:
+//!
+//! ```
+//! # extern crate exa;
+//! # extern crate libc;
+//! use std::env::temp_dir;
+//! use std::fs::{create_dir_all, File as StdFile};
+//! # use std::fs::remove_dir_all;
+//!
+//! use exa::fs::{Dir, File};
+//! use exa::fs::filter::{FileFilter, IgnorePatterns, SortField, SortCase};
+//!
+//! let path = temp_dir().join("exa-doctest-listing");
+//! # let path = temp_dir().join(format!("exa-doctest-listing-{}", unsafe { libc::getpid() }));
+//! create_dir_all(&path).unwrap();
+//! for name in &[ "b.txt", "A.txt", "c.tmp", ".hidden" ] {
+//!     StdFile::create(path.join(name)).unwrap();
+//! }
+//!
+//! let (ignore_patterns, errors) = IgnorePatterns::parse_from_iter(vec![ "*.tmp" ]);
+//! assert!(errors.is_empty());
+//!
+//! let filter = FileFilter {
+//!     sort_field: SortField::Name(SortCase::Insensitive),
+//!     ignore_patterns: ignore_patterns,
+//!     ..FileFilter::default()
+//! };
+//!
+//! let dir = Dir::read_dir(path.clone(), None).unwrap();
+//! let mut files: Vec<File> = dir.files(filter.dot_filter)
+//!                               .filter_map(Result::ok)
+//!                               .collect();
+//! filter.filter_child_files(&mut files, 0);
+//! filter.sort_files(&mut files);
+//!
+//! let names: Vec<&str> = files.iter().map(|f| &*f.name).collect();
+//! assert_eq!(names, vec![ "A.txt", "b.txt" ]);
+//! # remove_dir_all(&path).unwrap();
+//! ```
+
 mod dir;
 pub use self::dir::{Dir, DotFilter};
 
 mod file;
-pub use self::file::{File, FileTarget};
+pub use self::file::{File, FileTarget, TargetPath};
+
+mod access;
+pub use self::access::Identity;
+
+mod content;
+mod entries;
+
+#[cfg(target_os="linux")]
+mod getdents;
 
 pub mod feature;
 pub mod fields;
 pub mod filter;
 pub mod dir_action;
+pub mod gitignore;
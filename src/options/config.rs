@@ -0,0 +1,265 @@
+//! Reading defaults for the file filter from a configuration file.
+//!
+//! The file is written in TOML, and read with the `toml` crate. Its keys go
+//! at the top level, with the values each one can have being checked here.
+//!
+//! Everything in the file gets overridden by the command-line, so the file
+//! only holds *defaults*: a user who sets `sort = "size"` can still run
+//! `exa --sort=name` to sort by name once.
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::PathBuf;
+
+use toml::Value;
+
+use fs::DotFilter;
+use fs::filter::{IgnorePatterns, SortField};
+
+use options::{flags, Misfire, Vars};
+use options::parser::MatchedFlags;
+
+
+/// A **filter config** holds the parts of the file filter that were given
+/// in the configuration file, with `None` for the ones that weren’t. These
+/// get used in place of the defaults when the corresponding command-line
+/// options are missing.
+#[derive(PartialEq, Debug, Default)]
+pub struct FilterConfig {
+
+    /// The field to sort by, from the `sort` key.
+    pub sort_field: Option<SortField>,
+
+    /// Which dotfiles to show, from the `all` key, which works like the
+    /// number given to `--all-level`.
+    pub dot_filter: Option<DotFilter>,
+
+    /// The patterns to ignore, from the `ignore-glob` key, which can be
+    /// either an array of globs or a single string of pipe-separated ones.
+    pub ignore_patterns: Option<IgnorePatterns>,
+}
+
+/// The path of the configuration file inside the user’s configuration
+/// directory, which gets read if it exists and no other file has been given.
+const DEFAULT_PATH: &str = "exa/config.toml";
+
+impl FilterConfig {
+
+    /// Reads the configuration file given with `--config`, or the default
+    /// one in the user’s configuration directory. A file that was given
+    /// explicitly has to exist, but the default one doesn’t, as most people
+    /// won’t have one. A file that exists but can’t be read or parsed is an
+    /// error.
+    pub fn deduce<V: Vars>(matches: &MatchedFlags, vars: V) -> Result<FilterConfig, Misfire> {
+        let (path, required) = match matches.get(&flags::CONFIG)? {
+            Some(path)  => (PathBuf::from(path), true),
+            None        => match default_path(&vars) {
+                Some(path)  => (path, false),
+                None        => return Ok(FilterConfig::default()),
+            },
+        };
+
+        let mut contents = String::new();
+        if let Err(e) = File::open(&path).and_then(|mut f| f.read_to_string(&mut contents)) {
+            if e.kind() == ErrorKind::NotFound && !required {
+                return Ok(FilterConfig::default());
+            }
+
+            return Err(Misfire::FailedConfig(format!("{}: {}", path.display(), e)));
+        }
+
+        FilterConfig::parse(&contents)
+            .map_err(|e| Misfire::FailedConfig(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parses the contents of a configuration file, returning a message
+    /// that says where the file is malformed, or which key is at fault if
+    /// something’s wrong with one of the values.
+    pub fn parse(contents: &str) -> Result<FilterConfig, String> {
+        let table = match contents.parse::<Value>() {
+            Ok(Value::Table(table))  => table,
+            Ok(_)                    => return Err("expected a table of keys".into()),
+            Err(e)                   => return Err(e.to_string()),
+        };
+
+        let mut config = FilterConfig::default();
+        for (key, value) in &table {
+            config.set(key, value).map_err(|e| format!("key {:?} {}", key, e))?;
+        }
+
+        Ok(config)
+    }
+
+    /// Sets the part of the config that the given key is for, returning
+    /// what’s wrong with the value if it can’t be used.
+    fn set(&mut self, key: &str, value: &Value) -> Result<(), String> {
+        match key {
+            "sort" => {
+                let word = string(value)?;
+                match SortField::from_word(OsStr::new(word)) {
+                    Some(SortField::GitStatus) if !cfg!(feature="git")  => return Err("needs exa to be built with Git support".into()),
+                    Some(field)  => self.sort_field = Some(field),
+                    None         => return Err(format!("has no sort field {:?}", word)),
+                }
+            },
+
+            "all" => {
+                self.dot_filter = Some(match value.as_integer() {
+                    Some(0)  => DotFilter::JustFiles,
+                    Some(1)  => DotFilter::Dotfiles,
+                    Some(2)  => DotFilter::DotfilesAndDots,
+                    _        => return Err("should be 0, 1, or 2".into()),
+                });
+            },
+
+            "ignore-glob" => {
+                let globs: Vec<&str> = match *value {
+                    Value::Array(ref globs)  => globs.iter().map(string).collect::<Result<_, _>>()
+                                                     .map_err(|_| "should only contain strings".to_string())?,
+                    ref value                => string(value)?.split('|').collect(),
+                };
+
+                let (patterns, mut errors) = IgnorePatterns::parse_from_iter(globs);
+                if let Some(e) = errors.pop() {
+                    return Err(format!("has an invalid glob: {}", e));
+                }
+
+                // Defaults apply to every directory, so most of them won’t
+                // match anything most of the time, and that’s fine.
+                self.ignore_patterns = Some(patterns.report_only_first(0));
+            },
+
+            _ => return Err("isn’t a known option".into()),
+        }
+
+        Ok(())
+    }
+}
+
+
+/// The path of the default configuration file, inside `$XDG_CONFIG_HOME`,
+/// or `~/.config` when that isn’t set. Like other programs, this ignores
+/// `$XDG_CONFIG_HOME` when it isn’t an absolute path.
+fn default_path<V: Vars>(vars: &V) -> Option<PathBuf> {
+    match vars.get("XDG_CONFIG_HOME").map(PathBuf::from) {
+        Some(ref config) if config.is_absolute()  => Some(config.join(DEFAULT_PATH)),
+        _  => vars.get("HOME").map(|home| PathBuf::from(home).join(".config").join(DEFAULT_PATH)),
+    }
+}
+
+/// The string in the given value, if it holds one.
+fn string(value: &Value) -> Result<&str, String> {
+    value.as_str().ok_or_else(|| "should be a string".to_string())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+    use fs::filter::SortCase;
+
+    #[test]
+    fn empty() {
+        assert_eq!(Ok(FilterConfig::default()), FilterConfig::parse("\n# nothing here\n"));
+    }
+
+    #[test]
+    fn everything() {
+        let config = FilterConfig::parse("sort = \"Name\"  # case-insensitively\nall = 1\nignore-glob = [ \"*.tmp\", 'target' ]\n").unwrap();
+        assert_eq!(config.sort_field, Some(SortField::Name(SortCase::Insensitive)));
+        assert_eq!(config.dot_filter, Some(DotFilter::Dotfiles));
+
+        let patterns = config.ignore_patterns.unwrap();
+        assert!(patterns.is_ignored("backup.tmp"));
+        assert!(patterns.is_ignored("target"));
+        assert!(!patterns.is_ignored("src"));
+    }
+
+    #[test]
+    fn pipe_separated_globs() {
+        let patterns = FilterConfig::parse("ignore-glob = \"*.tmp|target\"").unwrap().ignore_patterns.unwrap();
+        assert!(patterns.is_ignored("backup.tmp"));
+        assert!(patterns.is_ignored("target"));
+    }
+
+    #[test]
+    fn multi_line_values() {
+        let config = FilterConfig::parse("sort = '''size'''\nignore-glob = [\n  \"*.tmp\",\n  \"target\",  # build output\n]\n").unwrap();
+        assert_eq!(config.sort_field, Some(SortField::Size));
+
+        let patterns = config.ignore_patterns.unwrap();
+        assert!(patterns.is_ignored("backup.tmp"));
+        assert!(patterns.is_ignored("target"));
+    }
+
+    #[test]
+    fn bad_sort_field() {
+        assert_eq!(Err("key \"sort\" has no sort field \"colour\"".to_string()), FilterConfig::parse("\nsort = \"colour\""));
+    }
+
+    #[test]
+    fn bad_dot_filter() {
+        assert_eq!(Err("key \"all\" should be 0, 1, or 2".to_string()), FilterConfig::parse("all = true"));
+    }
+
+    #[test]
+    fn bad_glob_array() {
+        assert_eq!(Err("key \"ignore-glob\" should only contain strings".to_string()), FilterConfig::parse("ignore-glob = [ 1, 2 ]"));
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert_eq!(Err("key \"colour\" isn’t a known option".to_string()), FilterConfig::parse("colour = \"always\""));
+    }
+
+    #[test]
+    fn unknown_table() {
+        assert_eq!(Err("key \"filter\" isn’t a known option".to_string()), FilterConfig::parse("[filter]\nsort = \"size\""));
+    }
+
+    #[test]
+    fn unterminated() {
+        let error = FilterConfig::parse("sort = \"size").unwrap_err();
+        assert!(error.contains("line 1"), "{}", error);
+    }
+
+    struct MockVars {
+        home: &'static str,
+        xdg: &'static str,
+    }
+
+    impl Vars for MockVars {
+        fn get(&self, name: &'static str) -> Option<OsString> {
+            match name {
+                "HOME"             if !self.home.is_empty()  => Some(OsString::from(self.home)),
+                "XDG_CONFIG_HOME"  if !self.xdg.is_empty()   => Some(OsString::from(self.xdg)),
+                _                                            => None,
+            }
+        }
+    }
+
+    #[test]
+    fn default_in_home() {
+        let path = default_path(&MockVars { home: "/home/me", xdg: "" });
+        assert_eq!(path, Some(PathBuf::from("/home/me/.config/exa/config.toml")));
+    }
+
+    #[test]
+    fn default_in_xdg_config_home() {
+        let path = default_path(&MockVars { home: "/home/me", xdg: "/config" });
+        assert_eq!(path, Some(PathBuf::from("/config/exa/config.toml")));
+    }
+
+    #[test]
+    fn relative_xdg_config_home() {
+        let path = default_path(&MockVars { home: "/home/me", xdg: "config" });
+        assert_eq!(path, Some(PathBuf::from("/home/me/.config/exa/config.toml")));
+    }
+
+    #[test]
+    fn no_default() {
+        assert_eq!(default_path(&MockVars { home: "", xdg: "" }), None);
+    }
+}
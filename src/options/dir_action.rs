@@ -3,7 +3,7 @@
 use options::parser::MatchedFlags;
 use options::{flags, Misfire};
 
-use fs::dir_action::{DirAction, RecurseOptions};
+use fs::dir_action::{DirAction, RecurseOptions, RecurseOrder};
 
 
 impl DirAction {
@@ -28,6 +28,10 @@ impl DirAction {
             else if tree && as_file {
                 return Err(Misfire::Conflict(&flags::TREE, &flags::LIST_DIRS));
             }
+            else if (!recurse || tree) && matches.get(&flags::RECURSE_ORDER)?.is_some() {
+                // The tree view always lists directories depth-first.
+                return Err(Misfire::Useless(&flags::RECURSE_ORDER, false, &flags::RECURSE));
+            }
         }
 
         if tree {
@@ -63,7 +67,34 @@ impl RecurseOptions {
             None
         };
 
-        Ok(RecurseOptions { tree, max_depth })
+        let order = RecurseOrder::deduce(matches)?;
+        Ok(RecurseOptions { tree, max_depth, order })
+    }
+}
+
+
+const ORDERS: &[&str] = &[ "dfs", "bfs" ];
+
+impl RecurseOrder {
+
+    /// Determine which order to list directories in while recursing, based
+    /// on the `--recurse-order` flag’s value. Depth-first is the default,
+    /// to match `ls`.
+    fn deduce(matches: &MatchedFlags) -> Result<RecurseOrder, Misfire> {
+        let word = match matches.get(&flags::RECURSE_ORDER)? {
+            Some(w)  => w,
+            None     => return Ok(RecurseOrder::default()),
+        };
+
+        if word == "dfs" {
+            Ok(RecurseOrder::DepthFirst)
+        }
+        else if word == "bfs" {
+            Ok(RecurseOrder::BreadthFirst)
+        }
+        else {
+            Err(Misfire::bad_argument(&flags::RECURSE_ORDER, word, ORDERS))
+        }
     }
 }
 
@@ -71,6 +102,7 @@ impl RecurseOptions {
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::ffi::OsStr;
     use options::flags;
     use options::parser::Flag;
 
@@ -82,7 +114,7 @@ mod test {
                 use options::test::parse_for_test;
                 use options::test::Strictnesses::*;
 
-                static TEST_ARGS: &[&Arg] = &[&flags::RECURSE, &flags::LIST_DIRS, &flags::TREE, &flags::LEVEL ];
+                static TEST_ARGS: &[&Arg] = &[&flags::RECURSE, &flags::LIST_DIRS, &flags::TREE, &flags::LEVEL, &flags::RECURSE_ORDER ];
                 for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| $type::deduce(mf)) {
                     assert_eq!(result, $result);
                 }
@@ -100,19 +132,20 @@ mod test {
 
     // Recursing
     use self::DirAction::Recurse;
-    test!(rec_short:       DirAction <- ["-R"];                           Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None })));
-    test!(rec_long:        DirAction <- ["--recurse"];                    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None })));
-    test!(rec_lim_short:   DirAction <- ["-RL4"];                         Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(4) })));
-    test!(rec_lim_short_2: DirAction <- ["-RL=5"];                        Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(5) })));
-    test!(rec_lim_long:    DirAction <- ["--recurse", "--level", "666"];  Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(666) })));
-    test!(rec_lim_long_2:  DirAction <- ["--recurse", "--level=0118"];    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(118) })));
-    test!(tree:            DirAction <- ["--tree"];                       Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None })));
-    test!(rec_tree:        DirAction <- ["--recurse", "--tree"];          Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None })));
-    test!(rec_short_tree:  DirAction <- ["-TR"];                          Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None })));
+    use self::RecurseOrder::*;
+    test!(rec_short:       DirAction <- ["-R"];                           Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None, order: DepthFirst })));
+    test!(rec_long:        DirAction <- ["--recurse"];                    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None, order: DepthFirst })));
+    test!(rec_lim_short:   DirAction <- ["-RL4"];                         Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(4), order: DepthFirst })));
+    test!(rec_lim_short_2: DirAction <- ["-RL=5"];                        Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(5), order: DepthFirst })));
+    test!(rec_lim_long:    DirAction <- ["--recurse", "--level", "666"];  Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(666), order: DepthFirst })));
+    test!(rec_lim_long_2:  DirAction <- ["--recurse", "--level=0118"];    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(118), order: DepthFirst })));
+    test!(tree:            DirAction <- ["--tree"];                       Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, order: DepthFirst })));
+    test!(rec_tree:        DirAction <- ["--recurse", "--tree"];          Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, order: DepthFirst })));
+    test!(rec_short_tree:  DirAction <- ["-TR"];                          Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, order: DepthFirst })));
 
     // Overriding --list-dirs, --recurse, and --tree
-    test!(dirs_recurse:    DirAction <- ["--list-dirs", "--recurse"];     Last => Ok(Recurse(RecurseOptions { tree: false, max_depth: None })));
-    test!(dirs_tree:       DirAction <- ["--list-dirs", "--tree"];        Last => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None })));
+    test!(dirs_recurse:    DirAction <- ["--list-dirs", "--recurse"];     Last => Ok(Recurse(RecurseOptions { tree: false, max_depth: None, order: DepthFirst })));
+    test!(dirs_tree:       DirAction <- ["--list-dirs", "--tree"];        Last => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, order: DepthFirst })));
     test!(just_level:      DirAction <- ["--level=4"];                    Last => Ok(DirAction::List));
 
     test!(dirs_recurse_2:  DirAction <- ["--list-dirs", "--recurse"]; Complain => Err(Misfire::Conflict(&flags::RECURSE, &flags::LIST_DIRS)));
@@ -121,6 +154,13 @@ mod test {
 
 
     // Overriding levels
-    test!(overriding_1:    DirAction <- ["-RL=6", "-L=7"];                Last => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(7) })));
+    test!(overriding_1:    DirAction <- ["-RL=6", "-L=7"];                Last => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(7), order: DepthFirst })));
     test!(overriding_2:    DirAction <- ["-RL=6", "-L=7"];            Complain => Err(Misfire::Duplicate(Flag::Short(b'L'), Flag::Short(b'L'))));
+
+    // Recursing order
+    test!(rec_dfs:         DirAction <- ["--recurse", "--recurse-order=dfs"];  Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None, order: DepthFirst })));
+    test!(rec_bfs:         DirAction <- ["-R", "--recurse-order", "bfs"];      Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None, order: BreadthFirst })));
+    test!(rec_bad_order:   DirAction <- ["-R", "--recurse-order=random"];      Both => Err(Misfire::bad_argument(&flags::RECURSE_ORDER, OsStr::new("random"), super::ORDERS)));
+    test!(just_order:      DirAction <- ["--recurse-order=bfs"];  Complain => Err(Misfire::Useless(&flags::RECURSE_ORDER, false, &flags::RECURSE)));
+    test!(tree_order:      DirAction <- ["-T", "--recurse-order=bfs"];  Complain => Err(Misfire::Useless(&flags::RECURSE_ORDER, false, &flags::RECURSE)));
 }
@@ -1,29 +1,86 @@
 //! Parsing the options for `FileFilter`.
 
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{Read, Write, stderr};
+use std::path::Path;
+
+use locale;
+use users::{Users, Groups, UsersCache};
+
 use fs::DotFilter;
-use fs::filter::{FileFilter, SortField, SortCase, IgnorePatterns};
+use fs::filter::{FileFilter, SortField, SortExec, SortCommand, SortCase, DirsFirst, FilterMode, IgnorePatterns, NameRegex, OwnerFilter, TrackedFilter};
 
 use options::{flags, Misfire};
+use options::config::FilterConfig;
 use options::parser::MatchedFlags;
 
 
 impl FileFilter {
 
-    /// Determines which of all the file filter options to use.
-    pub fn deduce(matches: &MatchedFlags) -> Result<FileFilter, Misfire> {
+    /// Determines which of all the file filter options to use, falling back
+    /// to the ones in the configuration file for any that weren’t given on
+    /// the command-line.
+    pub fn deduce(matches: &MatchedFlags, config: &FilterConfig) -> Result<FileFilter, Misfire> {
+        // Listing `.` and `..` in a tree would loop forever, which is an
+        // error when asked for on the command-line; from the config file,
+        // it just gets the other dotfiles instead.
+        let dot_filter = match config.dot_filter {
+            Some(DotFilter::DotfilesAndDots) if !DotFilter::is_given(matches)? && matches.count(&flags::TREE) > 0  => DotFilter::Dotfiles,
+            Some(dots) if !DotFilter::is_given(matches)?  => dots,
+            _                                            => DotFilter::deduce(matches)?,
+        };
+
+        let sort_field = match config.sort_field {
+            Some(field) if matches.get(&flags::SORT)?.is_none()  => field,
+            _                                                    => SortField::deduce(matches)?,
+        };
+
+        // Counting the items in directories has to use the same dot filter
+        // as the listing, wherever that came from.
+        let sort_field = match sort_field {
+            SortField::ItemCount(_)  => SortField::ItemCount(dot_filter),
+            field                    => field,
+        };
+
+        // Turning ignoring off overrides every source of patterns, so none
+        // of them even get read.
+        let no_ignore = matches.has(&flags::NO_IGNORE)?;
+
+        let ignore_patterns = match config.ignore_patterns {
+            _ if no_ignore                                             => IgnorePatterns::empty(),
+            Some(ref patterns) if !IgnorePatterns::is_given(matches)?  => patterns.clone(),
+            _                                                          => IgnorePatterns::deduce(matches)?,
+        };
+
         Ok(FileFilter {
-            list_dirs_first: matches.has(&flags::DIRS_FIRST)?,
+            list_dirs_first: DirsFirst::deduce(matches)?,
             reverse:         matches.has(&flags::REVERSE)?,
-            sort_field:      SortField::deduce(matches)?,
-            dot_filter:      DotFilter::deduce(matches)?,
-            ignore_patterns: IgnorePatterns::deduce(matches)?,
+            sort_field,
+            sort_exec:       SortExec::deduce(matches)?,
+            sort_command:    SortCommand::deduce(matches)?,
+            dot_filter,
+            ignore_patterns,
+            name_regex:      NameRegex::deduce(matches)?,
+            git_ignore:      !no_ignore && matches.has(&flags::IGNORE_FILE_LIKE_GIT)?,
+            changed_only:    matches.has(&flags::CHANGED_ONLY)?,
+            git_since_only:  matches.get(&flags::GIT_SINCE_ONLY)?.is_some(),
+            dirty_only:      matches.has(&flags::GIT_ONLY_DIRTY)?,
+            tracked_filter:  TrackedFilter::deduce(matches)?,
+            owner_filter:    OwnerFilter::deduce(matches)?,
+            links_only:      matches.has(&flags::LINKS_ONLY)?,
+            executable_only: matches.has(&flags::EXECUTABLE)?,
+            top:             deduce_top(matches)?,
+            thousands_separator: deduce_thousands_separator(matches, user_numeric)?,
+            filter_mode:     FilterMode::deduce(matches)?,
         })
     }
 }
 
 const SORTS: &[&str] = &[ "name", "Name", "size", "extension",
-                          "Extension", "modified", "accessed",
-                          "created", "inode", "type", "none" ];
+                          "Extension", "modified", "mtime", "accessed",
+                          "atime", "created", "birth", "crtime", "ctime", "inode", "type",
+                          "entries", "items", "mixed", "git", "segments", "none" ];
 
 impl SortField {
 
@@ -37,78 +94,148 @@ impl SortField {
             None     => return Ok(SortField::default()),
         };
 
+        match SortField::from_word(word) {
+            Some(SortField::ItemCount(_))  => Ok(SortField::ItemCount(DotFilter::deduce(matches)?)),
+            Some(SortField::GitStatus) if !cfg!(feature="git")  => Err(Misfire::NeedsGit(&flags::SORT, word.to_os_string())),
+            Some(field)                    => Ok(field),
+            None                           => Err(Misfire::bad_argument(&flags::SORT, word, SORTS)),
+        }
+    }
+
+    /// Turns one of the words that `--sort` accepts into the sort field it
+    /// stands for, if it’s one of them. Counting items uses the default dot
+    /// filter, which should get replaced with the one actually in use.
+    pub fn from_word(word: &OsStr) -> Option<SortField> {
+
         // The field is an OsStr, so can’t be matched.
         if word == "name" || word == "filename" {
-            Ok(SortField::Name(SortCase::Sensitive))
+            Some(SortField::Name(SortCase::Sensitive))
         }
         else if word == "Name" || word == "Filename" {
-            Ok(SortField::Name(SortCase::Insensitive))
+            Some(SortField::Name(SortCase::Insensitive))
         }
         else if word == "size" || word == "filesize" {
-            Ok(SortField::Size)
+            Some(SortField::Size)
         }
         else if word == "ext" || word == "extension" {
-            Ok(SortField::Extension(SortCase::Sensitive))
+            Some(SortField::Extension(SortCase::Sensitive))
         }
         else if word == "Ext" || word == "Extension" {
-            Ok(SortField::Extension(SortCase::Insensitive))
+            Some(SortField::Extension(SortCase::Insensitive))
+        }
+        else if word == "mod" || word == "modified" || word == "mtime" {
+            Some(SortField::ModifiedDate)
         }
-        else if word == "mod" || word == "modified" {
-            Ok(SortField::ModifiedDate)
+        else if word == "acc" || word == "accessed" || word == "atime" {
+            Some(SortField::AccessedDate)
         }
-        else if word == "acc" || word == "accessed" {
-            Ok(SortField::AccessedDate)
+        else if word == "cr" || word == "created" || word == "birth" || word == "crtime" {
+            Some(SortField::CreatedDate)
         }
-        else if word == "cr" || word == "created" {
-            Ok(SortField::CreatedDate)
+        else if word == "ctime" {
+            Some(SortField::ChangedDate)
         }
         else if word == "inode" {
-            Ok(SortField::FileInode)
+            Some(SortField::FileInode)
         }
         else if word == "type" {
-            Ok(SortField::FileType)
+            Some(SortField::FileType)
+        }
+        else if word == "entries" {
+            Some(SortField::EntryCount)
+        }
+        else if word == "items" {
+            Some(SortField::ItemCount(DotFilter::default()))
+        }
+        else if word == "mixed" {
+            Some(SortField::Mixed)
+        }
+        else if word == "git" {
+            Some(SortField::GitStatus)
+        }
+        else if word == "segments" {
+            Some(SortField::Segments)
         }
         else if word == "none" {
-            Ok(SortField::Unsorted)
+            Some(SortField::Unsorted)
         }
         else {
-            Err(Misfire::bad_argument(&flags::SORT, word, SORTS))
+            None
         }
     }
 }
 
-impl Default for SortField {
-    fn default() -> SortField {
-        SortField::Name(SortCase::Sensitive)
-    }
-}
-
+const ALL_LEVELS: &[&str] = &[ "0", "1", "2" ];
 
 impl DotFilter {
 
+    /// Whether any of the options that pick the dot filter were given, so
+    /// the one in the configuration file shouldn’t be used.
+    fn is_given(matches: &MatchedFlags) -> Result<bool, Misfire> {
+        Ok(matches.count(&flags::ALL) > 0
+            || matches.has(&flags::ALMOST_ALL)?
+            || matches.has(&flags::ALL_ALL)?
+            || matches.get(&flags::ALL_LEVEL)?.is_some())
+    }
+
     /// Determines the dot filter based on how many `--all` options were
     /// given: one will show dotfiles, but two will show `.` and `..` too.
     ///
+    /// The `--almost-all` and `--all-all` options are longer ways of saying
+    /// the same things. A single `--all` already leaves out `.` and `..`,
+    /// so `--almost-all` behaves exactly like it does, which is how `ls -A`
+    /// behaves; it’s `--all-all` that matches `ls -a`.
+    ///
+    /// The `--all-level` option gives the number of `--all` options
+    /// directly, which reads better in scripts. In strict mode, it can’t be
+    /// combined with any of the others.
+    ///
     /// It also checks for the `--tree` option in strict mode, because of a
     /// special case where `--tree --all --all` won't work: listing the
     /// parent directory in tree mode would loop onto itself!
     pub fn deduce(matches: &MatchedFlags) -> Result<DotFilter, Misfire> {
-        let count = matches.count(&flags::ALL);
+        let mut count = matches.count(&flags::ALL);
+        let almost_all = matches.has(&flags::ALMOST_ALL)?;
+        let all_all = matches.has(&flags::ALL_ALL)?;
 
-        if count == 0 {
-            Ok(DotFilter::JustFiles)
+        if let Some(level) = matches.get(&flags::ALL_LEVEL)? {
+            if matches.is_strict() {
+                if count > 0 {
+                    return Err(Misfire::Conflict(&flags::ALL_LEVEL, &flags::ALL));
+                }
+                else if almost_all {
+                    return Err(Misfire::Conflict(&flags::ALL_LEVEL, &flags::ALMOST_ALL));
+                }
+                else if all_all {
+                    return Err(Misfire::Conflict(&flags::ALL_LEVEL, &flags::ALL_ALL));
+                }
+            }
+
+            count = match ALL_LEVELS.iter().position(|l| *l == level) {
+                Some(c)  => c,
+                None     => return Err(Misfire::bad_argument(&flags::ALL_LEVEL, level, ALL_LEVELS)),
+            };
         }
-        else if count == 1 {
-            Ok(DotFilter::Dotfiles)
+
+        if almost_all && all_all && matches.is_strict() {
+            Err(Misfire::Conflict(&flags::ALL_ALL, &flags::ALMOST_ALL))
         }
-        else if matches.count(&flags::TREE) > 0 {
-            Err(Misfire::TreeAllAll)
+        else if count >= 2 || all_all {
+            if matches.count(&flags::TREE) > 0 {
+                Err(Misfire::TreeAllAll)
+            }
+            else if count >= 3 && matches.is_strict() {
+                Err(Misfire::Conflict(&flags::ALL, &flags::ALL))
+            }
+            else {
+                Ok(DotFilter::DotfilesAndDots)
+            }
         }
-        else if count >= 3 && matches.is_strict() {
-            Err(Misfire::Conflict(&flags::ALL, &flags::ALL))
+        else if count == 1 || almost_all {
+            Ok(DotFilter::Dotfiles)
         }
         else {
-            Ok(DotFilter::DotfilesAndDots)
+            Ok(DotFilter::JustFiles)
         }
     }
 }
@@ -116,28 +243,324 @@ impl DotFilter {
 
 impl IgnorePatterns {
 
+    /// Whether any patterns were given on the command-line, either directly
+    /// or in ignore files, so the ones in the configuration file shouldn’t
+    /// be used.
+    fn is_given(matches: &MatchedFlags) -> Result<bool, Misfire> {
+        Ok(matches.get(&flags::IGNORE_GLOB)?.is_some() || !matches.get_all(&flags::IGNORE_FILE).is_empty())
+    }
+
     /// Determines the set of glob patterns to use based on the
     /// `--ignore-patterns` argument’s value. This is a list of strings
     /// separated by pipe (`|`) characters, given in any order.
+    ///
+    /// The `--ignore-file` argument can be given any number of times, and
+    /// the patterns in each file, one per line, get added to the rest. A
+    /// file that can’t be read is an error in strict mode; otherwise, it
+    /// gets complained about and skipped.
+    ///
+    /// The `--ignore-glob-below` argument limits the patterns to the files
+    /// deeper than the depth it’s given, which only makes a difference when
+    /// there are patterns to limit and directories to recurse into.
     pub fn deduce(matches: &MatchedFlags) -> Result<IgnorePatterns, Misfire> {
+        let below = match matches.get(&flags::IGNORE_GLOB_BELOW)? {
+            None     => None,
+            Some(d)  => match d.to_string_lossy().parse() {
+                Ok(d)   => Some(d),
+                Err(e)  => return Err(Misfire::FailedParse(e)),
+            },
+        };
+
+        let files = matches.get_all(&flags::IGNORE_FILE);
+
+        if matches.is_strict() && below.is_some() {
+            if matches.get(&flags::IGNORE_GLOB)?.is_none() && files.is_empty() {
+                return Err(Misfire::Useless(&flags::IGNORE_GLOB_BELOW, false, &flags::IGNORE_GLOB));
+            }
+            else if !matches.has(&flags::RECURSE)? && !matches.has(&flags::TREE)? {
+                return Err(Misfire::Useless2(&flags::IGNORE_GLOB_BELOW, &flags::RECURSE, &flags::TREE));
+            }
+        }
+
+        let mut inputs: Vec<String> = match matches.get(&flags::IGNORE_GLOB)? {
+            Some(is)  => is.to_string_lossy().split('|').map(String::from).collect(),
+            None      => Vec::new(),
+        };
+
+        // Only the patterns given on the command-line get reported when they
+        // don’t match anything, and they come before the ones from files.
+        let given = inputs.len();
+
+        for path in files {
+            match read_ignore_file(Path::new(path)) {
+                Ok(lines)                      => inputs.extend(lines),
+                Err(e) if matches.is_strict()  => return Err(Misfire::FailedIgnoreFile(e)),
+                Err(e)                         => { let _ = writeln!(stderr(), "exa: {}", e); },
+            }
+        }
 
         // If there are no inputs, we return a set of patterns that doesn’t
         // match anything, rather than, say, `None`.
-        let inputs = match matches.get(&flags::IGNORE_GLOB)? {
-            None => return Ok(IgnorePatterns::empty()),
-            Some(is) => is,
-        };
+        if inputs.is_empty() {
+            return Ok(IgnorePatterns::empty());
+        }
 
         // Awkwardly, though, a glob pattern can be invalid, and we need to
         // deal with invalid patterns somehow.
-        let (patterns, mut errors) = IgnorePatterns::parse_from_iter(inputs.to_string_lossy().split('|'));
+        let (patterns, mut errors) = IgnorePatterns::parse_from_iter(inputs.iter().map(|i| &i[..]));
+        let patterns = patterns.report_only_first(given);
 
         // It can actually return more than one glob error,
         // but we only use one. (TODO)
-        match errors.pop() {
-            Some(e) => Err(e.into()),
-            None    => Ok(patterns),
+        match (errors.pop(), below) {
+            (Some(e), _)         => Err(e.into()),
+            (None, Some(depth))  => Ok(patterns.only_below(depth)),
+            (None, None)         => Ok(patterns),
+        }
+    }
+}
+
+impl SortExec {
+
+    /// Determines the command to sort files by, from the `--sort-exec`
+    /// argument, if it’s been given. It conflicts with picking a sort field
+    /// in strict mode, as the field would get ignored.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<SortExec>, Misfire> {
+        let command = match matches.get(&flags::SORT_EXEC)? {
+            Some(c)  => c.to_string_lossy().into_owned(),
+            None     => return Ok(None),
+        };
+
+        if matches.is_strict() && matches.get(&flags::SORT)?.is_some() {
+            return Err(Misfire::Conflict(&flags::SORT_EXEC, &flags::SORT));
+        }
+
+        Ok(Some(SortExec { command }))
+    }
+}
+
+impl SortCommand {
+
+    /// Determines the command to put the files in order with, from the
+    /// `--sort-cmd` argument, if it’s been given. It conflicts with
+    /// `--sort-exec` in strict mode, as only the order the command gets the
+    /// names in would change, and only for the ones it leaves out.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<SortCommand>, Misfire> {
+        let command = match matches.get(&flags::SORT_CMD)? {
+            Some(c)  => c.to_string_lossy().into_owned(),
+            None     => return Ok(None),
+        };
+
+        if matches.is_strict() && matches.get(&flags::SORT_EXEC)?.is_some() {
+            return Err(Misfire::Conflict(&flags::SORT_CMD, &flags::SORT_EXEC));
+        }
+
+        Ok(Some(SortCommand { command }))
+    }
+}
+
+impl NameRegex {
+
+    /// Determines the regular expression that file names have to match,
+    /// from the `--grep` argument, if it’s been given.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<NameRegex>, Misfire> {
+        let input = match matches.get(&flags::GREP)? {
+            Some(i)  => i,
+            None     => return Ok(None),
+        };
+
+        match NameRegex::new(&input.to_string_lossy()) {
+            Ok(regex)  => Ok(Some(regex)),
+            Err(e)     => Err(Misfire::FailedRegex(e.to_string())),
+        }
+    }
+}
+
+const FILTER_MODES: &[&str] = &[ "and", "or" ];
+
+impl FilterMode {
+
+    /// Determines how the filters combine based on the `--filter-mode`
+    /// argument, which is `and` by default.
+    fn deduce(matches: &MatchedFlags) -> Result<FilterMode, Misfire> {
+        let word = match matches.get(&flags::FILTER_MODE)? {
+            Some(w)  => w,
+            None     => return Ok(FilterMode::And),
+        };
+
+        if word == "and" {
+            Ok(FilterMode::And)
+        }
+        else if word == "or" {
+            Ok(FilterMode::Or)
+        }
+        else {
+            Err(Misfire::bad_argument(&flags::FILTER_MODE, word, FILTER_MODES))
+        }
+    }
+}
+
+/// Determines how many files to show from each directory, if the `--top`
+/// argument has been given.
+fn deduce_top(matches: &MatchedFlags) -> Result<Option<usize>, Misfire> {
+    match matches.get(&flags::TOP)? {
+        None     => Ok(None),
+        Some(n)  => match n.to_string_lossy().parse() {
+            Ok(n)   => Ok(Some(n)),
+            Err(e)  => Err(Misfire::FailedParse(e)),
+        },
+    }
+}
+
+/// Determines the separator to read numbers in file names with when
+/// sorting, if the `--locale-numbers` argument has been given. It comes
+/// from the locale returned by `load_numeric`, which only gets called if
+/// the argument is there. Sorting by the output of a command doesn’t use
+/// it, so the two can’t be given together in strict mode.
+///
+/// Some locales, such as the C one, have no separator at all, which would
+/// leave the argument doing nothing: strict mode complains about that, and
+/// otherwise English’s comma gets used instead.
+fn deduce_thousands_separator<L>(matches: &MatchedFlags, load_numeric: L) -> Result<Option<String>, Misfire>
+where L: FnOnce() -> locale::Numeric {
+    if !matches.has(&flags::LOCALE_NUMBERS)? {
+        return Ok(None);
+    }
+
+    if matches.is_strict() && matches.get(&flags::SORT_EXEC)?.is_some() {
+        return Err(Misfire::Useless(&flags::LOCALE_NUMBERS, true, &flags::SORT_EXEC));
+    }
+
+    let numeric = load_numeric();
+    if !numeric.thousands_sep.is_empty() {
+        Ok(Some(numeric.thousands_sep))
+    }
+    else if matches.is_strict() {
+        Err(Misfire::NoThousandsSeparator)
+    }
+    else {
+        Ok(Some(locale::Numeric::english().thousands_sep))
+    }
+}
+
+/// The user’s numeric locale, falling back to English if it can’t be
+/// loaded, as with the numbers in the details view.
+fn user_numeric() -> locale::Numeric {
+    locale::Numeric::load_user_locale()
+        .unwrap_or_else(|_| locale::Numeric::english())
+}
+
+
+/// Reads the patterns from an ignore file, skipping blank lines and the
+/// comments that start with a `#`. Returns a message saying which file it
+/// was if it can’t be read.
+fn read_ignore_file(path: &Path) -> Result<Vec<String>, String> {
+    let mut contents = String::new();
+    if let Err(e) = File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+        return Err(format!("{}: {}", path.display(), e));
+    }
+
+    Ok(contents.lines()
+               .map(|line| line.trim_right())
+               .filter(|line| !line.is_empty() && !line.starts_with('#'))
+               .map(String::from)
+               .collect())
+}
+
+
+impl TrackedFilter {
+
+    /// Determines whether to only show tracked or untracked files. The two
+    /// flags can’t be given together in strict mode; otherwise, the last
+    /// one wins.
+    pub fn deduce(matches: &MatchedFlags) -> Result<TrackedFilter, Misfire> {
+        if matches.is_strict() && matches.has(&flags::TRACKED_ONLY)? && matches.has(&flags::UNTRACKED_ONLY)? {
+            return Err(Misfire::Conflict(&flags::UNTRACKED_ONLY, &flags::TRACKED_ONLY));
         }
+
+        let flag = matches.has_where(|f| f.matches(&flags::TRACKED_ONLY) || f.matches(&flags::UNTRACKED_ONLY))?;
+
+        Ok(match flag {
+            Some(f) if f.matches(&flags::TRACKED_ONLY)    => TrackedFilter::Tracked,
+            Some(f) if f.matches(&flags::UNTRACKED_ONLY)  => TrackedFilter::Untracked,
+            _                                             => TrackedFilter::Any,
+        })
+    }
+}
+
+
+const DIRS_FIRSTS: &[&str] = &[ "real" ];
+
+impl DirsFirst {
+
+    /// Determines whether to list directories first, and which files count
+    /// as directories when doing so: on its own, the flag puts symlinks to
+    /// directories in with them, and with `=real`, it leaves them with the
+    /// other files.
+    pub fn deduce(matches: &MatchedFlags) -> Result<Option<DirsFirst>, Misfire> {
+        if let Some(word) = matches.get(&flags::DIRS_FIRST)? {
+            if word == "real" {
+                Ok(Some(DirsFirst::Real))
+            }
+            else {
+                Err(Misfire::bad_argument(&flags::DIRS_FIRST, word, DIRS_FIRSTS))
+            }
+        }
+        else if matches.has(&flags::DIRS_FIRST)? {
+            Ok(Some(DirsFirst::WithLinks))
+        }
+        else {
+            Ok(None)
+        }
+    }
+}
+
+
+impl OwnerFilter {
+
+    /// Determines whose files to hide: the ones owned by root, with the
+    /// `--no-root` flag, and the ones not owned by the user or group given
+    /// to `--owner` or `--owner-group`.
+    pub fn deduce(matches: &MatchedFlags) -> Result<OwnerFilter, Misfire> {
+        OwnerFilter::deduce_with(matches, &UsersCache::new())
+    }
+
+    /// Determines whose files to hide, looking up the names of users and
+    /// groups with the given resolver. Each name only gets looked up once,
+    /// here, rather than for every file. Numbers are taken to be IDs, and
+    /// don’t get looked up at all.
+    fn deduce_with<U: Users + Groups>(matches: &MatchedFlags, users: &U) -> Result<OwnerFilter, Misfire> {
+        let hidden_users = if matches.has(&flags::NO_ROOT)? { vec![ 0 ] } else { Vec::new() };
+
+        let only_user = match matches.get(&flags::OWNER)? {
+            None        => None,
+            Some(name)  => {
+                let name = name.to_string_lossy();
+                match name.parse() {
+                    Ok(uid)  => Some(uid),
+                    Err(_)   => match users.get_user_by_name(&name) {
+                        Some(user)  => Some(user.uid()),
+                        None        => return Err(Misfire::UnknownUser(name.into_owned())),
+                    },
+                }
+            },
+        };
+
+        let only_group = match matches.get(&flags::OWNER_GROUP)? {
+            None        => None,
+            Some(name)  => {
+                let name = name.to_string_lossy();
+                match name.parse() {
+                    Ok(gid)  => Some(gid),
+                    Err(_)   => match users.get_group_by_name(&name) {
+                        Some(group)  => Some(group.gid()),
+                        None         => return Err(Misfire::UnknownGroup(name.into_owned())),
+                    },
+                }
+            },
+        };
+
+        Ok(OwnerFilter { hidden_users, only_user, only_group })
     }
 }
 
@@ -164,7 +587,7 @@ mod test {
                 use options::test::parse_for_test;
                 use options::test::Strictnesses::*;
 
-                static TEST_ARGS: &[&Arg] = &[ &flags::SORT, &flags::ALL, &flags::TREE, &flags::IGNORE_GLOB ];
+                static TEST_ARGS: &[&Arg] = &[ &flags::SORT, &flags::ALL, &flags::ALMOST_ALL, &flags::ALL_ALL, &flags::ALL_LEVEL, &flags::TREE, &flags::IGNORE_GLOB, &flags::IGNORE_FILE, &flags::IGNORE_GLOB_BELOW, &flags::RECURSE, &flags::NO_ROOT, &flags::OWNER, &flags::OWNER_GROUP, &flags::TRACKED_ONLY, &flags::UNTRACKED_ONLY, &flags::DIRS_FIRST, &flags::FILTER_MODE, &flags::GREP, &flags::SORT_EXEC, &flags::SORT_CMD ];
                 for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| $type::deduce(mf)) {
                     assert_eq!(result, $result);
                 }
@@ -185,9 +608,30 @@ mod test {
         test!(lowercase:     SortField <- ["--sort", "name"];  Both => Ok(SortField::Name(SortCase::Sensitive)));
         test!(uppercase:     SortField <- ["--sort", "Name"];  Both => Ok(SortField::Name(SortCase::Insensitive)));
 
+        // Time aliases
+        test!(mtime:         SortField <- ["--sort=mtime"];    Both => Ok(SortField::ModifiedDate));
+        test!(atime:         SortField <- ["--sort=atime"];    Both => Ok(SortField::AccessedDate));
+        test!(ctime:         SortField <- ["--sort=ctime"];    Both => Ok(SortField::ChangedDate));
+        test!(mod_short:     SortField <- ["--sort=mod"];      Both => Ok(SortField::ModifiedDate));
+        test!(acc_short:     SortField <- ["--sort=acc"];      Both => Ok(SortField::AccessedDate));
+        test!(created:       SortField <- ["--sort=created"];  Both => Ok(SortField::CreatedDate));
+        test!(birth:         SortField <- ["--sort=birth"];    Both => Ok(SortField::CreatedDate));
+        test!(crtime:        SortField <- ["--sort=crtime"];   Both => Ok(SortField::CreatedDate));
+        test!(entries:       SortField <- ["--sort=entries"];  Both => Ok(SortField::EntryCount));
+        test!(items:         SortField <- ["--sort=items"];    Both => Ok(SortField::ItemCount(DotFilter::JustFiles)));
+        test!(items_all:     SortField <- ["--sort=items", "--all"];  Both => Ok(SortField::ItemCount(DotFilter::Dotfiles)));
+        test!(mixed:         SortField <- ["--sort=mixed"];    Both => Ok(SortField::Mixed));
+        test!(segments:      SortField <- ["--sort=segments"]; Both => Ok(SortField::Segments));
+
+        #[cfg(feature="git")]
+        test!(git:           SortField <- ["--sort=git"];      Both => Ok(SortField::GitStatus));
+
         // Errors
         test!(error:         SortField <- ["--sort=colour"];   Both => Err(Misfire::bad_argument(&flags::SORT, &os("colour"), super::SORTS)));
 
+        #[cfg(not(feature="git"))]
+        test!(no_git:        SortField <- ["--sort=git"];      Both => Err(Misfire::NeedsGit(&flags::SORT, os("git"))));
+
         // Overriding
         test!(overridden:    SortField <- ["--sort=cr",       "--sort", "mod"];     Last => Ok(SortField::ModifiedDate));
         test!(overridden_2:  SortField <- ["--sort", "none",  "--sort=Extension"];  Last => Ok(SortField::Extension(SortCase::Insensitive)));
@@ -196,6 +640,54 @@ mod test {
     }
 
 
+    mod sort_descriptions {
+        use super::*;
+        use options::parser::Arg;
+        use options::test::parse_for_test;
+        use options::test::Strictnesses::Both;
+
+        fn describe(inputs: &[&str]) -> Vec<String> {
+            static TEST_ARGS: &[&Arg] = &[ &flags::SORT, &flags::REVERSE ];
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| FileFilter::deduce(mf, &FilterConfig::default()).unwrap().sort_description())
+        }
+
+        #[test]
+        fn default() {
+            for description in describe(&[]) {
+                assert_eq!(description, "sort: Name(Sensitive) reverse=false");
+            }
+        }
+
+        #[test]
+        fn insensitive_reversed() {
+            for description in describe(&[ "--sort=Name", "--reverse" ]) {
+                assert_eq!(description, "sort: Name(Insensitive) reverse=true");
+            }
+        }
+
+        #[test]
+        fn extension() {
+            for description in describe(&[ "-sext" ]) {
+                assert_eq!(description, "sort: Extension(Sensitive) reverse=false");
+            }
+        }
+
+        #[test]
+        fn time() {
+            for description in describe(&[ "--sort=ctime", "-r" ]) {
+                assert_eq!(description, "sort: ChangedDate reverse=true");
+            }
+        }
+
+        #[test]
+        fn unsorted() {
+            for description in describe(&[ "--sort=none" ]) {
+                assert_eq!(description, "sort: Unsorted reverse=false");
+            }
+        }
+    }
+
+
     mod dot_filters {
         use super::*;
 
@@ -214,6 +706,40 @@ mod test {
         test!(tree_a:     DotFilter <- ["-Ta"];          Both => Ok(DotFilter::Dotfiles));
         test!(tree_aa:    DotFilter <- ["-Taa"];         Both => Err(Misfire::TreeAllAll));
         test!(tree_aaa:   DotFilter <- ["-Taaa"];        Both => Err(Misfire::TreeAllAll));
+
+        // --almost-all
+        test!(almost:     DotFilter <- ["--almost-all"];            Both => Ok(DotFilter::Dotfiles));
+        test!(almost_a:   DotFilter <- ["--almost-all", "-a"];      Both => Ok(DotFilter::Dotfiles));
+        test!(almost_aa:  DotFilter <- ["--almost-all", "-aa"];     Both => Ok(DotFilter::DotfilesAndDots));
+        test!(almost_2:   DotFilter <- ["--almost-all", "--almost-all"];  Last => Ok(DotFilter::Dotfiles));
+        test!(almost_3:   DotFilter <- ["--almost-all", "--almost-all"];  Complain => Err(Misfire::Duplicate(Flag::Long("almost-all"), Flag::Long("almost-all"))));
+        test!(tree_almost: DotFilter <- ["-T", "--almost-all"];     Both => Ok(DotFilter::Dotfiles));
+
+        // --all-all
+        test!(all_all_l:  DotFilter <- ["--all-all"];               Both => Ok(DotFilter::DotfilesAndDots));
+        test!(all_all_a:  DotFilter <- ["--all-all", "-a"];         Both => Ok(DotFilter::DotfilesAndDots));
+        test!(all_all_2l: DotFilter <- ["--all-all", "--all-all"];  Last => Ok(DotFilter::DotfilesAndDots));
+        test!(all_all_3l: DotFilter <- ["--all-all", "--all-all"];  Complain => Err(Misfire::Duplicate(Flag::Long("all-all"), Flag::Long("all-all"))));
+        test!(tree_all_all: DotFilter <- ["-T", "--all-all"];       Both => Err(Misfire::TreeAllAll));
+
+        // Both of them
+        test!(both:       DotFilter <- ["--almost-all", "--all-all"];  Last => Ok(DotFilter::DotfilesAndDots));
+        test!(both_2:     DotFilter <- ["--almost-all", "--all-all"];  Complain => Err(Misfire::Conflict(&flags::ALL_ALL, &flags::ALMOST_ALL)));
+
+        // --all-level
+        test!(level_0:    DotFilter <- ["--all-level=0"];           Both => Ok(DotFilter::JustFiles));
+        test!(level_1:    DotFilter <- ["--all-level=1"];           Both => Ok(DotFilter::Dotfiles));
+        test!(level_2:    DotFilter <- ["--all-level=2"];           Both => Ok(DotFilter::DotfilesAndDots));
+        test!(level_3:    DotFilter <- ["--all-level=3"];           Both => Err(Misfire::bad_argument(&flags::ALL_LEVEL, &os("3"), super::ALL_LEVELS)));
+        test!(tree_level: DotFilter <- ["-T", "--all-level=2"];     Both => Err(Misfire::TreeAllAll));
+
+        // --all-level with the others
+        test!(level_a:    DotFilter <- ["--all-level=0", "-a"];             Last => Ok(DotFilter::JustFiles));
+        test!(level_a_2:  DotFilter <- ["--all-level=0", "-a"];             Complain => Err(Misfire::Conflict(&flags::ALL_LEVEL, &flags::ALL)));
+        test!(level_aa:   DotFilter <- ["-aa", "--all-level=1"];            Last => Ok(DotFilter::Dotfiles));
+        test!(level_aa_2: DotFilter <- ["-aa", "--all-level=1"];            Complain => Err(Misfire::Conflict(&flags::ALL_LEVEL, &flags::ALL)));
+        test!(level_alm:  DotFilter <- ["--all-level=2", "--almost-all"];   Complain => Err(Misfire::Conflict(&flags::ALL_LEVEL, &flags::ALMOST_ALL)));
+        test!(level_aal:  DotFilter <- ["--all-level=1", "--all-all"];      Complain => Err(Misfire::Conflict(&flags::ALL_LEVEL, &flags::ALL_ALL)));
     }
 
 
@@ -221,6 +747,7 @@ mod test {
         use super::*;
         use std::iter::FromIterator;
         use glob;
+        use options::test::{parse_for_test, Strictnesses};
 
         fn pat(string: &'static str) -> glob::Pattern {
             glob::Pattern::new(string).unwrap()
@@ -237,5 +764,380 @@ mod test {
         test!(overridden_2: IgnorePatterns <- ["-I", "*.OGG", "-I*.MP3"];      Last => Ok(IgnorePatterns::from_iter(vec![ pat("*.MP3") ])));
         test!(overridden_3: IgnorePatterns <- ["-I=*.ogg",    "-I", "*.mp3"];  Complain => Err(Misfire::Duplicate(Flag::Short(b'I'), Flag::Short(b'I'))));
         test!(overridden_4: IgnorePatterns <- ["-I", "*.OGG", "-I*.MP3"];      Complain => Err(Misfire::Duplicate(Flag::Short(b'I'), Flag::Short(b'I'))));
+
+        // Depths
+        test!(below:        IgnorePatterns <- ["-R", "-I*.log", "--ignore-glob-below=1"];  Both => Ok(IgnorePatterns::from_iter(vec![ pat("*.log") ]).only_below(1)));
+        test!(below_tree:   IgnorePatterns <- ["-T", "-I*.log", "--ignore-glob-below=0"];  Both => Ok(IgnorePatterns::from_iter(vec![ pat("*.log") ]).only_below(0)));
+        test!(below_nan:    IgnorePatterns <- ["-R", "-I*.log", "--ignore-glob-below=two"];  Both => Err(Misfire::FailedParse("two".parse::<usize>().unwrap_err())));
+        test!(below_no_globs:  IgnorePatterns <- ["-R", "--ignore-glob-below=1"];  Last => Ok(IgnorePatterns::empty()));
+        test!(below_no_globs_2: IgnorePatterns <- ["-R", "--ignore-glob-below=1"];  Complain => Err(Misfire::Useless(&flags::IGNORE_GLOB_BELOW, false, &flags::IGNORE_GLOB)));
+        test!(below_no_recurse:  IgnorePatterns <- ["-I*.log", "--ignore-glob-below=1"];  Last => Ok(IgnorePatterns::from_iter(vec![ pat("*.log") ]).only_below(1)));
+        test!(below_no_recurse_2: IgnorePatterns <- ["-I*.log", "--ignore-glob-below=1"];  Complain => Err(Misfire::Useless2(&flags::IGNORE_GLOB_BELOW, &flags::RECURSE, &flags::TREE)));
+
+        // Ignore files
+        fn from_files(files: &[(&str, &[u8])], extra: &[&str], strictnesses: Strictnesses) -> Vec<Result<IgnorePatterns, Misfire>> {
+            use options::parser::Arg;
+            use scratch::ScratchDir;
+            use std::fs::File;
+            use std::io::Write;
+
+            static TEST_ARGS: &[&Arg] = &[ &flags::IGNORE_GLOB, &flags::IGNORE_FILE ];
+
+            let scratch = ScratchDir::new("ignore-files");
+            let mut args = Vec::new();
+            for &(name, contents) in files {
+                let path = scratch.join(name);
+                File::create(&path).unwrap().write_all(contents).unwrap();
+                args.push(format!("--ignore-file={}", path.display()));
+            }
+            args.extend(extra.iter().map(|e| e.to_string()));
+
+            let args: Vec<&str> = args.iter().map(|a| &a[..]).collect();
+            parse_for_test(&args, TEST_ARGS, strictnesses, |mf| IgnorePatterns::deduce(mf))
+        }
+
+        #[test]
+        fn two_files() {
+            let files: &[(&str, &[u8])] = &[ ("ignore-file-a", b"# logs\n*.log\n\n"), ("ignore-file-b", b"target\n*.tmp   \n") ];
+            for result in from_files(files, &[], Strictnesses::Both) {
+                assert_eq!(result, Ok(IgnorePatterns::from_iter(vec![ pat("*.log"), pat("target"), pat("*.tmp") ]).report_only_first(0)));
+            }
+        }
+
+        #[test]
+        fn files_and_globs() {
+            let files: &[(&str, &[u8])] = &[ ("ignore-file-globs", b"*.log\n") ];
+            for result in from_files(files, &[ "-I*.ogg" ], Strictnesses::Both) {
+                assert_eq!(result, Ok(IgnorePatterns::from_iter(vec![ pat("*.ogg"), pat("*.log") ]).report_only_first(1)));
+            }
+        }
+
+        test!(missing_file: IgnorePatterns <- ["--ignore-file=/does/not/exist", "-I*.ogg"];  Last => Ok(IgnorePatterns::from_iter(vec![ pat("*.ogg") ])));
+
+        #[test]
+        fn missing_file_2() {
+            for result in from_files(&[], &[ "--ignore-file=/does/not/exist" ], Strictnesses::Complain) {
+                match result {
+                    Err(Misfire::FailedIgnoreFile(ref e))  => assert!(e.starts_with("/does/not/exist: ")),
+                    _                                      => panic!("read a missing file: {:?}", result),
+                }
+            }
+        }
+    }
+
+
+    mod locale_numbers {
+        use super::*;
+        use options::parser::Arg;
+        use options::test::parse_for_test;
+        use options::test::Strictnesses::*;
+
+        static TEST_ARGS: &[&Arg] = &[ &flags::LOCALE_NUMBERS, &flags::SORT_EXEC ];
+
+        /// A locale that separates thousands with `sep`.
+        fn numeric(sep: &'static str) -> locale::Numeric {
+            locale::Numeric { decimal_sep: ".".into(), thousands_sep: sep.into() }
+        }
+
+        #[test]
+        fn none() {
+            for result in parse_for_test(&[], TEST_ARGS, Both, |mf| deduce_thousands_separator(mf, || numeric("."))) {
+                assert_eq!(result, Ok(None));
+            }
+        }
+
+        #[test]
+        fn given() {
+            for result in parse_for_test(&[ "--locale-numbers" ], TEST_ARGS, Both, |mf| deduce_thousands_separator(mf, user_numeric)) {
+                assert!(result.unwrap().is_some());
+            }
+        }
+
+        #[test]
+        fn forced_locale() {
+            for result in parse_for_test(&[ "--locale-numbers" ], TEST_ARGS, Both, |mf| deduce_thousands_separator(mf, || numeric("."))) {
+                assert_eq!(result, Ok(Some(".".into())));
+            }
+        }
+
+        #[test]
+        fn no_separator() {
+            for result in parse_for_test(&[ "--locale-numbers" ], TEST_ARGS, Last, |mf| deduce_thousands_separator(mf, || numeric(""))) {
+                assert_eq!(result, Ok(Some(",".into())));
+            }
+        }
+
+        #[test]
+        fn no_separator_strict() {
+            for result in parse_for_test(&[ "--locale-numbers" ], TEST_ARGS, Complain, |mf| deduce_thousands_separator(mf, || numeric(""))) {
+                assert_eq!(result, Err(Misfire::NoThousandsSeparator));
+            }
+        }
+
+        #[test]
+        fn with_sort_exec() {
+            for result in parse_for_test(&[ "--locale-numbers", "--sort-exec=cat" ], TEST_ARGS, Complain, |mf| deduce_thousands_separator(mf, user_numeric)) {
+                assert_eq!(result, Err(Misfire::Useless(&flags::LOCALE_NUMBERS, true, &flags::SORT_EXEC)));
+            }
+        }
+    }
+
+
+    mod tops {
+        use super::*;
+        use options::parser::Arg;
+        use options::test::parse_for_test;
+        use options::test::Strictnesses::*;
+
+        static TEST_ARGS: &[&Arg] = &[ &flags::TOP ];
+
+        #[test]
+        fn none() {
+            for result in parse_for_test(&[], TEST_ARGS, Both, |mf| deduce_top(mf)) {
+                assert_eq!(result, Ok(None));
+            }
+        }
+
+        #[test]
+        fn ten() {
+            for result in parse_for_test(&[ "--top=10" ], TEST_ARGS, Both, |mf| deduce_top(mf)) {
+                assert_eq!(result, Ok(Some(10)));
+            }
+        }
+
+        #[test]
+        fn overridden() {
+            for result in parse_for_test(&[ "--top=10", "--top", "3" ], TEST_ARGS, Last, |mf| deduce_top(mf)) {
+                assert_eq!(result, Ok(Some(3)));
+            }
+
+            for result in parse_for_test(&[ "--top=10", "--top", "3" ], TEST_ARGS, Complain, |mf| deduce_top(mf)) {
+                assert_eq!(result, Err(Misfire::Duplicate(Flag::Long("top"), Flag::Long("top"))));
+            }
+        }
+
+        #[test]
+        fn not_a_number() {
+            for result in parse_for_test(&[ "--top=lots" ], TEST_ARGS, Both, |mf| deduce_top(mf)) {
+                assert_eq!(result, Err(Misfire::FailedParse("lots".parse::<usize>().unwrap_err())));
+            }
+        }
+    }
+
+
+    mod tracked_filters {
+        use super::*;
+
+        test!(empty:        TrackedFilter <- [];                                      Both => Ok(TrackedFilter::Any));
+        test!(tracked:      TrackedFilter <- ["--tracked-only"];                      Both => Ok(TrackedFilter::Tracked));
+        test!(untracked:    TrackedFilter <- ["--untracked-only"];                    Both => Ok(TrackedFilter::Untracked));
+        test!(both:         TrackedFilter <- ["--tracked-only", "--untracked-only"];  Last => Ok(TrackedFilter::Untracked));
+        test!(both_2:       TrackedFilter <- ["--untracked-only", "--tracked-only"];  Last => Ok(TrackedFilter::Tracked));
+        test!(both_3:       TrackedFilter <- ["--tracked-only", "--untracked-only"];  Complain => Err(Misfire::Conflict(&flags::UNTRACKED_ONLY, &flags::TRACKED_ONLY)));
+        test!(twice:        TrackedFilter <- ["--tracked-only", "--tracked-only"];    Complain => Err(Misfire::Duplicate(Flag::Long("tracked-only"), Flag::Long("tracked-only"))));
+    }
+
+
+    mod dirs_firsts {
+        use super::*;
+
+        test!(empty:        DirsFirst <- [];                                      Both => Ok(None));
+        test!(with_links:   DirsFirst <- ["--group-directories-first"];           Both => Ok(Some(DirsFirst::WithLinks)));
+        test!(real:         DirsFirst <- ["--group-directories-first=real"];      Both => Ok(Some(DirsFirst::Real)));
+        test!(not_a_value:  DirsFirst <- ["--group-directories-first", "real"];   Both => Ok(Some(DirsFirst::WithLinks)));
+        test!(unknown:      DirsFirst <- ["--group-directories-first=fake"];      Both => Err(Misfire::bad_argument(&flags::DIRS_FIRST, &os("fake"), super::DIRS_FIRSTS)));
+        test!(twice:        DirsFirst <- ["--group-directories-first=real", "--group-directories-first=real"];  Complain => Err(Misfire::Duplicate(Flag::Long("group-directories-first"), Flag::Long("group-directories-first"))));
+    }
+
+
+    mod sort_execs {
+        use super::*;
+
+        fn exec(command: &str) -> Option<SortExec> {
+            Some(SortExec { command: command.into() })
+        }
+
+        test!(empty:        SortExec <- [];                                   Both => Ok(None));
+        test!(command:      SortExec <- ["--sort-exec=cat"];                  Both => Ok(exec("cat")));
+        test!(with_sort:    SortExec <- ["--sort-exec=cat", "--sort=size"];   Last => Ok(exec("cat")));
+        test!(conflict:     SortExec <- ["--sort-exec=cat", "--sort=size"];   Complain => Err(Misfire::Conflict(&flags::SORT_EXEC, &flags::SORT)));
+        test!(twice:        SortExec <- ["--sort-exec=cat", "--sort-exec=wc"];  Complain => Err(Misfire::Duplicate(Flag::Long("sort-exec"), Flag::Long("sort-exec"))));
+    }
+
+
+    mod sort_commands {
+        use super::*;
+
+        fn command(command: &str) -> Option<SortCommand> {
+            Some(SortCommand { command: command.into() })
+        }
+
+        test!(empty:        SortCommand <- [];                                    Both => Ok(None));
+        test!(reversing:    SortCommand <- ["--sort-cmd=sort -r"];                Both => Ok(command("sort -r")));
+        test!(with_sort:    SortCommand <- ["--sort-cmd=tac", "--sort=size"];     Both => Ok(command("tac")));
+        test!(with_exec:    SortCommand <- ["--sort-cmd=tac", "--sort-exec=cat"];  Last => Ok(command("tac")));
+        test!(conflict:     SortCommand <- ["--sort-cmd=tac", "--sort-exec=cat"];  Complain => Err(Misfire::Conflict(&flags::SORT_CMD, &flags::SORT_EXEC)));
+        test!(twice:        SortCommand <- ["--sort-cmd=tac", "--sort-cmd=sort"];  Complain => Err(Misfire::Duplicate(Flag::Long("sort-cmd"), Flag::Long("sort-cmd"))));
+    }
+
+
+    mod name_regexes {
+        use super::*;
+
+        fn regex(input: &str) -> Option<NameRegex> {
+            Some(NameRegex::new(input).unwrap())
+        }
+
+        test!(empty:        NameRegex <- [];                                  Both => Ok(None));
+        test!(readme:       NameRegex <- ["--grep=^README"];                  Both => Ok(regex("^README")));
+        test!(overridden:   NameRegex <- ["--grep=^README", "--grep", "rs$"];  Last => Ok(regex("rs$")));
+        test!(twice:        NameRegex <- ["--grep=^README", "--grep", "rs$"];  Complain => Err(Misfire::Duplicate(Flag::Long("grep"), Flag::Long("grep"))));
+        test!(invalid:      NameRegex <- ["--grep=(unclosed"];                 Both => Err(Misfire::FailedRegex(NameRegex::new("(unclosed").unwrap_err().to_string())));
+    }
+
+
+    mod filter_modes {
+        use super::*;
+
+        test!(empty:        FilterMode <- [];                                          Both => Ok(FilterMode::And));
+        test!(and:          FilterMode <- ["--filter-mode=and"];                       Both => Ok(FilterMode::And));
+        test!(or:           FilterMode <- ["--filter-mode", "or"];                     Both => Ok(FilterMode::Or));
+        test!(overridden:   FilterMode <- ["--filter-mode=or", "--filter-mode=and"];   Last => Ok(FilterMode::And));
+        test!(unknown:      FilterMode <- ["--filter-mode=xor"];                       Both => Err(Misfire::bad_argument(&flags::FILTER_MODE, &os("xor"), super::FILTER_MODES)));
+        test!(twice:        FilterMode <- ["--filter-mode=or", "--filter-mode=and"];   Complain => Err(Misfire::Duplicate(Flag::Long("filter-mode"), Flag::Long("filter-mode"))));
+    }
+
+
+    mod configs {
+        use super::*;
+        use options::parser::Arg;
+        use options::test::parse_for_test;
+        use options::test::Strictnesses::Both;
+
+        fn filters(inputs: &[&str], config: &str) -> Vec<FileFilter> {
+            static TEST_ARGS: &[&Arg] = &[ &flags::SORT, &flags::ALL, &flags::TREE, &flags::IGNORE_GLOB, &flags::IGNORE_FILE, &flags::IGNORE_FILE_LIKE_GIT, &flags::NO_IGNORE ];
+            let config = FilterConfig::parse(config).unwrap();
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| FileFilter::deduce(mf, &config).unwrap())
+        }
+
+        #[test]
+        fn sort_from_config() {
+            for filter in filters(&[], "sort = \"size\"") {
+                assert_eq!(filter.sort_field, SortField::Size);
+            }
+        }
+
+        #[test]
+        fn sort_from_command_line_wins() {
+            for filter in filters(&[ "--sort=name" ], "sort = \"size\"") {
+                assert_eq!(filter.sort_field, SortField::Name(SortCase::Sensitive));
+            }
+        }
+
+        #[test]
+        fn items_use_dots_from_config() {
+            for filter in filters(&[ "--sort=items" ], "all = 1") {
+                assert_eq!(filter.sort_field, SortField::ItemCount(DotFilter::Dotfiles));
+            }
+        }
+
+        #[test]
+        fn dots_from_command_line_win() {
+            for filter in filters(&[ "--all" ], "all = 0") {
+                assert_eq!(filter.dot_filter, DotFilter::Dotfiles);
+            }
+        }
+
+        #[test]
+        fn no_dots_in_a_tree() {
+            for filter in filters(&[ "--tree" ], "all = 2") {
+                assert_eq!(filter.dot_filter, DotFilter::Dotfiles);
+            }
+        }
+
+        #[test]
+        fn ignores_from_config() {
+            for filter in filters(&[], "ignore-glob = [ \"*.tmp\" ]") {
+                assert!(filter.ignore_patterns.is_ignored("backup.tmp"));
+            }
+        }
+
+        #[test]
+        fn ignores_from_command_line_win() {
+            for filter in filters(&[ "--ignore-glob=*.bak" ], "ignore-glob = [ \"*.tmp\" ]") {
+                assert!(!filter.ignore_patterns.is_ignored("backup.tmp"));
+                assert!(filter.ignore_patterns.is_ignored("backup.bak"));
+            }
+        }
+
+        #[test]
+        fn no_ignore_overrides_everything() {
+            for filter in filters(&[ "--ignore-glob=*.rs", "--ignore-file-like-git", "--ignore-file=/nonexistent", "--no-ignore" ], "ignore-glob = [ \"*.tmp\" ]") {
+                assert!(!filter.ignore_patterns.is_ignored("main.rs"));
+                assert!(!filter.ignore_patterns.is_ignored("backup.tmp"));
+                assert!(!filter.git_ignore);
+            }
+        }
+    }
+
+
+    mod owner_filters {
+        use super::*;
+
+        test!(empty:        OwnerFilter <- [];                          Both => Ok(OwnerFilter::default()));
+        test!(no_root:      OwnerFilter <- ["--no-root"];               Both => Ok(OwnerFilter { hidden_users: vec![ 0 ], ..OwnerFilter::default() }));
+        test!(no_root_2:    OwnerFilter <- ["--no-root", "--no-root"];  Last => Ok(OwnerFilter::no_root()));
+        test!(no_root_3:    OwnerFilter <- ["--no-root", "--no-root"];  Complain => Err(Misfire::Duplicate(Flag::Long("no-root"), Flag::Long("no-root"))));
+
+        // Numeric IDs don’t need looking up
+        test!(uid:          OwnerFilter <- ["--owner=1000"];            Both => Ok(OwnerFilter { only_user: Some(1000), ..OwnerFilter::default() }));
+        test!(gid:          OwnerFilter <- ["--owner-group=100"];       Both => Ok(OwnerFilter { only_group: Some(100), ..OwnerFilter::default() }));
+        test!(both:         OwnerFilter <- ["--owner=0", "--owner-group=0", "--no-root"];  Both => Ok(OwnerFilter { hidden_users: vec![ 0 ], only_user: Some(0), only_group: Some(0) }));
+
+        mod names {
+            use super::*;
+            use options::parser::Arg;
+            use options::test::parse_for_test;
+            use options::test::Strictnesses::*;
+            use users::{User, Group};
+            use users::mock::MockUsers;
+
+            static TEST_ARGS: &[&Arg] = &[ &flags::OWNER, &flags::OWNER_GROUP ];
+
+            fn deduce(inputs: &[&str]) -> Vec<Result<OwnerFilter, Misfire>> {
+                let mut users = MockUsers::with_current_uid(0);
+                let _ = users.add_user(User::new(1001, "alice", 100));
+                let _ = users.add_group(Group::new(100, "devs"));
+                parse_for_test(inputs, TEST_ARGS, Both, |mf| OwnerFilter::deduce_with(mf, &users))
+            }
+
+            #[test]
+            fn user_name() {
+                for result in deduce(&[ "--owner=alice" ]) {
+                    assert_eq!(result, Ok(OwnerFilter { only_user: Some(1001), ..OwnerFilter::default() }));
+                }
+            }
+
+            #[test]
+            fn group_name() {
+                for result in deduce(&[ "--owner-group=devs" ]) {
+                    assert_eq!(result, Ok(OwnerFilter { only_group: Some(100), ..OwnerFilter::default() }));
+                }
+            }
+
+            #[test]
+            fn unknown_user() {
+                for result in deduce(&[ "--owner=mallory" ]) {
+                    assert_eq!(result, Err(Misfire::UnknownUser("mallory".into())));
+                }
+            }
+
+            #[test]
+            fn unknown_group() {
+                for result in deduce(&[ "--owner-group=admins" ]) {
+                    assert_eq!(result, Err(Misfire::UnknownGroup("admins".into())));
+                }
+            }
+        }
     }
 }
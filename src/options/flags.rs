@@ -8,57 +8,132 @@ pub static HELP:    Arg = Arg { short: Some(b'?'), long: "help",     takes_value
 // display options
 pub static ONE_LINE: Arg = Arg { short: Some(b'1'), long: "oneline",  takes_value: TakesValue::Forbidden };
 pub static LONG:     Arg = Arg { short: Some(b'l'), long: "long",     takes_value: TakesValue::Forbidden };
+pub static TSV:      Arg = Arg { short: None,       long: "tsv",      takes_value: TakesValue::Forbidden };
+pub static JSON:     Arg = Arg { short: None,       long: "json",     takes_value: TakesValue::Forbidden };
 pub static GRID:     Arg = Arg { short: Some(b'G'), long: "grid",     takes_value: TakesValue::Forbidden };
 pub static ACROSS:   Arg = Arg { short: Some(b'x'), long: "across",   takes_value: TakesValue::Forbidden };
+pub static SPACING:  Arg = Arg { short: None,       long: "spacing",  takes_value: TakesValue::Necessary };
 pub static RECURSE:  Arg = Arg { short: Some(b'R'), long: "recurse",  takes_value: TakesValue::Forbidden };
 pub static TREE:     Arg = Arg { short: Some(b'T'), long: "tree",     takes_value: TakesValue::Forbidden };
 pub static CLASSIFY: Arg = Arg { short: Some(b'F'), long: "classify", takes_value: TakesValue::Forbidden };
+pub static SLASH_DIRS: Arg = Arg { short: None,     long: "slash-dirs", takes_value: TakesValue::Forbidden };
+pub static ICONS:    Arg = Arg { short: None,       long: "icons",    takes_value: TakesValue::Optional };
 
 pub static COLOR:  Arg = Arg { short: None, long: "color",  takes_value: TakesValue::Necessary };
 pub static COLOUR: Arg = Arg { short: None, long: "colour", takes_value: TakesValue::Necessary };
 
 pub static COLOR_SCALE:  Arg = Arg { short: None, long: "color-scale",  takes_value: TakesValue::Forbidden };
 pub static COLOUR_SCALE: Arg = Arg { short: None, long: "colour-scale", takes_value: TakesValue::Forbidden };
+pub static COLOR_BY_ACCESS:  Arg = Arg { short: None, long: "color-by-access",  takes_value: TakesValue::Forbidden };
+pub static COLOUR_BY_ACCESS: Arg = Arg { short: None, long: "colour-by-access", takes_value: TakesValue::Forbidden };
+
+pub static HIGHLIGHT_RECENT: Arg = Arg { short: None, long: "highlight-recent", takes_value: TakesValue::Forbidden };
+
+pub static PAGINATE: Arg = Arg { short: None, long: "paginate", takes_value: TakesValue::Forbidden };
+pub static SUMMARY:  Arg = Arg { short: None, long: "summary",  takes_value: TakesValue::Forbidden };
+pub static SUMMARY_RECURSE: Arg = Arg { short: None, long: "summary-recurse", takes_value: TakesValue::Forbidden };
+pub static ALPHABET_DIVIDERS: Arg = Arg { short: None, long: "alphabet-dividers", takes_value: TakesValue::Forbidden };
+
+pub static EXT_COLORS:  Arg = Arg { short: None, long: "ext-colors",  takes_value: TakesValue::Necessary };
+pub static EXT_COLOURS: Arg = Arg { short: None, long: "ext-colours", takes_value: TakesValue::Necessary };
+
+pub static LINK_TARGET: Arg = Arg { short: None, long: "link-target", takes_value: TakesValue::Necessary };
 
 // filtering and sorting options
 pub static ALL:         Arg = Arg { short: Some(b'a'), long: "all",         takes_value: TakesValue::Forbidden };
+pub static ALMOST_ALL:  Arg = Arg { short: None,       long: "almost-all",  takes_value: TakesValue::Forbidden };
+pub static ALL_ALL:     Arg = Arg { short: None,       long: "all-all",     takes_value: TakesValue::Forbidden };
+pub static ALL_LEVEL:   Arg = Arg { short: None,       long: "all-level",   takes_value: TakesValue::Necessary };
 pub static LIST_DIRS:   Arg = Arg { short: Some(b'd'), long: "list-dirs",   takes_value: TakesValue::Forbidden };
 pub static LEVEL:       Arg = Arg { short: Some(b'L'), long: "level",       takes_value: TakesValue::Necessary };
+pub static RECURSE_ORDER: Arg = Arg { short: None, long: "recurse-order", takes_value: TakesValue::Necessary };
 pub static REVERSE:     Arg = Arg { short: Some(b'r'), long: "reverse",     takes_value: TakesValue::Forbidden };
 pub static SORT:        Arg = Arg { short: Some(b's'), long: "sort",        takes_value: TakesValue::Necessary };
+pub static SORT_EXEC:   Arg = Arg { short: None,       long: "sort-exec",   takes_value: TakesValue::Necessary };
+pub static SORT_CMD:    Arg = Arg { short: None,       long: "sort-cmd",    takes_value: TakesValue::Necessary };
+pub static LOCALE_NUMBERS: Arg = Arg { short: None,    long: "locale-numbers", takes_value: TakesValue::Forbidden };
 pub static IGNORE_GLOB: Arg = Arg { short: Some(b'I'), long: "ignore-glob", takes_value: TakesValue::Necessary };
-pub static DIRS_FIRST:  Arg = Arg { short: None, long: "group-directories-first",  takes_value: TakesValue::Forbidden };
+pub static IGNORE_FILE: Arg = Arg { short: None,     long: "ignore-file", takes_value: TakesValue::Necessary };
+pub static IGNORE_GLOB_BELOW: Arg = Arg { short: None, long: "ignore-glob-below", takes_value: TakesValue::Necessary };
+pub static GREP:        Arg = Arg { short: None, long: "grep", takes_value: TakesValue::Necessary };
+pub static IGNORE_FILE_LIKE_GIT: Arg = Arg { short: None, long: "ignore-file-like-git", takes_value: TakesValue::Forbidden };
+pub static NO_IGNORE:   Arg = Arg { short: None, long: "no-ignore", takes_value: TakesValue::Forbidden };
+pub static DIRS_FIRST:  Arg = Arg { short: None, long: "group-directories-first",  takes_value: TakesValue::Optional };
+pub static CHANGED_ONLY: Arg = Arg { short: None, long: "changed-only", takes_value: TakesValue::Forbidden };
+pub static TOP:         Arg = Arg { short: None, long: "top",        takes_value: TakesValue::Necessary };
+pub static CONFIG:      Arg = Arg { short: None, long: "config", takes_value: TakesValue::Necessary };
+pub static FILTER_MODE: Arg = Arg { short: None, long: "filter-mode", takes_value: TakesValue::Necessary };
+pub static LINKS_ONLY:  Arg = Arg { short: None, long: "links-only", takes_value: TakesValue::Forbidden };
+pub static EXECUTABLE:  Arg = Arg { short: None, long: "executable", takes_value: TakesValue::Forbidden };
+pub static NO_ROOT:     Arg = Arg { short: None, long: "no-root", takes_value: TakesValue::Forbidden };
+pub static OWNER:       Arg = Arg { short: None, long: "owner", takes_value: TakesValue::Necessary };
+pub static OWNER_GROUP: Arg = Arg { short: None, long: "owner-group", takes_value: TakesValue::Necessary };
+pub static PRINT_SORT:  Arg = Arg { short: None, long: "print-sort", takes_value: TakesValue::Forbidden };
+pub static PRINT_WIDTH: Arg = Arg { short: None, long: "print-width", takes_value: TakesValue::Forbidden };
+pub static TIMINGS:     Arg = Arg { short: None, long: "timings", takes_value: TakesValue::Forbidden };
+pub static GLOB_ARGS:   Arg = Arg { short: None, long: "glob-args", takes_value: TakesValue::Forbidden };
+pub static DEREF_ARGS:  Arg = Arg { short: None, long: "dereference-command-line", takes_value: TakesValue::Forbidden };
 
 // display options
 pub static BINARY:     Arg = Arg { short: Some(b'b'), long: "binary",     takes_value: TakesValue::Forbidden };
 pub static BYTES:      Arg = Arg { short: Some(b'B'), long: "bytes",      takes_value: TakesValue::Forbidden };
 pub static GROUP:      Arg = Arg { short: Some(b'g'), long: "group",      takes_value: TakesValue::Forbidden };
+pub static COLLAPSE_OWNER: Arg = Arg { short: None,  long: "collapse-owner", takes_value: TakesValue::Forbidden };
 pub static HEADER:     Arg = Arg { short: Some(b'h'), long: "header",     takes_value: TakesValue::Forbidden };
 pub static INODE:      Arg = Arg { short: Some(b'i'), long: "inode",      takes_value: TakesValue::Forbidden };
-pub static LINKS:      Arg = Arg { short: Some(b'H'), long: "links",      takes_value: TakesValue::Forbidden };
+pub static LINKS:      Arg = Arg { short: Some(b'H'), long: "links",      takes_value: TakesValue::Optional };
+pub static BLANK_SYMLINK_META: Arg = Arg { short: None, long: "blank-symlink-meta", takes_value: TakesValue::Forbidden };
 pub static MODIFIED:   Arg = Arg { short: Some(b'm'), long: "modified",   takes_value: TakesValue::Forbidden };
 pub static BLOCKS:     Arg = Arg { short: Some(b'S'), long: "blocks",     takes_value: TakesValue::Forbidden };
+pub static SPARSE:     Arg = Arg { short: None,       long: "show-sparse", takes_value: TakesValue::Forbidden };
+pub static FILE_FLAGS: Arg = Arg { short: None,       long: "flags",      takes_value: TakesValue::Forbidden };
+pub static TIME_ANOMALIES: Arg = Arg { short: None,   long: "show-time-anomalies", takes_value: TakesValue::Forbidden };
+pub static ENTRIES:    Arg = Arg { short: None,       long: "entries",    takes_value: TakesValue::Forbidden };
+pub static DIR_ENTRIES: Arg = Arg { short: None,      long: "dir-entries", takes_value: TakesValue::Forbidden };
+pub static CONTENT_TYPE: Arg = Arg { short: None,     long: "content-type", takes_value: TakesValue::Forbidden };
+pub static LINE_COUNT: Arg = Arg { short: None,       long: "wc",           takes_value: TakesValue::Forbidden };
+pub static SYMLINK_GROUPS: Arg = Arg { short: None,   long: "symlink-groups", takes_value: TakesValue::Forbidden };
+pub static AGE_COLUMN: Arg = Arg { short: None,       long: "age-column",   takes_value: TakesValue::Forbidden };
+pub static AGE_THRESHOLDS: Arg = Arg { short: None,   long: "age-thresholds", takes_value: TakesValue::Necessary };
+pub static CONTEXT:    Arg = Arg { short: None,       long: "context",      takes_value: TakesValue::Forbidden };
+pub static SIZE_PERCENT: Arg = Arg { short: None,     long: "size-percent", takes_value: TakesValue::Forbidden };
 pub static TIME:       Arg = Arg { short: Some(b't'), long: "time",       takes_value: TakesValue::Necessary };
 pub static ACCESSED:   Arg = Arg { short: Some(b'u'), long: "accessed",   takes_value: TakesValue::Forbidden };
 pub static CREATED:    Arg = Arg { short: Some(b'U'), long: "created",    takes_value: TakesValue::Forbidden };
 pub static TIME_STYLE: Arg = Arg { short: None,       long: "time-style", takes_value: TakesValue::Necessary };
+pub static ALIGN:      Arg = Arg { short: None,       long: "align",      takes_value: TakesValue::Necessary };
+pub static COMPACT:    Arg = Arg { short: None,       long: "compact",    takes_value: TakesValue::Forbidden };
+pub static COMPACT_LONG: Arg = Arg { short: None,     long: "compact-long", takes_value: TakesValue::Forbidden };
+pub static COLUMNS:    Arg = Arg { short: None,       long: "columns",    takes_value: TakesValue::Necessary };
 
 // optional feature options
 pub static GIT:       Arg = Arg { short: None,       long: "git",      takes_value: TakesValue::Forbidden };
+pub static GIT_SINCE: Arg = Arg { short: None,       long: "git-since", takes_value: TakesValue::Necessary };
+pub static GIT_SINCE_ONLY: Arg = Arg { short: None,  long: "git-since-only", takes_value: TakesValue::Necessary };
+pub static GIT_LAST_COMMIT: Arg = Arg { short: None, long: "git-last-commit", takes_value: TakesValue::Forbidden };
+pub static GIT_ONLY_DIRTY: Arg = Arg { short: None,  long: "git-only-dirty", takes_value: TakesValue::Forbidden };
+pub static TRACKED_ONLY: Arg = Arg { short: None,    long: "tracked-only", takes_value: TakesValue::Forbidden };
+pub static UNTRACKED_ONLY: Arg = Arg { short: None,  long: "untracked-only", takes_value: TakesValue::Forbidden };
+pub static GIT_RECURSE_SUBMODULES: Arg = Arg { short: None, long: "git-recurse-submodules", takes_value: TakesValue::Forbidden };
+pub static GIT_REMOTE: Arg = Arg { short: None,      long: "git-remote", takes_value: TakesValue::Forbidden };
+pub static GIT_REMOTE_NAME: Arg = Arg { short: None, long: "git-remote-name", takes_value: TakesValue::Necessary };
+pub static GIT_DIM_IGNORED: Arg = Arg { short: None, long: "git-dim-ignored", takes_value: TakesValue::Forbidden };
 pub static EXTENDED:  Arg = Arg { short: Some(b'@'), long: "extended", takes_value: TakesValue::Forbidden };
+pub static ACL:       Arg = Arg { short: None,       long: "acl",      takes_value: TakesValue::Forbidden };
 
 
 pub static ALL_ARGS: Args = Args(&[
     &VERSION, &HELP,
 
-    &ONE_LINE, &LONG, &GRID, &ACROSS, &RECURSE, &TREE, &CLASSIFY,
-    &COLOR, &COLOUR, &COLOR_SCALE, &COLOUR_SCALE,
+    &ONE_LINE, &LONG, &TSV, &JSON, &GRID, &ACROSS, &SPACING, &RECURSE, &TREE, &CLASSIFY, &SLASH_DIRS, &ICONS,
+    &COLOR, &COLOUR, &COLOR_SCALE, &COLOUR_SCALE, &COLOR_BY_ACCESS, &COLOUR_BY_ACCESS, &EXT_COLORS, &EXT_COLOURS,
+    &PAGINATE, &HIGHLIGHT_RECENT, &SUMMARY, &SUMMARY_RECURSE, &ALPHABET_DIVIDERS, &LINK_TARGET,
 
-    &ALL, &LIST_DIRS, &LEVEL, &REVERSE, &SORT, &IGNORE_GLOB, &DIRS_FIRST,
+    &ALL, &ALMOST_ALL, &ALL_ALL, &ALL_LEVEL, &LIST_DIRS, &LEVEL, &RECURSE_ORDER, &REVERSE, &SORT, &SORT_EXEC, &SORT_CMD, &LOCALE_NUMBERS, &IGNORE_GLOB, &IGNORE_FILE, &IGNORE_GLOB_BELOW, &GREP, &IGNORE_FILE_LIKE_GIT, &NO_IGNORE, &DIRS_FIRST, &DEREF_ARGS, &GLOB_ARGS, &CHANGED_ONLY, &LINKS_ONLY, &EXECUTABLE, &FILTER_MODE, &TOP, &NO_ROOT, &OWNER, &OWNER_GROUP, &PRINT_SORT, &PRINT_WIDTH, &TIMINGS, &CONFIG,
 
-    &BINARY, &BYTES, &GROUP, &HEADER, &INODE, &LINKS, &MODIFIED, &BLOCKS,
-    &TIME, &ACCESSED, &CREATED, &TIME_STYLE,
+    &BINARY, &BYTES, &GROUP, &COLLAPSE_OWNER, &HEADER, &INODE, &LINKS, &BLANK_SYMLINK_META, &MODIFIED, &BLOCKS, &SPARSE, &TIME_ANOMALIES, &FILE_FLAGS, &ENTRIES, &DIR_ENTRIES, &CONTENT_TYPE, &LINE_COUNT, &SYMLINK_GROUPS, &AGE_COLUMN, &AGE_THRESHOLDS, &CONTEXT, &SIZE_PERCENT,
+    &TIME, &ACCESSED, &CREATED, &TIME_STYLE, &ALIGN, &COLUMNS, &COMPACT, &COMPACT_LONG,
 
-    &GIT, &EXTENDED,
+    &GIT, &GIT_SINCE, &GIT_SINCE_ONLY, &GIT_LAST_COMMIT, &GIT_ONLY_DIRTY, &TRACKED_ONLY, &UNTRACKED_ONLY, &GIT_RECURSE_SUBMODULES, &GIT_REMOTE, &GIT_REMOTE_NAME, &GIT_DIM_IGNORED, &EXTENDED, &ACL,
 ]);
 
@@ -0,0 +1,86 @@
+//! Parsing the options for `GitOptions`.
+
+use options::parser::MatchedFlags;
+use options::{flags, Misfire};
+
+use fs::feature::GitOptions;
+
+
+impl GitOptions {
+
+    /// Determine what to find out about a Git repository beyond the usual
+    /// statuses. The reference to compare against can come from either
+    /// `--git-since` or `--git-since-only`, as the latter just adds the
+    /// filtering on top, so only one of them can be given.
+    ///
+    /// Recursing into submodules only changes the statuses shown in the Git
+    /// column or used by `--changed-only`, so it’s useless without either.
+    /// The commits that last changed the files are only looked up when the
+    /// column showing them is asked for.
+    pub fn deduce(matches: &MatchedFlags) -> Result<GitOptions, Misfire> {
+        let since      = matches.get(&flags::GIT_SINCE)?;
+        let since_only = matches.get(&flags::GIT_SINCE_ONLY)?;
+        let recurse_submodules = matches.has(&flags::GIT_RECURSE_SUBMODULES)?;
+        let last_commits = matches.has(&flags::GIT_LAST_COMMIT)?;
+
+        if matches.is_strict() && since.is_some() && since_only.is_some() {
+            return Err(Misfire::Conflict(&flags::GIT_SINCE_ONLY, &flags::GIT_SINCE));
+        }
+
+        if matches.is_strict() && recurse_submodules && !matches.has(&flags::GIT)? && !matches.has(&flags::CHANGED_ONLY)? {
+            return Err(Misfire::Useless2(&flags::GIT_RECURSE_SUBMODULES, &flags::GIT, &flags::CHANGED_ONLY));
+        }
+
+        let since = since_only.or(since).map(|r| r.to_string_lossy().into_owned());
+        Ok(GitOptions { since, recurse_submodules, last_commits })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use options::flags;
+    use options::parser::Flag;
+
+    macro_rules! test {
+        ($name:ident: $type:ident <- $inputs:expr; $stricts:expr => $result:expr) => {
+            #[test]
+            fn $name() {
+                use options::parser::Arg;
+                use options::test::parse_for_test;
+                use options::test::Strictnesses::*;
+
+                static TEST_ARGS: &[&Arg] = &[ &flags::GIT_SINCE, &flags::GIT_SINCE_ONLY, &flags::GIT, &flags::CHANGED_ONLY, &flags::GIT_RECURSE_SUBMODULES, &flags::GIT_LAST_COMMIT ];
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| $type::deduce(mf)) {
+                    assert_eq!(result, $result);
+                }
+            }
+        };
+    }
+
+
+    // Default behaviour
+    test!(empty:        GitOptions <- [];                                          Both => Ok(GitOptions::default()));
+
+    // Picking a reference
+    test!(since:        GitOptions <- ["--git-since=v1.0"];                        Both => Ok(GitOptions { since: Some("v1.0".into()), ..GitOptions::default() }));
+    test!(since_only:   GitOptions <- ["--git-since-only", "main"];                Both => Ok(GitOptions { since: Some("main".into()), ..GitOptions::default() }));
+
+    // Overriding
+    test!(overridden:   GitOptions <- ["--git-since=one", "--git-since=two"];      Last => Ok(GitOptions { since: Some("two".into()), ..GitOptions::default() }));
+    test!(overridden_2: GitOptions <- ["--git-since=one", "--git-since=two"];      Complain => Err(Misfire::Duplicate(Flag::Long("git-since"), Flag::Long("git-since"))));
+
+    // Both flags
+    test!(both:         GitOptions <- ["--git-since=one", "--git-since-only=two"]; Last => Ok(GitOptions { since: Some("two".into()), ..GitOptions::default() }));
+    test!(both_2:       GitOptions <- ["--git-since=one", "--git-since-only=two"]; Complain => Err(Misfire::Conflict(&flags::GIT_SINCE_ONLY, &flags::GIT_SINCE)));
+
+    // Submodules
+    test!(submodules:   GitOptions <- ["--git", "--git-recurse-submodules"];       Both => Ok(GitOptions { recurse_submodules: true, ..GitOptions::default() }));
+    test!(submodules_2: GitOptions <- ["--changed-only", "--git-recurse-submodules"];  Both => Ok(GitOptions { recurse_submodules: true, ..GitOptions::default() }));
+    test!(submodules_3: GitOptions <- ["--git-recurse-submodules"];                Last => Ok(GitOptions { recurse_submodules: true, ..GitOptions::default() }));
+    test!(submodules_4: GitOptions <- ["--git-recurse-submodules"];                Complain => Err(Misfire::Useless2(&flags::GIT_RECURSE_SUBMODULES, &flags::GIT, &flags::CHANGED_ONLY)));
+
+    // Last commits
+    test!(last_commits: GitOptions <- ["--git-last-commit"];                       Both => Ok(GitOptions { last_commits: true, ..GitOptions::default() }));
+}
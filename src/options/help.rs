@@ -2,6 +2,7 @@ use std::fmt;
 
 use options::flags;
 use options::parser::MatchedFlags;
+use fs::feature::acl;
 use fs::feature::xattr;
 
 
@@ -12,23 +13,67 @@ static OPTIONS: &str = r##"
 DISPLAY OPTIONS
   -1, --oneline      display one entry per line
   -l, --long         display extended file metadata as a table
+  --tsv              display the same metadata separated by tabs, for scripts
+  --json             print a --tree as nested JSON, for scripts
   -G, --grid         display entries as a grid (default)
   -x, --across       sort the grid across, rather than downwards
+  --spacing=N        put N spaces between the columns of the grid (1 to 16)
   -R, --recurse      recurse into directories
   -T, --tree         recurse into directories as a tree
   -F, --classify     display type indicator by file names
+  --slash-dirs       display a slash after directory names only
   --colo[u]r=WHEN    when to use terminal colours (always, auto, never)
   --colo[u]r-scale   highlight levels of file sizes distinctly
+  --ext-colo[u]rs=PATH  style files by extension, from a file of ext=style lines
+  --paginate         send output that doesn't fit on the screen through a pager
+  --highlight-recent  show names in bold if they changed in the last 24 hours
+  --colo[u]r-by-access  colour names by whether you can read and write them
+  --summary          count how many of each kind of file were listed
+  --summary-recurse  count the files inside directories for --summary too
+  --alphabet-dividers  divide names by first letter when sorting by name
+  --link-target=HOW  how to show symlink targets (raw, absolute, relative)
+  --icons[=WHICH]    show icons before file names (all, files, none)
 
 FILTERING AND SORTING OPTIONS
   -a, --all                  show hidden and 'dot' files
+  --almost-all               show dot files, but not '.' and '..' (same as -a)
+  --all-all                  show dot files, and '.' and '..' too (same as -aa)
+  --all-level LEVEL          how many times to apply --all (0, 1, 2)
   -d, --list-dirs            list directories like regular files
+  --recurse-order ORDER      list directories depth-first or breadth-first (dfs, bfs)
   -r, --reverse              reverse the sort order
   -s, --sort SORT_FIELD      which field to sort by
-  --group-directories-first  list directories before other files
+  --sort-exec CMD            sort by what CMD prints when run on each file
+  --sort-cmd CMD             reorder files by sending their names to CMD and
+                             reading back the order it prints them in
+  --locale-numbers           read numbers in names with the locale's thousands
+                             separator when sorting, so 1,000 sorts as 1000
+  --group-directories-first[=real]  list directories, and links to them unless
+                             =real is given, before other files
+  --dereference-command-line  follow symlinks given as arguments
+  --glob-args                expand arguments that are glob patterns
   -I, --ignore-glob GLOBS    glob patterns (pipe-separated) of files to ignore
+  --ignore-file FILE         ignore files that match the glob patterns in FILE
+  --ignore-glob-below DEPTH  only ignore files deeper than DEPTH when recursing
+  --grep REGEX               only show files whose names match REGEX
+  --ignore-file-like-git     ignore files mentioned in .gitignore and .ignore files
+  --no-ignore                don't ignore any files, whatever else says to
+  --links-only               only show symbolic links
+  --executable               only show files with an execute bit set
+  --filter-mode MODE         whether files pass all the filters (and) or any (or)
+  --top N                    only show the first N files in each directory, once sorted
+  --no-root                  hide files owned by root
+  --owner USER               only show files owned by USER, by name or ID
+  --owner-group GROUP        only show files owned by GROUP, by name or ID
+  --print-sort               print the sort field being used to stderr
+  --print-width              print the terminal width and grid layout to stderr
+  --timings                  print how long each stage of listing took to stderr
+  --config FILE              read sort and filter defaults from FILE, rather than
+                             $XDG_CONFIG_HOME/exa/config.toml
   Valid sort fields:         name, Name, extension, Extension, size, type,
-                             modified, accessed, created, inode, none
+                             modified, accessed, created, inode, none,
+                             mtime, atime, ctime, birth, crtime, entries,
+                             items, mixed, git, segments
 "##;
 
 static LONG_OPTIONS: &str = r##"
@@ -36,19 +81,50 @@ LONG VIEW OPTIONS
   -b, --binary       list file sizes with binary prefixes
   -B, --bytes        list file sizes in bytes, without any prefixes
   -g, --group        list each file's group
+  --collapse-owner   leave the group blank when it's named after the user
   -h, --header       add a header row to each column
-  -H, --links        list each file's number of hard links
+  -H, --links[=WHEN] list each file's number of hard links (always, multiple)
+  --blank-symlink-meta  leave the sizes and timestamps of symlinks blank
   -i, --inode        list each file's inode number
   -L, --level DEPTH  limit the depth of recursion
   -m, --modified     use the modified timestamp field
   -S, --blocks       show number of file system blocks
+  --show-sparse      show whether each file is sparse
+  --show-time-anomalies  mark files that were modified before they were created
+  --flags            list each file's BSD flags, such as uchg or hidden
+  --entries          count the entries inside each directory, recursively
+  --dir-entries      show how many entries each directory has in place of its size
+  --content-type     show whether each file looks like text or binary
+  --wc               list the number of lines in each text file
+  --symlink-groups   number symlinks by the file they lead to, to group them
+  --age-column       list how old each file is (now, hour, day, week, month, old)
+  --age-thresholds LIST  the five durations that end each age bucket
+  --context          list the directory each file is in, when recursing
+  --size-percent     list each file's share of the total size of those listed
   -t, --time FIELD   which timestamp field to list (modified, accessed, created)
   -u, --accessed     use the accessed timestamp field
   -U, --created      use the created timestamp field
-  --time-style       how to format timestamps (default, iso, long-iso, full-iso)"##;
-
-static GIT_HELP:      &str = r##"  --git              list each file's Git status, if tracked"##;
+  --time-style       how to format timestamps (default, iso, long-iso, full-iso)
+  --align COLUMNS    override alignments, such as size:left,links:right
+  --columns COLUMNS  which columns to show, in order, such as size,user,name
+  --compact          only show permissions and sizes
+  --compact-long     drop columns and shorten dates until the table fits,
+                     as on terminals under 60 columns wide"##;
+
+static GIT_HELP:      &str = r##"  --git              list each file's Git status, if tracked
+  --changed-only     only show files changed since the last Git commit
+  --git-since REF    list each file's Git status compared to REF
+  --git-since-only REF  only show files changed since REF
+  --git-last-commit  list the commit that last changed each file
+  --git-only-dirty   only show files with staged or unstaged changes
+  --tracked-only     only show files tracked by Git
+  --untracked-only   only show files not tracked by Git, and not ignored
+  --git-dim-ignored  dim the names of files ignored by Git
+  --git-recurse-submodules  show the statuses of the files inside submodules
+  --git-remote       list the URL of each repository's origin remote
+  --git-remote-name NAME  list the URL of the remote called NAME instead"##;
 static EXTENDED_HELP: &str = r##"  -@, --extended     list each file's extended attributes and sizes"##;
+static ACL_HELP:      &str = r##"  --acl              list each file's access control list entries"##;
 
 
 /// All the information needed to display the help text, which depends
@@ -65,6 +141,9 @@ pub struct HelpString {
 
     /// Whether the --extended option should be included in the help.
     xattrs: bool,
+
+    /// Whether the --acl option should be included in the help.
+    acl: bool,
 }
 
 impl HelpString {
@@ -81,7 +160,8 @@ impl HelpString {
             let only_long = matches.count(&flags::LONG) > 0;
             let git       = cfg!(feature="git");
             let xattrs    = xattr::ENABLED;
-            Err(HelpString { only_long, git, xattrs })
+            let acl       = acl::ENABLED;
+            Err(HelpString { only_long, git, xattrs, acl })
         }
         else {
             Ok(())  // no help needs to be shown
@@ -110,6 +190,10 @@ impl fmt::Display for HelpString {
             try!(write!(f, "\n{}", EXTENDED_HELP));
         }
 
+        if self.acl {
+            try!(write!(f, "\n{}", ACL_HELP));
+        }
+
         Ok(())
     }
 }
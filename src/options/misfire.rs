@@ -53,11 +53,50 @@ pub enum Misfire {
     /// A very specific edge case where --tree can’t be used with --all twice.
     TreeAllAll,
 
+    /// The list of columns didn’t end with the file names, which are always
+    /// shown last.
+    ColumnsWithoutName,
+
     /// A numeric option was given that failed to be parsed as a number.
     FailedParse(ParseIntError),
 
     /// A glob ignore was given that failed to be parsed as a pattern.
     FailedGlobPattern(String),
+
+    /// The file of extension colours couldn’t be read, or had a line in it
+    /// that couldn’t be parsed.
+    FailedExtColours(String),
+
+    /// A regular expression was given that failed to be compiled.
+    FailedRegex(String),
+
+    /// The thresholds for the age column couldn’t be parsed, or weren’t in
+    /// ascending order.
+    FailedAgeThresholds(String),
+
+    /// A glob key in the `EXA_COLORS` variable couldn’t be parsed.
+    FailedExaColors(String),
+
+    /// The user given to `--owner` doesn’t exist.
+    UnknownUser(String),
+
+    /// The group given to `--owner-group` doesn’t exist.
+    UnknownGroup(String),
+
+    /// A file of ignore patterns couldn’t be read.
+    FailedIgnoreFile(String),
+
+    /// The configuration file couldn’t be read, or had a line in it that
+    /// couldn’t be used.
+    FailedConfig(String),
+
+    /// An option was given a value that only works when exa has been built
+    /// with Git support, and it hasn’t been.
+    NeedsGit(&'static Arg, OsString),
+
+    /// `--locale-numbers` was given in strict mode, but the user’s locale
+    /// doesn’t separate thousands with anything.
+    NoThousandsSeparator,
 }
 
 impl Misfire {
@@ -71,6 +110,40 @@ impl Misfire {
         }
     }
 
+    /// A short code that identifies what kind of misfire this is, such as
+    /// `E001` for an option being given a value it doesn’t accept. Unlike
+    /// the messages, these never change between versions, so scripts can
+    /// match against them. The help and version “misfires” aren’t errors,
+    /// so their codes start with an `I` instead.
+    pub fn code(&self) -> &'static str {
+        use self::Misfire::*;
+
+        match *self {
+            BadArgument(..)       => "E001",
+            InvalidOptions(_)     => "E002",
+            Duplicate(..)         => "E003",
+            Conflict(..)          => "E004",
+            Useless(..)           => "E005",
+            Useless2(..)          => "E006",
+            ColumnsWithoutName    => "E007",
+            FailedParse(_)        => "E008",
+            FailedGlobPattern(_)  => "E009",
+            TreeAllAll            => "E010",
+            FailedExtColours(_)   => "E011",
+            FailedIgnoreFile(_)   => "E012",
+            FailedConfig(_)       => "E013",
+            NeedsGit(..)          => "E014",
+            FailedRegex(_)        => "E015",
+            FailedAgeThresholds(_) => "E016",
+            FailedExaColors(_)    => "E017",
+            UnknownUser(_)        => "E018",
+            UnknownGroup(_)       => "E019",
+            NoThousandsSeparator  => "E020",
+            Help(_)               => "I001",
+            Version(_)            => "I002",
+        }
+    }
+
     /// The Misfire that happens when an option gets given the wrong
     /// argument. This has to use one of the `getopts` failure
     /// variants--it’s meant to take just an option name, rather than an
@@ -101,8 +174,19 @@ impl fmt::Display for Misfire {
             Useless(ref a, true, ref b)      => write!(f, "Option {} is useless given option {}.", a, b),
             Useless2(ref a, ref b1, ref b2)  => write!(f, "Option {} is useless without options {} or {}.", a, b1, b2),
             TreeAllAll                       => write!(f, "Option --tree is useless given --all --all."),
+            ColumnsWithoutName               => write!(f, "Option --columns has to end with name."),
             FailedParse(ref e)               => write!(f, "Failed to parse number: {}", e),
             FailedGlobPattern(ref e)         => write!(f, "Failed to parse glob pattern: {}", e),
+            FailedExtColours(ref e)          => write!(f, "Failed to load extension colours: {}", e),
+            FailedRegex(ref e)               => write!(f, "Failed to parse regex: {}", e),
+            FailedAgeThresholds(ref e)       => write!(f, "Failed to parse age thresholds: {}", e),
+            FailedExaColors(ref e)           => write!(f, "Failed to parse EXA_COLORS: {}", e),
+            UnknownUser(ref name)            => write!(f, "Unknown user {:?}", name),
+            UnknownGroup(ref name)           => write!(f, "Unknown group {:?}", name),
+            FailedIgnoreFile(ref e)          => write!(f, "Failed to read ignore file: {}", e),
+            FailedConfig(ref e)              => write!(f, "Failed to load config file: {}", e),
+            NeedsGit(ref a, ref b)           => write!(f, "Option {}={} needs exa to be built with Git support.", a, b.to_string_lossy()),
+            NoThousandsSeparator             => write!(f, "Option --locale-numbers is useless: the locale has no thousands separator."),
         }
     }
 }
@@ -119,3 +203,30 @@ impl fmt::Display for ParseError {
         }
     }
 }
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use options::flags;
+
+    #[test]
+    fn bad_argument_code() {
+        assert_eq!("E001", Misfire::bad_argument(&flags::SORT, OsStr::new("colour"), &[ "name" ]).code());
+    }
+
+    #[test]
+    fn tree_all_all_code() {
+        assert_eq!("E010", Misfire::TreeAllAll.code());
+    }
+
+    #[test]
+    fn conflict_code() {
+        assert_eq!("E004", Misfire::Conflict(&flags::TREE, &flags::LIST_DIRS).code());
+    }
+
+    #[test]
+    fn duplicate_code() {
+        assert_eq!("E003", Misfire::Duplicate(Flag::Long("all"), Flag::Short(b'a')).code());
+    }
+}
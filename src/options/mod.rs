@@ -72,12 +72,16 @@
 use std::ffi::{OsStr, OsString};
 
 use fs::dir_action::DirAction;
+use fs::feature::GitOptions;
 use fs::filter::FileFilter;
 use output::{View, Mode};
 use output::details;
+use output::pager::Pager;
 
+mod config;
 mod dir_action;
 mod filter;
+mod git;
 mod view;
 
 mod help;
@@ -92,6 +96,7 @@ pub use self::misfire::Misfire;
 mod parser;
 mod flags;
 use self::parser::MatchedFlags;
+use self::config::FilterConfig;
 
 
 /// These **options** represent a parsed, error-checked versions of the
@@ -108,6 +113,35 @@ pub struct Options {
 
     /// The type of output to use (lines, grid, or details).
     pub view: View,
+
+    /// The pager to send the output through if it doesn’t fit on the
+    /// screen, if paging has been turned on.
+    pub pager: Option<Pager>,
+
+    /// Whether symbolic links given as command-line arguments should be
+    /// treated as the files they point to. Links found while listing a
+    /// directory are left alone either way.
+    pub dereference_args: bool,
+
+    /// Whether arguments that aren’t the paths of files, but look like glob
+    /// patterns, should be expanded into the files they match.
+    pub glob_args: bool,
+
+    /// What to find out about any Git repositories, on top of the statuses
+    /// of the files inside them.
+    pub git: GitOptions,
+
+    /// Whether to print how the files are getting sorted to stderr before
+    /// listing them, to check what a set of options adds up to.
+    pub print_sort: bool,
+
+    /// Whether to print how long each stage of listing the files took to
+    /// stderr once they’ve all been listed.
+    pub timings: bool,
+
+    /// Whether to treat problems that only turn up while listing, such as
+    /// asking for dirty files outside of a Git repository, as errors.
+    pub strict: bool,
 }
 
 impl Options {
@@ -140,24 +174,55 @@ impl Options {
     }
 
     /// Whether the View specified in this set of options includes a Git
-    /// status column. It’s only worth trying to discover a repository if the
-    /// results will end up being displayed.
+    /// status column, or the filter or the file names need to know files’
+    /// Git statuses. It’s
+    /// only worth trying to discover a repository if the results will end up
+    /// being used.
     pub fn should_scan_for_git(&self) -> bool {
+        if self.filter.needs_git() || self.view.style.dim_ignored {
+            return true;
+        }
+
         match self.view.mode {
             Mode::Details(details::Options { table: Some(ref table), .. }) |
             Mode::GridDetails(_, details::Options { table: Some(ref table), .. }) => table.extra_columns.should_scan_for_git(),
+            Mode::Tsv(ref tsv) => tsv.table.extra_columns.should_scan_for_git(),
             _ => false,
         }
     }
 
+    /// The options to scan a directory’s Git repository with, if it should
+    /// be scanned for at all.
+    pub fn git_scan(&self) -> Option<&GitOptions> {
+        if self.should_scan_for_git() { Some(&self.git) }
+                                 else { None }
+    }
+
+    /// The options to scan the directories found while recursing with, if
+    /// they should be scanned. Only the filter and the dimming of ignored
+    /// files need these directories’ Git statuses, as the columns are only
+    /// shown for the top-level ones.
+    pub fn child_git_scan(&self) -> Option<&GitOptions> {
+        if self.filter.needs_git() || self.view.style.dim_ignored { Some(&self.git) }
+                                                             else { None }
+    }
+
     /// Determines the complete set of options based on the given command-line
     /// arguments, after they’ve been parsed.
     fn deduce<V: Vars>(matches: &MatchedFlags, vars: V) -> Result<Options, Misfire> {
         let dir_action = DirAction::deduce(matches)?;
-        let filter = FileFilter::deduce(matches)?;
+        let config = FilterConfig::deduce(matches, &vars)?;
+        let filter = FileFilter::deduce(matches, &config)?;
+        let pager = Pager::deduce(matches, &vars)?;
         let view = View::deduce(matches, vars)?;
-
-        Ok(Options { dir_action, view, filter })
+        let dereference_args = matches.has(&flags::DEREF_ARGS)?;
+        let glob_args = matches.has(&flags::GLOB_ARGS)?;
+        let git = GitOptions::deduce(matches)?;
+        let print_sort = matches.has(&flags::PRINT_SORT)?;
+        let timings = matches.has(&flags::TIMINGS)?;
+        let strict = matches.is_strict();
+
+        Ok(Options { dir_action, view, filter, pager, dereference_args, glob_args, git, print_sort, timings, strict })
     }
 }
 
@@ -167,6 +232,12 @@ pub trait Vars {
     fn get(&self, name: &'static str) -> Option<OsString>;
 }
 
+impl<'a, V: Vars> Vars for &'a V {
+    fn get(&self, name: &'static str) -> Option<OsString> {
+        (*self).get(name)
+    }
+}
+
 
 
 
@@ -255,4 +326,18 @@ pub mod test {
         let opts = Options::parse(&args, None);
         assert_eq!(opts.unwrap_err(), Misfire::Useless(&flags::ACROSS, true, &flags::ONE_LINE))
     }
+
+    #[test]
+    fn dereference_args() {
+        let args = [ os("--dereference-command-line") ];
+        let opts = Options::parse(&args, None);
+        assert_eq!(opts.unwrap().0.dereference_args, true)
+    }
+
+    #[test]
+    fn no_dereference_args() {
+        let args = [ os("--long") ];
+        let opts = Options::parse(&args, None);
+        assert_eq!(opts.unwrap().0.dereference_args, false)
+    }
 }
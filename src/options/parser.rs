@@ -92,6 +92,10 @@ pub enum TakesValue {
 
     /// This flag will throw an error if there’s a value after it.
     Forbidden,
+
+    /// This flag can be given a value, but only after an equals sign, so
+    /// the argument after it never gets taken as its value.
+    Optional,
 }
 
 
@@ -171,8 +175,8 @@ impl Args {
                     let arg = self.lookup_long(before)?;
                     let flag = Flag::Long(arg.long);
                     match arg.takes_value {
-                        Necessary  => result_flags.push((flag, Some(after))),
-                        Forbidden  => return Err(ParseError::ForbiddenValue { flag })
+                        Necessary | Optional  => result_flags.push((flag, Some(after))),
+                        Forbidden             => return Err(ParseError::ForbiddenValue { flag })
                     }
                 }
 
@@ -182,8 +186,8 @@ impl Args {
                     let arg = self.lookup_long(long_arg_name)?;
                     let flag = Flag::Long(arg.long);
                     match arg.takes_value {
-                        Forbidden  => result_flags.push((flag, None)),
-                        Necessary  => {
+                        Forbidden | Optional  => result_flags.push((flag, None)),
+                        Necessary             => {
                             if let Some(next_arg) = inputs.next() {
                                 result_flags.push((flag, Some(next_arg)));
                             }
@@ -220,8 +224,8 @@ impl Args {
                         let arg = self.lookup_short(*byte)?;
                         let flag = Flag::Short(*byte);
                         match arg.takes_value {
-                            Forbidden  => result_flags.push((flag, None)),
-                            Necessary  => return Err(ParseError::NeedsValue { flag })
+                            Forbidden | Optional  => result_flags.push((flag, None)),
+                            Necessary             => return Err(ParseError::NeedsValue { flag })
                         }
                     }
 
@@ -229,8 +233,8 @@ impl Args {
                     let arg = self.lookup_short(*arg_with_value)?;
                     let flag = Flag::Short(arg.short.unwrap());
                     match arg.takes_value {
-                        Necessary  => result_flags.push((flag, Some(after))),
-                        Forbidden  => return Err(ParseError::ForbiddenValue { flag })
+                        Necessary | Optional  => result_flags.push((flag, Some(after))),
+                        Forbidden             => return Err(ParseError::ForbiddenValue { flag })
                     }
                 }
 
@@ -251,8 +255,8 @@ impl Args {
                         let arg = self.lookup_short(*byte)?;
                         let flag = Flag::Short(*byte);
                         match arg.takes_value {
-                            Forbidden  => result_flags.push((flag, None)),
-                            Necessary  => {
+                            Forbidden | Optional  => result_flags.push((flag, None)),
+                            Necessary             => {
                                 if index < bytes.len() - 1 {
                                     let remnants = &bytes[index+1 ..];
                                     result_flags.push((flag, Some(OsStr::from_bytes(remnants))));
@@ -388,6 +392,16 @@ impl<'a> MatchedFlags<'a> {
         }
     }
 
+    /// Returns every value given to the argument, in the order they were
+    /// given, for the arguments that can be given more than once. Unlike
+    /// `get`, this never complains about duplicates, even in strict mode.
+    pub fn get_all(&self, arg: &'static Arg) -> Vec<&OsStr> {
+        self.flags.iter()
+            .filter(|tuple| tuple.1.is_some() && tuple.0.matches(arg))
+            .map(|tuple| tuple.1.unwrap())
+            .collect()
+    }
+
     // It’s annoying that ‘has’ and ‘get’ won’t work when accidentally given
     // flags that do/don’t take values, but this should be caught by tests.
 
@@ -546,7 +560,8 @@ mod parse_test {
     static TEST_ARGS: &[&Arg] = &[
         &Arg { short: Some(b'l'), long: "long",     takes_value: TakesValue::Forbidden },
         &Arg { short: Some(b'v'), long: "verbose",  takes_value: TakesValue::Forbidden },
-        &Arg { short: Some(b'c'), long: "count",    takes_value: TakesValue::Necessary }
+        &Arg { short: Some(b'c'), long: "count",    takes_value: TakesValue::Necessary },
+        &Arg { short: Some(b'w'), long: "wrap",     takes_value: TakesValue::Optional }
     ];
 
 
@@ -573,6 +588,11 @@ mod parse_test {
     test!(arg_equals:  ["--count=4"]      => frees: [],  flags: [ (Flag::Long("count"), Some(OsStr::new("4"))) ]);
     test!(arg_then:    ["--count", "4"]   => frees: [],  flags: [ (Flag::Long("count"), Some(OsStr::new("4"))) ]);
 
+    // Long args with optional values
+    test!(opt_none:    ["--wrap"]         => frees: [],       flags: [ (Flag::Long("wrap"), None) ]);
+    test!(opt_equals:  ["--wrap=4"]       => frees: [],       flags: [ (Flag::Long("wrap"), Some(OsStr::new("4"))) ]);
+    test!(opt_then:    ["--wrap", "4"]    => frees: [ "4" ],  flags: [ (Flag::Long("wrap"), None) ]);
+
 
     // Short args
     test!(short:       ["-l"]            => frees: [],       flags: [ (Flag::Short(b'l'), None) ]);
@@ -589,6 +609,11 @@ mod parse_test {
     test!(short_two_equals:   ["-lc=two"]     => frees: [],  flags: [(Flag::Short(b'l'), None), (Flag::Short(b'c'), Some(OsStr::new("two"))) ]);
     test!(short_two_next:     ["-lc", "two"]  => frees: [],  flags: [(Flag::Short(b'l'), None), (Flag::Short(b'c'), Some(OsStr::new("two"))) ]);
 
+    // Short args with optional values
+    test!(short_opt_none:     ["-wl"]         => frees: [],       flags: [(Flag::Short(b'w'), None), (Flag::Short(b'l'), None) ]);
+    test!(short_opt_eq:       ["-lw=4"]       => frees: [],       flags: [(Flag::Short(b'l'), None), (Flag::Short(b'w'), Some(OsStr::new("4"))) ]);
+    test!(short_opt_then:     ["-w", "4"]     => frees: [ "4" ],  flags: [(Flag::Short(b'w'), None) ]);
+
 
     // Unknown args
     test!(unknown_long:          ["--quiet"]      => error UnknownArgument      { attempt: os("quiet") });
@@ -656,6 +681,20 @@ mod matches_test {
         assert_eq!(flags.get(&COUNT), Ok(Some(&*nothing)));
     }
 
+    #[test]
+    fn every_count() {
+        let everything = os("everything");
+        let nothing    = os("nothing");
+
+        let flags = MatchedFlags {
+            flags: vec![ (Flag::Short(b'c'), Some(&*everything)),
+                         (Flag::Long("count"), Some(&*nothing)) ],
+            strictness: Strictness::ComplainAboutRedundantArguments,
+        };
+
+        assert_eq!(flags.get_all(&COUNT), vec![ &*everything, &*nothing ]);
+    }
+
     #[test]
     fn no_count() {
         let flags = MatchedFlags { flags: Vec::new(), strictness: Strictness::UseLastArguments };
@@ -1,23 +1,152 @@
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use glob;
+
 use output::Colours;
-use output::{View, Mode, grid, details};
-use output::table::{TimeTypes, Environment, SizeFormat, Columns, Options as TableOptions};
-use output::file_name::{Classify, FileStyle};
+use output::{View, Mode, grid, details, tsv};
+use output::table::{TimeType, TimeTypes, Environment, SizeFormat, Columns, Options as TableOptions};
+use output::table::{Alignment, Alignments, Column, COLUMN_NAMES, ORDER_NAMES, DEFAULT_REMOTE};
+use output::table::{AgeBuckets, Fit, DEFAULT_AGE_THRESHOLDS};
+use output::file_name::{Classify, FileStyle, ExtensionColours, Icons, RecentFiles};
+use output::lsc::LSColors;
+use output::pager::{Pager, DEFAULT_PAGER};
+use output::summary::SummaryScope;
 use output::time::TimeFormat;
 
 use options::{flags, Misfire, Vars};
 use options::parser::MatchedFlags;
 
+use fs::feature::acl;
 use fs::feature::xattr;
+use fs::{DotFilter, Identity, TargetPath};
 use info::filetype::FileExtensions;
 
 impl View {
 
     /// Determine which view to use and all of that view’s arguments.
+    ///
+    /// Styles from the `LS_COLORS` variable go underneath exa’s own, and
+    /// `EXA_COLORS` goes on top of both, but they only get used when there
+    /// are colours to begin with.
     pub fn deduce<V: Vars>(matches: &MatchedFlags, vars: V) -> Result<View, Misfire> {
-        let mode = Mode::deduce(matches, vars)?;
-        let colours = Colours::deduce(matches)?;
-        let style = FileStyle::deduce(matches)?;
-        Ok(View { mode, colours, style })
+        let mode = Mode::deduce(matches, &vars)?;
+        let mut colours = Colours::deduce(matches)?;
+        let mut style = FileStyle::deduce(matches)?;
+
+        if colours != Colours::plain() {
+            if let Some(lsc) = vars.get("LS_COLORS") {
+                apply_ls_colors(&lsc.to_string_lossy(), &mut colours, &mut style.ext_colours);
+            }
+
+            if let Some(exa) = vars.get("EXA_COLORS") {
+                apply_exa_colors(&exa.to_string_lossy(), &mut colours, &mut style.ext_colours)?;
+            }
+        }
+
+        let summary = SummaryScope::deduce(matches)?;
+        let alphabet_dividers = matches.has(&flags::ALPHABET_DIVIDERS)?;
+
+        // The long view uses --header for its column headers instead.
+        let header_totals = match mode {
+            Mode::Grid(_) | Mode::Lines  => matches.has(&flags::HEADER)?,
+            _                            => false,
+        };
+
+        Ok(View { mode, colours, style, summary, alphabet_dividers, header_totals })
+    }
+}
+
+
+impl SummaryScope {
+
+    /// Determines whether to show a summary line, and whether it should
+    /// count the files inside directories too. Asking for that without
+    /// asking for the summary does nothing, which is an error in strict
+    /// mode.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<SummaryScope>, Misfire> {
+        let summary = matches.has(&flags::SUMMARY)?;
+        let recurse = matches.has(&flags::SUMMARY_RECURSE)?;
+
+        if !summary {
+            if recurse && matches.is_strict() {
+                Err(Misfire::Useless(&flags::SUMMARY_RECURSE, false, &flags::SUMMARY))
+            }
+            else {
+                Ok(None)
+            }
+        }
+        else if recurse {
+            Ok(Some(SummaryScope::Recursive))
+        }
+        else {
+            Ok(Some(SummaryScope::Listed))
+        }
+    }
+}
+
+
+/// Applies the pairs in an `LS_COLORS` string: the ones for types of file
+/// replace exa’s default styles, and the ones for extensions get added to
+/// the extension colours, without replacing any given on the command-line.
+/// Keys that exa doesn’t know about are ignored.
+fn apply_ls_colors(input: &str, colours: &mut Colours, ext_colours: &mut ExtensionColours) {
+    LSColors(input).each_pair(|pair| {
+        if !colours.set_ls(&pair) {
+            ext_colours.add_ls(&pair);
+        }
+    });
+}
+
+/// Applies the pairs in an `EXA_COLORS` string, which uses the same keys
+/// as `LS_COLORS`. Any key that isn’t a two-letter code is a glob matched
+/// against whole file names, such as `*.min.js` or `Makefile`, which gets
+/// checked before the extension colours. Unlike with `LS_COLORS`, which
+/// other programs read too, a glob that can’t be parsed is an error.
+fn apply_exa_colors(input: &str, colours: &mut Colours, ext_colours: &mut ExtensionColours) -> Result<(), Misfire> {
+    let mut result = Ok(());
+
+    LSColors(input).each_pair(|pair| {
+        if result.is_err() || colours.set_ls(&pair) || is_type_code(pair.key) {
+            return;
+        }
+
+        match glob::Pattern::new(pair.key) {
+            Ok(pattern) => if let Some(style) = pair.to_style() {
+                ext_colours.add_glob(pattern, style);
+            },
+            Err(e) => result = Err(Misfire::FailedExaColors(format!("key {:?}: {}", pair.key, e))),
+        }
+    });
+
+    result
+}
+
+/// Whether this key looks like one of the two-letter codes for a type of
+/// file, such as `di`, rather than a file name.
+fn is_type_code(key: &str) -> bool {
+    key.len() == 2 && key.bytes().all(|b| b >= b'a' && b <= b'z')
+}
+
+
+impl Pager {
+
+    /// Determine which pager to use, if paging has been turned on. The
+    /// `EXA_PAGER` variable takes priority over the more general `PAGER`,
+    /// and if neither are set (or they’re set to nothing), exa uses `less`.
+    pub fn deduce<V: Vars>(matches: &MatchedFlags, vars: V) -> Result<Option<Pager>, Misfire> {
+        if !matches.has(&flags::PAGINATE)? {
+            return Ok(None);
+        }
+
+        let command = vars.get("EXA_PAGER").into_iter()
+                          .chain(vars.get("PAGER"))
+                          .find(|c| !c.is_empty())
+                          .unwrap_or_else(|| DEFAULT_PAGER.into());
+
+        Ok(Some(Pager { command }))
     }
 }
 
@@ -35,17 +164,23 @@ impl Mode {
             else if matches.has(&flags::ONE_LINE)? {
                 Err(Useless(&flags::ONE_LINE, true, &flags::LONG))
             }
+            else if matches.is_strict() && matches.get(&flags::SPACING)?.is_some() && !matches.has(&flags::GRID)? {
+                Err(Useless(&flags::SPACING, true, &flags::LONG))
+            }
             else {
+                let term_width = TerminalWidth::deduce(&vars)?;
+
                 Ok(details::Options {
-                    table: Some(TableOptions::deduce(matches)?),
+                    table: Some(TableOptions::deduce(matches, matches.has(&flags::COMPACT)?, &term_width)?),
                     header: matches.has(&flags::HEADER)?,
                     xattr: xattr::ENABLED && matches.has(&flags::EXTENDED)?,
+                    acl: acl::ENABLED && matches.has(&flags::ACL)?,
                 })
             }
         };
 
         let other_options_scan = || {
-            if let Some(width) = TerminalWidth::deduce(vars)?.width() {
+            if let Some(width) = TerminalWidth::deduce(&vars)?.width() {
                 if matches.has(&flags::ONE_LINE)? {
                     if matches.has(&flags::ACROSS)? {
                         Err(Useless(&flags::ACROSS, true, &flags::ONE_LINE))
@@ -59,6 +194,7 @@ impl Mode {
                         table: None,
                         header: false,
                         xattr: xattr::ENABLED && matches.has(&flags::EXTENDED)?,
+                        acl: acl::ENABLED && matches.has(&flags::ACL)?,
                     };
 
                     Ok(Mode::Details(details))
@@ -67,6 +203,8 @@ impl Mode {
                     let grid = grid::Options {
                         across: matches.has(&flags::ACROSS)?,
                         console_width: width,
+                        spacing: deduce_spacing(matches)?,
+                        print_width: matches.has(&flags::PRINT_WIDTH)?,
                     };
 
                     Ok(Mode::Grid(grid))
@@ -82,6 +220,7 @@ impl Mode {
                         table: None,
                         header: false,
                         xattr: xattr::ENABLED && matches.has(&flags::EXTENDED)?,
+                        acl: acl::ENABLED && matches.has(&flags::ACL)?,
                     };
 
                     Ok(Mode::Details(details))
@@ -92,6 +231,44 @@ impl Mode {
             }
         };
 
+        // The JSON view only prints trees, so without --tree it has nothing
+        // to nest, and gets left out.
+        if matches.has(&flags::JSON)? {
+            if matches.has(&flags::TREE)? {
+                if matches.is_strict() {
+                    for option in &[ &flags::LONG, &flags::TSV, &flags::GRID, &flags::ACROSS, &flags::ONE_LINE ] {
+                        if matches.has(option)? {
+                            return Err(Conflict(&flags::JSON, *option));
+                        }
+                    }
+                }
+
+                return Ok(Mode::Json);
+            }
+            else if matches.is_strict() {
+                return Err(Useless(&flags::JSON, false, &flags::TREE));
+            }
+        }
+
+        // The TSV view shows the same columns as the long view, so it
+        // doesn’t need --long to be passed as well.
+        if matches.has(&flags::TSV)? {
+            if matches.is_strict() {
+                for option in &[ &flags::GRID, &flags::ACROSS, &flags::ONE_LINE, &flags::TREE ] {
+                    if matches.has(option)? {
+                        return Err(Conflict(&flags::TSV, *option));
+                    }
+                }
+            }
+
+            let tsv = tsv::Options {
+                table: TableOptions::deduce(matches, matches.has(&flags::COMPACT)?, &TerminalWidth::Unset)?,
+                header: matches.has(&flags::HEADER)?,
+            };
+
+            return Ok(Mode::Tsv(tsv));
+        }
+
         if matches.has(&flags::LONG)? {
             let details = long()?;
             if matches.has(&flags::GRID)? {
@@ -108,16 +285,40 @@ impl Mode {
         // If --long hasn’t been passed, then check if we need to warn the
         // user about flags that won’t have any effect.
         if matches.is_strict() {
-            for option in &[ &flags::BINARY, &flags::BYTES, &flags::INODE, &flags::LINKS,
-                             &flags::HEADER, &flags::BLOCKS, &flags::TIME, &flags::GROUP ] {
-                if matches.has(option)? {
+            for option in &[ &flags::BINARY, &flags::BYTES, &flags::INODE, &flags::LINKS, &flags::BLANK_SYMLINK_META, &flags::COLLAPSE_OWNER,
+                             &flags::BLOCKS, &flags::SPARSE, &flags::TIME_ANOMALIES, &flags::FILE_FLAGS, &flags::ENTRIES, &flags::DIR_ENTRIES, &flags::CONTENT_TYPE, &flags::LINE_COUNT, &flags::SYMLINK_GROUPS, &flags::AGE_COLUMN, &flags::CONTEXT, &flags::SIZE_PERCENT, &flags::GROUP,
+                             &flags::COMPACT, &flags::COMPACT_LONG ] {
+                if matches.has(option)? || matches.get(option)?.is_some() {
+                    return Err(Useless(*option, false, &flags::LONG));
+                }
+            }
+
+            // The time field also picks which timestamp gets checked when
+            // highlighting recent files, which works in every view.
+            for option in &[ &flags::ALIGN, &flags::COLUMNS, &flags::GIT_REMOTE_NAME, &flags::AGE_THRESHOLDS ] {
+                if matches.get(option)?.is_some() {
                     return Err(Useless(*option, false, &flags::LONG));
                 }
             }
 
+            if matches.get(&flags::TIME)?.is_some() && !matches.has(&flags::HIGHLIGHT_RECENT)? {
+                return Err(Useless(&flags::TIME, false, &flags::LONG));
+            }
+
             if cfg!(feature="git") && matches.has(&flags::GIT)? {
                 return Err(Useless(&flags::GIT, false, &flags::LONG));
             }
+            else if cfg!(feature="git") && matches.has(&flags::GIT_REMOTE)? {
+                return Err(Useless(&flags::GIT_REMOTE, false, &flags::LONG));
+            }
+            else if cfg!(feature="git") && matches.has(&flags::GIT_LAST_COMMIT)? {
+                return Err(Useless(&flags::GIT_LAST_COMMIT, false, &flags::LONG));
+            }
+            else if cfg!(feature="git") && matches.get(&flags::GIT_SINCE)?.is_some() && matches.get(&flags::GIT_SINCE_ONLY)?.is_none() {
+                // --git-since-only still filters the files in other views,
+                // so the column not being shown is fine.
+                return Err(Useless(&flags::GIT_SINCE, false, &flags::LONG));
+            }
             else if matches.has(&flags::LEVEL)? && !matches.has(&flags::RECURSE)? && !matches.has(&flags::TREE)? {
                 // TODO: I'm not sure if the code even gets this far.
                 // There is an identical check in dir_action
@@ -130,6 +331,38 @@ impl Mode {
 }
 
 
+/// The number of spaces between the columns of a grid, unless the
+/// `--spacing` argument picks a different number.
+const DEFAULT_SPACING: usize = 2;
+
+/// The most spaces that can go between the columns of a grid.
+const MAX_SPACING: usize = 16;
+
+/// The choices listed when the `--spacing` argument is out of range.
+const SPACINGS: &[&str] = &[ "1 to 16" ];
+
+/// Determines the number of spaces between the columns of a grid, which
+/// has to be at least one, so the names don’t run together, and at most
+/// sixteen.
+fn deduce_spacing(matches: &MatchedFlags) -> Result<usize, Misfire> {
+    let word = match matches.get(&flags::SPACING)? {
+        Some(w)  => w,
+        None     => return Ok(DEFAULT_SPACING),
+    };
+
+    match word.to_string_lossy().parse() {
+        Ok(n) if n >= 1 && n <= MAX_SPACING  => Ok(n),
+        Ok(_)                                => Err(Misfire::bad_argument(&flags::SPACING, word, SPACINGS)),
+        Err(e)                               => Err(Misfire::FailedParse(e)),
+    }
+}
+
+
+/// Terminals narrower than this get the long view made to fit into them, as
+/// with `--compact-long`, as the full one would wrap onto more than one line.
+const COMPACT_WIDTH: usize = 60;
+
+
 /// The width of the terminal requested by the user.
 #[derive(PartialEq, Debug)]
 enum TerminalWidth {
@@ -164,6 +397,15 @@ impl TerminalWidth {
         }
     }
 
+    /// Whether the terminal is too narrow for the full long view, so it
+    /// should be made to fit.
+    fn is_narrow(&self) -> bool {
+        match self.width() {
+            Some(width)  => width < COMPACT_WIDTH,
+            None         => false,
+        }
+    }
+
     fn width(&self) -> Option<usize> {
         match *self {
             TerminalWidth::Set(width)       |
@@ -175,27 +417,182 @@ impl TerminalWidth {
 
 
 impl TableOptions {
-    fn deduce(matches: &MatchedFlags) -> Result<Self, Misfire> {
+
+    /// Determine the table options. A compact table only has the
+    /// permissions and size columns, unless the columns have been picked
+    /// with `--columns`, and shortens any dates it shows unless a time
+    /// style has been asked for. Sizes are left however they were asked
+    /// for, which is already abbreviated unless `--bytes` was given.
+    ///
+    /// The table gets made to fit into the terminal’s width when it’s been
+    /// asked to, or when the terminal is too narrow for it, as long as
+    /// there’s a terminal width to fit into.
+    fn deduce(matches: &MatchedFlags, compact: bool, term_width: &TerminalWidth) -> Result<Self, Misfire> {
+        use output::time::ISOFormat;
+
         let env = Environment::load_all();
-        let time_format = TimeFormat::deduce(matches)?;
+        let mut time_format = TimeFormat::deduce(matches)?;
         let size_format = SizeFormat::deduce(matches)?;
-        let extra_columns = Columns::deduce(matches)?;
-        Ok(TableOptions { env, time_format, size_format, extra_columns })
+        let mut extra_columns = Columns::deduce(matches)?;
+        let alignments = Alignments::deduce(matches)?;
+
+        if compact {
+            extra_columns.compact = true;
+
+            if matches.get(&flags::TIME_STYLE)?.is_none() {
+                time_format = TimeFormat::ISOFormat(ISOFormat::new());
+            }
+        }
+
+        let fit = match term_width.width() {
+            Some(width) if matches.has(&flags::COMPACT_LONG)? || term_width.is_narrow()  => Some(Fit {
+                width,
+                shorten_time: matches.get(&flags::TIME_STYLE)?.is_none(),
+                picked:       extra_columns.order.is_some(),
+            }),
+            _ => None,
+        };
+
+        Ok(TableOptions { env, time_format, size_format, extra_columns, alignments, fit })
     }
 }
 
 
+/// The values `--links` can be given: `always` is the same as giving it on
+/// its own, and `multiple` only fills in counts above one.
+const LINKSES: &[&str] = &[ "always", "multiple" ];
+
 impl Columns {
     fn deduce(matches: &MatchedFlags) -> Result<Self, Misfire> {
         let time_types = TimeTypes::deduce(matches)?;
         let git = cfg!(feature="git") && matches.has(&flags::GIT)?;
+        let git_since = cfg!(feature="git") && (matches.get(&flags::GIT_SINCE)?.is_some() || matches.get(&flags::GIT_SINCE_ONLY)?.is_some());
+        let git_last_commit = cfg!(feature="git") && matches.has(&flags::GIT_LAST_COMMIT)?;
 
         let blocks = matches.has(&flags::BLOCKS)?;
+        let sparse = matches.has(&flags::SPARSE)?;
+        let time_anomalies = matches.has(&flags::TIME_ANOMALIES)?;
+        let file_flags = matches.has(&flags::FILE_FLAGS)?;
+        let entries = matches.has(&flags::ENTRIES)?;
+        let content = matches.has(&flags::CONTENT_TYPE)?;
+        let lines = matches.has(&flags::LINE_COUNT)?;
+        let symlink_groups = matches.has(&flags::SYMLINK_GROUPS)?;
+        let age = matches.has(&flags::AGE_COLUMN)?;
+        let age_buckets = AgeBuckets::deduce(matches)?;
+        let context = matches.has(&flags::CONTEXT)?;
+        let size_percent = matches.has(&flags::SIZE_PERCENT)?;
         let group  = matches.has(&flags::GROUP)?;
         let inode  = matches.has(&flags::INODE)?;
-        let links  = matches.has(&flags::LINKS)?;
+        let (links, multiple_links) = match matches.get(&flags::LINKS)? {
+            Some(word) if word == "always"    => (true, false),
+            Some(word) if word == "multiple"  => (false, true),
+            Some(word)                        => return Err(Misfire::bad_argument(&flags::LINKS, word, LINKSES)),
+            None                              => (matches.has(&flags::LINKS)?, false),
+        };
+        let blank_symlink_meta = matches.has(&flags::BLANK_SYMLINK_META)?;
+        let collapse_owner = matches.has(&flags::COLLAPSE_OWNER)?;
+
+        // Directory entries get counted the same way the listing shows them,
+        // so the counts only include dotfiles when they’d be listed.
+        let dir_entries = if matches.has(&flags::DIR_ENTRIES)? { Some(DotFilter::deduce(matches)?) }
+                                                             else { None };
+
+        let git_remote = cfg!(feature="git") && matches.has(&flags::GIT_REMOTE)?;
+        let remote_name = match matches.get(&flags::GIT_REMOTE_NAME)? {
+            Some(name)  => name.to_string_lossy().into_owned(),
+            None        => DEFAULT_REMOTE.into(),
+        };
+
+        let order = Columns::deduce_order(matches)?;
+
+        // The name only matters if the remote column is going to be shown,
+        // whether by its own flag or by being picked with --columns.
+        let remote_picked = order.as_ref().map(|o| o.contains(&Column::GitRemote)).unwrap_or(false);
+        if matches.is_strict() && matches.get(&flags::GIT_REMOTE_NAME)?.is_some() && !git_remote && !remote_picked {
+            return Err(Misfire::Useless(&flags::GIT_REMOTE_NAME, false, &flags::GIT_REMOTE));
+        }
+
+        // The same goes for the age thresholds and the age column.
+        let age_picked = order.as_ref().map(|o| o.contains(&Column::Age)).unwrap_or(false);
+        if matches.is_strict() && matches.get(&flags::AGE_THRESHOLDS)?.is_some() && !age && !age_picked {
+            return Err(Misfire::Useless(&flags::AGE_THRESHOLDS, false, &flags::AGE_COLUMN));
+        }
+
+        // And for collapsing the group when there’s no group column.
+        let group_picked = order.as_ref().map(|o| o.contains(&Column::Group)).unwrap_or(false);
+        if matches.is_strict() && collapse_owner && !group && !group_picked {
+            return Err(Misfire::Useless(&flags::COLLAPSE_OWNER, false, &flags::GROUP));
+        }
 
-        Ok(Columns { time_types, git, blocks, group, inode, links })
+        Ok(Columns { time_types, git, git_since, git_last_commit, blocks, sparse, time_anomalies, flags: file_flags, entries, content, lines, symlink_groups, age, group, context, size_percent, inode, links, multiple_links, blank_symlink_meta, collapse_owner, dir_entries, git_remote, remote_name, age_buckets, compact: false, order })
+    }
+
+    /// Determine which columns to show, and in which order, from a
+    /// comma-separated list of the names in `ORDER_NAMES`. This overrides
+    /// the flags for the individual columns. The list has to end with
+    /// `name`, as the file names always get shown last; a column named more
+    /// than once only gets shown the first time.
+    fn deduce_order(matches: &MatchedFlags) -> Result<Option<Vec<Column>>, Misfire> {
+        let input = match matches.get(&flags::COLUMNS)? {
+            Some(i) => i.to_string_lossy(),
+            None    => return Ok(None),
+        };
+
+        let mut names: Vec<&str> = input.split(',').filter(|n| !n.is_empty()).collect();
+        if let Some(unknown) = names.iter().find(|n| !ORDER_NAMES.contains(n)) {
+            return Err(Misfire::bad_argument(&flags::COLUMNS, OsStr::new(unknown), ORDER_NAMES));
+        }
+
+        if names.pop() != Some("name") || names.contains(&"name") {
+            return Err(Misfire::ColumnsWithoutName);
+        }
+
+        let mut order = Vec::new();
+        for column in names.into_iter().filter_map(Column::from_name) {
+            if !order.contains(&column) {
+                order.push(column);
+            }
+        }
+
+        Ok(Some(order))
+    }
+}
+
+
+const ALIGNMENTS: &[&str] = &[ "left", "right" ];
+
+impl Alignments {
+
+    /// Determine which columns should have their alignments overridden,
+    /// from a comma-separated list of `column:alignment` pairs. Each column
+    /// has to be referred to by one of the names in `COLUMN_NAMES`.
+    fn deduce(matches: &MatchedFlags) -> Result<Alignments, Misfire> {
+        let input = match matches.get(&flags::ALIGN)? {
+            Some(i) => i.to_string_lossy(),
+            None    => return Ok(Alignments::default()),
+        };
+
+        let mut alignments = Vec::new();
+        for pair in input.split(',').filter(|p| !p.is_empty()) {
+            let mut halves = pair.splitn(2, ':');
+            let name = halves.next().unwrap_or("");
+            let alignment = halves.next().unwrap_or("");
+
+            let name = match COLUMN_NAMES.iter().find(|n| **n == name) {
+                Some(n) => *n,
+                None    => return Err(Misfire::bad_argument(&flags::ALIGN, OsStr::new(name), COLUMN_NAMES)),
+            };
+
+            let alignment = match alignment {
+                "left"   => Alignment::Left,
+                "right"  => Alignment::Right,
+                other    => return Err(Misfire::bad_argument(&flags::ALIGN, OsStr::new(other), ALIGNMENTS)),
+            };
+
+            alignments.push((name, alignment));
+        }
+
+        Ok(Alignments(alignments))
     }
 }
 
@@ -381,17 +778,215 @@ impl Colours {
 impl FileStyle {
     fn deduce(matches: &MatchedFlags) -> Result<FileStyle, Misfire> {
         let classify = Classify::deduce(matches)?;
+        let icons = Icons::deduce(matches)?;
         let exts = FileExtensions;
-        Ok(FileStyle { classify, exts })
+        let ext_colours = ExtensionColours::deduce(matches)?;
+        let recent = RecentFiles::deduce(matches)?;
+        let dim_ignored = cfg!(feature="git") && matches.has(&flags::GIT_DIM_IGNORED)?;
+        let link_target = TargetPath::deduce(matches)?;
+        let access = if matches.has(&flags::COLOR_BY_ACCESS)? || matches.has(&flags::COLOUR_BY_ACCESS)? { Some(Identity::current()) }
+                                                                                                else { None };
+        Ok(FileStyle { classify, icons, exts, ext_colours, recent, dim_ignored, access, link_target })
+    }
+}
+
+const TARGET_PATHS: &[&str] = &[ "raw", "absolute", "relative" ];
+
+impl TargetPath {
+
+    /// Determines how to show the paths of symlinks’ targets, based on the
+    /// `--link-target` argument, keeping them as they were written if it
+    /// isn’t given.
+    fn deduce(matches: &MatchedFlags) -> Result<TargetPath, Misfire> {
+        let word = match matches.get(&flags::LINK_TARGET)? {
+            Some(w)  => w,
+            None     => return Ok(TargetPath::default()),
+        };
+
+        if word == "raw" {
+            Ok(TargetPath::Raw)
+        }
+        else if word == "absolute" {
+            Ok(TargetPath::Absolute)
+        }
+        else if word == "relative" {
+            Ok(TargetPath::Relative)
+        }
+        else {
+            Err(Misfire::bad_argument(&flags::LINK_TARGET, word, TARGET_PATHS))
+        }
+    }
+}
+
+impl RecentFiles {
+
+    /// Determines whether to highlight recently-changed files, and which
+    /// timestamp to check, using the same options that pick the timestamp
+    /// to show in the details view. If several are picked, the first of
+    /// modified, created, then accessed gets used.
+    fn deduce(matches: &MatchedFlags) -> Result<Option<RecentFiles>, Misfire> {
+        if !matches.has(&flags::HIGHLIGHT_RECENT)? {
+            return Ok(None);
+        }
+
+        let time_type = first_time_type(matches)?;
+        Ok(Some(RecentFiles { time_type, now: now_seconds() }))
+    }
+}
+
+impl AgeBuckets {
+
+    /// Determines how to sort files into buckets for the age column. This
+    /// goes by the same timestamp as highlighting recent files, and the
+    /// thresholds come from the `--age-thresholds` argument: five
+    /// comma-separated durations, in ascending order, such as the default
+    /// of `1m,1h,1d,1w,30d`.
+    fn deduce(matches: &MatchedFlags) -> Result<AgeBuckets, Misfire> {
+        let thresholds = match matches.get(&flags::AGE_THRESHOLDS)? {
+            Some(input)  => parse_age_thresholds(&input.to_string_lossy())?,
+            None         => DEFAULT_AGE_THRESHOLDS,
+        };
+
+        let time_type = first_time_type(matches)?;
+        Ok(AgeBuckets { time_type, now: now_seconds(), thresholds })
+    }
+}
+
+/// Picks the one timestamp to check out of the ones picked to be shown in
+/// the details view. If several are picked, the first of modified, created,
+/// then accessed gets used.
+fn first_time_type(matches: &MatchedFlags) -> Result<TimeType, Misfire> {
+    let time_types = TimeTypes::deduce(matches)?;
+
+    let time_type = if time_types.modified     { TimeType::Modified }
+               else if time_types.created      { TimeType::Created }
+                                          else { TimeType::Accessed };
+
+    Ok(time_type)
+}
+
+/// The current time, in seconds since the Unix epoch.
+fn now_seconds() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration)  => duration.as_secs() as i64,
+        Err(_)        => 0,
+    }
+}
+
+/// Parses the five comma-separated durations that the age thresholds are
+/// given as, making sure they go up from one to the next.
+fn parse_age_thresholds(input: &str) -> Result<[i64; 5], Misfire> {
+    let words: Vec<&str> = input.split(',').collect();
+    if words.len() != 5 {
+        return Err(Misfire::FailedAgeThresholds(format!("expected five durations, such as 1m,1h,1d,1w,30d, but got {}", words.len())));
+    }
+
+    let mut thresholds = [0; 5];
+    for (threshold, word) in thresholds.iter_mut().zip(words.iter()) {
+        *threshold = match parse_duration(word) {
+            Some(seconds)  => seconds,
+            None           => return Err(Misfire::FailedAgeThresholds(format!("invalid duration {:?}", word))),
+        };
+    }
+
+    if thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(Misfire::FailedAgeThresholds(format!("{} is not in ascending order", input)));
+    }
+
+    Ok(thresholds)
+}
+
+/// Parses a duration such as `90s`, `5m`, `2h`, `1d`, or `1w` into a number
+/// of seconds. A number without a unit is a number of seconds.
+fn parse_duration(word: &str) -> Option<i64> {
+    let (number, unit) = match word.char_indices().last() {
+        Some((i, c)) if c.is_alphabetic()  => (&word[.. i], &word[i ..]),
+        _                                  => (word, "s"),
+    };
+
+    let multiplier = match unit {
+        "s"  => 1,
+        "m"  => 60,
+        "h"  => 60 * 60,
+        "d"  => 24 * 60 * 60,
+        "w"  => 7 * 24 * 60 * 60,
+        _    => return None,
+    };
+
+    match number.parse::<i64>() {
+        Ok(n) if n >= 0  => Some(n * multiplier),
+        _                => None,
+    }
+}
+
+impl ExtensionColours {
+
+    /// Reads the user’s extension colours from the file given on the
+    /// command-line, if one was given, failing if it can’t be read or has
+    /// a style in it that isn’t valid.
+    fn deduce(matches: &MatchedFlags) -> Result<ExtensionColours, Misfire> {
+        let path = match matches.get_where(|f| f.matches(&flags::EXT_COLORS) || f.matches(&flags::EXT_COLOURS))? {
+            Some(p) => Path::new(p),
+            None    => return Ok(ExtensionColours::default()),
+        };
+
+        let mut contents = String::new();
+        if let Err(e) = File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+            return Err(Misfire::FailedExtColours(format!("{}: {}", path.display(), e)));
+        }
+
+        ExtensionColours::parse(&contents)
+            .map_err(|e| Misfire::FailedExtColours(format!("{}: {}", path.display(), e)))
     }
 }
 
 impl Classify {
     fn deduce(matches: &MatchedFlags) -> Result<Classify, Misfire> {
         let flagged = matches.has(&flags::CLASSIFY)?;
+        let slashes = matches.has(&flags::SLASH_DIRS)?;
 
-        Ok(if flagged { Classify::AddFileIndicators }
-                 else { Classify::JustFilenames })
+        // Classifying already puts a slash after directories, so asking
+        // for just the slashes as well does nothing.
+        if flagged && slashes && matches.is_strict() {
+            return Err(Misfire::Useless(&flags::SLASH_DIRS, true, &flags::CLASSIFY));
+        }
+
+        Ok(if flagged      { Classify::AddFileIndicators }
+           else if slashes { Classify::AddDirectorySlashes }
+           else            { Classify::JustFilenames })
+    }
+}
+
+const ICONSES: &[&str] = &[ "all", "files", "none" ];
+
+impl Icons {
+
+    /// Determines which files get icons: `--icons` on its own puts one
+    /// before every file, and it can be given `=files` to leave directories
+    /// without them, or `=none` to turn them back off.
+    fn deduce(matches: &MatchedFlags) -> Result<Icons, Misfire> {
+        if let Some(word) = matches.get(&flags::ICONS)? {
+            if word == "all" {
+                Ok(Icons::All)
+            }
+            else if word == "files" {
+                Ok(Icons::FilesOnly)
+            }
+            else if word == "none" {
+                Ok(Icons::Off)
+            }
+            else {
+                Err(Misfire::bad_argument(&flags::ICONS, word, ICONSES))
+            }
+        }
+        else if matches.has(&flags::ICONS)? {
+            Ok(Icons::All)
+        }
+        else {
+            Ok(Icons::Off)
+        }
     }
 }
 
@@ -430,9 +1025,13 @@ mod test {
     static TEST_ARGS: &[&Arg] = &[ &flags::BINARY, &flags::BYTES,    &flags::TIME_STYLE,
                                    &flags::TIME,   &flags::MODIFIED, &flags::CREATED, &flags::ACCESSED,
                                    &flags::COLOR,  &flags::COLOUR,
-                                   &flags::HEADER, &flags::GROUP,  &flags::INODE, &flags::GIT,
-                                   &flags::LINKS,  &flags::BLOCKS, &flags::LONG,  &flags::LEVEL,
-                                   &flags::GRID,   &flags::ACROSS, &flags::ONE_LINE ];
+                                   &flags::HEADER, &flags::GROUP,  &flags::COLLAPSE_OWNER, &flags::INODE, &flags::GIT,
+                                   &flags::GIT_SINCE, &flags::GIT_SINCE_ONLY, &flags::GIT_LAST_COMMIT,
+                                   &flags::LINKS,  &flags::BLANK_SYMLINK_META, &flags::BLOCKS, &flags::SPARSE, &flags::TIME_ANOMALIES, &flags::FILE_FLAGS, &flags::ENTRIES, &flags::DIR_ENTRIES, &flags::CONTENT_TYPE, &flags::LINE_COUNT, &flags::SYMLINK_GROUPS, &flags::AGE_COLUMN, &flags::AGE_THRESHOLDS, &flags::CONTEXT, &flags::SIZE_PERCENT, &flags::LONG,  &flags::TSV, &flags::JSON, &flags::TREE, &flags::LEVEL,
+                                   &flags::GRID,   &flags::ACROSS, &flags::ONE_LINE, &flags::SPACING, &flags::PRINT_WIDTH,
+                                   &flags::EXT_COLORS, &flags::EXT_COLOURS, &flags::PAGINATE, &flags::SUMMARY, &flags::SUMMARY_RECURSE,
+                                   &flags::HIGHLIGHT_RECENT, &flags::ALIGN, &flags::COLUMNS, &flags::COMPACT, &flags::COMPACT_LONG,
+                                   &flags::GIT_REMOTE, &flags::GIT_REMOTE_NAME, &flags::LINK_TARGET, &flags::ICONS, &flags::CLASSIFY, &flags::SLASH_DIRS, &flags::ALL ];
 
     macro_rules! test {
 
@@ -629,10 +1228,21 @@ mod test {
         test!(empty:         Mode <- [], None;            Both => like Ok(Mode::Grid(_)));
 
         // Grid views
-        test!(original_g:    Mode <- ["-G"], None;        Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _ })));
-        test!(grid:          Mode <- ["--grid"], None;    Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _ })));
-        test!(across:        Mode <- ["--across"], None;  Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: _ })));
-        test!(gracross:      Mode <- ["-xG"], None;       Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: _ })));
+        test!(original_g:    Mode <- ["-G"], None;        Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _, spacing: _, print_width: false })));
+        test!(grid:          Mode <- ["--grid"], None;    Both => like Ok(Mode::Grid(GridOptions { across: false, console_width: _, spacing: _, print_width: false })));
+        test!(across:        Mode <- ["--across"], None;  Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: _, spacing: _, print_width: false })));
+        test!(gracross:      Mode <- ["-xG"], None;       Both => like Ok(Mode::Grid(GridOptions { across: true,  console_width: _, spacing: _, print_width: false })));
+
+        // Grid spacing
+        test!(print_width:   Mode <- ["--print-width"], None;   Both => like Ok(Mode::Grid(GridOptions { print_width: true, .. })));
+        test!(spacing_default: Mode <- [], None;                  Both => like Ok(Mode::Grid(GridOptions { spacing: 2, .. })));
+        test!(spacing_one:   Mode <- ["--spacing=1"], None;      Both => like Ok(Mode::Grid(GridOptions { spacing: 1, .. })));
+        test!(spacing_max:   Mode <- ["--spacing", "16"], None;  Both => like Ok(Mode::Grid(GridOptions { spacing: 16, .. })));
+        test!(spacing_zero:  Mode <- ["--spacing=0"], None;      Both => err Misfire::bad_argument(&flags::SPACING, &os("0"), super::SPACINGS));
+        test!(spacing_big:   Mode <- ["--spacing=17"], None;     Both => err Misfire::bad_argument(&flags::SPACING, &os("17"), super::SPACINGS));
+        test!(spacing_nan:   Mode <- ["--spacing=wide"], None;   Both => err Misfire::FailedParse("wide".parse::<usize>().unwrap_err()));
+        test!(spacing_long:  Mode <- ["--long", "--spacing=4"], None;  Complain => err Misfire::Useless(&flags::SPACING, true, &flags::LONG));
+        test!(spacing_lid:   Mode <- ["--long", "--grid", "--spacing=4"], None;  Both => like Ok(Mode::GridDetails(GridOptions { spacing: 4, .. }, _)));
 
         // Lines views
         test!(lines:         Mode <- ["--oneline"], None; Both => like Ok(Mode::Lines));
@@ -646,6 +1256,22 @@ mod test {
         test!(lid:           Mode <- ["--long", "--grid"], None;  Both => like Ok(Mode::GridDetails(_, _)));
         test!(leg:           Mode <- ["-lG"], None;               Both => like Ok(Mode::GridDetails(_, _)));
 
+        // JSON views
+        test!(json:          Mode <- ["--json"], None;             Last => like Ok(Mode::Grid(_)));
+        test!(json_2:        Mode <- ["--json"], None;             Complain => err Misfire::Useless(&flags::JSON, false, &flags::TREE));
+        test!(json_tree:     Mode <- ["--tree", "--json"], None;   Both => like Ok(Mode::Json));
+        test!(json_long:     Mode <- ["--tree", "--json", "--long"], None;  Last => like Ok(Mode::Json));
+        test!(json_long_2:   Mode <- ["--tree", "--json", "--long"], None;  Complain => err Misfire::Conflict(&flags::JSON, &flags::LONG));
+        test!(json_tsv_2:    Mode <- ["--tree", "--json", "--tsv"], None;   Complain => err Misfire::Conflict(&flags::JSON, &flags::TSV));
+
+        // TSV views
+        test!(tsv:           Mode <- ["--tsv"], None;             Both => like Ok(Mode::Tsv(_)));
+        test!(tsv_long:      Mode <- ["--tsv", "--long"], None;   Both => like Ok(Mode::Tsv(_)));
+        test!(tsv_header:    Mode <- ["--tsv", "--header"], None; Both => like Ok(Mode::Tsv(tsv::Options { header: true, .. })));
+        test!(tsv_grid:      Mode <- ["--tsv", "--grid"], None;   Last => like Ok(Mode::Tsv(_)));
+        test!(tsv_grid_2:    Mode <- ["--tsv", "--grid"], None;   Complain => err Misfire::Conflict(&flags::TSV, &flags::GRID));
+        test!(tsv_tree:      Mode <- ["--tree", "--tsv"], None;   Complain => err Misfire::Conflict(&flags::TSV, &flags::TREE));
+
 
         // Options that do nothing without --long
         test!(just_header:   Mode <- ["--header"], None;  Last => like Ok(Mode::Grid(_)));
@@ -653,21 +1279,692 @@ mod test {
         test!(just_inode:    Mode <- ["--inode"],  None;  Last => like Ok(Mode::Grid(_)));
         test!(just_links:    Mode <- ["--links"],  None;  Last => like Ok(Mode::Grid(_)));
         test!(just_blocks:   Mode <- ["--blocks"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_sparse:   Mode <- ["--show-sparse"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_anomalies: Mode <- ["--show-time-anomalies"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_flags:    Mode <- ["--flags"],  None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_lines:    Mode <- ["--wc"],     None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_symlink_groups: Mode <- ["--symlink-groups"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_age_column: Mode <- ["--age-column"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_age_thresholds: Mode <- ["--age-thresholds=1m,1h,1d,1w,30d"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_context:  Mode <- ["--context"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_collapse_owner: Mode <- ["--collapse-owner"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_size_percent: Mode <- ["--size-percent"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_compact_long: Mode <- ["--compact-long"], None;  Last => like Ok(Mode::Grid(_)));
+        test!(just_entries:  Mode <- ["--entries"], None;      Last => like Ok(Mode::Grid(_)));
         test!(just_binary:   Mode <- ["--binary"], None;  Last => like Ok(Mode::Grid(_)));
         test!(just_bytes:    Mode <- ["--bytes"],  None;  Last => like Ok(Mode::Grid(_)));
 
         #[cfg(feature="git")]
         test!(just_git:      Mode <- ["--git"],    None;  Last => like Ok(Mode::Grid(_)));
 
-        test!(just_header_2: Mode <- ["--header"], None;  Complain => err Misfire::Useless(&flags::HEADER, false, &flags::LONG));
+        test!(just_header_2: Mode <- ["--header"], None;  Complain => like Ok(Mode::Grid(_)));
         test!(just_group_2:  Mode <- ["--group"],  None;  Complain => err Misfire::Useless(&flags::GROUP,  false, &flags::LONG));
         test!(just_inode_2:  Mode <- ["--inode"],  None;  Complain => err Misfire::Useless(&flags::INODE,  false, &flags::LONG));
         test!(just_links_2:  Mode <- ["--links"],  None;  Complain => err Misfire::Useless(&flags::LINKS,  false, &flags::LONG));
+        test!(just_multiple_links_2: Mode <- ["--links=multiple"], None; Complain => err Misfire::Useless(&flags::LINKS, false, &flags::LONG));
+        test!(just_collapse_owner_2: Mode <- ["--collapse-owner"], None; Complain => err Misfire::Useless(&flags::COLLAPSE_OWNER, false, &flags::LONG));
+        test!(just_blank_symlink_meta_2: Mode <- ["--blank-symlink-meta"], None; Complain => err Misfire::Useless(&flags::BLANK_SYMLINK_META, false, &flags::LONG));
         test!(just_blocks_2: Mode <- ["--blocks"], None;  Complain => err Misfire::Useless(&flags::BLOCKS, false, &flags::LONG));
+        test!(just_sparse_2: Mode <- ["--show-sparse"], None;  Complain => err Misfire::Useless(&flags::SPARSE, false, &flags::LONG));
+        test!(just_anomalies_2: Mode <- ["--show-time-anomalies"], None;  Complain => err Misfire::Useless(&flags::TIME_ANOMALIES, false, &flags::LONG));
+        test!(just_flags_2:  Mode <- ["--flags"],  None;  Complain => err Misfire::Useless(&flags::FILE_FLAGS, false, &flags::LONG));
+        test!(just_content_2: Mode <- ["--content-type"], None; Complain => err Misfire::Useless(&flags::CONTENT_TYPE, false, &flags::LONG));
+        test!(just_lines_2:  Mode <- ["--wc"],     None;  Complain => err Misfire::Useless(&flags::LINE_COUNT, false, &flags::LONG));
+        test!(just_symlink_groups_2: Mode <- ["--symlink-groups"], None;  Complain => err Misfire::Useless(&flags::SYMLINK_GROUPS, false, &flags::LONG));
+        test!(just_age_column_2: Mode <- ["--age-column"], None;  Complain => err Misfire::Useless(&flags::AGE_COLUMN, false, &flags::LONG));
+        test!(just_context_2: Mode <- ["--context"], None;  Complain => err Misfire::Useless(&flags::CONTEXT, false, &flags::LONG));
+        test!(just_size_percent_2: Mode <- ["--size-percent"], None;  Complain => err Misfire::Useless(&flags::SIZE_PERCENT, false, &flags::LONG));
+        test!(just_compact_long_2: Mode <- ["--compact-long"], None;  Complain => err Misfire::Useless(&flags::COMPACT_LONG, false, &flags::LONG));
+        test!(just_age_thresholds_2: Mode <- ["--age-thresholds=1m,1h,1d,1w,30d"], None;  Complain => err Misfire::Useless(&flags::AGE_THRESHOLDS, false, &flags::LONG));
+        test!(just_entries_2: Mode <- ["--entries"], None;     Complain => err Misfire::Useless(&flags::ENTRIES, false, &flags::LONG));
+        test!(just_dir_entries_2: Mode <- ["--dir-entries"], None; Complain => err Misfire::Useless(&flags::DIR_ENTRIES, false, &flags::LONG));
+        test!(just_time_2:   Mode <- ["--time=accessed"], None;  Complain => err Misfire::Useless(&flags::TIME, false, &flags::LONG));
+        test!(recent_time:   Mode <- ["--time=accessed", "--highlight-recent"], None;  Complain => like Ok(Mode::Grid(_)));
+        test!(just_align:    Mode <- ["--align=size:left"], None;  Complain => err Misfire::Useless(&flags::ALIGN, false, &flags::LONG));
         test!(just_binary_2: Mode <- ["--binary"], None;  Complain => err Misfire::Useless(&flags::BINARY, false, &flags::LONG));
         test!(just_bytes_2:  Mode <- ["--bytes"],  None;  Complain => err Misfire::Useless(&flags::BYTES,  false, &flags::LONG));
 
         #[cfg(feature="git")]
         test!(just_git_2:    Mode <- ["--git"],    None;  Complain => err Misfire::Useless(&flags::GIT,    false, &flags::LONG));
+
+        #[cfg(feature="git")]
+        test!(just_since:    Mode <- ["--git-since=HEAD"],       None;  Complain => err Misfire::Useless(&flags::GIT_SINCE, false, &flags::LONG));
+
+        #[cfg(feature="git")]
+        test!(just_last_commit: Mode <- ["--git-last-commit"],   None;  Last => like Ok(Mode::Grid(_)));
+
+        #[cfg(feature="git")]
+        test!(just_last_commit_2: Mode <- ["--git-last-commit"], None;  Complain => err Misfire::Useless(&flags::GIT_LAST_COMMIT, false, &flags::LONG));
+
+        #[cfg(feature="git")]
+        test!(just_since_only: Mode <- ["--git-since-only=HEAD"], None;  Complain => like Ok(Mode::Grid(_)));
+    }
+
+
+    mod columns {
+        use super::*;
+        use output::table::TimeType;
+
+        fn order(inputs: &[&str]) -> Vec<Result<Option<Vec<Column>>, Misfire>> {
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| Columns::deduce(mf).map(|c| c.order))
+        }
+
+        #[test]
+        fn unordered() {
+            for result in order(&[ "--inode" ]) {
+                assert_eq!(result, Ok(None));
+            }
+        }
+
+        #[test]
+        fn size_by_name() {
+            for result in order(&[ "--columns=permissions,user,modified,size,name" ]) {
+                assert_eq!(result, Ok(Some(vec![ Column::Permissions, Column::User, Column::Timestamp(TimeType::Modified), Column::FileSize ])));
+            }
+        }
+
+        #[test]
+        fn optional_columns() {
+            for result in order(&[ "--columns", "inode,links,blocks,name" ]) {
+                assert_eq!(result, Ok(Some(vec![ Column::Inode, Column::HardLinks, Column::Blocks ])));
+            }
+        }
+
+        #[test]
+        fn overrides_flags() {
+            for result in order(&[ "--group", "--columns=size,,size,name" ]) {
+                assert_eq!(result, Ok(Some(vec![ Column::FileSize ])));
+            }
+        }
+
+        test!(unknown:     Mode <- ["--long", "--columns=size,colour,name"], None;  Both => err Misfire::bad_argument(&flags::COLUMNS, &os("colour"), ORDER_NAMES));
+        test!(no_name:     Mode <- ["--long", "--columns=size,user"],        None;  Both => err Misfire::ColumnsWithoutName);
+        test!(name_first:  Mode <- ["--long", "--columns=name,size"],        None;  Both => err Misfire::ColumnsWithoutName);
+        test!(short:       Mode <- ["--columns=size,name"],                  None;  Complain => err Misfire::Useless(&flags::COLUMNS, false, &flags::LONG));
+    }
+
+
+    mod links {
+        use super::*;
+
+        /// Whether every link count is shown, and whether only the counts
+        /// above one are.
+        fn links(inputs: &[&str]) -> Vec<Result<(bool, bool), Misfire>> {
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| Columns::deduce(mf).map(|c| (c.links, c.multiple_links)))
+        }
+
+        #[test]
+        fn hidden() {
+            for result in links(&[]) {
+                assert_eq!(result, Ok((false, false)));
+            }
+        }
+
+        #[test]
+        fn bare() {
+            for result in links(&[ "--links" ]) {
+                assert_eq!(result, Ok((true, false)));
+            }
+        }
+
+        #[test]
+        fn always() {
+            for result in links(&[ "--links=always" ]) {
+                assert_eq!(result, Ok((true, false)));
+            }
+        }
+
+        #[test]
+        fn multiple() {
+            for result in links(&[ "--links=multiple" ]) {
+                assert_eq!(result, Ok((false, true)));
+            }
+        }
+
+        #[test]
+        fn clustered() {
+            for result in links(&[ "-lHi" ]) {
+                assert_eq!(result, Ok((true, false)));
+            }
+        }
+
+        test!(unknown:  Mode <- ["--long", "--links=some"], None;  Both => err Misfire::bad_argument(&flags::LINKS, &os("some"), LINKSES));
+    }
+
+
+    #[cfg(feature="git")]
+    mod git_remotes {
+        use super::*;
+
+        fn remote(inputs: &[&str]) -> Vec<Result<(bool, String), Misfire>> {
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| Columns::deduce(mf).map(|c| (c.git_remote, c.remote_name)))
+        }
+
+        #[test]
+        fn hidden() {
+            for result in remote(&[]) {
+                assert_eq!(result, Ok((false, "origin".into())));
+            }
+        }
+
+        #[test]
+        fn origin() {
+            for result in remote(&[ "--git-remote" ]) {
+                assert_eq!(result, Ok((true, "origin".into())));
+            }
+        }
+
+        #[test]
+        fn named() {
+            for result in remote(&[ "--git-remote", "--git-remote-name=upstream" ]) {
+                assert_eq!(result, Ok((true, "upstream".into())));
+            }
+        }
+
+        #[test]
+        fn named_column() {
+            for result in remote(&[ "--columns=remote,name", "--git-remote-name=upstream" ]) {
+                assert_eq!(result, Ok((false, "upstream".into())));
+            }
+        }
+
+        test!(just_name:  Mode <- ["--long", "--git-remote-name=upstream"], None;  Complain => err Misfire::Useless(&flags::GIT_REMOTE_NAME, false, &flags::GIT_REMOTE));
+        test!(short:      Mode <- ["--git-remote"],                         None;  Complain => err Misfire::Useless(&flags::GIT_REMOTE, false, &flags::LONG));
+    }
+
+
+    mod dir_entries {
+        use super::*;
+
+        /// Which entries get counted for each directory, if any.
+        fn counted(inputs: &[&str]) -> Vec<Option<DotFilter>> {
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| {
+                match Mode::deduce(mf, None).unwrap() {
+                    Mode::Details(details::Options { table: Some(t), .. }) => t.extra_columns.dir_entries,
+                    _ => panic!("not a long view"),
+                }
+            })
+        }
+
+        #[test]
+        fn off() {
+            for dots in counted(&[ "--long" ]) {
+                assert_eq!(dots, None);
+            }
+        }
+
+        #[test]
+        fn on() {
+            for dots in counted(&[ "--long", "--dir-entries" ]) {
+                assert_eq!(dots, Some(DotFilter::JustFiles));
+            }
+        }
+
+        #[test]
+        fn with_dotfiles() {
+            for dots in counted(&[ "--long", "--dir-entries", "--all" ]) {
+                assert_eq!(dots, Some(DotFilter::Dotfiles));
+            }
+        }
+    }
+
+
+    mod compact {
+        use super::*;
+        use output::table::TimeType;
+
+        /// The columns picked for the long view, and whether it was made
+        /// compact, given the terminal width.
+        fn columns(inputs: &[&str], width: Option<&'static str>) -> Vec<(Option<Vec<Column>>, bool)> {
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| {
+                match Mode::deduce(mf, width.map(os)).unwrap() {
+                    Mode::Details(details::Options { table: Some(t), .. }) => (t.extra_columns.order, t.extra_columns.compact),
+                    _ => panic!("not a long view"),
+                }
+            })
+        }
+
+        #[test]
+        fn wide() {
+            for result in columns(&[ "--long" ], Some("120")) {
+                assert_eq!(result, (None, false));
+            }
+        }
+
+        #[test]
+        fn narrow() {
+            for result in columns(&[ "--long", "--inode" ], Some("40")) {
+                assert_eq!(result, (None, false));
+            }
+        }
+
+        #[test]
+        fn forced() {
+            for result in columns(&[ "--long", "--compact" ], Some("120")) {
+                assert_eq!(result, (None, true));
+            }
+        }
+
+        #[test]
+        fn picked_columns() {
+            for result in columns(&[ "--long", "--compact", "--columns=modified,name" ], Some("40")) {
+                assert_eq!(result, (Some(vec![ Column::Timestamp(TimeType::Modified) ]), true));
+            }
+        }
+
+        #[test]
+        fn bytes_kept() {
+            let sizes = parse_for_test(&[ "--long", "--bytes", "--compact" ], TEST_ARGS, Both, |mf| {
+                match Mode::deduce(mf, None).unwrap() {
+                    Mode::Details(details::Options { table: Some(t), .. }) => t.size_format,
+                    _ => panic!("not a long view"),
+                }
+            });
+
+            for size_format in sizes {
+                assert_eq!(size_format, SizeFormat::JustBytes);
+            }
+        }
+
+        test!(short:  Mode <- ["--compact"], None;  Complain => err Misfire::Useless(&flags::COMPACT, false, &flags::LONG));
+    }
+
+
+    mod compact_long {
+        use super::*;
+
+        /// How the long view’s table should be made to fit, given the
+        /// terminal width.
+        fn fits(inputs: &[&str], width: Option<&'static str>) -> Vec<Option<Fit>> {
+            parse_for_test(inputs, TEST_ARGS, Both, |mf| {
+                match Mode::deduce(mf, width.map(os)).unwrap() {
+                    Mode::Details(details::Options { table: Some(t), .. }) => t.fit,
+                    _ => panic!("not a long view"),
+                }
+            })
+        }
+
+        #[test]
+        fn off() {
+            for fit in fits(&[ "--long" ], Some("80")) {
+                assert_eq!(fit, None);
+            }
+        }
+
+        #[test]
+        fn on() {
+            for fit in fits(&[ "--long", "--compact-long" ], Some("80")) {
+                assert_eq!(fit, Some(Fit { width: 80, shorten_time: true, picked: false }));
+            }
+        }
+
+        #[test]
+        fn time_style_kept() {
+            for fit in fits(&[ "--long", "--compact-long", "--time-style=full-iso" ], Some("80")) {
+                assert_eq!(fit, Some(Fit { width: 80, shorten_time: false, picked: false }));
+            }
+        }
+
+        #[test]
+        fn picked_columns() {
+            for fit in fits(&[ "--long", "--compact-long", "--columns=size,user,name" ], Some("80")) {
+                assert_eq!(fit, Some(Fit { width: 80, shorten_time: true, picked: true }));
+            }
+        }
+
+        #[test]
+        fn compact_not_picked() {
+            for fit in fits(&[ "--long", "--compact-long", "--compact" ], Some("80")) {
+                assert_eq!(fit, Some(Fit { width: 80, shorten_time: true, picked: false }));
+            }
+        }
+
+        #[test]
+        fn at_the_threshold() {
+            for fit in fits(&[ "--long" ], Some("60")) {
+                assert_eq!(fit, None);
+            }
+        }
+
+        #[test]
+        fn narrow() {
+            for fit in fits(&[ "--long" ], Some("59")) {
+                assert_eq!(fit, Some(Fit { width: 59, shorten_time: true, picked: false }));
+            }
+        }
+
+        test!(short:  Mode <- ["--compact-long"], None;  Complain => err Misfire::Useless(&flags::COMPACT_LONG, false, &flags::LONG));
+    }
+
+
+    mod alignments {
+        use super::*;
+        use output::table::{Alignment, Alignments};
+        use output::table::Alignment::*;
+
+        fn aligned(pairs: &[(&'static str, Alignment)]) -> Alignments {
+            Alignments(pairs.to_vec())
+        }
+
+        // Default
+        test!(empty:       Alignments <- [];                                 Both => Ok(Alignments::default()));
+
+        // Overriding
+        test!(size_left:   Alignments <- ["--align=size:left"];              Both => Ok(aligned(&[ ("size", Left) ])));
+        test!(two:         Alignments <- ["--align", "size:left,links:right"];  Both => Ok(aligned(&[ ("size", Left), ("links", Right) ])));
+        test!(trailing:    Alignments <- ["--align=user:right,"];            Both => Ok(aligned(&[ ("user", Right) ])));
+        test!(last_wins:   Alignments <- ["--align=size:left", "--align=user:right"];  Last => Ok(aligned(&[ ("user", Right) ])));
+
+        // Errors
+        test!(unknown:     Alignments <- ["--align=colour:left"];            Both => Err(Misfire::bad_argument(&flags::ALIGN, &os("colour"), COLUMN_NAMES)));
+        test!(no_side:     Alignments <- ["--align=size"];                   Both => Err(Misfire::bad_argument(&flags::ALIGN, &os(""), ALIGNMENTS)));
+        test!(bad_side:    Alignments <- ["--align=size:middle"];            Both => Err(Misfire::bad_argument(&flags::ALIGN, &os("middle"), ALIGNMENTS)));
+        test!(duplicate:   Alignments <- ["--align=size:left", "--align=user:right"];  Complain => Err(Misfire::Duplicate(Flag::Long("align"), Flag::Long("align"))));
+    }
+
+
+    mod recent_fileses {
+        use super::*;
+
+        // Default
+        test!(empty:     RecentFiles <- [];                                          Both => Ok(None));
+
+        // Picking a timestamp
+        test!(modified:  RecentFiles <- ["--highlight-recent"];                      Both => like Ok(Some(RecentFiles { time_type: TimeType::Modified, .. })));
+        test!(accessed:  RecentFiles <- ["--highlight-recent", "--time=accessed"];   Both => like Ok(Some(RecentFiles { time_type: TimeType::Accessed, .. })));
+        test!(created:   RecentFiles <- ["--highlight-recent", "-U"];                Both => like Ok(Some(RecentFiles { time_type: TimeType::Created,  .. })));
+        test!(both:      RecentFiles <- ["--highlight-recent", "-uU"];               Both => like Ok(Some(RecentFiles { time_type: TimeType::Created,  .. })));
+
+        // Errors
+        test!(bad_time:  RecentFiles <- ["--highlight-recent", "--time=yesterday"];  Both => like Err(Misfire::BadArgument(_, _, _)));
+    }
+
+
+    mod age_bucketses {
+        use super::*;
+
+        // Defaults
+        test!(empty:       AgeBuckets <- [];                                        Both => like Ok(AgeBuckets { time_type: TimeType::Modified, thresholds: DEFAULT_AGE_THRESHOLDS, .. }));
+        test!(accessed:    AgeBuckets <- ["--time=accessed"];                       Both => like Ok(AgeBuckets { time_type: TimeType::Accessed, .. }));
+
+        // Thresholds
+        test!(units:       AgeBuckets <- ["--age-thresholds=30,5m,2h,3d,2w"];       Both => like Ok(AgeBuckets { thresholds: [ 30, 300, 7200, 259200, 1209600 ], .. }));
+        test!(defaults:    AgeBuckets <- ["--age-thresholds=1m,1h,1d,1w,30d"];      Both => like Ok(AgeBuckets { thresholds: DEFAULT_AGE_THRESHOLDS, .. }));
+
+        // Errors
+        test!(too_few:     AgeBuckets <- ["--age-thresholds=1m,1h,1d"];             Both => like Err(Misfire::FailedAgeThresholds(_)));
+        test!(bad_unit:    AgeBuckets <- ["--age-thresholds=1m,1h,1d,1w,1y"];       Both => like Err(Misfire::FailedAgeThresholds(_)));
+        test!(negative:    AgeBuckets <- ["--age-thresholds=-1m,1h,1d,1w,30d"];     Both => like Err(Misfire::FailedAgeThresholds(_)));
+        test!(descending:  AgeBuckets <- ["--age-thresholds=1h,1m,1d,1w,30d"];      Both => like Err(Misfire::FailedAgeThresholds(_)));
+    }
+
+
+    mod age_columns {
+        use super::*;
+
+        fn age(inputs: &[&str]) -> Vec<Result<bool, Misfire>> {
+            parse_for_test(inputs, TEST_ARGS, Complain, |mf| Columns::deduce(mf).map(|c| c.age))
+        }
+
+        #[test]
+        fn shown() {
+            for result in age(&[ "--age-column", "--age-thresholds=1m,1h,1d,1w,30d" ]) {
+                assert_eq!(result, Ok(true));
+            }
+        }
+
+        #[test]
+        fn picked_by_name() {
+            for result in age(&[ "--columns=age,name", "--age-thresholds=1m,1h,1d,1w,30d" ]) {
+                assert_eq!(result, Ok(false));
+            }
+        }
+
+        #[test]
+        fn thresholds_without_column() {
+            for result in age(&[ "--age-thresholds=1m,1h,1d,1w,30d" ]) {
+                assert_eq!(result, Err(Misfire::Useless(&flags::AGE_THRESHOLDS, false, &flags::AGE_COLUMN)));
+            }
+        }
+    }
+
+
+    mod collapse_owners {
+        use super::*;
+
+        fn collapse_owner(inputs: &[&str]) -> Vec<Result<bool, Misfire>> {
+            parse_for_test(inputs, TEST_ARGS, Complain, |mf| Columns::deduce(mf).map(|c| c.collapse_owner))
+        }
+
+        #[test]
+        fn with_group() {
+            for result in collapse_owner(&[ "--group", "--collapse-owner" ]) {
+                assert_eq!(result, Ok(true));
+            }
+        }
+
+        #[test]
+        fn group_picked_by_name() {
+            for result in collapse_owner(&[ "--columns=user,group,name", "--collapse-owner" ]) {
+                assert_eq!(result, Ok(true));
+            }
+        }
+
+        #[test]
+        fn without_group() {
+            for result in collapse_owner(&[ "--collapse-owner" ]) {
+                assert_eq!(result, Err(Misfire::Useless(&flags::COLLAPSE_OWNER, false, &flags::GROUP)));
+            }
+        }
+    }
+
+
+    mod target_paths {
+        use super::*;
+
+        // Default
+        test!(empty:     TargetPath <- [];                           Both => Ok(TargetPath::Raw));
+
+        // Modes
+        test!(raw:       TargetPath <- ["--link-target=raw"];        Both => Ok(TargetPath::Raw));
+        test!(absolute:  TargetPath <- ["--link-target=absolute"];   Both => Ok(TargetPath::Absolute));
+        test!(relative:  TargetPath <- ["--link-target", "relative"]; Both => Ok(TargetPath::Relative));
+
+        // Errors
+        test!(bad:       TargetPath <- ["--link-target=canonical"];  Both => err Misfire::bad_argument(&flags::LINK_TARGET, &os("canonical"), super::TARGET_PATHS));
+    }
+
+
+    mod classifies {
+        use super::*;
+
+        test!(empty:     Classify <- [];                              Both => Ok(Classify::JustFilenames));
+        test!(classify:  Classify <- ["--classify"];                  Both => Ok(Classify::AddFileIndicators));
+        test!(slashes:   Classify <- ["--slash-dirs"];                Both => Ok(Classify::AddDirectorySlashes));
+
+        // Classifying already does what the slashes would
+        test!(both:      Classify <- ["--classify", "--slash-dirs"];  Last => Ok(Classify::AddFileIndicators));
+        test!(both_2:    Classify <- ["--slash-dirs", "-F"];          Complain => err Misfire::Useless(&flags::SLASH_DIRS, true, &flags::CLASSIFY));
+    }
+
+
+    mod summary_scopes {
+        use super::*;
+
+        test!(empty:     SummaryScope <- [];                                   Both => Ok(None));
+        test!(listed:    SummaryScope <- ["--summary"];                        Both => Ok(Some(SummaryScope::Listed)));
+        test!(recursive: SummaryScope <- ["--summary", "--summary-recurse"];   Both => Ok(Some(SummaryScope::Recursive)));
+
+        // Recursing does nothing without a summary
+        test!(alone:     SummaryScope <- ["--summary-recurse"];                Last => Ok(None));
+        test!(alone_2:   SummaryScope <- ["--summary-recurse"];                Complain => err Misfire::Useless(&flags::SUMMARY_RECURSE, false, &flags::SUMMARY));
+    }
+
+
+    mod iconses {
+        use super::*;
+
+        // Default
+        test!(empty:     Icons <- [];                     Both => Ok(Icons::Off));
+
+        // Modes
+        test!(bare:      Icons <- ["--icons"];            Both => Ok(Icons::All));
+        test!(all:       Icons <- ["--icons=all"];        Both => Ok(Icons::All));
+        test!(files:     Icons <- ["--icons=files"];      Both => Ok(Icons::FilesOnly));
+        test!(none:      Icons <- ["--icons=none"];       Both => Ok(Icons::Off));
+        test!(not_value: Icons <- ["--icons", "files"];   Both => Ok(Icons::All));
+
+        // Errors
+        test!(bad:       Icons <- ["--icons=dirs"];       Both => err Misfire::bad_argument(&flags::ICONS, &os("dirs"), super::ICONSES));
+    }
+
+
+    mod ls_colors {
+        use super::*;
+        use ansi_term::Style;
+        use ansi_term::Colour::*;
+
+        static LS_COLORS: &str = "rs=0:di=01;34:ln=01;36:mh=00:pi=40;33:so=01;35:do=01;35:bd=40;33;01:cd=40;33;01:or=40;31;01:mi=00:su=37;41:sg=30;43:ca=30;41:tw=30;42:ow=34;42:st=37;44:ex=01;32:*.tar=01;31:*.tgz=01;31:*.JPG=01;35:*.rs=00;36:*README=04:*.tar.gz=01;31:";
+
+        #[test]
+        fn file_types() {
+            let mut colours = Colours::colourful(false);
+            let mut exts = ExtensionColours::default();
+            super::super::apply_ls_colors(LS_COLORS, &mut colours, &mut exts);
+
+            assert_eq!(colours.filetypes.directory, Blue.bold());
+            assert_eq!(colours.filetypes.symlink, Cyan.bold());
+            assert_eq!(colours.filetypes.device, Yellow.on(Black).bold());
+            assert_eq!(colours.filetypes.sticky_other_writable, Black.on(Green));
+            assert_eq!(colours.filetypes.executable, Green.bold());
+            assert_eq!(colours.broken_arrow, Red.on(Black).bold());
+            assert_eq!(colours.broken_filename, Style::default());
+
+            // Keys exa doesn’t know about leave everything else alone.
+            assert_eq!(colours.filetypes.normal, Style::default());
+            assert_eq!(colours.date, Colours::colourful(false).date);
+        }
+
+        #[test]
+        fn extensions() {
+            let mut colours = Colours::colourful(false);
+            let mut exts = ExtensionColours::default();
+            super::super::apply_ls_colors(LS_COLORS, &mut colours, &mut exts);
+            assert_eq!(exts, ExtensionColours::parse("tar=01;31\ntgz=01;31\njpg=01;35\nrs=00;36").unwrap());
+        }
+
+        #[test]
+        fn extensions_given_on_the_command_line_win() {
+            let mut colours = Colours::colourful(false);
+            let mut exts = ExtensionColours::parse("rs=33").unwrap();
+            super::super::apply_ls_colors(LS_COLORS, &mut colours, &mut exts);
+            assert_eq!(exts, ExtensionColours::parse("tar=01;31\ntgz=01;31\njpg=01;35\nrs=33").unwrap());
+        }
+    }
+
+
+    mod exa_colors {
+        use super::*;
+        use ansi_term::Colour::*;
+
+        fn apply(input: &str) -> Result<(Colours, ExtensionColours), Misfire> {
+            let mut colours = Colours::colourful(false);
+            let mut exts = ExtensionColours::default();
+            super::super::apply_exa_colors(input, &mut colours, &mut exts)?;
+            Ok((colours, exts))
+        }
+
+        #[test]
+        fn file_types() {
+            let (colours, exts) = apply("di=01;33:zz=01;31").unwrap();
+            assert_eq!(colours.filetypes.directory, Yellow.bold());
+            assert_eq!(exts, ExtensionColours::default());
+        }
+
+        #[test]
+        fn globs() {
+            let (_, exts) = apply("*.min.js=38;5;240:Makefile=4;33:*.js=33").unwrap();
+
+            let mut expected = ExtensionColours::default();
+            expected.add_glob(glob::Pattern::new("*.min.js").unwrap(), Fixed(240).normal());
+            expected.add_glob(glob::Pattern::new("Makefile").unwrap(), Yellow.underline());
+            expected.add_glob(glob::Pattern::new("*.js").unwrap(), Yellow.normal());
+            assert_eq!(exts, expected);
+        }
+
+        #[test]
+        fn bad_glob() {
+            let result = apply("*.js=33:*[.js=31");
+            assert_eq!(result.err().map(|e| e.to_string()),
+                       Some("Failed to parse EXA_COLORS: key \"*[.js\": Pattern syntax error near position 1: invalid range pattern".to_string()));
+        }
+    }
+
+
+    mod ext_colourses {
+        use super::*;
+        use scratch::ScratchDir;
+        use std::fs::File;
+        use std::io::Write;
+
+        // Default
+        test!(empty:    ExtensionColours <- [];                                Both => Ok(ExtensionColours::default()));
+
+        // Errors
+        test!(missing:  ExtensionColours <- ["--ext-colours=/does/not/exist"];  Both => like Err(Misfire::FailedExtColours(_)));
+        test!(missing2: ExtensionColours <- ["--ext-colors", "/does/not/exist"]; Both => like Err(Misfire::FailedExtColours(_)));
+
+        #[test]
+        fn from_file() {
+            let scratch = ScratchDir::new("ext-colours-from-file");
+            let path = scratch.join("colours");
+            File::create(&path).unwrap().write_all(b"# my colours\n*.foo=01;35\n\n.bar=32\n").unwrap();
+
+            let arg = format!("--ext-colours={}", path.display());
+            for result in parse_for_test(&[ &*arg ], TEST_ARGS, Both, |mf| ExtensionColours::deduce(mf)) {
+                assert_eq!(result, Ok(ExtensionColours::parse("foo=01;35\nbar=32").unwrap()));
+            }
+        }
+
+        #[test]
+        fn bad_style() {
+            let scratch = ScratchDir::new("ext-colours-bad-style");
+            let path = scratch.join("colours");
+            File::create(&path).unwrap().write_all(b"foo=01;35\nbar=rainbow\n").unwrap();
+
+            let arg = format!("--ext-colours={}", path.display());
+            for result in parse_for_test(&[ &*arg ], TEST_ARGS, Both, |mf| ExtensionColours::deduce(mf)) {
+                let message = format!("{}: line 2: unknown style \"rainbow\"", path.display());
+                assert_eq!(result, Err(Misfire::FailedExtColours(message)));
+            }
+        }
+    }
+
+
+    mod pagers {
+        use super::*;
+
+        test!(off:      Pager <- [], None;  Both => like Ok(None));
+
+        #[test]
+        fn default() {
+            for result in parse_for_test(&[ "--paginate" ], TEST_ARGS, Both, |mf| Pager::deduce(mf, None)) {
+                assert_eq!(result, Ok(Some(Pager { command: os(DEFAULT_PAGER) })));
+            }
+        }
+
+        #[test]
+        fn from_env() {
+            for result in parse_for_test(&[ "--paginate" ], TEST_ARGS, Both, |mf| Pager::deduce(mf, Some(os("most")))) {
+                assert_eq!(result, Ok(Some(Pager { command: os("most") })));
+            }
+        }
+
+        #[test]
+        fn empty_env() {
+            for result in parse_for_test(&[ "--paginate" ], TEST_ARGS, Both, |mf| Pager::deduce(mf, Some(os("")))) {
+                assert_eq!(result, Ok(Some(Pager { command: os(DEFAULT_PAGER) })));
+            }
+        }
     }
 }
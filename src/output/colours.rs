@@ -1,5 +1,7 @@
 use ansi_term::Style;
-use ansi_term::Colour::{Red, Green, Yellow, Blue, Cyan, Purple, Fixed};
+use ansi_term::Colour::{Black, Red, Green, Yellow, Blue, Cyan, Purple, White, Fixed};
+
+use output::lsc::Pair;
 
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -12,23 +14,39 @@ pub struct Colours {
     pub users:      Users,
     pub links:      Links,
     pub git:        Git,
+    pub content:    Content,
+    pub access:     Access,
 
     pub punctuation:  Style,
     pub date:         Style,
     pub inode:        Style,
     pub blocks:       Style,
+    pub sparse:       Style,
+    pub time_anomaly: Style,
+    pub flags:        Style,
+    pub entries:      Style,
+    pub lines:        Style,
     pub header:       Style,
 
     pub symlink_path:     Style,
     pub broken_arrow:     Style,
     pub broken_filename:  Style,
     pub control_char:     Style,
+
+    /// Added on top of the usual style of files that have changed recently.
+    pub recent:           Style,
+
+    /// Added on top of the usual style of files flagged as immutable.
+    pub immutable:        Style,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct FileTypes {
     pub normal: Style,
     pub directory: Style,
+    pub other_writable: Style,
+    pub sticky: Style,
+    pub sticky_other_writable: Style,
     pub symlink: Style,
     pub pipe: Style,
     pub device: Style,
@@ -89,6 +107,10 @@ pub struct Users {
     pub user_someone_else: Style,
     pub group_yours: Style,
     pub group_not_yours: Style,
+
+    /// Used for IDs that don’t belong to any user or group, such as those
+    /// left behind by deleted users.
+    pub orphan: Style,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -104,6 +126,32 @@ pub struct Git {
     pub deleted: Style,
     pub renamed: Style,
     pub typechange: Style,
+    pub conflicted: Style,
+
+    /// The URLs of repositories’ remotes.
+    pub remote: Style,
+
+    /// The hashes of the commits that last changed files.
+    pub commit: Style,
+
+    /// Added on top of the usual style of files that Git ignores.
+    pub ignored: Style,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Content {
+    pub text: Style,
+    pub binary: Style,
+    pub empty: Style,
+}
+
+/// The styles of file names when they’re coloured by what the user can do
+/// to them, rather than by their types.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Access {
+    pub full: Style,
+    pub read_only: Style,
+    pub denied: Style,
 }
 
 impl Colours {
@@ -118,6 +166,9 @@ impl Colours {
             filetypes: FileTypes {
                 normal:      Style::default(),
                 directory:   Blue.bold(),
+                other_writable:         Blue.on(Green),
+                sticky:                 White.on(Blue),
+                sticky_other_writable:  Black.on(Green),
                 symlink:     Cyan.normal(),
                 pipe:        Yellow.normal(),
                 device:      Yellow.bold(),
@@ -175,6 +226,7 @@ impl Colours {
                 user_someone_else:  Style::default(),
                 group_yours:        Yellow.bold(),
                 group_not_yours:    Style::default(),
+                orphan:             Red.normal(),
             },
 
             links: Links {
@@ -188,21 +240,74 @@ impl Colours {
                 deleted:     Red.normal(),
                 renamed:     Yellow.normal(),
                 typechange:  Purple.normal(),
+                conflicted:  Red.bold(),
+                remote:      Cyan.normal(),
+                commit:      Yellow.normal(),
+                ignored:     Style::default().dimmed(),
+            },
+
+            content: Content {
+                text:    Green.normal(),
+                binary:  Purple.normal(),
+                empty:   Fixed(244).normal(),
+            },
+
+            access: Access {
+                full:       Green.bold(),
+                read_only:  Yellow.normal(),
+                denied:     Red.normal(),
             },
 
             punctuation:  Fixed(244).normal(),
             date:         Blue.normal(),
             inode:        Purple.normal(),
             blocks:       Cyan.normal(),
+            sparse:       Yellow.normal(),
+            time_anomaly: Red.bold(),
+            flags:        Purple.normal(),
+            entries:      Cyan.bold(),
+            lines:        Green.normal(),
             header:       Style::default().underline(),
 
             symlink_path:     Cyan.normal(),
             broken_arrow:     Red.normal(),
             broken_filename:  Red.underline(),
             control_char:     Red.normal(),
+
+            recent:           Style::default().bold(),
+            immutable:        Style::default().underline(),
         }
     }
 
+    /// Sets the style for a type of file from an `LS_COLORS` pair, if its
+    /// key is one of the two-letter codes for a type of file that exa can
+    /// tell apart, returning whether it was. A style that can’t be parsed
+    /// leaves the style as it was.
+    pub fn set_ls(&mut self, pair: &Pair) -> bool {
+        let style = match pair.key {
+            "fi"  => &mut self.filetypes.normal,
+            "di"  => &mut self.filetypes.directory,
+            "ln"  => &mut self.filetypes.symlink,
+            "pi"  => &mut self.filetypes.pipe,
+            "so"  => &mut self.filetypes.socket,
+            "bd"  => &mut self.filetypes.device,
+            "cd"  => &mut self.filetypes.device,
+            "ex"  => &mut self.filetypes.executable,
+            "ow"  => &mut self.filetypes.other_writable,
+            "st"  => &mut self.filetypes.sticky,
+            "tw"  => &mut self.filetypes.sticky_other_writable,
+            "or"  => &mut self.broken_arrow,
+            "mi"  => &mut self.broken_filename,
+            _     => return false,
+        };
+
+        if let Some(s) = pair.to_style() {
+            *style = s;
+        }
+
+        true
+    }
+
     pub fn file_size(&self, size: u64) -> Style {
         if self.scale {
             if size < 1024 {
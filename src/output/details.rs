@@ -13,11 +13,11 @@
 //! this:
 //!
 //! ```text
-//!     .rw-r--r--  9.6k ben 29 Jun 16:16 Cargo.lock
-//!     .rw-r--r--   547 ben 23 Jun 10:54 Cargo.toml
-//!     .rw-r--r--  1.1k ben 23 Nov  2014 LICENCE
-//!     .rw-r--r--  2.5k ben 21 May 14:38 README.md
-//!     .rw-r--r--  382k ben  8 Jun 21:00 screenshot.png
+//!     -rw-r--r--  9.6k ben 29 Jun 16:16 Cargo.lock
+//!     -rw-r--r--   547 ben 23 Jun 10:54 Cargo.toml
+//!     -rw-r--r--  1.1k ben 23 Nov  2014 LICENCE
+//!     -rw-r--r--  2.5k ben 21 May 14:38 README.md
+//!     -rw-r--r--  382k ben  8 Jun 21:00 screenshot.png
 //!     drwxr-xr-x     - ben 29 Jun 14:50 src
 //!     drwxr-xr-x     - ben 28 Jun 19:53 target
 //! ```
@@ -48,8 +48,8 @@
 //!     │ columns: [ Permissions,  Size,   User,  Date(Modified) ]                │
 //!     ├─────────────────────────────────────────────────────────────────────────┤
 //!     │   rows:  cells:                                            filename:    │
-//!     │   row 1: [ ".rw-r--r--", "9.6k", "ben", "29 Jun 16:16" ]   Cargo.lock   │
-//!     │   row 2: [ ".rw-r--r--",  "547", "ben", "23 Jun 10:54" ]   Cargo.toml   │
+//!     │   row 1: [ "-rw-r--r--", "9.6k", "ben", "29 Jun 16:16" ]   Cargo.lock   │
+//!     │   row 2: [ "-rw-r--r--",  "547", "ben", "23 Jun 10:54" ]   Cargo.toml   │
 //!     │   row 3: [ "drwxr-xr-x",    "-", "ben", "29 Jun 14:50" ]   src          │
 //!     │   row 4: [ "drwxr-xr-x",    "-", "ben", "28 Jun 19:53" ]   target       │
 //!     └─────────────────────────────────────────────────────────────────────────┘
@@ -60,16 +60,19 @@
 //! can be displayed, in order to make sure that every column is wide enough.
 
 
-use std::io::{Write, Error as IOError, Result as IOResult};
+use std::io::{Write, Error as IOError, ErrorKind, Result as IOResult};
 use std::path::PathBuf;
 use std::vec::IntoIter as VecIntoIter;
 
 use fs::{Dir, File};
 use fs::dir_action::RecurseOptions;
-use fs::filter::FileFilter;
+use fs::feature::GitOptions;
+use fs::filter::{FileFilter, SortCase};
+use fs::feature::acl::{self, Entry as AclEntry, Tag as AclTag};
 use fs::feature::xattr::{Attribute, FileAttributes};
 use output::colours::Colours;
 use output::cell::TextCell;
+use output::dividers::Dividers;
 use output::tree::{TreeTrunk, TreeParams, TreeDepth};
 use output::file_name::FileStyle;
 use output::table::{Table, Options as TableOptions, Row as TableRow};
@@ -100,6 +103,19 @@ pub struct Options {
 
     /// Whether to show each file's extended attributes.
     pub xattr: bool,
+
+    /// Whether to show the entries in each file’s access control list.
+    pub acl: bool,
+}
+
+
+/// The text to show in place of the contents of a directory that couldn’t
+/// be read, such as `[permission denied]`.
+pub fn unreadable_marker(error: &IOError) -> String {
+    match error.kind() {
+        ErrorKind::PermissionDenied  => "[permission denied]".into(),
+        _                            => format!("[{}]", error),
+    }
 }
 
 
@@ -118,14 +134,30 @@ pub struct Render<'a> {
 
     /// How to sort and filter the files after getting their details.
     pub filter: &'a FileFilter,
+
+    /// The options to scan the directories in a tree view with, if their
+    /// files’ Git statuses are needed.
+    pub git: Option<&'a GitOptions>,
+
+    /// The case the files are sorted by name in, if there should be
+    /// dividers between the letters. These only go between the top-level
+    /// files, not the ones inside them in a tree.
+    pub dividers: Option<SortCase>,
+
+    /// How many directories down from the arguments the files are, counting
+    /// the arguments’ own directories, for the context column. This is 0
+    /// for the arguments themselves.
+    pub depth: usize,
 }
 
 
 struct Egg<'a> {
     table_row: Option<TableRow>,
     xattrs:    Vec<Attribute>,
+    acl:       Vec<AclEntry>,
     errors:    Vec<(IOError, Option<PathBuf>)>,
     dir:       Option<Dir>,
+    unreadable: Option<IOError>,
     file:      &'a File<'a>,
 }
 
@@ -137,12 +169,27 @@ impl<'a> AsRef<File<'a>> for Egg<'a> {
 
 
 impl<'a> Render<'a> {
-    pub fn render<W: Write>(self, w: &mut W) -> IOResult<()> {
+
+    /// Renders the files, returning the paths of any directories in a tree
+    /// that couldn’t be read, along with their errors, so they can be
+    /// reported after the listing.
+    pub fn render<W: Write>(self, w: &mut W) -> IOResult<Vec<(PathBuf, IOError)>> {
         let mut rows = Vec::new();
+        let mut unreadable = Vec::new();
 
         if let Some(ref table) = self.opts.table {
+            let fit = table.fit;
             let mut table = Table::new(&table, self.dir, &self.colours);
 
+            if let Some(ref fit) = fit {
+                table.add_symlink_targets(&self.files);
+                table.set_context_depth(self.depth);
+                table.set_size_total(&self.files);
+
+                let name_width = self.files.iter().map(|f| *self.style.for_file(f, self.colours).with_link_paths().paint().width()).max().unwrap_or(0);
+                table.fit_into_width(fit, &self.files, self.opts.header, name_width);
+            }
+
             if self.opts.header {
                 let header = table.header_row();
                 table.add_widths(&header);
@@ -152,31 +199,37 @@ impl<'a> Render<'a> {
             // This is weird, but I can't find a way around it:
             // https://internals.rust-lang.org/t/should-option-mut-t-implement-copy/3715/6
             let mut table = Some(table);
-            self.add_files_to_table(&mut table, &mut rows, &self.files, TreeDepth::root());
+            self.add_files_to_table(&mut table, &mut rows, &mut unreadable, &self.files, TreeDepth::root());
 
             for row in self.iterate_with_table(table.unwrap(), rows) {
                 writeln!(w, "{}", row.strings())?
             }
         }
         else {
-            self.add_files_to_table(&mut None, &mut rows, &self.files, TreeDepth::root());
+            self.add_files_to_table(&mut None, &mut rows, &mut unreadable, &self.files, TreeDepth::root());
 
             for row in self.iterate(rows) {
                 writeln!(w, "{}", row.strings())?
             }
         }
 
-        Ok(())
+        Ok(unreadable)
     }
 
     /// Adds files to the table, possibly recursively. This is easily
     /// parallelisable, and uses a pool of threads.
-    fn add_files_to_table<'dir>(&self, table: &mut Option<Table<'a>>, rows: &mut Vec<Row>, src: &Vec<File<'dir>>, depth: TreeDepth) {
+    fn add_files_to_table<'dir>(&self, table: &mut Option<Table<'a>>, rows: &mut Vec<Row>, unreadable_dirs: &mut Vec<(PathBuf, IOError)>, src: &Vec<File<'dir>>, depth: TreeDepth) {
         use num_cpus;
         use scoped_threadpool::Pool;
         use std::sync::{Arc, Mutex};
         use fs::feature::xattr;
 
+        if let Some(ref mut t) = *table {
+            t.add_symlink_targets(src);
+            t.set_context_depth(self.depth + depth.0);
+            t.set_size_total(src);
+        }
+
         let mut pool = Pool::new(num_cpus::get() as u32);
         let mut file_eggs = Vec::new();
 
@@ -219,30 +272,46 @@ impl<'a> Render<'a> {
                         };
                     }
 
-                    let table_row = table.as_ref().map(|t| t.row_for_file(&file, !xattrs.is_empty()));
+                    // Files with an ACL get a + instead of an @ for it, so
+                    // the attribute holding it doesn’t count here.
+                    let has_xattrs = xattrs.iter().any(|a| !acl::is_acl_attribute(a));
+                    let has_acl = acl::has_extended_acl(&xattrs);
+                    let table_row = table.as_ref().map(|t| t.row_for_file(&file, has_xattrs, has_acl));
 
                     if !self.opts.xattr {
                         xattrs.clear();
                     }
 
+                    let mut acl = Vec::new();
+                    if self.opts.acl && has_acl {
+                        match acl::entries(&file.path) {
+                            Ok(es) => acl.extend(es),
+                            Err(e) => errors.push((e, None)),
+                        }
+                    }
+
                     let mut dir = None;
+                    let mut unreadable = None;
 
                     if let Some(r) = self.recurse {
                         if file.is_directory() && r.tree && !r.is_too_deep(depth.0) {
-                            match file.to_dir(false) {
+                            match self.filter.read_dir(file, self.git) {
                                 Ok(d)  => { dir = Some(d); },
-                                Err(e) => { errors.push((e, None)) },
+                                Err(e) => { unreadable = Some(e); },
                             }
                         }
                     };
 
-                    let egg = Egg { table_row, xattrs, errors, dir, file };
+                    let egg = Egg { table_row, xattrs, acl, errors, dir, unreadable, file };
                     file_eggs.lock().unwrap().push(egg);
                 });
             }
         });
 
         self.filter.sort_files(&mut file_eggs);
+        self.filter.keep_top(&mut file_eggs);
+
+        let mut dividers = if depth.0 == 0 { self.dividers.map(Dividers::new) } else { None };
 
         for (tree_params, egg) in depth.iterate_over(file_eggs.into_iter()) {
             let mut files = Vec::new();
@@ -252,6 +321,15 @@ impl<'a> Render<'a> {
                 t.add_widths(row);
             }
 
+            let divider = match dividers {
+                Some(ref mut d)  => d.before(egg.file),
+                None             => None,
+            };
+
+            if let Some(letter) = divider {
+                rows.push(self.render_divider(letter));
+            }
+
             let row = Row {
                 tree:   tree_params,
                 cells:  egg.table_row,
@@ -270,30 +348,49 @@ impl<'a> Render<'a> {
                     }
                 }
 
-                self.filter.filter_child_files(&mut files);
+                self.filter.filter_child_files(&mut files, depth.0);
 
                 if !files.is_empty() {
                     for xattr in egg.xattrs {
                         rows.push(self.render_xattr(xattr, TreeParams::new(depth.deeper(), false)));
                     }
 
+                    for entry in egg.acl {
+                        rows.push(self.render_acl_entry(entry, TreeParams::new(depth.deeper(), false)));
+                    }
+
                     for (error, path) in errors {
                         rows.push(self.render_error(&error, TreeParams::new(depth.deeper(), false), path));
                     }
 
-                    self.add_files_to_table(table, rows, &files, depth.deeper());
+                    self.add_files_to_table(table, rows, unreadable_dirs, &files, depth.deeper());
                     continue;
                 }
             }
 
-            let count = egg.xattrs.len();
+            // A directory that couldn’t be read gets a marker as its last
+            // child, rather than leaving a gap where its files would be.
+            let readable = egg.unreadable.is_none();
+
+            let count = egg.xattrs.len() + egg.acl.len();
+            let xattr_count = egg.xattrs.len();
             for (index, xattr) in egg.xattrs.into_iter().enumerate() {
-                rows.push(self.render_xattr(xattr, TreeParams::new(depth.deeper(), errors.is_empty() && index == count - 1)));
+                rows.push(self.render_xattr(xattr, TreeParams::new(depth.deeper(), readable && errors.is_empty() && index == count - 1)));
+            }
+
+            for (index, entry) in egg.acl.into_iter().enumerate() {
+                rows.push(self.render_acl_entry(entry, TreeParams::new(depth.deeper(), readable && errors.is_empty() && xattr_count + index == count - 1)));
             }
 
             let count = errors.len();
             for (index, (error, path)) in errors.into_iter().enumerate() {
-                rows.push(self.render_error(&error, TreeParams::new(depth.deeper(), index == count - 1), path));
+                rows.push(self.render_error(&error, TreeParams::new(depth.deeper(), readable && index == count - 1), path));
+            }
+
+            if let Some(error) = egg.unreadable {
+                let name = TextCell::paint(self.colours.broken_arrow, unreadable_marker(&error));
+                rows.push(Row { cells: None, name, tree: TreeParams::new(depth.deeper(), true) });
+                unreadable_dirs.push((egg.file.path.clone(), error));
             }
         }
     }
@@ -306,6 +403,14 @@ impl<'a> Render<'a> {
         }
     }
 
+    fn render_divider(&self, letter: char) -> Row {
+        Row {
+            tree:   TreeParams::new(TreeDepth::root(), false),
+            cells:  None,
+            name:   Dividers::render(letter, self.colours),
+        }
+    }
+
     fn render_error(&self, error: &IOError, tree: TreeParams, path: Option<PathBuf>) -> Row {
         let error_message = match path {
             Some(path) => format!("<{}: {}>", path.display(), error),
@@ -321,6 +426,28 @@ impl<'a> Render<'a> {
         Row { cells: None, name, tree }
     }
 
+    /// Renders an ACL entry in the same form `getfacl` uses, such as
+    /// `user:1000:r--` or `default:group::r-x`.
+    fn render_acl_entry(&self, entry: AclEntry, tree: TreeParams) -> Row {
+        let qualifier = match entry.tag {
+            AclTag::UserObj    => "user:".into(),
+            AclTag::User(id)   => format!("user:{}", id),
+            AclTag::GroupObj   => "group:".into(),
+            AclTag::Group(id)  => format!("group:{}", id),
+            AclTag::Mask       => "mask:".into(),
+            AclTag::Other      => "other:".into(),
+        };
+
+        let text = format!("{}{}:{}{}{}",
+                           if entry.default { "default:" } else { "" }, qualifier,
+                           if entry.read    { "r" } else { "-" },
+                           if entry.write   { "w" } else { "-" },
+                           if entry.execute { "x" } else { "-" });
+
+        let name = TextCell::paint(self.colours.perms.attribute, text);
+        Row { cells: None, name, tree }
+    }
+
     pub fn render_file(&self, cells: TableRow, name: TextCell, tree: TreeParams) -> Row {
         Row { cells: Some(cells), name, tree }
     }
@@ -0,0 +1,113 @@
+//! Dividers between the groups of files whose names start with the same
+//! letter, for scanning through big directories sorted by name.
+
+use fs::File;
+use fs::filter::SortCase;
+use output::cell::TextCell;
+use output::colours::Colours;
+
+
+/// Keeps track of the letter the last file’s name started with, so it can
+/// tell when a divider needs to go before the next one.
+pub struct Dividers {
+
+    /// The case the files are being sorted in, as when sorting
+    /// case-insensitively, `a` and `A` go in the same group.
+    case: SortCase,
+
+    /// The letter the last file’s name started with.
+    last: Option<char>,
+}
+
+impl Dividers {
+    pub fn new(case: SortCase) -> Dividers {
+        Dividers { case, last: None }
+    }
+
+    /// Returns the letter to label a divider with if one should go before
+    /// this file, which is when its name starts with a different letter to
+    /// the last file’s. No divider goes before the first file.
+    pub fn before(&mut self, file: &File) -> Option<char> {
+        let letter = leading_letter(&file.name, self.case);
+        let changed = self.last.is_some() && self.last != letter;
+        self.last = letter;
+
+        if changed { letter } else { None }
+    }
+
+    /// Renders a divider labelled with the given letter.
+    pub fn render(letter: char, colours: &Colours) -> TextCell {
+        TextCell::paint(colours.punctuation, format!("── {}", letter))
+    }
+}
+
+/// The letter a name gets grouped under, lowercased when sorting
+/// case-insensitively.
+fn leading_letter(name: &str, case: SortCase) -> Option<char> {
+    let first = name.chars().next();
+    match case {
+        SortCase::Sensitive    => first,
+        SortCase::Insensitive  => first.and_then(|c| c.to_lowercase().next()),
+    }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use fs::{Dir, DotFilter, TargetPath};
+    use fs::filter::{FileFilter, SortField};
+    use output::lines;
+    use output::file_name::{FileStyle, Classify, Icons, ExtensionColours};
+    use info::filetype::FileExtensions;
+
+    fn listing(name: &str, files: &[&str], case: SortCase) -> String {
+        let scratch = ScratchDir::new(&format!("dividers-{}", name));
+        let path = &scratch.path;
+        for file in files {
+            let _ = std_fs::File::create(path.join(file)).unwrap();
+        }
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+        let filter = FileFilter { sort_field: SortField::Name(case), ..FileFilter::default() };
+        filter.sort_files(&mut files);
+
+        let colours = Colours::plain();
+        let style = FileStyle { classify: Classify::JustFilenames, icons: Icons::Off, exts: FileExtensions, ext_colours: ExtensionColours::default(), recent: None, dim_ignored: false, access: None, link_target: TargetPath::Raw };
+        let mut output = Vec::new();
+        lines::Render { files, colours: &colours, style: &style, dividers: Some(case) }.render(&mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn at_letter_boundaries() {
+        let files = [ "apple", "avocado", "banana", "cherry", "cranberry" ];
+        assert_eq!(listing("boundaries", &files, SortCase::Sensitive),
+                   "apple\navocado\n── b\nbanana\n── c\ncherry\ncranberry\n");
+    }
+
+    #[test]
+    fn single_letter() {
+        let files = [ "one", "other", "out" ];
+        assert_eq!(listing("single", &files, SortCase::Sensitive), "one\nother\nout\n");
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let files = [ "Beta", "alpha", "bravo" ];
+        assert_eq!(listing("sensitive", &files, SortCase::Sensitive),
+                   "Beta\n── a\nalpha\n── b\nbravo\n");
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let files = [ "Beta", "alpha", "bravo" ];
+        assert_eq!(listing("insensitive", &files, SortCase::Insensitive),
+                   "alpha\n── b\nBeta\nbravo\n");
+    }
+}
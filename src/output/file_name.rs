@@ -1,12 +1,17 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 use ansi_term::{ANSIString, Style};
+use glob;
 
-use fs::{File, FileTarget};
+use fs::{File, FileTarget, Identity, TargetPath};
+use fs::fields as f;
 use info::filetype::FileExtensions;
 use output::Colours;
 use output::escape;
 use output::cell::TextCellContents;
+use output::lsc::{parse_style, Pair};
+use output::table::TimeType;
 
 
 /// Basically a file name factory.
@@ -16,8 +21,27 @@ pub struct FileStyle {
     /// Whether to append file class characters to file names.
     pub classify: Classify,
 
+    /// Which files to put an icon before the names of.
+    pub icons: Icons,
+
     /// Mapping of file extensions to colours, to highlight regular files.
     pub exts: FileExtensions,
+
+    /// User-supplied extension colours, which take priority over `exts`.
+    pub ext_colours: ExtensionColours,
+
+    /// Which files to highlight for having changed recently, if any.
+    pub recent: Option<RecentFiles>,
+
+    /// Whether to dim the names of files that Git ignores.
+    pub dim_ignored: bool,
+
+    /// Who to colour file names by the access of, in place of their types,
+    /// if anyone.
+    pub access: Option<Identity>,
+
+    /// How to show the paths of the files that symlinks point to.
+    pub link_target: TargetPath,
 }
 
 impl FileStyle {
@@ -29,14 +53,162 @@ impl FileStyle {
             file, colours,
             link_style: LinkStyle::JustFilenames,
             exts:       &self.exts,
+            ext_colours: &self.ext_colours,
+            recent:     self.recent,
+            dim_ignored: self.dim_ignored,
+            access:     self.access.as_ref(),
             classify:   self.classify,
-            target:     if file.is_link() { Some(file.link_target()) }
+            icons:      self.icons,
+            target:     if file.is_link() { Some(file.link_target_as(self.link_target)) }
                                      else { None }
         }
     }
 }
 
 
+/// A user-supplied mapping of file extensions to the styles that files with
+/// those extensions should be painted in, read from a file of `ext=style`
+/// lines using the same codes as `LS_COLORS`, along with any glob keys
+/// from the `EXA_COLORS` variable.
+#[derive(PartialEq, Debug, Default)]
+pub struct ExtensionColours {
+    styles: HashMap<String, Style>,
+
+    /// Styles for the files whose whole names match a glob, in the order
+    /// they were given. These get checked before the extensions.
+    globs: Vec<(glob::Pattern, Style)>,
+}
+
+impl ExtensionColours {
+
+    /// Parses the contents of an extension colours file. Blank lines and
+    /// lines beginning with `#` are skipped, and the extensions can be
+    /// written with or without a leading `*.` or `.`. Returns a description
+    /// of the first line that couldn’t be parsed, if any.
+    pub fn parse(contents: &str) -> Result<ExtensionColours, String> {
+        use std::ascii::AsciiExt;
+
+        let mut styles = HashMap::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (ext, code) = match line.find('=') {
+                Some(index)  => (&line[.. index], &line[index + 1 ..]),
+                None         => return Err(format!("line {}: expected ext=style, got {:?}", number + 1, line)),
+            };
+
+            let ext = ext.trim().trim_left_matches('*').trim_left_matches('.');
+            match parse_style(code.trim()) {
+                Some(style)  => { let _ = styles.insert(ext.to_ascii_lowercase(), style); },
+                None         => return Err(format!("line {}: unknown style {:?}", number + 1, code.trim())),
+            }
+        }
+
+        Ok(ExtensionColours { styles, globs: Vec::new() })
+    }
+
+    /// Adds the style from an `LS_COLORS` pair with a `*.ext` key, unless
+    /// that extension already has a style. Keys that are any other sort of
+    /// glob get skipped, as only the ones from `EXA_COLORS` get matched
+    /// against whole names.
+    pub fn add_ls(&mut self, pair: &Pair) {
+        use std::ascii::AsciiExt;
+
+        if !pair.key.starts_with("*.") {
+            return;
+        }
+
+        let ext = &pair.key[2..];
+        if ext.is_empty() || ext.contains(|c| c == '.' || c == '*' || c == '?' || c == '[') {
+            return;
+        }
+
+        if let Some(style) = pair.to_style() {
+            let _ = self.styles.entry(ext.to_ascii_lowercase()).or_insert(style);
+        }
+    }
+
+    /// Adds the style for the files whose names match a glob, such as
+    /// `*.min.js`, or that have exactly a name with no wildcards in it,
+    /// such as `Makefile`.
+    pub fn add_glob(&mut self, pattern: glob::Pattern, style: Style) {
+        self.globs.push((pattern, style));
+    }
+
+    /// The style for the given file, if its name matches one of the globs,
+    /// or its extension has been given one. File extensions are already
+    /// ASCII-lowercased, as are the ones in the map, so this is
+    /// case-insensitive, but the globs aren’t.
+    fn style(&self, file: &File) -> Option<Style> {
+        self.glob_style(&file.name)
+            .or_else(|| file.ext.as_ref().and_then(|ext| self.styles.get(ext)).cloned())
+    }
+
+    /// The style of the most specific glob that matches the given name, if
+    /// any do. As with `LS_COLORS`, a key that’s the exact name beats any
+    /// glob, and between globs, the longer one wins, so `*.min.js` goes
+    /// over `*.js`. When two are as specific as each other, the one given
+    /// last wins.
+    fn glob_style(&self, name: &str) -> Option<Style> {
+        let mut best: Option<&(glob::Pattern, Style)> = None;
+
+        for glob in &self.globs {
+            if glob.0.matches(name) && best.map_or(true, |b| specificity(&glob.0) >= specificity(&b.0)) {
+                best = Some(glob);
+            }
+        }
+
+        best.map(|b| b.1)
+    }
+}
+
+/// How specific a glob is, for picking between two that match the same
+/// name: whether it has no wildcards, then how long it is.
+fn specificity(pattern: &glob::Pattern) -> (bool, usize) {
+    let string = pattern.as_str();
+    (!string.contains(|c| c == '*' || c == '?' || c == '['), string.len())
+}
+
+
+/// How long ago a file’s timestamp can be for it to count as recent: a day.
+pub const RECENT_SECONDS: i64 = 24 * 60 * 60;
+
+/// The files whose names get highlighted for having changed recently: the
+/// ones with a timestamp in the 24 hours before now. It’s a fixed length of
+/// time, rather than since midnight, so there’s no time zone to worry about,
+/// and files from late last night still count.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct RecentFiles {
+
+    /// Which of the file’s timestamps to check.
+    pub time_type: TimeType,
+
+    /// The time to count back from, in seconds since the Unix epoch. This is
+    /// the time exa started running, except in tests.
+    pub now: i64,
+}
+
+impl RecentFiles {
+
+    /// Whether the given file’s timestamp falls within the last day.
+    /// Timestamps in the future don’t count.
+    pub fn includes(&self, file: &File) -> bool {
+        let time = match self.time_type {
+            TimeType::Modified  => file.modified_time(),
+            TimeType::Created   => file.created_time(),
+            TimeType::Accessed  => file.accessed_time(),
+        };
+
+        let seconds = i64::from(time.seconds);
+        seconds <= self.now && self.now - seconds < RECENT_SECONDS
+    }
+}
+
+
 /// When displaying a file name, there needs to be some way to handle broken
 /// links, depending on how long the resulting Cell can be.
 #[derive(PartialEq, Debug, Copy, Clone)]
@@ -63,6 +235,10 @@ pub enum Classify {
     /// Add a character after the file name depending on what class of file
     /// it is.
     AddFileIndicators,
+
+    /// Only add a slash after the names of directories, leaving every other
+    /// kind of file alone.
+    AddDirectorySlashes,
 }
 
 impl Default for Classify {
@@ -72,6 +248,41 @@ impl Default for Classify {
 }
 
 
+/// Which files get an icon before their names, for terminals with a font
+/// that has them, such as one of the Nerd Fonts.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Icons {
+
+    /// Don’t show any icons.
+    Off,
+
+    /// Only show icons for files that aren’t directories, so a listing full
+    /// of directories isn’t cluttered with the same icon over and over.
+    FilesOnly,
+
+    /// Show an icon for every file.
+    All,
+}
+
+impl Default for Icons {
+    fn default() -> Icons {
+        Icons::Off
+    }
+}
+
+impl Icons {
+
+    /// Whether the given file gets an icon.
+    fn shows(&self, file: &File) -> bool {
+        match *self {
+            Icons::Off        => false,
+            Icons::FilesOnly  => !file.is_directory(),
+            Icons::All        => true,
+        }
+    }
+}
+
+
 
 /// A **file name** holds all the information necessary to display the name
 /// of the given file. This is used in all of the views.
@@ -92,8 +303,23 @@ pub struct FileName<'a, 'dir: 'a> {
     /// Whether to append file class characters to file names.
     classify: Classify,
 
+    /// Which files to put an icon before the names of.
+    icons: Icons,
+
     /// Mapping of file extensions to colours, to highlight regular files.
     exts: &'a FileExtensions,
+
+    /// User-supplied extension colours, which take priority over `exts`.
+    ext_colours: &'a ExtensionColours,
+
+    /// Which files to highlight for having changed recently, if any.
+    recent: Option<RecentFiles>,
+
+    /// Whether to dim the names of files that Git ignores.
+    dim_ignored: bool,
+
+    /// Who to colour the name by the access of, if anyone.
+    access: Option<&'a Identity>,
 }
 
 
@@ -115,6 +341,11 @@ impl<'a, 'dir> FileName<'a, 'dir> {
     pub fn paint(&self) -> TextCellContents {
         let mut bits = Vec::new();
 
+        if self.icons.shows(self.file) {
+            bits.push(self.style().paint(self.icon_char()));
+            bits.push(Style::default().paint(" "));
+        }
+
         if self.file.parent_dir.is_none() {
             if let Some(parent) = self.file.path.parent() {
                 self.add_parent_bits(&mut bits, parent);
@@ -145,7 +376,12 @@ impl<'a, 'dir> FileName<'a, 'dir> {
                             target: None,
                             link_style: LinkStyle::FullLinkPaths,
                             classify: Classify::JustFilenames,
+                            icons: Icons::Off,
                             exts: self.exts,
+                            ext_colours: self.ext_colours,
+                            recent: None,
+                            dim_ignored: false,
+                            access: self.access,
                         };
 
                         for bit in target.coloured_file_name() {
@@ -166,8 +402,14 @@ impl<'a, 'dir> FileName<'a, 'dir> {
                 },
             }
         }
-        else if let Classify::AddFileIndicators = self.classify {
-            if let Some(class) = self.classify_char() {
+        else {
+            let class = match self.classify {
+                Classify::JustFilenames        => None,
+                Classify::AddFileIndicators    => self.classify_char(),
+                Classify::AddDirectorySlashes  => if self.file.is_directory() { Some("/") } else { None },
+            };
+
+            if let Some(class) = class {
                 bits.push(Style::default().paint(class));
             }
         }
@@ -191,6 +433,28 @@ impl<'a, 'dir> FileName<'a, 'dir> {
     }
 
 
+    /// The icon to display before a file when icons are on, going by its
+    /// type, or by its extension if it’s a regular file. These are in the
+    /// Private Use Area, where the Nerd Fonts keep them.
+    fn icon_char(&self) -> &'static str {
+        match self.file {
+            f if f.is_directory()            => "\u{f115}",
+            f if f.is_link()                 => "\u{f0c1}",
+            f if !f.is_file()                => "\u{f2db}",
+            f if f.is_executable_file()      => "\u{f489}",
+            f if self.exts.is_image(f)       => "\u{f1c5}",
+            f if self.exts.is_video(f)       => "\u{f1c8}",
+            f if self.exts.is_music(f)
+               | self.exts.is_lossless(f)    => "\u{f1c7}",
+            f if self.exts.is_crypto(f)      => "\u{f023}",
+            f if self.exts.is_document(f)    => "\u{f1c2}",
+            f if self.exts.is_compressed(f)  => "\u{f1c6}",
+            f if self.exts.is_compiled(f)    => "\u{f1c9}",
+            _                                => "\u{f15b}",
+        }
+    }
+
+
     /// The character to be displayed after a file when classifying is on, if
     /// the file’s type has one associated with it.
     fn classify_char(&self) -> Option<&'static str> {
@@ -221,7 +485,19 @@ impl<'a, 'dir> FileName<'a, 'dir> {
     /// So in that situation, those characters will be escaped and highlighted in
     /// a different colour.
     fn coloured_file_name<'unused>(&self) -> Vec<ANSIString<'unused>> {
-        let file_style = self.style();
+        let mut file_style = self.style();
+        if self.recent.map(|r| r.includes(self.file)).unwrap_or(false) {
+            file_style = overlay(file_style, self.colours.recent);
+        }
+
+        if self.file.flags().is_immutable() {
+            file_style = overlay(file_style, self.colours.immutable);
+        }
+
+        if self.dim_ignored && self.file.is_git_ignored() {
+            file_style = overlay(file_style, self.colours.git.ignored);
+        }
+
         let mut bits = Vec::new();
         escape(self.file.name.clone(), &mut bits, file_style, self.colours.control_char);
         bits
@@ -244,10 +520,32 @@ impl<'a, 'dir> FileName<'a, 'dir> {
             }
         }
 
+        // When colouring by access, what the user can do to the file matters
+        // more than what type it is. Symlinks always look like they can be
+        // read and written to, so they keep their usual colour.
+        if let Some(identity) = self.access {
+            if !self.file.is_link() {
+                return match self.file.access(identity) {
+                    f::Access::Full      => self.colours.access.full,
+                    f::Access::ReadOnly  => self.colours.access.read_only,
+                    f::Access::Denied    => self.colours.access.denied,
+                };
+            }
+        }
+
+        // Users can override the style of regular files based on their
+        // extensions, but this doesn’t apply to directories or executables,
+        // which get styled based on what they are rather than their names.
+        if self.file.is_file() && !self.file.is_executable_file() {
+            if let Some(style) = self.ext_colours.style(self.file) {
+                return style;
+            }
+        }
+
         // Otherwise, just apply a bunch of rules in order. For example,
         // executable image files should be executable rather than images.
         match self.file {
-            f if f.is_directory()        => self.colours.filetypes.directory,
+            f if f.is_directory()        => self.directory_style(),
             f if f.is_executable_file()  => self.colours.filetypes.executable,
             f if f.is_link()             => self.colours.filetypes.symlink,
             f if f.is_pipe()             => self.colours.filetypes.pipe,
@@ -269,4 +567,355 @@ impl<'a, 'dir> FileName<'a, 'dir> {
             _                                => self.colours.filetypes.normal,
         }
     }
+
+    /// Picks the style for a directory, which depends on whether others can
+    /// write to it, and whether it has the sticky bit set: a directory that
+    /// anyone can write to, without the sticky bit stopping them deleting
+    /// each other’s files, is worth pointing out. These are the same cases
+    /// as the `ow`, `st`, and `tw` keys that `dircolors` uses.
+    fn directory_style(&self) -> Style {
+        let permissions = self.file.permissions();
+        match (permissions.sticky, permissions.other_write) {
+            (true,  true)   => self.colours.filetypes.sticky_other_writable,
+            (false, true)   => self.colours.filetypes.other_writable,
+            (true,  false)  => self.colours.filetypes.sticky,
+            (false, false)  => self.colours.filetypes.directory,
+        }
+    }
+}
+
+/// Adds the attributes of the `extra` style, such as boldness, on top of the
+/// given style, and replaces its colours only if `extra` has any of its own.
+fn overlay(mut style: Style, extra: Style) -> Style {
+    style.foreground = extra.foreground.or(style.foreground);
+    style.background = extra.background.or(style.background);
+    style.is_bold          |= extra.is_bold;
+    style.is_dimmed        |= extra.is_dimmed;
+    style.is_italic        |= extra.is_italic;
+    style.is_underline     |= extra.is_underline;
+    style.is_blink         |= extra.is_blink;
+    style.is_reverse       |= extra.is_reverse;
+    style.is_hidden        |= extra.is_hidden;
+    style.is_strikethrough |= extra.is_strikethrough;
+    style
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs::{self, create_dir_all};
+
+    use ansi_term::Colour::*;
+
+    #[test]
+    fn parse_empty() {
+        assert_eq!(Ok(ExtensionColours::default()), ExtensionColours::parse("\n# nothing here\n"));
+    }
+
+    #[test]
+    fn parse_missing_equals() {
+        assert_eq!(Err("line 1: expected ext=style, got \"foo\"".to_string()), ExtensionColours::parse("foo"));
+    }
+
+    #[test]
+    fn custom_extension() {
+        let scratch = ScratchDir::new("ext-colours-style");
+        let dir = &scratch.path;
+        for name in &[ "song.FOO", "song.mp3", "song.ogg" ] {
+            let _ = fs::File::create(dir.join(name)).unwrap();
+        }
+
+        let style = FileStyle {
+            classify: Classify::JustFilenames,
+            icons: Icons::Off,
+            exts: FileExtensions,
+            ext_colours: ExtensionColours::parse("*.foo=01;35\n.mp3=32").unwrap(),
+            recent: None,
+            dim_ignored: false,
+            access: None,
+            link_target: TargetPath::Raw,
+        };
+
+        let colours = Colours::colourful(false);
+        let style_of = |name: &str| {
+            let file = File::new(dir.join(name), None, None).unwrap();
+            style.for_file(&file, &colours).style()
+        };
+
+        assert_eq!(Purple.bold(), style_of("song.FOO"));
+        assert_eq!(Green.normal(), style_of("song.mp3"));
+        assert_eq!(colours.filetypes.music, style_of("song.ogg"));
+    }
+
+    fn globs(keys: &[(&str, Style)]) -> ExtensionColours {
+        let mut colours = ExtensionColours::default();
+        for &(key, style) in keys {
+            colours.add_glob(glob::Pattern::new(key).unwrap(), style);
+        }
+        colours
+    }
+
+    #[test]
+    fn longer_globs_win() {
+        let colours = globs(&[ ("*.min.js", Fixed(240).normal()), ("*.js", Yellow.normal()) ]);
+        assert_eq!(Some(Fixed(240).normal()), colours.glob_style("app.min.js"));
+        assert_eq!(Some(Yellow.normal()),     colours.glob_style("app.js"));
+        assert_eq!(None,                      colours.glob_style("app.json"));
+    }
+
+    #[test]
+    fn exact_names_win() {
+        let colours = globs(&[ ("app.min.js", Red.bold()), ("*.js", Yellow.normal()), ("*.min.js", Fixed(240).normal()) ]);
+        assert_eq!(Some(Red.bold()),          colours.glob_style("app.min.js"));
+        assert_eq!(Some(Fixed(240).normal()), colours.glob_style("lib.min.js"));
+        assert_eq!(Some(Yellow.normal()),     colours.glob_style("lib.js"));
+    }
+
+    #[test]
+    fn later_globs_win_ties() {
+        let colours = globs(&[ ("*.js", Yellow.normal()), ("*.js", Blue.normal()) ]);
+        assert_eq!(Some(Blue.normal()), colours.glob_style("app.js"));
+    }
+
+    #[test]
+    fn globs_before_extensions() {
+        let scratch = ScratchDir::new("ext-colours-globs");
+        let dir = &scratch.path;
+        for name in &[ "Makefile", "app.min.js", "app.js" ] {
+            let _ = fs::File::create(dir.join(name)).unwrap();
+        }
+
+        let mut ext_colours = ExtensionColours::parse("js=32").unwrap();
+        ext_colours.add_glob(glob::Pattern::new("Makefile").unwrap(), Yellow.underline());
+        ext_colours.add_glob(glob::Pattern::new("*.min.js").unwrap(), Fixed(240).normal());
+
+        let style = FileStyle {
+            classify: Classify::JustFilenames,
+            icons: Icons::Off,
+            exts: FileExtensions,
+            ext_colours,
+            recent: None,
+            dim_ignored: false,
+            access: None,
+            link_target: TargetPath::Raw,
+        };
+
+        let colours = Colours::colourful(false);
+        let style_of = |name: &str| {
+            let file = File::new(dir.join(name), None, None).unwrap();
+            style.for_file(&file, &colours).style()
+        };
+
+        assert_eq!(Yellow.underline(),   style_of("Makefile"));
+        assert_eq!(Fixed(240).normal(),  style_of("app.min.js"));
+        assert_eq!(Green.normal(),       style_of("app.js"));
+    }
+
+    #[test]
+    fn writable_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let scratch = ScratchDir::new("writable-dirs");
+        let dir = &scratch.path;
+        for &(name, mode) in &[ ("normal", 0o755), ("other-writable", 0o777), ("sticky", 0o1755), ("both", 0o1777) ] {
+            let path = dir.join(name);
+            create_dir_all(&path).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+
+        let style = FileStyle {
+            classify: Classify::JustFilenames,
+            icons: Icons::Off,
+            exts: FileExtensions,
+            ext_colours: ExtensionColours::default(),
+            recent: None,
+            dim_ignored: false,
+            access: None,
+            link_target: TargetPath::Raw,
+        };
+
+        let colours = Colours::colourful(false);
+        let style_of = |name: &str| {
+            let file = File::new(dir.join(name), None, None).unwrap();
+            style.for_file(&file, &colours).style()
+        };
+
+        assert_eq!(Blue.bold(),         style_of("normal"));
+        assert_eq!(Blue.on(Green),      style_of("other-writable"));
+        assert_eq!(White.on(Blue),      style_of("sticky"));
+        assert_eq!(Black.on(Green),     style_of("both"));
+    }
+
+    fn recent_files(time: i64) -> RecentFiles {
+        RecentFiles { time_type: TimeType::Modified, now: time }
+    }
+
+    #[test]
+    fn recent_boundary() {
+        let scratch = ScratchDir::new("recent-boundary");
+        let path = scratch.join("file");
+        let _ = fs::File::create(&path).unwrap();
+        let file = File::new(path.clone(), None, None).unwrap();
+        let modified = i64::from(file.modified_time().seconds);
+
+        assert_eq!(true,  recent_files(modified).includes(&file));
+        assert_eq!(true,  recent_files(modified + RECENT_SECONDS - 1).includes(&file));
+        assert_eq!(false, recent_files(modified + RECENT_SECONDS).includes(&file));
+        assert_eq!(false, recent_files(modified + RECENT_SECONDS + 1).includes(&file));
+        assert_eq!(false, recent_files(modified - 1).includes(&file));
+    }
+
+    #[test]
+    fn recent_names_are_bold() {
+        let scratch = ScratchDir::new("recent-names");
+        let dir = &scratch.path;
+        let _ = fs::File::create(dir.join("file")).unwrap();
+        create_dir_all(dir.join("dir")).unwrap();
+
+        let colours = Colours::colourful(false);
+        let paint = |name: &str, seconds_later: i64| {
+            let file = File::new(dir.join(name), None, None).unwrap();
+            let now = i64::from(file.modified_time().seconds) + seconds_later;
+
+            let style = FileStyle {
+                classify: Classify::JustFilenames,
+                icons: Icons::Off,
+                exts: FileExtensions,
+                ext_colours: ExtensionColours::default(),
+                recent: Some(recent_files(now)),
+                dim_ignored: false,
+                access: None,
+                link_target: TargetPath::Raw,
+            };
+
+            style.for_file(&file, &colours).coloured_file_name()
+        };
+
+        assert_eq!(vec![ Style::default().bold().paint("file") ], paint("file", 60));
+        assert_eq!(vec![ Style::default().paint("file") ],        paint("file", RECENT_SECONDS + 60));
+        assert_eq!(vec![ Blue.bold().paint("dir") ],              paint("dir", 60));
+    }
+
+    #[test]
+    fn names_coloured_by_access() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let scratch = ScratchDir::new("access-names");
+        let dir = &scratch.path;
+        for &(name, mode) in &[ ("full", 0o666), ("read-only", 0o444), ("denied", 0o000) ] {
+            let path = dir.join(name);
+            let _ = fs::File::create(&path).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+
+        let colours = Colours::colourful(false);
+        let paint = |name: &str| {
+            let file = File::new(dir.join(name), None, None).unwrap();
+
+            // Someone who doesn’t own the file, so the other bits apply.
+            let identity = Identity { uid: file.user().0 + 1, gids: Vec::new() };
+
+            let style = FileStyle {
+                classify: Classify::JustFilenames,
+                icons: Icons::Off,
+                exts: FileExtensions,
+                ext_colours: ExtensionColours::default(),
+                recent: None,
+                dim_ignored: false,
+                access: Some(identity),
+                link_target: TargetPath::Raw,
+            };
+
+            style.for_file(&file, &colours).style()
+        };
+
+        assert_eq!(colours.access.full,      paint("full"));
+        assert_eq!(colours.access.read_only, paint("read-only"));
+        assert_eq!(colours.access.denied,    paint("denied"));
+    }
+
+    #[test]
+    fn icons_for_files_only() {
+        use fs::{Dir, DotFilter};
+
+        let scratch = ScratchDir::new("icons-files-only");
+        let dir = &scratch.path;
+        create_dir_all(dir.join("directory")).unwrap();
+        let _ = fs::File::create(dir.join("file.png")).unwrap();
+
+        let listing = Dir::read_dir(dir.clone(), None).unwrap();
+        let colours = Colours::plain();
+        let painted = |icons, name: &str| {
+            let style = FileStyle {
+                classify: Classify::JustFilenames,
+                icons,
+                exts: FileExtensions,
+                ext_colours: ExtensionColours::default(),
+                recent: None,
+                dim_ignored: false,
+                access: None,
+                link_target: TargetPath::Raw,
+            };
+
+            let file = listing.files(DotFilter::JustFiles).map(Result::unwrap).find(|f| f.name == name).unwrap();
+            let contents = style.for_file(&file, &colours).paint();
+            (contents.strings().to_string(), *contents.width())
+        };
+
+        assert_eq!(painted(Icons::FilesOnly, "directory"), ("directory".to_string(), 9));
+        assert_eq!(painted(Icons::FilesOnly, "file.png"),  ("\u{f1c5} file.png".to_string(), 10));
+        assert_eq!(painted(Icons::All, "directory"),       ("\u{f115} directory".to_string(), 11));
+        assert_eq!(painted(Icons::Off, "file.png"),        ("file.png".to_string(), 8));
+    }
+}
+
+
+#[cfg(all(test, feature="git"))]
+mod test_ignored {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs;
+    use std::io::Write;
+    use std::process::Command;
+
+    use fs::{Dir, DotFilter};
+    use fs::feature::GitOptions;
+
+    #[test]
+    fn ignored_names_are_dimmed() {
+        let scratch = ScratchDir::new("dim-ignored");
+        let path = &scratch.path;
+
+        fs::File::create(path.join(".gitignore")).unwrap().write_all(b"ignored\n").unwrap();
+        let _ = fs::File::create(path.join("ignored")).unwrap();
+        let _ = fs::File::create(path.join("shown")).unwrap();
+        assert!(Command::new("git").arg("init").arg("-q").current_dir(&path).status().unwrap().success());
+
+        let dir = Dir::read_dir(path.clone(), Some(&GitOptions::default())).unwrap();
+        let colours = Colours::colourful(false);
+        let style = FileStyle {
+            classify: Classify::JustFilenames,
+            icons: Icons::Off,
+            exts: FileExtensions,
+            ext_colours: ExtensionColours::default(),
+            recent: None,
+            dim_ignored: true,
+            access: None,
+            link_target: TargetPath::Raw,
+        };
+
+        let mut painted = Vec::new();
+        for file in dir.files(DotFilter::JustFiles) {
+            let file = file.unwrap();
+            painted.push((file.name.clone(), style.for_file(&file, &colours).paint().strings().to_string()));
+        }
+        painted.sort();
+
+        assert_eq!(painted, vec![
+            ("ignored".to_string(), "\x1B[2mignored\x1B[0m".to_string()),
+            ("shown".to_string(),   "shown".to_string()),
+        ]);
+    }
 }
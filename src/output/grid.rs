@@ -1,4 +1,4 @@
-use std::io::{Write, Result as IOResult};
+use std::io::{Write, stderr, Result as IOResult};
 
 use term_grid as tg;
 
@@ -11,6 +11,14 @@ use output::file_name::FileStyle;
 pub struct Options {
     pub across: bool,
     pub console_width: usize,
+
+    /// The number of spaces between each column. This counts towards the
+    /// width of the grid, so it changes how many columns fit.
+    pub spacing: usize,
+
+    /// Whether to print the width and the layout the grid was given to
+    /// stderr before displaying it.
+    pub print_width: bool,
 }
 
 impl Options {
@@ -18,6 +26,23 @@ impl Options {
         if self.across { tg::Direction::LeftToRight }
                   else { tg::Direction::TopToBottom }
     }
+
+    /// Describes how a grid of cells with the given widths was laid out
+    /// over the given number of rows, for `--print-width`.
+    pub fn width_report(&self, cell_widths: &[usize], rows: usize) -> String {
+        let rows = rows.max(1);
+        let columns = (cell_widths.len() + rows - 1) / rows;
+        let mut column_widths = vec![0; columns];
+
+        for (i, width) in cell_widths.iter().enumerate() {
+            let column = if self.across { i % columns } else { i / rows };
+            column_widths[column] = column_widths[column].max(*width);
+        }
+
+        let column_widths: Vec<String> = column_widths.iter().map(|w| w.to_string()).collect();
+        format!("width: {}, columns: {}, column widths: [{}], spacing: {}",
+                self.console_width, columns, column_widths.join(", "), self.spacing)
+    }
 }
 
 
@@ -32,14 +57,16 @@ impl<'a> Render<'a> {
     pub fn render<W: Write>(&self, w: &mut W) -> IOResult<()> {
         let mut grid = tg::Grid::new(tg::GridOptions {
             direction:  self.opts.direction(),
-            filling:    tg::Filling::Spaces(2),
+            filling:    tg::Filling::Spaces(self.opts.spacing),
         });
 
         grid.reserve(self.files.len());
+        let mut widths = Vec::with_capacity(self.files.len());
 
         for file in self.files.iter() {
             let filename = self.style.for_file(file, self.colours).paint();
             let width = filename.width();
+            widths.push(*width);
 
             grid.add(tg::Cell {
                 contents:  filename.strings().to_string(),
@@ -47,7 +74,14 @@ impl<'a> Render<'a> {
             });
         }
 
-        if let Some(display) = grid.fit_into_width(self.opts.console_width) {
+        let display = grid.fit_into_width(self.opts.console_width);
+
+        if self.opts.print_width {
+            let rows = display.as_ref().map(|d| d.row_count()).unwrap_or(widths.len());
+            writeln!(stderr(), "{}", self.opts.width_report(&widths, rows))?;
+        }
+
+        if let Some(display) = display {
             write!(w, "{}", display)
         }
         else {
@@ -62,3 +96,86 @@ impl<'a> Render<'a> {
         }
     }
 }
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use fs::{Dir, DotFilter, TargetPath};
+    use output::file_name::{Classify, Icons, ExtensionColours};
+    use info::filetype::FileExtensions;
+
+    /// The names on the first line of a grid of eight five-letter names,
+    /// thirty characters wide.
+    fn first_line(spacing: usize) -> Vec<String> {
+        let scratch = ScratchDir::new(&format!("grid-spacing-{}", spacing));
+        let path = &scratch.path;
+        for i in 0 .. 8 {
+            let _ = std_fs::File::create(path.join(format!("file{}", i))).unwrap();
+        }
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let colours = Colours::plain();
+        let style = FileStyle { classify: Classify::JustFilenames, icons: Icons::Off, exts: FileExtensions, ext_colours: ExtensionColours::default(), recent: None, dim_ignored: false, access: None, link_target: TargetPath::Raw };
+        let opts = Options { across: false, console_width: 30, spacing, print_width: false };
+        let mut output = Vec::new();
+        Render { files, colours: &colours, style: &style, opts: &opts }.render(&mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        output.lines().next().unwrap().split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn narrow_spacing() {
+        assert_eq!(first_line(1), vec![ "file0", "file2", "file4", "file6" ]);
+    }
+
+    #[test]
+    fn wide_spacing() {
+        assert_eq!(first_line(4), vec![ "file0", "file3", "file6" ]);
+    }
+
+    #[test]
+    fn directory_slashes_take_up_width() {
+        let scratch = ScratchDir::new("grid-slash-dirs");
+        let path = &scratch.path;
+        for i in 0 .. 8 {
+            std_fs::create_dir(path.join(format!("dir{}", i))).unwrap();
+        }
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Three columns of the names without their slashes would fit into
+        // fourteen characters, but only two fit with them.
+        let colours = Colours::plain();
+        let style = FileStyle { classify: Classify::AddDirectorySlashes, icons: Icons::Off, exts: FileExtensions, ext_colours: ExtensionColours::default(), recent: None, dim_ignored: false, access: None, link_target: TargetPath::Raw };
+        let opts = Options { across: false, console_width: 14, spacing: 1, print_width: false };
+        let mut output = Vec::new();
+        Render { files, colours: &colours, style: &style, opts: &opts }.render(&mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, "dir0/ dir4/\ndir1/ dir5/\ndir2/ dir6/\ndir3/ dir7/\n");
+    }
+
+    #[test]
+    fn width_report_contains_width() {
+        let opts = Options { across: false, console_width: 37, spacing: 2, print_width: true };
+        let report = opts.width_report(&[ 5, 3, 8, 4, 6 ], 2);
+        assert_eq!(report, "width: 37, columns: 3, column widths: [5, 8, 6], spacing: 2");
+    }
+
+    #[test]
+    fn width_report_across() {
+        let opts = Options { across: true, console_width: 20, spacing: 1, print_width: true };
+        let report = opts.width_report(&[ 5, 3, 8, 4, 6 ], 2);
+        assert_eq!(report, "width: 20, columns: 3, column widths: [5, 6, 8], spacing: 1");
+    }
+}
@@ -4,6 +4,7 @@ use ansi_term::ANSIStrings;
 use term_grid as grid;
 
 use fs::{Dir, File};
+use fs::feature::acl;
 use fs::feature::xattr::FileAttributes;
 use fs::filter::FileFilter;
 
@@ -24,6 +25,10 @@ pub struct Render<'a> {
     pub grid: &'a GridOptions,
     pub details: &'a DetailsOptions,
     pub filter: &'a FileFilter,
+
+    /// How many directories down from the arguments the files are, for the
+    /// context column.
+    pub depth: usize,
 }
 
 impl<'a> Render<'a> {
@@ -36,6 +41,9 @@ impl<'a> Render<'a> {
             opts: self.details,
             recurse: None,
             filter: self.filter,
+            git: None,
+            dividers: None,
+            depth: self.depth,
         }
     }
 
@@ -45,10 +53,16 @@ impl<'a> Render<'a> {
 
         let drender = self.clone().details();
 
-        let (first_table, _) = self.make_table(options, &drender);
+        let (mut first_table, _) = self.make_table(options, &drender);
+        first_table.add_symlink_targets(&self.files);
+        first_table.set_context_depth(self.depth);
+        first_table.set_size_total(&self.files);
 
         let rows = self.files.iter()
-                       .map(|file| first_table.row_for_file(file, file_has_xattrs(file)))
+                       .map(|file| {
+                           let (xattrs, acl) = file_attribute_flags(file);
+                           first_table.row_for_file(file, xattrs, acl)
+                       })
                        .collect::<Vec<TableRow>>();
 
         let file_names = self.files.iter()
@@ -169,9 +183,11 @@ fn divide_rounding_up(a: usize, b: usize) -> usize {
 }
 
 
-fn file_has_xattrs(file: &File) -> bool {
+/// Whether the file has any extended attributes, and whether it has an ACL,
+/// for the indicators in the permissions column.
+fn file_attribute_flags(file: &File) -> (bool, bool) {
     match file.path.attributes() {
-        Ok(attrs) => !attrs.is_empty(),
-        Err(_) => false,
+        Ok(attrs) => (attrs.iter().any(|a| !acl::is_acl_attribute(a)), acl::has_extended_acl(&attrs)),
+        Err(_) => (false, false),
     }
 }
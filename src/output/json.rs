@@ -0,0 +1,424 @@
+//! The **JSON** view prints a tree of files as a nested JSON document, so
+//! it can be read by other programs without having to parse the glyphs of
+//! the tree view.
+//!
+//! The document is an object with a `files` array, holding an object for
+//! each file given on the command-line, and an `errors` array. Each file
+//! has its name and type, and directories that were recursed into have a
+//! `children` array holding an object for each file inside them, nested as
+//! deep as the tree goes:
+//!
+//! ```text
+//!     {
+//!       "files": [
+//!         {
+//!           "name": "src",
+//!           "type": "directory",
+//!           "children": [
+//!             {
+//!               "name": "main.rs",
+//!               "type": "file"
+//!             }
+//!           ]
+//!         }
+//!       ],
+//!       "errors": [
+//!         {
+//!           "path": "missing",
+//!           "code": 2,
+//!           "message": "No such file or directory (os error 2)"
+//!         }
+//!       ]
+//!     }
+//! ```
+//!
+//! A directory that couldn’t be read has an `error` string in place of its
+//! children, and a directory that’s also one of its own parents, which
+//! would make the tree go on forever, has `"loop": true` instead.
+//!
+//! Every argument that couldn’t be listed, and every directory or entry in
+//! the tree that couldn’t be read, gets an object in the `errors` array,
+//! with its path, the number of the OS error if there was one or `null` if
+//! not, and the same message that gets printed to stderr.
+
+use std::io::{Write, Error as IOError, Result as IOResult};
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+
+use fs::File;
+use fs::dir_action::RecurseOptions;
+use fs::feature::GitOptions;
+use fs::filter::FileFilter;
+
+
+pub struct Render<'a> {
+    pub files: Vec<File<'a>>,
+
+    /// The arguments that couldn’t be listed, along with their errors,
+    /// which go first in the document’s errors.
+    pub failed: Vec<(PathBuf, IOError)>,
+
+    /// How deep to recurse into directories, if at all.
+    pub recurse: Option<RecurseOptions>,
+
+    /// How to sort and filter the files inside each directory.
+    pub filter: &'a FileFilter,
+
+    /// The options to scan the directories with, if their files’ Git
+    /// statuses are needed.
+    pub git: Option<&'a GitOptions>,
+}
+
+/// One file in the tree, along with everything underneath it.
+#[derive(PartialEq, Debug)]
+struct Node {
+    name: String,
+    kind: &'static str,
+    contents: Contents,
+}
+
+/// What’s known about the files inside a node.
+#[derive(PartialEq, Debug)]
+enum Contents {
+
+    /// This isn’t a directory, or it wasn’t recursed into.
+    Nothing,
+
+    /// This directory was read, and had these files in it.
+    Children(Vec<Node>),
+
+    /// This directory couldn’t be read.
+    Unreadable(String),
+
+    /// This directory is one of its own parents.
+    Loop,
+}
+
+impl<'a> Render<'a> {
+
+    /// Renders the files, returning the paths of any directories or
+    /// entries that couldn’t be read, along with their errors, so they can
+    /// be reported after the document as well as in it.
+    pub fn render<W: Write>(mut self, w: &mut W) -> IOResult<Vec<(PathBuf, IOError)>> {
+        let mut unreadable = Vec::new();
+
+        self.filter.sort_files(&mut self.files);
+        let nodes: Vec<Node> = self.files.iter()
+                                   .map(|f| self.node(f, 0, &mut Vec::new(), &mut unreadable))
+                                   .collect();
+
+        let errors: Vec<&(PathBuf, IOError)> = self.failed.iter().chain(unreadable.iter()).collect();
+
+        let mut output = String::new();
+        output.push_str("{\n");
+        push_indent(&mut output, 1);
+        output.push_str("\"files\": ");
+        write_nodes(&mut output, &nodes, 1);
+        output.push_str(",\n");
+        push_indent(&mut output, 1);
+        output.push_str("\"errors\": ");
+        write_errors(&mut output, &errors, 1);
+        output.push_str("\n}");
+        writeln!(w, "{}", output)?;
+
+        Ok(unreadable)
+    }
+
+    /// Builds the node for the given file, reading the directory it is if
+    /// the tree goes that deep. The ancestors are the device and inode
+    /// numbers of every directory above this one, which get checked so a
+    /// directory that contains itself isn’t read forever.
+    fn node(&self, file: &File, depth: usize, ancestors: &mut Vec<(u64, u64)>, unreadable: &mut Vec<(PathBuf, IOError)>) -> Node {
+        let name = file.name.clone();
+        let kind = kind_of(file);
+
+        let recurse = match self.recurse {
+            Some(r)  => r.tree && file.is_directory() && !r.is_too_deep(depth),
+            None     => false,
+        };
+
+        if !recurse {
+            return Node { name, kind, contents: Contents::Nothing };
+        }
+
+        let id = (file.metadata.dev(), file.metadata.ino());
+        if ancestors.contains(&id) {
+            return Node { name, kind, contents: Contents::Loop };
+        }
+
+        let dir = match self.filter.read_dir(file, self.git) {
+            Ok(d)   => d,
+            Err(e)  => {
+                let contents = Contents::Unreadable(e.to_string());
+                unreadable.push((file.path.clone(), e));
+                return Node { name, kind, contents };
+            },
+        };
+
+        let mut files = Vec::new();
+        for file_to_add in dir.files(self.filter.dot_filter) {
+            match file_to_add {
+                Ok(f)           => files.push(f),
+                Err((path, e))  => unreadable.push((path, e)),
+            }
+        }
+
+        self.filter.filter_child_files(&mut files, depth);
+        self.filter.sort_files(&mut files);
+        self.filter.keep_top(&mut files);
+
+        ancestors.push(id);
+        let children = files.iter().map(|f| self.node(f, depth + 1, ancestors, unreadable)).collect();
+        let _ = ancestors.pop();
+
+        Node { name, kind, contents: Contents::Children(children) }
+    }
+}
+
+
+/// The word for the given file’s type.
+fn kind_of(file: &File) -> &'static str {
+    if file.is_directory()          { "directory" }
+    else if file.is_link()          { "link" }
+    else if file.is_pipe()          { "pipe" }
+    else if file.is_socket()        { "socket" }
+    else if file.is_char_device()   { "char-device" }
+    else if file.is_block_device()  { "block-device" }
+    else if file.is_file()          { "file" }
+    else                            { "special" }
+}
+
+/// Writes an array of nodes, indented by the given number of levels.
+fn write_nodes(output: &mut String, nodes: &[Node], indent: usize) {
+    if nodes.is_empty() {
+        output.push_str("[]");
+        return;
+    }
+
+    output.push_str("[\n");
+
+    for (index, node) in nodes.iter().enumerate() {
+        push_indent(output, indent + 1);
+        write_node(output, node, indent + 1);
+
+        if index + 1 < nodes.len() {
+            output.push(',');
+        }
+
+        output.push('\n');
+    }
+
+    push_indent(output, indent);
+    output.push(']');
+}
+
+/// Writes one node as an object, indented by the given number of levels.
+fn write_node(output: &mut String, node: &Node, indent: usize) {
+    output.push_str("{\n");
+
+    push_indent(output, indent + 1);
+    output.push_str(&format!("\"name\": {},\n", json_string(&node.name)));
+    push_indent(output, indent + 1);
+    output.push_str(&format!("\"type\": {}", json_string(node.kind)));
+
+    match node.contents {
+        Contents::Nothing => {},
+
+        Contents::Children(ref children) => {
+            output.push_str(",\n");
+            push_indent(output, indent + 1);
+            output.push_str("\"children\": ");
+            write_nodes(output, children, indent + 1);
+        },
+
+        Contents::Unreadable(ref error) => {
+            output.push_str(",\n");
+            push_indent(output, indent + 1);
+            output.push_str(&format!("\"error\": {}", json_string(error)));
+        },
+
+        Contents::Loop => {
+            output.push_str(",\n");
+            push_indent(output, indent + 1);
+            output.push_str("\"loop\": true");
+        },
+    }
+
+    output.push('\n');
+    push_indent(output, indent);
+    output.push('}');
+}
+
+/// Writes an array of errors, each as an object with the path it happened
+/// at, the number of the OS error, and its message, indented by the given
+/// number of levels.
+fn write_errors(output: &mut String, errors: &[&(PathBuf, IOError)], indent: usize) {
+    if errors.is_empty() {
+        output.push_str("[]");
+        return;
+    }
+
+    output.push_str("[\n");
+
+    for (index, &&(ref path, ref error)) in errors.iter().enumerate() {
+        let code = match error.raw_os_error() {
+            Some(code)  => code.to_string(),
+            None        => "null".into(),
+        };
+
+        push_indent(output, indent + 1);
+        output.push_str("{\n");
+        push_indent(output, indent + 2);
+        output.push_str(&format!("\"path\": {},\n", json_string(&path.to_string_lossy())));
+        push_indent(output, indent + 2);
+        output.push_str(&format!("\"code\": {},\n", code));
+        push_indent(output, indent + 2);
+        output.push_str(&format!("\"message\": {}\n", json_string(&error.to_string())));
+        push_indent(output, indent + 1);
+        output.push('}');
+
+        if index + 1 < errors.len() {
+            output.push(',');
+        }
+
+        output.push('\n');
+    }
+
+    push_indent(output, indent);
+    output.push(']');
+}
+
+fn push_indent(output: &mut String, indent: usize) {
+    for _ in 0 .. indent {
+        output.push_str("  ");
+    }
+}
+
+/// Quotes a string for JSON, escaping quotes, backslashes, and control
+/// characters.
+fn json_string(input: &str) -> String {
+    let mut quoted = String::with_capacity(input.len() + 2);
+    quoted.push('"');
+
+    for c in input.chars() {
+        match c {
+            '"'   => quoted.push_str("\\\""),
+            '\\'  => quoted.push_str("\\\\"),
+            '\n'  => quoted.push_str("\\n"),
+            '\r'  => quoted.push_str("\\r"),
+            '\t'  => quoted.push_str("\\t"),
+            c if c < (0x20 as char)  => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c     => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use fs::dir_action::RecurseOrder;
+
+    fn tree(max_depth: Option<usize>) -> Option<RecurseOptions> {
+        Some(RecurseOptions { tree: true, max_depth, order: RecurseOrder::DepthFirst })
+    }
+
+    fn render(files: Vec<File>, recurse: Option<RecurseOptions>) -> String {
+        let filter = FileFilter::default();
+        let mut output = Vec::new();
+        let _ = Render { files, failed: Vec::new(), recurse, filter: &filter, git: None }.render(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("a \"b\" c"), "\"a \\\"b\\\" c\"");
+        assert_eq!(json_string("back\\slash"), "\"back\\\\slash\"");
+        assert_eq!(json_string("new\nline\t\u{1b}"), "\"new\\nline\\t\\u001b\"");
+    }
+
+    #[test]
+    fn nested() {
+        let scratch = ScratchDir::new("json-tree");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("src/empty")).unwrap();
+        let _ = std_fs::File::create(path.join("src/main.rs")).unwrap();
+        let _ = std_fs::File::create(path.join("README")).unwrap();
+
+        let root = File::new(path.clone(), None, "root".to_string()).unwrap();
+        let output = render(vec![ root ], tree(None));
+
+        let expected = "\
+{
+  \"files\": [
+    {
+      \"name\": \"root\",
+      \"type\": \"directory\",
+      \"children\": [
+        {
+          \"name\": \"README\",
+          \"type\": \"file\"
+        },
+        {
+          \"name\": \"src\",
+          \"type\": \"directory\",
+          \"children\": [
+            {
+              \"name\": \"empty\",
+              \"type\": \"directory\",
+              \"children\": []
+            },
+            {
+              \"name\": \"main.rs\",
+              \"type\": \"file\"
+            }
+          ]
+        }
+      ]
+    }
+  ],
+  \"errors\": []
+}
+";
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn level_limit() {
+        let scratch = ScratchDir::new("json-level");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("a/b")).unwrap();
+
+        let root = File::new(path.clone(), None, "root".to_string()).unwrap();
+        let output = render(vec![ root ], tree(Some(1)));
+        assert!(output.contains("\"name\": \"a\",\n          \"type\": \"directory\"\n"), "{}", output);
+        assert!(!output.contains("\"b\""), "{}", output);
+    }
+
+    #[test]
+    fn loops_are_marked() {
+        let scratch = ScratchDir::new("json-loop");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("inner")).unwrap();
+
+        // Pretend the directory has already been seen above itself, as it
+        // would be if a mount put it inside itself.
+        let root = File::new(path.clone(), None, "root".to_string()).unwrap();
+        let mut ancestors = vec![ (root.metadata.dev(), root.metadata.ino()) ];
+
+        let filter = FileFilter::default();
+        let render = Render { files: Vec::new(), failed: Vec::new(), recurse: tree(None), filter: &filter, git: None };
+        let node = render.node(&root, 0, &mut ancestors, &mut Vec::new());
+        assert_eq!(node, Node { name: "root".into(), kind: "directory", contents: Contents::Loop });
+
+        let mut output = String::new();
+        write_nodes(&mut output, &[ node ], 0);
+        assert_eq!(output, "[\n  {\n    \"name\": \"root\",\n    \"type\": \"directory\",\n    \"loop\": true\n  }\n]");
+    }
+}
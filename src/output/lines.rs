@@ -3,8 +3,10 @@ use std::io::{Write, Result as IOResult};
 use ansi_term::ANSIStrings;
 
 use fs::File;
+use fs::filter::SortCase;
 
 use output::file_name::{FileName, FileStyle};
+use output::dividers::Dividers;
 use super::colours::Colours;
 
 
@@ -13,11 +15,21 @@ pub struct Render<'a> {
     pub files: Vec<File<'a>>,
     pub colours: &'a Colours,
     pub style: &'a FileStyle,
+
+    /// The case the files are sorted by name in, if there should be
+    /// dividers between the letters.
+    pub dividers: Option<SortCase>,
 }
 
 impl<'a> Render<'a> {
     pub fn render<W: Write>(&self, w: &mut W) -> IOResult<()> {
+        let mut dividers = self.dividers.map(Dividers::new);
+
         for file in &self.files {
+            if let Some(letter) = dividers.as_mut().and_then(|d| d.before(file)) {
+                writeln!(w, "{}", Dividers::render(letter, self.colours).strings())?;
+            }
+
             let name_cell = self.render_file(file).paint();
             writeln!(w, "{}", ANSIStrings(&name_cell))?;
         }
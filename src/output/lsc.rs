@@ -0,0 +1,205 @@
+//! Parsing the ANSI style codes used by `ls`-style colour configuration.
+//!
+//! Tools such as `dircolors` describe the way each kind of file should look
+//! using the raw numbers that make up an ANSI “Select Graphic Rendition”
+//! escape sequence, separated by semicolons: `01;34` is bold blue, and
+//! `38;5;208` is colour number 208 from the 256-colour palette. Reading
+//! styles in this format means users can copy them straight from their
+//! existing configuration.
+//!
+//! The same codes make up the values in the `LS_COLORS` environment
+//! variable, which can be split into its key-value pairs here.
+
+use ansi_term::Style;
+use ansi_term::Colour::{self, Black, Red, Green, Yellow, Blue, Purple, Cyan, White, Fixed};
+
+
+/// Parses a string of semicolon-separated ANSI codes into a `Style`,
+/// returning `None` if any of the codes aren’t recognised.
+///
+/// Empty codes are skipped over, so `01;;34` and `;01;34;` both parse the
+/// same way as `01;34`.
+pub fn parse_style(input: &str) -> Option<Style> {
+    let mut style = Style::default();
+    let mut codes = input.split(';').filter(|c| !c.is_empty());
+
+    while let Some(code) = codes.next() {
+        let number: u8 = match code.parse() {
+            Ok(n)   => n,
+            Err(_)  => return None,
+        };
+
+        match number {
+            0  => style = Style::default(),
+            1  => style.is_bold = true,
+            2  => style.is_dimmed = true,
+            3  => style.is_italic = true,
+            4  => style.is_underline = true,
+            5  => style.is_blink = true,
+            7  => style.is_reverse = true,
+            8  => style.is_hidden = true,
+            9  => style.is_strikethrough = true,
+
+            38 => match fixed_colour(&mut codes) {
+                Some(colour)  => style.foreground = Some(colour),
+                None          => return None,
+            },
+
+            48 => match fixed_colour(&mut codes) {
+                Some(colour)  => style.background = Some(colour),
+                None          => return None,
+            },
+
+            39 => style.foreground = None,
+            49 => style.background = None,
+
+            n if n >= 30 && n <= 37  => style.foreground = Some(basic_colour(n - 30)),
+            n if n >= 40 && n <= 47  => style.background = Some(basic_colour(n - 40)),
+
+            // The “bright” variants are the second eight of the palette.
+            n if n >= 90  && n <= 97   => style.foreground = Some(Fixed(n - 90 + 8)),
+            n if n >= 100 && n <= 107  => style.background = Some(Fixed(n - 100 + 8)),
+
+            _  => return None,
+        }
+    }
+
+    Some(style)
+}
+
+/// The contents of an `LS_COLORS` variable: a colon-separated list of
+/// `key=codes` pairs, where the key is either a two-letter code for a type
+/// of file, such as `di` for directories, or a glob, such as `*.txt`.
+pub struct LSColors<'var>(pub &'var str);
+
+impl<'var> LSColors<'var> {
+
+    /// Calls the given function with each pair that has both a key and a
+    /// value. Anything else gets skipped over, as with `ls`.
+    pub fn each_pair<C: FnMut(Pair<'var>)>(&self, mut callback: C) {
+        for next in self.0.split(':') {
+            let mut bits = next.splitn(2, '=');
+
+            if let (Some(key), Some(value)) = (bits.next(), bits.next()) {
+                if !key.is_empty() && !value.is_empty() {
+                    callback(Pair { key, value });
+                }
+            }
+        }
+    }
+}
+
+/// One of the pairs from an `LS_COLORS` variable.
+#[derive(PartialEq, Debug)]
+pub struct Pair<'var> {
+    pub key: &'var str,
+    pub value: &'var str,
+}
+
+impl<'var> Pair<'var> {
+
+    /// The style this pair’s codes describe, if they’re valid.
+    pub fn to_style(&self) -> Option<Style> {
+        parse_style(self.value)
+    }
+}
+
+
+/// Returns one of the eight basic colours, numbered from zero.
+fn basic_colour(number: u8) -> Colour {
+    match number {
+        0 => Black,
+        1 => Red,
+        2 => Green,
+        3 => Yellow,
+        4 => Blue,
+        5 => Purple,
+        6 => Cyan,
+        _ => White,
+    }
+}
+
+/// Reads the rest of a `38;5;n` or `48;5;n` sequence, having already read
+/// the first number.
+fn fixed_colour<'a, I: Iterator<Item=&'a str>>(codes: &mut I) -> Option<Colour> {
+    match (codes.next(), codes.next()) {
+        (Some("5"), Some(n))  => n.parse().ok().map(Fixed),
+        _                     => None,
+    }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        assert_eq!(Some(Style::default()), parse_style(""));
+    }
+
+    #[test]
+    fn bold_blue() {
+        assert_eq!(Some(Blue.bold()), parse_style("01;34"));
+    }
+
+    #[test]
+    fn extra_semicolons() {
+        assert_eq!(Some(Blue.bold()), parse_style(";01;;34;"));
+    }
+
+    #[test]
+    fn foreground_and_background() {
+        assert_eq!(Some(Red.on(Yellow)), parse_style("31;43"));
+    }
+
+    #[test]
+    fn bright() {
+        assert_eq!(Some(Fixed(9).normal()), parse_style("91"));
+    }
+
+    #[test]
+    fn palette() {
+        assert_eq!(Some(Fixed(208).underline()), parse_style("38;5;208;4"));
+    }
+
+    #[test]
+    fn reset() {
+        assert_eq!(Some(Green.normal()), parse_style("01;0;32"));
+    }
+
+    #[test]
+    fn unknown_code() {
+        assert_eq!(None, parse_style("01;66"));
+    }
+
+    #[test]
+    fn not_a_number() {
+        assert_eq!(None, parse_style("bold"));
+    }
+
+    #[test]
+    fn truncated_palette() {
+        assert_eq!(None, parse_style("38;5"));
+    }
+
+    fn pairs<'a>(input: &'a str) -> Vec<Pair<'a>> {
+        let mut pairs = Vec::new();
+        LSColors(input).each_pair(|p| pairs.push(p));
+        pairs
+    }
+
+    #[test]
+    fn ls_colors_pairs() {
+        assert_eq!(pairs("di=01;34:*.tar=01;31:"), vec![
+            Pair { key: "di",    value: "01;34" },
+            Pair { key: "*.tar", value: "01;31" },
+        ]);
+    }
+
+    #[test]
+    fn ls_colors_skips_incomplete() {
+        assert_eq!(pairs("::di:=01;34:ln=:ex=01;32"), vec![ Pair { key: "ex", value: "01;32" } ]);
+    }
+}
@@ -5,12 +5,18 @@ pub use self::colours::Colours;
 pub use self::escape::escape;
 
 pub mod details;
+pub mod dividers;
 pub mod file_name;
 pub mod grid_details;
 pub mod grid;
+pub mod json;
 pub mod lines;
+pub mod lsc;
+pub mod pager;
+pub mod summary;
 pub mod table;
 pub mod time;
+pub mod tsv;
 
 mod cell;
 mod colours;
@@ -25,6 +31,18 @@ pub struct View {
     pub mode: Mode,
     pub colours: Colours,
     pub style: FileStyle,
+
+    /// Whether to count the kinds of file after each list of them, and
+    /// which files to count if so.
+    pub summary: Option<summary::SummaryScope>,
+
+    /// Whether to put dividers between names starting with different
+    /// letters, when sorting by name.
+    pub alphabet_dividers: bool,
+
+    /// Whether to follow the path above each directory’s files with how
+    /// many there are and their total size, in the grid and lines views.
+    pub header_totals: bool,
 }
 
 
@@ -35,4 +53,6 @@ pub enum Mode {
     Details(details::Options),
     GridDetails(grid::Options, details::Options),
     Lines,
+    Tsv(tsv::Options),
+    Json,
 }
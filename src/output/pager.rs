@@ -0,0 +1,188 @@
+//! Sending output through a pager when it’s too long to fit on the screen.
+//!
+//! When paging is turned on, the output gets written to a buffer instead of
+//! the terminal, and it’s only once everything has been rendered that exa
+//! knows whether it fits. Short listings get printed as usual, and long
+//! ones get piped into the pager program.
+
+use std::ffi::OsString;
+use std::io::{self, Write, Stdout, stdout};
+use std::mem::replace;
+use std::process::{Command, Stdio};
+
+use libc;
+use term_size;
+
+
+/// The pager command to run when the output doesn’t fit on the screen.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Pager {
+
+    /// The command, which gets run by the shell, so it can contain
+    /// arguments (such as `less -R`).
+    pub command: OsString,
+}
+
+/// The pager to use when the user hasn’t specified one. `-R` keeps the
+/// colours, `-F` quits straight away if everything fits anyway, and `-X`
+/// stops the output from disappearing once the pager quits.
+pub const DEFAULT_PAGER: &str = "less -RFX";
+
+impl Pager {
+
+    /// Runs the pager, feeding it the given output, and waits for it to
+    /// finish. Returns `Ok(false)` if the pager couldn’t be started at all,
+    /// in which case the output hasn’t gone anywhere yet, and `Err` if it
+    /// was started but failed.
+    fn page(&self, output: &[u8]) -> io::Result<bool> {
+        let mut child = match Command::new("sh").arg("-c").arg(&self.command)
+                                                .stdin(Stdio::piped())
+                                                .spawn() {
+            Ok(child)  => child,
+            Err(_)     => return Ok(false),
+        };
+
+        // Interrupts from the terminal get sent to the whole process group,
+        // and the pager is the one that should handle them: exa has nothing
+        // left to do but wait, and dying now would leave the pager running
+        // with the terminal in a mess.
+        let previous = unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) };
+
+        let written = {
+            // The pager’s input gets closed at the end of this block, which
+            // lets it know there’s no more output coming.
+            let mut input = child.stdin.take().expect("Pager has no input");
+            match input.write_all(output) {
+                Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),  // quit early
+                result => result,
+            }
+        };
+
+        let status = child.wait();
+        let _ = unsafe { libc::signal(libc::SIGINT, previous) };
+
+        written?;
+        let status = status?;
+        if status.success() {
+            Ok(true)
+        }
+        else if status.code() == Some(127) {
+            // This is the shell’s way of saying the command wasn’t found,
+            // so the output never got shown anywhere.
+            Ok(false)
+        }
+        else {
+            Err(io::Error::new(io::ErrorKind::Other, format!("Pager exited with {}", status)))
+        }
+    }
+}
+
+
+/// Whether output with the given number of lines should be paged, on a
+/// screen with the given height. Nothing gets paged when there’s no
+/// screen, such as when the output is going into a pipe.
+///
+/// Output that’s exactly as tall as the screen still gets paged, because
+/// the shell prompt that comes afterwards would push its first line off.
+pub fn should_page(line_count: usize, screen_height: Option<usize>) -> bool {
+    match screen_height {
+        Some(height)  => line_count >= height,
+        None          => false,
+    }
+}
+
+/// The height of the terminal that stdout is connected to, if it is.
+fn screen_height() -> Option<usize> {
+    term_size::dimensions_stdout().map(|d| d.1)
+}
+
+
+/// Standard output, which may be buffered up so it can be paged afterwards.
+pub enum PagedOutput {
+
+    /// Output goes straight to the terminal, as usual.
+    Direct(Stdout),
+
+    /// Output is being collected, to be sent through this pager if it turns
+    /// out to be too long.
+    Buffered(Vec<u8>, Pager),
+}
+
+impl PagedOutput {
+
+    /// Writes to standard output directly.
+    pub fn stdout() -> PagedOutput {
+        PagedOutput::Direct(stdout())
+    }
+
+    /// Starts buffering output to send through the given pager, but only if
+    /// it’s going to a terminal: there’s no point when it isn’t.
+    pub fn page_with(&mut self, pager: Pager) {
+        if screen_height().is_some() {
+            *self = PagedOutput::Buffered(Vec::new(), pager);
+        }
+    }
+
+    /// Sends any buffered output either to the pager, or to standard output
+    /// if it fits on the screen or the pager can’t be run.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if let PagedOutput::Buffered(buffer, pager) = replace(self, PagedOutput::stdout()) {
+            let line_count = buffer.iter().filter(|&&b| b == b'\n').count();
+            if should_page(line_count, screen_height()) && pager.page(&buffer)? {
+                return Ok(());
+            }
+
+            self.write_all(&buffer)?;
+        }
+
+        self.flush()
+    }
+}
+
+impl Write for PagedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            PagedOutput::Direct(ref mut out)       => out.write(buf),
+            PagedOutput::Buffered(ref mut out, _)  => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            PagedOutput::Direct(ref mut out)   => out.flush(),
+            PagedOutput::Buffered(..)          => Ok(()),
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::should_page;
+
+    #[test]
+    fn no_terminal() {
+        assert_eq!(false, should_page(10000, None));
+    }
+
+    #[test]
+    fn fits() {
+        assert_eq!(false, should_page(23, Some(24)));
+    }
+
+    #[test]
+    fn exactly_the_height() {
+        assert_eq!(true, should_page(24, Some(24)));
+    }
+
+    #[test]
+    fn too_tall() {
+        assert_eq!(true, should_page(100, Some(24)));
+    }
+
+    #[test]
+    fn nothing() {
+        assert_eq!(false, should_page(0, Some(24)));
+    }
+}
@@ -0,0 +1,30 @@
+use output::cell::TextCell;
+use output::colours::Colours;
+use fs::fields as f;
+
+
+impl f::Age {
+    pub fn render(&self, colours: &Colours) -> TextCell {
+        TextCell::paint_str(colours.date, self.label())
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use output::colours::Colours;
+    use output::cell::TextCell;
+    use fs::fields as f;
+
+    use ansi_term::Colour::*;
+
+
+    #[test]
+    fn label() {
+        let mut colours = Colours::default();
+        colours.date = Blue.normal();
+
+        let expected = TextCell::paint_str(Blue.normal(), "week");
+        assert_eq!(expected, f::Age::Week.render(&colours).into());
+    }
+}
@@ -0,0 +1,54 @@
+use output::cell::TextCell;
+use output::colours::Colours;
+use fs::fields as f;
+
+
+impl f::ContentType {
+    pub fn render(&self, colours: &Colours) -> TextCell {
+        match *self {
+            f::ContentType::Text        => TextCell::paint_str(colours.content.text,   "text"),
+            f::ContentType::Binary      => TextCell::paint_str(colours.content.binary, "binary"),
+            f::ContentType::Empty       => TextCell::paint_str(colours.content.empty,  "empty"),
+            f::ContentType::Unreadable  => TextCell::paint_str(colours.punctuation,    "?"),
+            f::ContentType::NotAFile    => TextCell::blank(colours.punctuation),
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use output::colours::Colours;
+    use output::cell::TextCell;
+    use fs::fields as f;
+
+    use ansi_term::Colour::*;
+
+
+    #[test]
+    fn binary() {
+        let mut colours = Colours::default();
+        colours.content.binary = Purple.bold();
+
+        let expected = TextCell::paint_str(Purple.bold(), "binary");
+        assert_eq!(expected, f::ContentType::Binary.render(&colours).into());
+    }
+
+    #[test]
+    fn unreadable() {
+        let mut colours = Colours::default();
+        colours.punctuation = Green.italic();
+
+        let expected = TextCell::paint_str(Green.italic(), "?");
+        assert_eq!(expected, f::ContentType::Unreadable.render(&colours).into());
+    }
+
+    #[test]
+    fn not_a_file() {
+        let mut colours = Colours::default();
+        colours.punctuation = Green.italic();
+
+        let expected = TextCell::blank(Green.italic());
+        assert_eq!(expected, f::ContentType::NotAFile.render(&colours).into());
+    }
+}
@@ -0,0 +1,74 @@
+use output::cell::TextCell;
+use output::colours::Colours;
+use fs::fields as f;
+
+use locale;
+
+
+impl f::EntryCount {
+    pub fn render(&self, colours: &Colours, numeric: &locale::Numeric) -> TextCell {
+        match *self {
+            f::EntryCount::Some(count)  => TextCell::paint(colours.entries, numeric.format_int(count)),
+            f::EntryCount::None         => TextCell::blank(colours.punctuation),
+        }
+    }
+}
+
+impl f::ItemCount {
+    pub fn render(&self, colours: &Colours, numeric: &locale::Numeric) -> TextCell {
+        match *self {
+            f::ItemCount::Some(1)           => TextCell::paint_str(colours.entries, "1 item"),
+            f::ItemCount::Some(count)       => TextCell::paint(colours.entries, format!("{} items", numeric.format_int(count))),
+            f::ItemCount::Unreadable        => TextCell::blank(colours.punctuation),
+            f::ItemCount::NotADirectory     => TextCell::blank(colours.punctuation),
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use output::colours::Colours;
+    use output::cell::TextCell;
+    use fs::fields as f;
+
+    use ansi_term::Colour::*;
+    use locale;
+
+
+    #[test]
+    fn not_a_directory() {
+        let mut colours = Colours::default();
+        colours.punctuation = Green.italic();
+
+        let expected = TextCell::blank(Green.italic());
+        assert_eq!(expected, f::EntryCount::None.render(&colours, &locale::Numeric::english()).into());
+    }
+
+    #[test]
+    fn directory() {
+        let mut colours = Colours::default();
+        colours.entries = Blue.underline();
+
+        let expected = TextCell::paint_str(Blue.underline(), "1,234");
+        assert_eq!(expected, f::EntryCount::Some(1234).render(&colours, &locale::Numeric::english()).into());
+    }
+
+    #[test]
+    fn items() {
+        let mut colours = Colours::default();
+        colours.entries = Blue.underline();
+
+        let expected = TextCell::paint_str(Blue.underline(), "1,234 items");
+        assert_eq!(expected, f::ItemCount::Some(1234).render(&colours, &locale::Numeric::english()).into());
+    }
+
+    #[test]
+    fn one_item() {
+        let mut colours = Colours::default();
+        colours.entries = Blue.underline();
+
+        let expected = TextCell::paint_str(Blue.underline(), "1 item");
+        assert_eq!(expected, f::ItemCount::Some(1).render(&colours, &locale::Numeric::english()).into());
+    }
+}
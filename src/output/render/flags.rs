@@ -0,0 +1,46 @@
+use output::cell::TextCell;
+use output::colours::Colours;
+use fs::fields as f;
+
+
+impl f::FileFlags {
+    pub fn render(&self, colours: &Colours) -> TextCell {
+        let names = self.names();
+
+        if names.is_empty() {
+            TextCell::blank(colours.punctuation)
+        }
+        else {
+            TextCell::paint(colours.flags, names.join(","))
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use output::colours::Colours;
+    use output::cell::TextCell;
+    use fs::fields as f;
+
+    use ansi_term::Colour::*;
+
+
+    #[test]
+    fn none() {
+        let mut colours = Colours::default();
+        colours.punctuation = Green.italic();
+
+        let expected = TextCell::blank(Green.italic());
+        assert_eq!(expected, f::FileFlags(0).render(&colours).into());
+    }
+
+    #[test]
+    fn some() {
+        let mut colours = Colours::default();
+        colours.flags = Blue.underline();
+
+        let expected = TextCell::paint_str(Blue.underline(), "uchg,hidden");
+        assert_eq!(expected, f::FileFlags(0x0000_8002).render(&colours).into());
+    }
+}
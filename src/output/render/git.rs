@@ -1,11 +1,17 @@
-use ansi_term::ANSIString;
+use ansi_term::{ANSIString, Style};
+use datetime::TimeZone;
 
 use output::cell::{TextCell, DisplayWidth};
 use output::colours::Colours;
+use output::time::TimeFormat;
 use fs::fields as f;
 
 
 impl f::Git {
+
+    /// Renders the index status, then the working tree status, as two
+    /// separately-coloured characters, the same way round as the two
+    /// letters in `git status --short`.
     pub fn render(&self, colours: &Colours) -> TextCell {
         TextCell {
             width: DisplayWidth::from(2),
@@ -17,6 +23,43 @@ impl f::Git {
     }
 }
 
+impl f::GitSince {
+    pub fn render(&self, colours: &Colours) -> TextCell {
+        TextCell {
+            width: DisplayWidth::from(1),
+            contents: vec![ self.0.render(colours) ].into(),
+        }
+    }
+}
+
+impl f::GitRemote {
+    pub fn render(&self, colours: &Colours) -> TextCell {
+        match self.0 {
+            Some(ref url)  => TextCell::paint(colours.git.remote, url.clone()),
+            None           => TextCell::blank(colours.punctuation),
+        }
+    }
+}
+
+impl f::GitLastCommit {
+
+    /// Renders the commit’s short hash, its author, and when it was made,
+    /// with the time in the same format as the timestamp columns.
+    pub fn render(&self, colours: &Colours, tz: &Option<TimeZone>, style: &TimeFormat) -> TextCell {
+        match self.0 {
+            Some(ref commit) => {
+                let mut cell = TextCell::paint(colours.git.commit, commit.id.clone());
+                cell.add_spaces(1);
+                cell.append(TextCell::paint(Style::default(), commit.author.clone()));
+                cell.add_spaces(1);
+                cell.append(commit.time.render(colours, tz, style));
+                cell
+            },
+            None => TextCell::blank(colours.punctuation),
+        }
+    }
+}
+
 impl f::GitStatus {
     fn render(&self, colours: &Colours) -> ANSIString<'static> {
         match *self {
@@ -26,6 +69,8 @@ impl f::GitStatus {
             f::GitStatus::Deleted      => colours.git.deleted.paint("D"),
             f::GitStatus::Renamed      => colours.git.renamed.paint("R"),
             f::GitStatus::TypeChange   => colours.git.typechange.paint("T"),
+            f::GitStatus::IntentToAdd  => colours.git.new.paint("a"),
+            f::GitStatus::Conflicted   => colours.git.conflicted.paint("U"),
         }
     }
 }
@@ -83,4 +128,109 @@ pub mod test {
 
         assert_eq!(expected, stati.render(&colours).into())
     }
+
+
+    #[test]
+    fn git_staged_and_unstaged() {
+        let mut colours = Colours::default();
+        colours.git.modified = Purple.normal();
+
+        let stati = f::Git {
+            staged:   f::GitStatus::Modified,
+            unstaged: f::GitStatus::Modified,
+        };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![
+                Purple.paint("M"),
+                Purple.paint("M"),
+            ].into(),
+        };
+
+        assert_eq!(expected, stati.render(&colours).into())
+    }
+
+
+    #[test]
+    fn git_intent_to_add() {
+        let mut colours = Colours::default();
+        colours.git.new = Red.normal();
+        colours.git.modified = Purple.normal();
+
+        let stati = f::Git {
+            staged:   f::GitStatus::IntentToAdd,
+            unstaged: f::GitStatus::Modified,
+        };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![
+                Red.paint("a"),
+                Purple.paint("M"),
+            ].into(),
+        };
+
+        assert_eq!(expected, stati.render(&colours).into())
+    }
+
+
+    #[test]
+    fn git_conflicted() {
+        let mut colours = Colours::default();
+        colours.git.conflicted = Red.bold();
+
+        let stati = f::Git {
+            staged:   f::GitStatus::Conflicted,
+            unstaged: f::GitStatus::Conflicted,
+        };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![
+                Red.bold().paint("U"),
+                Red.bold().paint("U"),
+            ].into(),
+        };
+
+        assert_eq!(expected, stati.render(&colours).into())
+    }
+
+
+    #[test]
+    fn git_since_modified() {
+        let mut colours = Colours::default();
+        colours.git.modified = Purple.normal();
+
+        let since = f::GitSince(f::GitStatus::Modified);
+
+        let expected = TextCell {
+            width: DisplayWidth::from(1),
+            contents: vec![
+                Purple.paint("M"),
+            ].into(),
+        };
+
+        assert_eq!(expected, since.render(&colours).into())
+    }
+
+
+    #[test]
+    fn git_remote_url() {
+        let mut colours = Colours::default();
+        colours.git.remote = Cyan.underline();
+
+        let remote = f::GitRemote(Some("https://example.com/exa.git".into()));
+        let expected = TextCell::paint_str(Cyan.underline(), "https://example.com/exa.git");
+        assert_eq!(expected, remote.render(&colours).into())
+    }
+
+    #[test]
+    fn git_no_remote() {
+        let mut colours = Colours::default();
+        colours.punctuation = Fixed(44).normal();
+
+        let expected = TextCell::blank(Fixed(44).normal());
+        assert_eq!(expected, f::GitRemote(None).render(&colours).into())
+    }
 }
@@ -13,7 +13,7 @@ impl f::Group {
 
         let group = match users.get_group_by_gid(self.0) {
             Some(g) => (*g).clone(),
-            None    => return TextCell::paint(style, self.0.to_string()),
+            None    => return TextCell::paint(colours.users.orphan, self.0.to_string()),
         };
 
         let current_uid = users.get_current_uid();
@@ -26,6 +26,16 @@ impl f::Group {
 
         TextCell::paint(style, group.name().to_owned())
     }
+
+    /// Whether this group has the same name as the given user, as happens
+    /// on systems that give each user a group of their own. Groups and
+    /// users whose names can’t be looked up never count as the same.
+    pub fn is_named_after<U: Users+Groups>(&self, user: f::User, users: &U) -> bool {
+        match (users.get_group_by_gid(self.0), users.get_user_by_uid(user.0)) {
+            (Some(group), Some(user))  => group.name() == user.name(),
+            _                          => false,
+        }
+    }
 }
 
 
@@ -58,7 +68,8 @@ pub mod test {
     #[test]
     fn unnamed() {
         let mut colours = Colours::default();
-        colours.users.group_not_yours = Fixed(87).normal();
+        colours.users.group_not_yours = Fixed(101).normal();
+        colours.users.orphan = Fixed(87).normal();
 
         let users = MockUsers::with_current_uid(1000);
 
@@ -100,10 +111,36 @@ pub mod test {
     #[test]
     fn overflow() {
         let mut colours = Colours::default();
-        colours.users.group_not_yours = Blue.underline();
+        colours.users.orphan = Blue.underline();
 
         let group = f::Group(2_147_483_648);
         let expected = TextCell::paint_str(Blue.underline(), "2147483648");
         assert_eq!(expected, group.render(&colours, &MockUsers::with_current_uid(0)));
     }
+
+    #[test]
+    fn named_after_user() {
+        let mut users = MockUsers::with_current_uid(1000);
+        users.add_user(User::new(1000, "eve", 1000));
+        users.add_group(Group::new(1000, "eve"));
+
+        assert!(f::Group(1000).is_named_after(f::User(1000), &users));
+    }
+
+    #[test]
+    fn not_named_after_user() {
+        let mut users = MockUsers::with_current_uid(1000);
+        users.add_user(User::new(1000, "eve", 100));
+        users.add_group(Group::new(100, "folk"));
+
+        assert!(!f::Group(100).is_named_after(f::User(1000), &users));
+    }
+
+    #[test]
+    fn unnamed_not_named_after_user() {
+        let mut users = MockUsers::with_current_uid(1000);
+        users.add_user(User::new(1000, "1000", 1000));
+
+        assert!(!f::Group(1000).is_named_after(f::User(1000), &users));
+    }
 }
@@ -0,0 +1,45 @@
+use output::cell::TextCell;
+use output::colours::Colours;
+use fs::fields as f;
+
+use locale;
+
+
+impl f::LineCount {
+    pub fn render(&self, colours: &Colours, numeric: &locale::Numeric) -> TextCell {
+        match *self {
+            f::LineCount::Some(count)  => TextCell::paint(colours.lines, numeric.format_int(count)),
+            f::LineCount::None         => TextCell::blank(colours.punctuation),
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use output::colours::Colours;
+    use output::cell::TextCell;
+    use fs::fields as f;
+
+    use ansi_term::Colour::*;
+    use locale;
+
+
+    #[test]
+    fn no_count() {
+        let mut colours = Colours::default();
+        colours.punctuation = Green.italic();
+
+        let expected = TextCell::blank(Green.italic());
+        assert_eq!(expected, f::LineCount::None.render(&colours, &locale::Numeric::english()).into());
+    }
+
+    #[test]
+    fn count() {
+        let mut colours = Colours::default();
+        colours.lines = Yellow.bold();
+
+        let expected = TextCell::paint_str(Yellow.bold(), "12,345");
+        assert_eq!(expected, f::LineCount::Some(12345).render(&colours, &locale::Numeric::english()).into());
+    }
+}
@@ -1,9 +1,16 @@
+mod age;
 mod blocks;
+mod content;
+mod entries;
+mod flags;
 mod git;
 mod groups;
 mod inode;
+mod lines;
 mod links;
 mod permissions;
 mod size;
+mod sparseness;
+mod time_anomaly;
 mod times;
 mod users;
@@ -13,6 +13,10 @@ impl f::PermissionsPlus {
            chars.push(colours.perms.attribute.paint("@"));
         }
 
+        if self.acl {
+           chars.push(colours.perms.attribute.paint("+"));
+        }
+
         // As these are all ASCII characters, we can guarantee that they’re
         // all going to be one character wide, and don’t need to compute the
         // cell’s display width.
@@ -75,9 +79,9 @@ impl f::Permissions {
 impl f::Type {
     pub fn render(&self, colours: &Colours) -> ANSIString<'static> {
         match *self {
-            f::Type::File        => colours.filetypes.normal.paint("."),
+            f::Type::File        => colours.filetypes.normal.paint("-"),
             f::Type::Directory   => colours.filetypes.directory.paint("d"),
-            f::Type::Pipe        => colours.filetypes.pipe.paint("|"),
+            f::Type::Pipe        => colours.filetypes.pipe.paint("p"),
             f::Type::Link        => colours.filetypes.symlink.paint("l"),
             f::Type::CharDevice  => colours.filetypes.device.paint("c"),
             f::Type::BlockDevice => colours.filetypes.device.paint("b"),
@@ -194,4 +198,26 @@ pub mod test {
 
         assert_eq!(expected, bits.render(&colours, true).into())
     }
+
+
+    #[test]
+    fn file_types() {
+        let mut colours = Colours::default();
+        colours.filetypes.normal    = Fixed(1).normal();
+        colours.filetypes.directory = Fixed(2).normal();
+        colours.filetypes.pipe      = Fixed(3).normal();
+        colours.filetypes.symlink   = Fixed(4).normal();
+        colours.filetypes.device    = Fixed(5).normal();
+        colours.filetypes.socket    = Fixed(6).normal();
+        colours.filetypes.special   = Fixed(7).normal();
+
+        assert_eq!(Fixed(1).paint("-"), f::Type::File.render(&colours));
+        assert_eq!(Fixed(2).paint("d"), f::Type::Directory.render(&colours));
+        assert_eq!(Fixed(3).paint("p"), f::Type::Pipe.render(&colours));
+        assert_eq!(Fixed(4).paint("l"), f::Type::Link.render(&colours));
+        assert_eq!(Fixed(5).paint("c"), f::Type::CharDevice.render(&colours));
+        assert_eq!(Fixed(5).paint("b"), f::Type::BlockDevice.render(&colours));
+        assert_eq!(Fixed(6).paint("s"), f::Type::Socket.render(&colours));
+        assert_eq!(Fixed(7).paint("?"), f::Type::Special.render(&colours));
+    }
 }
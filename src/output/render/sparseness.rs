@@ -0,0 +1,81 @@
+use locale;
+
+use output::cell::TextCell;
+use output::colours::Colours;
+use output::table::SizeFormat;
+use fs::fields as f;
+
+
+impl f::Sparseness {
+    pub fn render(&self, colours: &Colours) -> TextCell {
+        match *self {
+            f::Sparseness::Sparse { .. }  => TextCell::paint_str(colours.sparse, "s"),
+            f::Sparseness::Dense          => TextCell::blank(colours.punctuation),
+        }
+    }
+
+    /// Renders the marker followed by the amount of space the file really
+    /// takes up, in the same format as the size column, so the two can be
+    /// compared.
+    pub fn render_allocated(&self, colours: &Colours, size_format: SizeFormat, numerics: &locale::Numeric) -> TextCell {
+        match *self {
+            f::Sparseness::Sparse { allocated }  => {
+                let mut cell = self.render(colours);
+                cell.add_spaces(1);
+                cell.append(f::Size::Some(allocated).render(colours, size_format, numerics));
+                cell
+            },
+            f::Sparseness::Dense  => self.render(colours),
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use output::colours::Colours;
+    use output::cell::TextCell;
+    use output::table::SizeFormat;
+    use fs::fields as f;
+
+    use locale;
+
+    use ansi_term::Colour::*;
+
+
+    #[test]
+    fn sparse() {
+        let mut colours = Colours::default();
+        colours.sparse = Purple.bold();
+
+        let expected = TextCell::paint_str(Purple.bold(), "s");
+        assert_eq!(expected, f::Sparseness::Sparse { allocated: 4096 }.render(&colours).into());
+    }
+
+    #[test]
+    fn dense() {
+        let mut colours = Colours::default();
+        colours.punctuation = Green.italic();
+
+        let expected = TextCell::blank(Green.italic());
+        assert_eq!(expected, f::Sparseness::Dense.render(&colours).into());
+    }
+
+    #[test]
+    fn sparse_with_allocation() {
+        let colours = Colours::plain();
+
+        let cell = f::Sparseness::Sparse { allocated: 4096 }.render_allocated(&colours, SizeFormat::JustBytes, &locale::Numeric::english());
+        assert_eq!(*cell.width, 7);
+        assert_eq!(cell.contents.strings().to_string(), "s 4,096");
+    }
+
+    #[test]
+    fn dense_with_allocation() {
+        let mut colours = Colours::default();
+        colours.punctuation = Green.italic();
+
+        let expected = TextCell::blank(Green.italic());
+        assert_eq!(expected, f::Sparseness::Dense.render_allocated(&colours, SizeFormat::JustBytes, &locale::Numeric::english()).into());
+    }
+}
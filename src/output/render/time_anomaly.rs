@@ -0,0 +1,49 @@
+use output::cell::TextCell;
+use output::colours::Colours;
+use fs::fields as f;
+
+
+impl f::TimeAnomaly {
+    pub fn render(&self, colours: &Colours) -> TextCell {
+        match *self {
+            f::TimeAnomaly::ModifiedBeforeBirth  => TextCell::paint_str(colours.time_anomaly, "!"),
+            f::TimeAnomaly::Consistent           => TextCell::blank(colours.punctuation),
+            f::TimeAnomaly::Unknown              => TextCell::default(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub mod test {
+    use output::colours::Colours;
+    use output::cell::TextCell;
+    use fs::fields as f;
+
+    use ansi_term::Colour::*;
+
+
+    #[test]
+    fn anomalous() {
+        let mut colours = Colours::default();
+        colours.time_anomaly = Red.bold();
+
+        let expected = TextCell::paint_str(Red.bold(), "!");
+        assert_eq!(expected, f::TimeAnomaly::ModifiedBeforeBirth.render(&colours).into());
+    }
+
+    #[test]
+    fn consistent() {
+        let mut colours = Colours::default();
+        colours.punctuation = Green.italic();
+
+        let expected = TextCell::blank(Green.italic());
+        assert_eq!(expected, f::TimeAnomaly::Consistent.render(&colours).into());
+    }
+
+    #[test]
+    fn unknown() {
+        let colours = Colours::default();
+        assert_eq!(TextCell::default(), f::TimeAnomaly::Unknown.render(&colours).into());
+    }
+}
@@ -7,9 +7,11 @@ use output::cell::TextCell;
 
 impl f::User {
     pub fn render(&self, colours: &Colours, users: &Users) -> TextCell {
+        // A user ID with no user is an orphan, which gets its own style so
+        // it stands out, rather than the usual one.
         let user_name = match users.get_user_by_uid(self.0) {
             Some(user)  => user.name().to_owned(),
-            None        => self.0.to_string(),
+            None        => return TextCell::paint(colours.users.orphan, self.0.to_string()),
         };
 
         let style = if users.get_current_uid() == self.0 { colours.users.user_you }
@@ -45,7 +47,8 @@ pub mod test {
     #[test]
     fn unnamed() {
         let mut colours = Colours::default();
-        colours.users.user_you = Cyan.bold();
+        colours.users.user_you = Green.bold();
+        colours.users.orphan = Cyan.bold();
 
         let users = MockUsers::with_current_uid(1000);
 
@@ -70,7 +73,8 @@ pub mod test {
     #[test]
     fn different_unnamed() {
         let mut colours = Colours::default();
-        colours.users.user_someone_else = Red.normal();
+        colours.users.user_someone_else = Green.bold();
+        colours.users.orphan = Red.normal();
 
         let user = f::User(1000);
         let expected = TextCell::paint_str(Red.normal(), "1000");
@@ -80,10 +84,23 @@ pub mod test {
     #[test]
     fn overflow() {
         let mut colours = Colours::default();
-        colours.users.user_someone_else = Blue.underline();
+        colours.users.orphan = Blue.underline();
 
         let user = f::User(2_147_483_648);
         let expected = TextCell::paint_str(Blue.underline(), "2147483648");
         assert_eq!(expected, user.render(&colours, &MockUsers::with_current_uid(0)));
     }
+
+    #[test]
+    fn orphaned_among_named() {
+        let mut colours = Colours::default();
+        colours.users.user_someone_else = Green.bold();
+        colours.users.orphan = Red.bold();
+
+        let mut users = MockUsers::with_current_uid(0);
+        users.add_user(User::new(1000, "enoch", 100));
+
+        assert_eq!(TextCell::paint_str(Green.bold(), "enoch"), f::User(1000).render(&colours, &users));
+        assert_eq!(TextCell::paint_str(Red.bold(),   "1001"),  f::User(1001).render(&colours, &users));
+    }
 }
@@ -0,0 +1,364 @@
+//! The summary line that can be shown after a list of files, counting how
+//! many of each kind of file got listed.
+//!
+//! Regular files get counted by extension, with the most common ones shown
+//! separately and the rest lumped together. Everything else gets counted by
+//! its type. Only the files that are actually listed get counted, after
+//! they’ve been filtered, and not the files inside them in a tree view,
+//! unless the summary has been asked to recurse into the directories too.
+//!
+//! There are also the shorter totals that can go after each directory’s
+//! path, with how many files it has and how big they are altogether.
+
+use std::collections::HashMap;
+use std::os::unix::fs::MetadataExt;
+
+use ansi_term::{ANSIString, Style};
+
+use fs::File;
+use fs::feature::GitOptions;
+use fs::fields as f;
+use fs::filter::FileFilter;
+use output::colours::Colours;
+
+
+/// How many extensions get their own count before the rest are lumped in
+/// with the files that don’t have one.
+const TOP_EXTENSIONS: usize = 5;
+
+
+/// Which files the summary line counts.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum SummaryScope {
+
+    /// Just the files in the list.
+    Listed,
+
+    /// The files in the list, and everything inside the directories among
+    /// them, all the way down.
+    Recursive,
+}
+
+
+/// The counts of each kind of file in a list.
+#[derive(PartialEq, Debug, Default)]
+pub struct Summary {
+
+    /// Regular files with extensions, by their (lowercased) extension.
+    extensions: HashMap<String, usize>,
+
+    /// Regular files without an extension.
+    others: usize,
+
+    directories: usize,
+    symlinks: usize,
+
+    /// Pipes, sockets, and devices.
+    specials: usize,
+}
+
+impl Summary {
+
+    /// Counts the kinds of each of the given files.
+    pub fn of_files(files: &[File]) -> Summary {
+        let mut summary = Summary::default();
+
+        for file in files {
+            summary.add(file);
+        }
+
+        summary
+    }
+
+    /// Counts the kinds of each of the given files, and of everything
+    /// inside the directories among them, all the way down. The files
+    /// inside get filtered the same way they would be if they were listed,
+    /// with the depth being how far down the given files are.
+    ///
+    /// Directories that can’t be read are counted without being looked
+    /// inside, as are `.` and `..`, and any directory that turns out to be
+    /// inside itself.
+    pub fn of_files_recursively(files: &[File], filter: &FileFilter, git: Option<&GitOptions>, depth: usize) -> Summary {
+        let mut summary = Summary::default();
+        let mut ancestors = Vec::new();
+
+        for file in files {
+            summary.add_recursively(file, filter, git, depth, &mut ancestors);
+        }
+
+        summary
+    }
+
+    fn add(&mut self, file: &File) {
+        if file.is_link() {
+            self.symlinks += 1;
+        }
+        else if file.is_directory() {
+            self.directories += 1;
+        }
+        else if !file.is_file() {
+            self.specials += 1;
+        }
+        else if let Some(ref ext) = file.ext {
+            *self.extensions.entry(ext.clone()).or_insert(0) += 1;
+        }
+        else {
+            self.others += 1;
+        }
+    }
+
+    /// Counts the given file, then everything inside it if it’s a
+    /// directory. The ancestors are the device and inode numbers of every
+    /// directory above this one, so one that contains itself doesn’t get
+    /// counted forever.
+    fn add_recursively(&mut self, file: &File, filter: &FileFilter, git: Option<&GitOptions>, depth: usize, ancestors: &mut Vec<(u64, u64)>) {
+        self.add(file);
+
+        // The `.` and `..` entries inside directories would lead back up
+        // the tree, but the same names given as arguments are fine.
+        let is_dots = depth > 0 && (file.name == "." || file.name == "..");
+
+        let id = (file.metadata.dev(), file.metadata.ino());
+        if !file.is_directory() || is_dots || ancestors.contains(&id) {
+            return;
+        }
+
+        let dir = match filter.read_dir(file, git) {
+            Ok(d)   => d,
+            Err(_)  => return,
+        };
+
+        let mut children: Vec<File> = dir.files(filter.dot_filter).filter_map(Result::ok).collect();
+        filter.filter_child_files(&mut children, depth);
+
+        // Only sort the files when some of them are going to be left off,
+        // as the sort field could be a slow one.
+        if filter.top.is_some() {
+            filter.sort_files(&mut children);
+            filter.keep_top(&mut children);
+        }
+
+        ancestors.push(id);
+        for child in &children {
+            self.add_recursively(child, filter, git, depth + 1, ancestors);
+        }
+        let _ = ancestors.pop();
+    }
+
+    /// Renders the counts as a list such as `12 .rs, 4 .toml, 3 dirs`,
+    /// leaving out any kinds of file there are none of. The extensions are
+    /// ordered from the most common down, with ties broken by name.
+    pub fn render(&self, colours: &Colours) -> Vec<ANSIString<'static>> {
+        let mut extensions: Vec<(&String, &usize)> = self.extensions.iter().collect();
+        extensions.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+        let mut parts = Vec::new();
+        let mut others = self.others;
+
+        for (index, (ext, &count)) in extensions.into_iter().enumerate() {
+            if index < TOP_EXTENSIONS {
+                parts.push(Style::default().paint(format!("{} .{}", count, ext)));
+            }
+            else {
+                others += count;
+            }
+        }
+
+        if others > 0 {
+            parts.push(Style::default().paint(format!("{} other", others)));
+        }
+
+        if self.directories > 0 {
+            parts.push(colours.filetypes.directory.paint(plural(self.directories, "dir")));
+        }
+
+        if self.symlinks > 0 {
+            parts.push(colours.filetypes.symlink.paint(plural(self.symlinks, "symlink")));
+        }
+
+        if self.specials > 0 {
+            parts.push(colours.filetypes.special.paint(plural(self.specials, "special file")));
+        }
+
+        let mut strings = Vec::new();
+        for (index, part) in parts.into_iter().enumerate() {
+            if index > 0 {
+                strings.push(colours.punctuation.paint(", "));
+            }
+
+            strings.push(part);
+        }
+
+        strings
+    }
+}
+
+/// Describes how many files there are and how big they are altogether,
+/// such as `42 items, 1.2 MiB`. Only regular files have sizes, so the
+/// directories and links don’t add to the total.
+pub fn totals(files: &[File]) -> String {
+    use number_prefix::{binary_prefix, Prefixed, Standalone, PrefixNames};
+
+    let bytes: u64 = files.iter().map(|file| match file.size() {
+        f::Size::Some(size)  => size,
+        _                    => 0,
+    }).sum();
+
+    let size = match binary_prefix(bytes as f64) {
+        Standalone(_)               => plural(bytes as usize, "byte"),
+        Prefixed(prefix, n) if n < 10f64  => format!("{:.1} {}B", n, prefix.symbol()),
+        Prefixed(prefix, n)         => format!("{:.0} {}B", n, prefix.symbol()),
+    };
+
+    format!("{}, {}", plural(files.len(), "item"), size)
+}
+
+/// Formats a count followed by a noun, adding an “s” when there’s more
+/// than one.
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 { format!("1 {}", noun) }
+             else { format!("{} {}s", count, noun) }
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use scratch::ScratchDir;
+    use std::fs as std_fs;
+    use std::os::unix::fs::symlink;
+    use ansi_term::ANSIStrings;
+    use fs::{Dir, DotFilter};
+
+    fn summarise(name: &str, files: &[&str], dirs: &[&str], links: &[&str]) -> String {
+        let scratch = ScratchDir::new(&format!("summary-{}", name));
+        let path = &scratch.path;
+
+        for file in files {
+            let _ = std_fs::File::create(path.join(file)).unwrap();
+        }
+
+        for dir in dirs {
+            std_fs::create_dir(path.join(dir)).unwrap();
+        }
+
+        for link in links {
+            symlink("nowhere", path.join(link)).unwrap();
+        }
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+        let summary = Summary::of_files(&files);
+
+        ANSIStrings(&summary.render(&Colours::plain())).to_string()
+    }
+
+    /// Summarises everything inside a directory made with the given
+    /// subdirectories and files, filtered with the given filter.
+    fn summarise_recursively(name: &str, dirs: &[&str], files: &[&str], filter: &FileFilter) -> String {
+        let scratch = ScratchDir::new(&format!("summary-recursive-{}", name));
+        let path = &scratch.path;
+
+        for dir in dirs {
+            std_fs::create_dir_all(path.join(dir)).unwrap();
+        }
+
+        for file in files {
+            let _ = std_fs::File::create(path.join(file)).unwrap();
+        }
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let mut files: Vec<File> = dir.files(filter.dot_filter).map(Result::unwrap).collect();
+        filter.filter_child_files(&mut files, 0);
+        let summary = Summary::of_files_recursively(&files, filter, None, 1);
+
+        ANSIStrings(&summary.render(&Colours::plain())).to_string()
+    }
+
+    fn total(name: &str, sizes: &[usize], dirs: &[&str]) -> String {
+        let scratch = ScratchDir::new(&format!("totals-{}", name));
+        let path = &scratch.path;
+
+        for (index, &size) in sizes.iter().enumerate() {
+            let file = std_fs::File::create(path.join(index.to_string())).unwrap();
+            file.set_len(size as u64).unwrap();
+        }
+
+        for dir in dirs {
+            std_fs::create_dir(path.join(dir)).unwrap();
+        }
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        totals(&files)
+    }
+
+    #[test]
+    fn totals_in_bytes() {
+        assert_eq!(total("bytes", &[ 1 ], &[]), "1 item, 1 byte");
+        assert_eq!(total("empty", &[], &[]), "0 items, 0 bytes");
+    }
+
+    #[test]
+    fn totals_with_prefixes() {
+        assert_eq!(total("mebi", &[ 1024 * 1024, 200 * 1024 ], &[ "dir" ]), "3 items, 1.2 MiB");
+        assert_eq!(total("kibi", &[ 42 * 1024 ], &[]), "1 item, 42 KiB");
+    }
+
+    #[test]
+    fn nothing() {
+        assert_eq!(summarise("nothing", &[], &[], &[]), "");
+    }
+
+    #[test]
+    fn extensions_and_types() {
+        let files = [ "main.rs", "lib.rs", "exa.RS", "Cargo.toml", "README" ];
+        assert_eq!(summarise("mix", &files, &[ "src", "target" ], &[ "link" ]),
+                   "3 .rs, 1 .toml, 1 other, 2 dirs, 1 symlink");
+    }
+
+    #[test]
+    fn most_common_first() {
+        let files = [ "a.md", "b.md", "c.txt", "d.json", "e.json", "f.json" ];
+        assert_eq!(summarise("order", &files, &[], &[]), "3 .json, 2 .md, 1 .txt");
+    }
+
+    #[test]
+    fn the_rest_are_other() {
+        let files = [ "a.a", "b.a", "c.b", "d.b", "e.c", "f.d", "g.e", "h.f", "i.g", "Makefile" ];
+        assert_eq!(summarise("other", &files, &[ "one" ], &[]),
+                   "2 .a, 2 .b, 1 .c, 1 .d, 1 .e, 3 other, 1 dir");
+    }
+
+    #[test]
+    fn nested() {
+        let dirs = [ "src/fs", "src/output", "target" ];
+        let files = [ "Cargo.toml", "src/main.rs", "src/fs/dir.rs", "src/fs/file.rs", "src/output/grid.rs", "target/exa", "src/fs/Cargo.toml" ];
+        assert_eq!(summarise_recursively("nested", &dirs, &files, &FileFilter::default()),
+                   "4 .rs, 2 .toml, 1 other, 4 dirs");
+    }
+
+    #[test]
+    fn nested_with_dots() {
+        let filter = FileFilter { dot_filter: DotFilter::DotfilesAndDots, ..FileFilter::default() };
+        let dirs = [ "a/b" ];
+        let files = [ "a/one.rs", "a/b/two.rs", "a/b/.three.rs" ];
+        assert_eq!(summarise_recursively("dots", &dirs, &files, &filter), "3 .rs, 8 dirs");
+    }
+
+    #[test]
+    fn nested_with_filters() {
+        use fs::filter::IgnorePatterns;
+
+        let filter = FileFilter {
+            ignore_patterns: IgnorePatterns::parse_from_iter(vec![ "target", "*.toml" ]).0,
+            ..FileFilter::default()
+        };
+
+        let dirs = [ "src/fs", "target/debug" ];
+        let files = [ "Cargo.toml", "src/main.rs", "src/.hidden.rs", "src/fs/dir.rs", "src/fs/Cargo.toml", "target/debug/exa.rs" ];
+        assert_eq!(summarise_recursively("filtered", &dirs, &files, &filter), "2 .rs, 2 dirs");
+    }
+}
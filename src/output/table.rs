@@ -1,5 +1,7 @@
 use std::cmp::max;
+use std::collections::HashMap;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::ops::Deref;
 use std::sync::{Mutex, MutexGuard};
 
@@ -10,11 +12,11 @@ use locale;
 
 use users::UsersCache;
 
-use output::cell::TextCell;
+use output::cell::{TextCell, DisplayWidth};
 use output::colours::Colours;
 use output::time::TimeFormat;
 
-use fs::{File, Dir, fields as f};
+use fs::{File, Dir, DotFilter, fields as f};
 
 
 
@@ -24,8 +26,45 @@ pub struct Options {
     pub size_format: SizeFormat,
     pub time_format: TimeFormat,
     pub extra_columns: Columns,
+    pub alignments: Alignments,
+
+    /// How to make the table fit into the terminal, if it should be made
+    /// to when it’s too wide.
+    pub fit: Option<Fit>,
 }
 
+/// How to make a table that’s too wide fit into the terminal: first by
+/// shortening its timestamps, then by dropping its columns one by one.
+///
+/// The columns get dropped in the order of `DROP_ORDER`, unless they were
+/// picked with `--columns`, in which case the last one picked goes first.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct Fit {
+
+    /// The width of the terminal the table, and the names after it, have
+    /// to fit into.
+    pub width: usize,
+
+    /// Whether the timestamps can be shortened, which they can’t when a
+    /// time style has been asked for.
+    pub shorten_time: bool,
+
+    /// Whether the columns were picked with `--columns`.
+    pub picked: bool,
+}
+
+/// The columns that get dropped to make a table fit into the terminal,
+/// starting with the first. The permissions, size, user, and modified time
+/// columns never get dropped.
+pub const DROP_ORDER: &[Column] = &[
+    Column::GitLastCommit, Column::GitRemote, Column::GitSince, Column::GitStatus,
+    Column::Group, Column::HardLinks, Column::Context, Column::SymlinkGroup,
+    Column::TimeAnomaly, Column::Age, Column::Flags, Column::LineCount,
+    Column::ContentType, Column::EntryCount, Column::Sparseness, Column::Blocks,
+    Column::SizePercent, Column::AllocatedSize, Column::Inode,
+    Column::Timestamp(TimeType::Accessed), Column::Timestamp(TimeType::Created),
+];
+
 /// Extra columns to display in the table.
 #[derive(PartialEq, Debug)]
 pub struct Columns {
@@ -37,8 +76,69 @@ pub struct Columns {
     pub inode: bool,
     pub links: bool,
     pub blocks: bool,
+    pub sparse: bool,
+    pub time_anomalies: bool,
+    pub flags: bool,
+    pub entries: bool,
+    pub content: bool,
+    pub lines: bool,
+    pub symlink_groups: bool,
+    pub age: bool,
     pub group: bool,
-    pub git: bool
+    pub git: bool,
+
+    /// Whether to show the path of the directory each file is in, from the
+    /// directory given as an argument, for when the files from more than
+    /// one directory are listed.
+    pub context: bool,
+
+    /// Whether to show each file’s size as a percentage of the sizes of all
+    /// the files listed alongside it.
+    pub size_percent: bool,
+
+    /// Whether to show each file’s status compared to a Git reference, when
+    /// the directory’s repository has been compared against one.
+    pub git_since: bool,
+
+    /// Whether to show the commit that last changed each file, when the
+    /// directory’s repository has had them looked up.
+    pub git_last_commit: bool,
+
+    /// Whether to show the links column with only the counts of files that
+    /// have more than one link filled in, from `--links=multiple`.
+    pub multiple_links: bool,
+
+    /// Whether to leave the size and timestamp columns blank for symlinks,
+    /// as they’re the link’s own, rather than those of the file it points to.
+    pub blank_symlink_meta: bool,
+
+    /// Whether to leave the group column blank for files whose group has
+    /// the same name as their user.
+    pub collapse_owner: bool,
+
+    /// Whether to show the number of entries inside each directory in the
+    /// size column, instead of a dash, counting dotfiles only if this dot
+    /// filter would show them.
+    pub dir_entries: Option<DotFilter>,
+
+    /// Whether to show the URL of a remote for directories that are at the
+    /// top of Git repositories.
+    pub git_remote: bool,
+
+    /// The name of the remote to show the URL of.
+    pub remote_name: String,
+
+    /// How the files get sorted into buckets in the age column, which is
+    /// needed whether the column was picked by its own flag or by name.
+    pub age_buckets: AgeBuckets,
+
+    /// Whether to only show the permissions and size columns, as asked for
+    /// with `--compact`. Columns picked with `--columns` still get shown.
+    pub compact: bool,
+
+    /// The columns the user picked, in the order they should be shown,
+    /// which get used instead of the ones picked by the other fields.
+    pub order: Option<Vec<Column>>,
 }
 
 impl fmt::Debug for Options {
@@ -51,10 +151,23 @@ impl fmt::Debug for Options {
 
 impl Columns {
     pub fn should_scan_for_git(&self) -> bool {
-        self.git
+        match self.order {
+            Some(ref order) => order.iter().any(|c| *c == Column::GitStatus || *c == Column::GitSince || *c == Column::GitLastCommit),
+            None            => !self.compact && (self.git || self.git_since || self.git_last_commit),
+        }
     }
 
     pub fn for_dir(&self, dir: Option<&Dir>) -> Vec<Column> {
+        if let Some(ref order) = self.order {
+            return order.iter().cloned()
+                        .filter(|c| c.is_available(dir))
+                        .collect();
+        }
+
+        if self.compact {
+            return vec![ Column::Permissions, Column::FileSize ];
+        }
+
         let mut columns = vec![];
 
         if self.inode {
@@ -63,22 +176,50 @@ impl Columns {
 
         columns.push(Column::Permissions);
 
-        if self.links {
+        if self.links || self.multiple_links {
             columns.push(Column::HardLinks);
         }
 
         columns.push(Column::FileSize);
 
+        if self.size_percent {
+            columns.push(Column::SizePercent);
+        }
+
         if self.blocks {
             columns.push(Column::Blocks);
         }
 
+        if self.sparse {
+            columns.push(Column::Sparseness);
+        }
+
+        if self.entries {
+            columns.push(Column::EntryCount);
+        }
+
+        if self.content {
+            columns.push(Column::ContentType);
+        }
+
+        if self.lines {
+            columns.push(Column::LineCount);
+        }
+
         columns.push(Column::User);
 
         if self.group {
             columns.push(Column::Group);
         }
 
+        if self.flags {
+            columns.push(Column::Flags);
+        }
+
+        if self.symlink_groups {
+            columns.push(Column::SymlinkGroup);
+        }
+
         if self.time_types.modified {
             columns.push(Column::Timestamp(TimeType::Modified));
         }
@@ -91,67 +232,233 @@ impl Columns {
             columns.push(Column::Timestamp(TimeType::Accessed));
         }
 
+        if self.age {
+            columns.push(Column::Age);
+        }
+
+        if self.time_anomalies {
+            columns.push(Column::TimeAnomaly);
+        }
+
         if cfg!(feature="git") {
             if let Some(d) = dir {
-                if self.should_scan_for_git() && d.has_git_repo() {
+                if self.git && d.has_git_repo() {
                     columns.push(Column::GitStatus);
                 }
+
+                if self.git_since && d.has_git_since() {
+                    columns.push(Column::GitSince);
+                }
+
+                if self.git_last_commit && d.has_git_last_commits() {
+                    columns.push(Column::GitLastCommit);
+                }
+            }
+
+            if self.git_remote {
+                columns.push(Column::GitRemote);
             }
         }
 
+        if self.context {
+            columns.push(Column::Context);
+        }
+
         columns
     }
 }
 
 
 /// A table contains these.
-#[derive(Debug)]
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub enum Column {
     Permissions,
     FileSize,
+    SizePercent,
+    AllocatedSize,
     Timestamp(TimeType),
+    Age,
+    TimeAnomaly,
+    Flags,
     Blocks,
+    Sparseness,
+    EntryCount,
+    ContentType,
+    LineCount,
     User,
     Group,
     HardLinks,
+    SymlinkGroup,
     Inode,
     GitStatus,
+    GitSince,
+    GitLastCommit,
+    GitRemote,
+    Context,
 }
 
 /// Each column can pick its own **Alignment**. Usually, numbers are
 /// right-aligned, and text is left-aligned.
-#[derive(Copy, Clone)]
+#[derive(PartialEq, Debug, Copy, Clone)]
 pub enum Alignment {
     Left, Right,
 }
 
+/// The names that columns can be referred to by on the command-line, as
+/// returned by `Column::name`.
+pub const COLUMN_NAMES: &[&str] = &[ "permissions", "size", "percent", "allocated", "modified", "created", "accessed", "age", "anomaly",
+                                     "blocks", "sparse", "entries", "content", "lines", "user", "group", "flags", "links",
+                                     "linkgroup", "inode", "git", "since", "commit", "remote", "context" ];
+
+/// The names that can be given to `--columns`: those of every column, and
+/// `name` for the file names, which always go at the end.
+pub const ORDER_NAMES: &[&str] = &[ "permissions", "size", "percent", "allocated", "modified", "created", "accessed", "age", "anomaly",
+                                    "blocks", "sparse", "entries", "content", "lines", "user", "group", "flags", "links",
+                                    "linkgroup", "inode", "git", "since", "commit", "remote", "context", "name" ];
+
+/// The remote whose URL gets shown in the remote column when no other one
+/// has been picked.
+pub const DEFAULT_REMOTE: &str = "origin";
+
+/// The alignments the user has picked for particular columns, which get
+/// used instead of the ones the columns would otherwise have.
+#[derive(PartialEq, Debug, Default)]
+pub struct Alignments(pub Vec<(&'static str, Alignment)>);
+
+impl Alignments {
+
+    /// The alignment to use for the given column: the last one picked for
+    /// it, or its usual alignment if none was.
+    pub fn for_column(&self, column: &Column) -> Alignment {
+        let name = column.name();
+        match self.0.iter().rev().find(|a| a.0 == name) {
+            Some(&(_, alignment))  => alignment,
+            None                   => column.alignment(),
+        }
+    }
+}
+
 impl Column {
 
     /// Get the alignment this column should use.
     pub fn alignment(&self) -> Alignment {
         match *self {
             Column::FileSize
+            | Column::SizePercent
+            | Column::AllocatedSize
             | Column::HardLinks
             | Column::Inode
             | Column::Blocks
-            | Column::GitStatus => Alignment::Right,
+            | Column::EntryCount
+            | Column::GitStatus
+            | Column::GitSince  => Alignment::Right,
             _                   => Alignment::Left,
         }
     }
 
+    /// Get the name this column can be referred to by on the command-line.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Column::Permissions                   => "permissions",
+            Column::FileSize                      => "size",
+            Column::SizePercent                   => "percent",
+            Column::AllocatedSize                 => "allocated",
+            Column::Timestamp(TimeType::Modified) => "modified",
+            Column::Timestamp(TimeType::Created)  => "created",
+            Column::Timestamp(TimeType::Accessed) => "accessed",
+            Column::Age                           => "age",
+            Column::TimeAnomaly                   => "anomaly",
+            Column::Flags                         => "flags",
+            Column::Blocks                        => "blocks",
+            Column::Sparseness                    => "sparse",
+            Column::EntryCount                    => "entries",
+            Column::ContentType                   => "content",
+            Column::LineCount                     => "lines",
+            Column::User                          => "user",
+            Column::Group                         => "group",
+            Column::HardLinks                     => "links",
+            Column::SymlinkGroup                  => "linkgroup",
+            Column::Inode                         => "inode",
+            Column::GitStatus                     => "git",
+            Column::GitSince                      => "since",
+            Column::GitLastCommit                 => "commit",
+            Column::GitRemote                     => "remote",
+            Column::Context                       => "context",
+        }
+    }
+
+    /// Get the column with the given name, as returned by `Column::name`.
+    pub fn from_name(name: &str) -> Option<Column> {
+        match name {
+            "permissions"  => Some(Column::Permissions),
+            "size"         => Some(Column::FileSize),
+            "percent"      => Some(Column::SizePercent),
+            "allocated"    => Some(Column::AllocatedSize),
+            "modified"     => Some(Column::Timestamp(TimeType::Modified)),
+            "created"      => Some(Column::Timestamp(TimeType::Created)),
+            "accessed"     => Some(Column::Timestamp(TimeType::Accessed)),
+            "age"          => Some(Column::Age),
+            "anomaly"      => Some(Column::TimeAnomaly),
+            "flags"        => Some(Column::Flags),
+            "blocks"       => Some(Column::Blocks),
+            "sparse"       => Some(Column::Sparseness),
+            "entries"      => Some(Column::EntryCount),
+            "content"      => Some(Column::ContentType),
+            "lines"        => Some(Column::LineCount),
+            "user"         => Some(Column::User),
+            "group"        => Some(Column::Group),
+            "links"        => Some(Column::HardLinks),
+            "linkgroup"    => Some(Column::SymlinkGroup),
+            "inode"        => Some(Column::Inode),
+            "git"          => Some(Column::GitStatus),
+            "since"        => Some(Column::GitSince),
+            "commit"       => Some(Column::GitLastCommit),
+            "remote"       => Some(Column::GitRemote),
+            "context"      => Some(Column::Context),
+            _              => None,
+        }
+    }
+
+    /// Whether this column can be shown for the files in the given
+    /// directory. The Git columns need the directory to be in a repository,
+    /// and to have been compared against a reference for `since`.
+    fn is_available(&self, dir: Option<&Dir>) -> bool {
+        match *self {
+            Column::GitStatus      => cfg!(feature="git") && dir.map(|d| d.has_git_repo()).unwrap_or(false),
+            Column::GitSince       => cfg!(feature="git") && dir.map(|d| d.has_git_since()).unwrap_or(false),
+            Column::GitLastCommit  => cfg!(feature="git") && dir.map(|d| d.has_git_last_commits()).unwrap_or(false),
+            Column::GitRemote      => cfg!(feature="git"),
+            _                      => true,
+        }
+    }
+
     /// Get the text that should be printed at the top, when the user elects
     /// to have a header row printed.
     pub fn header(&self) -> &'static str {
         match *self {
             Column::Permissions   => "Permissions",
             Column::FileSize      => "Size",
+            Column::SizePercent   => "Percent",
+            Column::AllocatedSize => "Allocated",
             Column::Timestamp(t)  => t.header(),
+            Column::Age           => "Age",
+            Column::TimeAnomaly   => "Anomaly",
+            Column::Flags         => "Flags",
             Column::Blocks        => "Blocks",
+            Column::Sparseness    => "Sparse",
+            Column::EntryCount    => "Entries",
+            Column::ContentType   => "Content",
+            Column::LineCount     => "Lines",
             Column::User          => "User",
             Column::Group         => "Group",
             Column::HardLinks     => "Links",
+            Column::SymlinkGroup  => "Link group",
             Column::Inode         => "inode",
             Column::GitStatus     => "Git",
+            Column::GitSince      => "Since",
+            Column::GitLastCommit => "Commit",
+            Column::GitRemote     => "Remote",
+            Column::Context       => "Context",
         }
     }
 }
@@ -230,6 +537,53 @@ impl Default for TimeTypes {
 }
 
 
+/// How many seconds old a file can be to fall into each of the buckets in
+/// the age column, unless others are picked: a minute, an hour, a day, a
+/// week, and thirty days. Anything older than that is old.
+pub const DEFAULT_AGE_THRESHOLDS: [i64; 5] = [ 60, 60 * 60, 24 * 60 * 60, 7 * 24 * 60 * 60, 30 * 24 * 60 * 60 ];
+
+/// The buckets that files get sorted into by how long ago one of their
+/// timestamps was, for the age column.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct AgeBuckets {
+
+    /// Which of the file’s timestamps to go by.
+    pub time_type: TimeType,
+
+    /// The time to count back from, in seconds since the Unix epoch. This is
+    /// the time exa started running, except in tests.
+    pub now: i64,
+
+    /// The number of seconds old a file can be to fall into each bucket but
+    /// the last, in ascending order.
+    pub thresholds: [i64; 5],
+}
+
+impl AgeBuckets {
+
+    /// The bucket that something the given number of seconds old falls
+    /// into. Timestamps in the future count as now.
+    pub fn bucket(&self, seconds_old: i64) -> f::Age {
+        let ages = [ f::Age::Now, f::Age::Hour, f::Age::Day, f::Age::Week, f::Age::Month ];
+
+        match self.thresholds.iter().position(|&t| seconds_old < t) {
+            Some(index)  => ages[index],
+            None         => f::Age::Old,
+        }
+    }
+
+    /// The bucket that the given file falls into.
+    pub fn age_of(&self, file: &File) -> f::Age {
+        let time = match self.time_type {
+            TimeType::Modified  => file.modified_time(),
+            TimeType::Created   => file.created_time(),
+            TimeType::Accessed  => file.accessed_time(),
+        };
+
+        self.bucket(self.now - i64::from(time.seconds))
+    }
+}
+
 
 
 /// The **environment** struct contains any data that could change between
@@ -287,6 +641,47 @@ pub struct Table<'a> {
     widths: TableWidths,
     time_format: &'a TimeFormat,
     size_format: SizeFormat,
+    alignments: &'a Alignments,
+
+    /// The shorter time format to use instead, when the table had to be
+    /// made to fit into the terminal.
+    short_time: Option<TimeFormat>,
+
+    /// Whether to leave the link counts of files with only one link blank.
+    multiple_links_only: bool,
+
+    /// Whether to leave the size and timestamps of symlinks blank.
+    blank_symlink_meta: bool,
+
+    /// Whether to leave the group blank when it’s named after the user.
+    collapse_owner: bool,
+
+    /// Whether to show directories’ entry counts in the size column, and
+    /// which entries to count.
+    dir_entries: Option<DotFilter>,
+
+    /// The name of the remote to show the URL of in the remote column.
+    remote_name: &'a str,
+
+    /// How to sort the files into buckets in the age column.
+    age_buckets: AgeBuckets,
+
+    /// How many components of the path of the directory each file is in
+    /// to show in the context column, which are the ones from the directory
+    /// given as an argument down.
+    context_depth: usize,
+
+    /// The total size of the files listed alongside the ones whose rows get
+    /// made next, for the percentages in the size percent column.
+    size_total: u64,
+
+    /// The number of the group of symlinks that lead to each canonical
+    /// path, for the link group column.
+    symlink_groups: HashMap<PathBuf, usize>,
+
+    /// The cells of the files that were measured to make the table fit
+    /// into the terminal, by path, so they don’t have to be made again.
+    fitted_cells: HashMap<PathBuf, Vec<TextCell>>,
 }
 
 #[derive(Clone)]
@@ -294,6 +689,14 @@ pub struct Row {
     cells: Vec<TextCell>,
 }
 
+impl Row {
+
+    /// The text in each of this row’s cells, without any padding.
+    pub fn cell_strings(&self) -> Vec<String> {
+        self.cells.iter().map(|c| c.contents.strings().to_string()).collect()
+    }
+}
+
 impl<'a, 'f> Table<'a> {
     pub fn new(options: &'a Options, dir: Option<&'a Dir>, colours: &'a Colours) -> Table<'a> {
         let colz = options.extra_columns.for_dir(dir);
@@ -303,6 +706,18 @@ impl<'a, 'f> Table<'a> {
             env:         &options.env,
             time_format: &options.time_format,
             size_format:  options.size_format,
+            alignments:  &options.alignments,
+            short_time:  None,
+            multiple_links_only: options.extra_columns.multiple_links && !options.extra_columns.links,
+            blank_symlink_meta: options.extra_columns.blank_symlink_meta,
+            collapse_owner: options.extra_columns.collapse_owner,
+            dir_entries: options.extra_columns.dir_entries,
+            remote_name: &options.extra_columns.remote_name,
+            age_buckets: options.extra_columns.age_buckets,
+            context_depth: 0,
+            size_total: 0,
+            symlink_groups: HashMap::new(),
+            fitted_cells: HashMap::new(),
         }
     }
 
@@ -310,6 +725,100 @@ impl<'a, 'f> Table<'a> {
         &self.widths
     }
 
+    fn time_format(&self) -> &TimeFormat {
+        self.short_time.as_ref().unwrap_or(self.time_format)
+    }
+
+    /// Shortens the timestamps and drops columns, as the given fit allows,
+    /// until the rows for the given files, with the header if there is one
+    /// and the widest name after them, fit into its width. If they still
+    /// don’t after every column that can be dropped has been, the table is
+    /// left as narrow as it gets.
+    ///
+    /// Each file’s cells only get made once: dropping a column just takes
+    /// its width off the total, and the cells that are left get used for
+    /// the files’ rows afterwards. Only the given files get measured, so in
+    /// a tree, the files further down could still be too wide.
+    pub fn fit_into_width(&mut self, fit: &Fit, files: &[File], header: bool, name_width: usize) {
+        use output::time::ISOFormat;
+
+        self.fitted_cells.clear();
+        let mut rows: Vec<Row> = files.iter().map(|f| self.row_for_file(f, false, false)).collect();
+        let mut widths = self.widths_for(&rows, header);
+
+        if fit.shorten_time && widths.total() + name_width > fit.width {
+            let times: Vec<usize> = self.columns.iter().enumerate()
+                                        .filter(|&(_, c)| match *c { Column::Timestamp(_) | Column::GitLastCommit => true, _ => false })
+                                        .map(|(n, _)| n)
+                                        .collect();
+
+            if !times.is_empty() {
+                self.short_time = Some(TimeFormat::ISOFormat(ISOFormat::new()));
+                self.redisplay(files, &mut rows, &times);
+                widths = self.widths_for(&rows, header);
+            }
+        }
+
+        while widths.total() + name_width > fit.width {
+
+            // Picked columns get dropped from the end, keeping the first.
+            let drop = if !fit.picked          { DROP_ORDER.iter().filter_map(|d| self.columns.iter().position(|c| c == d)).next() }
+                  else if self.columns.len() > 1 { Some(self.columns.len() - 1) }
+                  else                           { None };
+
+            let index = match drop {
+                Some(index)  => index,
+                None         => break,
+            };
+
+            let dropped = self.columns.remove(index);
+            let _ = widths.0.remove(index);
+            for row in &mut rows {
+                let _ = row.cells.remove(index);
+            }
+
+            // The sparseness column shows more when the size and block
+            // columns are both there, so it has to be made again without.
+            if dropped == Column::FileSize || dropped == Column::Blocks {
+                if let Some(sparse) = self.columns.iter().position(|c| *c == Column::Sparseness) {
+                    self.redisplay(files, &mut rows, &[ sparse ]);
+                    widths = self.widths_for(&rows, header);
+                }
+            }
+        }
+
+        self.fitted_cells = files.iter().map(|f| f.path.clone())
+                                 .zip(rows.into_iter().map(|r| r.cells))
+                                 .collect();
+        self.widths = TableWidths::zero(self.columns.len());
+    }
+
+    /// Makes the cells in the given columns of the given files’ rows again,
+    /// after something that changes how they look.
+    fn redisplay(&self, files: &[File], rows: &mut [Row], indices: &[usize]) {
+        for (file, row) in files.iter().zip(rows.iter_mut()) {
+            for &n in indices {
+                row.cells[n] = self.display(file, &self.columns[n], false, false);
+            }
+        }
+    }
+
+    /// The widths of the columns with just the given rows, and the header
+    /// if there is one.
+    fn widths_for(&self, rows: &[Row], header: bool) -> TableWidths {
+        let mut widths = TableWidths::zero(self.columns.len());
+
+        if header {
+            widths.add_widths(&self.header_row());
+        }
+
+        for row in rows {
+            widths.add_widths(row);
+        }
+
+        widths
+    }
+
     pub fn header_row(&self) -> Row {
         let cells = self.columns.iter()
                         .map(|c| TextCell::paint_str(self.colours.header, c.header()))
@@ -318,9 +827,17 @@ impl<'a, 'f> Table<'a> {
         Row { cells }
     }
 
-    pub fn row_for_file(&self, file: &File, xattrs: bool) -> Row {
-        let cells = self.columns.iter()
-                        .map(|c| self.display(file, c, xattrs))
+    /// Makes the row for the given file, re-using the cells made for it
+    /// while fitting the table into the terminal, if there are any. Only
+    /// the permissions get made again, as the extended attributes and ACL
+    /// weren’t known then.
+    pub fn row_for_file(&self, file: &File, xattrs: bool, acl: bool) -> Row {
+        let fitted = self.fitted_cells.get(&file.path);
+        let cells = self.columns.iter().enumerate()
+                        .map(|(n, c)| match fitted {
+                            Some(cells) if *c != Column::Permissions  => cells[n].clone(),
+                            _                                         => self.display(file, c, xattrs, acl),
+                        })
                         .collect();
 
         Row { cells }
@@ -330,30 +847,198 @@ impl<'a, 'f> Table<'a> {
         self.widths.add_widths(row)
     }
 
-    fn permissions_plus(&self, file: &File, xattrs: bool) -> f::PermissionsPlus {
+    /// Sets how many directories down from the arguments the files whose
+    /// rows get made next are, counting the arguments’ own directories, for
+    /// the context column.
+    pub fn set_context_depth(&mut self, depth: usize) {
+        self.context_depth = depth;
+    }
+
+    /// Adds up the sizes of the given files, which are the ones listed
+    /// together, so each one’s share of the total can be shown in the size
+    /// percent column. This has to happen before any of their rows get made.
+    /// Files without a size, such as directories, don’t count towards it.
+    pub fn set_size_total(&mut self, files: &[File]) {
+        if !self.columns.contains(&Column::SizePercent) {
+            return;
+        }
+
+        self.size_total = files.iter()
+                               .filter_map(|f| match f.size() { f::Size::Some(s) => Some(s), _ => None })
+                               .sum();
+    }
+
+    /// Numbers the canonical paths that the given files’ symlinks lead to,
+    /// so links to the same file show the same number in the link group
+    /// column. This has to happen before any of the files’ rows get made.
+    /// Paths from earlier calls keep their numbers, and new ones get
+    /// numbered in order, so the numbers don’t depend on the sort order.
+    pub fn add_symlink_targets(&mut self, files: &[File]) {
+        if !self.columns.contains(&Column::SymlinkGroup) {
+            return;
+        }
+
+        let mut targets: Vec<PathBuf> = files.iter()
+                                             .filter(|f| f.is_link())
+                                             .filter_map(File::canonical_target)
+                                             .filter(|t| !self.symlink_groups.contains_key(t))
+                                             .collect();
+        targets.sort();
+        targets.dedup();
+
+        for target in targets {
+            let number = self.symlink_groups.len() + 1;
+            let _ = self.symlink_groups.insert(target, number);
+        }
+    }
+
+    fn permissions_plus(&self, file: &File, xattrs: bool, acl: bool) -> f::PermissionsPlus {
         f::PermissionsPlus {
             file_type: file.type_char(),
             permissions: file.permissions(),
             xattrs: xattrs,
+            acl: acl,
+        }
+    }
+
+    /// Renders the file’s link count, unless only counts above one are
+    /// being shown and this isn’t one. Directories always have at least two
+    /// links, so they never count as having multiple.
+    fn links(&self, file: &File) -> TextCell {
+        let links = file.links();
+        if self.multiple_links_only && !links.multiple {
+            TextCell::default()
+        }
+        else {
+            links.render(&self.colours, &self.env.numeric)
+        }
+    }
+
+    /// Renders the number of the group of symlinks this one belongs to,
+    /// going by where it leads, or that it’s broken. Files that aren’t
+    /// symlinks don’t belong to any group.
+    fn symlink_group(&self, file: &File) -> TextCell {
+        if !file.is_link() {
+            return TextCell::blank(self.colours.punctuation);
+        }
+
+        match file.canonical_target().and_then(|t| self.symlink_groups.get(&t)) {
+            Some(number)  => TextCell::paint(self.colours.symlink_path, number.to_string()),
+            None          => TextCell::paint_str(self.colours.broken_arrow, "broken"),
         }
     }
 
-    fn display(&self, file: &File, column: &Column, xattrs: bool) -> TextCell {
+    /// Renders the path of the directory the file is in, starting from the
+    /// directory given as an argument. Files that were given as arguments
+    /// themselves show their directory as it was given, or `.` if there
+    /// wasn’t one.
+    fn context(&self, file: &File) -> TextCell {
+        let parent = file.path.parent().unwrap_or_else(|| Path::new(""));
+        let skip = if file.parent_dir.is_some() { parent.components().count().saturating_sub(self.context_depth) }
+                                           else { 0 };
+
+        let context: PathBuf = parent.components().skip(skip).collect();
+        if context.as_os_str().is_empty() {
+            TextCell::paint_str(self.colours.filetypes.directory, ".")
+        }
+        else {
+            TextCell::paint(self.colours.filetypes.directory, context.display().to_string())
+        }
+    }
+
+    /// Renders the file’s size, or the number of entries inside it if it’s a
+    /// directory and those are being shown instead.
+    fn size(&self, file: &File) -> TextCell {
+        if let Some(dot_filter) = self.dir_entries {
+            if file.is_directory() {
+                return file.item_count(dot_filter).render(&self.colours, &self.env.numeric);
+            }
+        }
+
+        file.size().render(&self.colours, self.size_format, &self.env.numeric)
+    }
+
+    /// Renders the file’s size as a percentage of the total size of the
+    /// files listed alongside it, or a blank if it doesn’t have a size or
+    /// there’s nothing to take a share of.
+    fn size_percent(&self, file: &File) -> TextCell {
+        match file.size() {
+            f::Size::Some(size) if self.size_total > 0 => {
+                let percent = size as f64 * 100f64 / self.size_total as f64;
+                let number = self.env.numeric.format_float(percent, 1);
+
+                TextCell {
+                    width:    DisplayWidth::from(number.len() + 1),
+                    contents: vec![
+                        self.colours.file_size(size).paint(number),
+                        self.colours.size.unit.paint("%"),
+                    ].into(),
+                }
+            },
+            _ => TextCell::blank(self.colours.punctuation),
+        }
+    }
+
+    /// Renders whether the file is sparse, along with how much space it
+    /// really takes up when the size and block columns are both being
+    /// shown, so the numbers can be compared.
+    fn sparseness(&self, file: &File) -> TextCell {
+        if self.columns.contains(&Column::FileSize) && self.columns.contains(&Column::Blocks) {
+            file.sparseness().render_allocated(&self.colours, self.size_format, &self.env.numeric)
+        }
+        else {
+            file.sparseness().render(&self.colours)
+        }
+    }
+
+    /// Renders the file’s group, unless it has the same name as the file’s
+    /// user and those are being collapsed.
+    fn group(&self, file: &File) -> TextCell {
+        let users = self.env.lock_users();
+        if self.collapse_owner && file.group().is_named_after(file.user(), &*users) {
+            TextCell::blank(self.colours.punctuation)
+        }
+        else {
+            file.group().render(&self.colours, &*users)
+        }
+    }
+
+    fn display(&self, file: &File, column: &Column, xattrs: bool, acl: bool) -> TextCell {
         use output::table::TimeType::*;
 
+        if self.blank_symlink_meta && file.is_link() {
+            if let Column::FileSize | Column::AllocatedSize | Column::Timestamp(_) | Column::Age = *column {
+                return TextCell::blank(self.colours.punctuation);
+            }
+        }
+
         match *column {
-            Column::Permissions    => self.permissions_plus(file, xattrs).render(&self.colours),
-            Column::FileSize       => file.size().render(&self.colours, self.size_format, &self.env.numeric),
-            Column::HardLinks      => file.links().render(&self.colours, &self.env.numeric),
+            Column::Permissions    => self.permissions_plus(file, xattrs, acl).render(&self.colours),
+            Column::FileSize       => self.size(file),
+            Column::SizePercent    => self.size_percent(file),
+            Column::AllocatedSize  => file.allocated_size().render(&self.colours, self.size_format, &self.env.numeric),
+            Column::HardLinks      => self.links(file),
+            Column::SymlinkGroup   => self.symlink_group(file),
             Column::Inode          => file.inode().render(&self.colours),
             Column::Blocks         => file.blocks().render(&self.colours),
+            Column::Sparseness     => self.sparseness(file),
+            Column::Age            => self.age_buckets.age_of(file).render(&self.colours),
+            Column::Context        => self.context(file),
+            Column::TimeAnomaly    => file.time_anomaly().render(&self.colours),
+            Column::Flags          => file.flags().render(&self.colours),
+            Column::ContentType    => file.content_type().render(&self.colours),
+            Column::LineCount      => file.line_count().render(&self.colours, &self.env.numeric),
+            Column::EntryCount     => file.entry_count().render(&self.colours, &self.env.numeric),
             Column::User           => file.user().render(&self.colours, &*self.env.lock_users()),
-            Column::Group          => file.group().render(&self.colours, &*self.env.lock_users()),
+            Column::Group          => self.group(file),
             Column::GitStatus      => file.git_status().render(&self.colours),
+            Column::GitSince       => file.git_since_status().render(&self.colours),
+            Column::GitLastCommit  => file.git_last_commit().render(&self.colours, &self.env.tz, self.time_format()),
+            Column::GitRemote      => file.git_remote(self.remote_name).render(&self.colours),
 
-            Column::Timestamp(Modified)  => file.modified_time().render(&self.colours, &self.env.tz, &self.time_format),
-            Column::Timestamp(Created)   => file.created_time().render( &self.colours, &self.env.tz, &self.time_format),
-            Column::Timestamp(Accessed)  => file.accessed_time().render(&self.colours, &self.env.tz, &self.time_format),
+            Column::Timestamp(Modified)  => file.modified_time().render(&self.colours, &self.env.tz, self.time_format()),
+            Column::Timestamp(Created)   => file.created_time().render( &self.colours, &self.env.tz, self.time_format()),
+            Column::Timestamp(Accessed)  => file.accessed_time().render(&self.colours, &self.env.tz, self.time_format()),
         }
     }
 
@@ -363,7 +1048,7 @@ impl<'a, 'f> Table<'a> {
         for (n, (this_cell, width)) in row.cells.into_iter().zip(self.widths.iter()).enumerate() {
             let padding = width - *this_cell.width;
 
-            match self.columns[n].alignment() {
+            match self.alignments.for_column(&self.columns[n]) {
                 Alignment::Left  => { cell.append(this_cell); cell.add_spaces(padding); }
                 Alignment::Right => { cell.add_spaces(padding); cell.append(this_cell); }
             }
@@ -402,3 +1087,549 @@ impl TableWidths {
         self.0.len() + self.0.iter().sum::<usize>()
     }
 }
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ansi_term::Style;
+
+    fn render(alignments: &Alignments) -> Vec<String> {
+        let env = Environment::load_all();
+        let colours = Colours::default();
+        let time_format = TimeFormat::LongISO;
+        let mut table = Table {
+            columns: vec![ Column::FileSize, Column::User ],
+            colours: &colours,
+            env: &env,
+            widths: TableWidths::zero(2),
+            time_format: &time_format,
+            size_format: SizeFormat::DecimalBytes,
+            alignments: alignments,
+            short_time: None,
+            multiple_links_only: false,
+            blank_symlink_meta: false,
+            collapse_owner: false,
+            dir_entries: None,
+            remote_name: DEFAULT_REMOTE,
+            symlink_groups: HashMap::new(),
+            fitted_cells: HashMap::new(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            context_depth: 0,
+            size_total: 0,
+        };
+
+        let rows = vec![
+            Row { cells: vec![ TextCell::paint_str(Style::default(), "1.2k"), TextCell::paint_str(Style::default(), "root") ] },
+            Row { cells: vec![ TextCell::paint_str(Style::default(), "5"),    TextCell::paint_str(Style::default(), "ben") ] },
+        ];
+
+        for row in &rows {
+            table.add_widths(row);
+        }
+
+        rows.into_iter().map(|r| table.render(r).contents.strings().to_string()).collect()
+    }
+
+    #[test]
+    fn default_alignments() {
+        assert_eq!(render(&Alignments::default()), vec![ "1.2k root ", "   5 ben  " ]);
+    }
+
+    #[test]
+    fn size_left_aligned() {
+        let alignments = Alignments(vec![ ("size", Alignment::Left) ]);
+        assert_eq!(render(&alignments), vec![ "1.2k root ", "5    ben  " ]);
+    }
+
+    #[test]
+    fn user_right_aligned() {
+        let alignments = Alignments(vec![ ("user", Alignment::Right), ("size", Alignment::Left), ("size", Alignment::Right) ]);
+        assert_eq!(render(&alignments), vec![ "1.2k root ", "   5  ben " ]);
+    }
+
+    #[test]
+    fn timestamp_names() {
+        assert_eq!("created", Column::Timestamp(TimeType::Created).name());
+        assert!(COLUMN_NAMES.contains(&Column::Timestamp(TimeType::Accessed).name()));
+    }
+
+    #[test]
+    fn ordered_columns() {
+        let columns = Columns {
+            time_types: TimeTypes::default(),
+            inode: true, links: false, blocks: false, sparse: false, time_anomalies: false, flags: false, entries: false,
+            content: false, lines: false, symlink_groups: false, age: false, group: false, git: false, context: false, size_percent: false, git_since: false, git_last_commit: false, multiple_links: false,
+            blank_symlink_meta: false, collapse_owner: false, dir_entries: None, git_remote: false, remote_name: DEFAULT_REMOTE.into(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            compact: false,
+            order: Some(vec![ Column::FileSize, Column::Permissions, Column::Timestamp(TimeType::Modified), Column::User ]),
+        };
+
+        let options = Options {
+            env: Environment::load_all(),
+            size_format: SizeFormat::DecimalBytes,
+            time_format: TimeFormat::LongISO,
+            extra_columns: columns,
+            alignments: Alignments::default(),
+            fit: None,
+        };
+
+        let colours = Colours::plain();
+        let table = Table::new(&options, None, &colours);
+        let headers: Vec<String> = table.header_row().cells.iter().map(|c| c.contents.strings().to_string()).collect();
+        assert_eq!(headers, vec![ "Size", "Permissions", "Date Modified", "User" ]);
+    }
+
+    #[test]
+    fn compact_columns() {
+        let columns = Columns {
+            time_types: TimeTypes::default(),
+            inode: true, links: true, blocks: false, sparse: false, time_anomalies: false, flags: false, entries: false,
+            content: false, lines: false, symlink_groups: false, age: false, group: true, git: true, context: false, size_percent: false, git_since: false, git_last_commit: false, multiple_links: false,
+            blank_symlink_meta: false, collapse_owner: false, dir_entries: None, git_remote: false, remote_name: DEFAULT_REMOTE.into(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            compact: true,
+            order: None,
+        };
+
+        assert_eq!(columns.for_dir(None), vec![ Column::Permissions, Column::FileSize ]);
+        assert!(!columns.should_scan_for_git());
+    }
+
+    #[test]
+    fn fitting_into_sixty_columns() {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+        use fs::{Dir, DotFilter};
+
+        let scratch = ScratchDir::new("table-fit");
+        let path = &scratch.path;
+        for name in &[ "one.txt", "two.txt", "three.rs" ] {
+            let _ = std_fs::File::create(path.join(name)).unwrap();
+        }
+
+        let columns = Columns {
+            time_types: TimeTypes { modified: true, accessed: true, created: false },
+            inode: true, links: true, blocks: true, sparse: false, time_anomalies: false, flags: false, entries: false,
+            content: false, lines: false, symlink_groups: false, age: false, group: true, git: false, context: false, size_percent: false, git_since: false, git_last_commit: false, multiple_links: false,
+            blank_symlink_meta: false, collapse_owner: false, dir_entries: None, git_remote: false, remote_name: DEFAULT_REMOTE.into(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            compact: false,
+            order: None,
+        };
+
+        let fit = Fit { width: 60, shorten_time: true, picked: false };
+        let options = Options {
+            env: Environment::load_all(),
+            size_format: SizeFormat::DecimalBytes,
+            time_format: TimeFormat::FullISO,
+            extra_columns: columns,
+            alignments: Alignments::default(),
+            fit: Some(fit),
+        };
+
+        let dir = Dir::read_dir(path.clone(), None).unwrap();
+        let files: Vec<File> = dir.files(DotFilter::JustFiles).map(Result::unwrap).collect();
+
+        let colours = Colours::plain();
+        let mut table = Table::new(&options, None, &colours);
+        table.fit_into_width(&fit, &files, true, 8);
+
+        // The group, links, blocks, and inode columns always have to go,
+        // but the accessed time only does for users with long names.
+        for dropped in &[ Column::Group, Column::HardLinks, Column::Blocks, Column::Inode ] {
+            assert!(!table.columns.contains(dropped), "{:?} was kept", dropped);
+        }
+
+        assert_eq!(&table.columns[.. 4], &[ Column::Permissions, Column::FileSize, Column::User, Column::Timestamp(TimeType::Modified) ]);
+
+        // Every file’s cells were kept, with just the columns left.
+        assert_eq!(table.fitted_cells.len(), files.len());
+        assert!(table.fitted_cells.values().all(|cells| cells.len() == table.columns.len()));
+
+        let header = table.header_row();
+        table.add_widths(&header);
+        for file in &files {
+            let row = table.row_for_file(file, false, false);
+            assert_eq!(row.cells[3].contents.strings().to_string().len(), "10-16 12:34".len());
+            table.add_widths(&row);
+        }
+
+        assert!(table.widths().total() + 8 <= 60, "{} columns wide", table.widths().total() + 8);
+    }
+
+    #[test]
+    fn age_thresholds() {
+        let buckets = AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: [ 10, 20, 30, 40, 50 ] };
+        assert_eq!(f::Age::Now,   buckets.bucket(-5));
+        assert_eq!(f::Age::Now,   buckets.bucket(9));
+        assert_eq!(f::Age::Hour,  buckets.bucket(10));
+        assert_eq!(f::Age::Day,   buckets.bucket(25));
+        assert_eq!(f::Age::Week,  buckets.bucket(39));
+        assert_eq!(f::Age::Month, buckets.bucket(40));
+        assert_eq!(f::Age::Old,   buckets.bucket(50));
+    }
+
+    #[test]
+    fn files_in_age_buckets() {
+        use scratch::ScratchDir;
+        use std::ffi::CString;
+        use std::fs as std_fs;
+        use std::os::unix::ffi::OsStrExt;
+        use libc;
+
+        let now = 1_500_000_000;
+        let scratch = ScratchDir::new("age-buckets");
+        let path = &scratch.path;
+
+        let fixtures = [
+            ("future",      now + 100,               f::Age::Now),
+            ("just-now",    now - 10,                f::Age::Now),
+            ("this-hour",   now - 10 * 60,           f::Age::Hour),
+            ("today",       now - 5 * 60 * 60,       f::Age::Day),
+            ("this-week",   now - 3 * 24 * 60 * 60,  f::Age::Week),
+            ("this-month",  now - 20 * 24 * 60 * 60, f::Age::Month),
+            ("ancient",     now - 400 * 24 * 60 * 60, f::Age::Old),
+        ];
+
+        let buckets = AgeBuckets { time_type: TimeType::Modified, now, thresholds: DEFAULT_AGE_THRESHOLDS };
+        for &(name, modified, age) in &fixtures {
+            let file_path = path.join(name);
+            let _ = std_fs::File::create(&file_path).unwrap();
+
+            let c_path = CString::new(file_path.as_os_str().as_bytes()).unwrap();
+            let times = [ libc::timeval { tv_sec: modified, tv_usec: 0 }; 2 ];
+            assert_eq!(0, unsafe { libc::utimes(c_path.as_ptr(), times.as_ptr()) });
+
+            let file = File::new(file_path, None, None).unwrap();
+            assert_eq!((name, age), (name, buckets.age_of(&file)));
+        }
+    }
+
+    fn link_counts(multiple_links_only: bool) -> Vec<String> {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+        use std::path::PathBuf;
+
+        let scratch = ScratchDir::new(&format!("multiple-links-{}", multiple_links_only));
+        let path = &scratch.path;
+        let _ = std_fs::File::create(path.join("single")).unwrap();
+        let _ = std_fs::File::create(path.join("linked")).unwrap();
+        std_fs::hard_link(path.join("linked"), path.join("also-linked")).unwrap();
+        std_fs::create_dir(path.join("dir")).unwrap();
+
+        let env = Environment::load_all();
+        let colours = Colours::plain();
+        let time_format = TimeFormat::LongISO;
+        let alignments = Alignments::default();
+        let table = Table {
+            columns: vec![ Column::HardLinks ],
+            colours: &colours,
+            env: &env,
+            widths: TableWidths::zero(1),
+            time_format: &time_format,
+            size_format: SizeFormat::DecimalBytes,
+            alignments: &alignments,
+            short_time: None,
+            multiple_links_only: multiple_links_only,
+            blank_symlink_meta: false,
+            collapse_owner: false,
+            dir_entries: None,
+            remote_name: DEFAULT_REMOTE,
+            symlink_groups: HashMap::new(),
+            fitted_cells: HashMap::new(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            context_depth: 0,
+            size_total: 0,
+        };
+
+        let counts = [ "single", "linked", "dir" ].iter().map(|name| {
+            let file = File::new(PathBuf::from(path.join(name)), None, None).unwrap();
+            table.links(&file).contents.strings().to_string()
+        }).collect();
+
+        counts
+    }
+
+    #[test]
+    fn every_link_count() {
+        assert_eq!(link_counts(false), vec![ "1", "2", "2" ]);
+    }
+
+    #[test]
+    fn only_multiple_link_counts() {
+        assert_eq!(link_counts(true), vec![ "", "2", "" ]);
+    }
+
+    fn symlink_cells(blank_symlink_meta: bool) -> Vec<Vec<String>> {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+        use std::os::unix::fs::symlink;
+        use std::path::PathBuf;
+
+        let scratch = ScratchDir::new(&format!("blank-symlink-meta-{}", blank_symlink_meta));
+        let path = &scratch.path;
+        let _ = std_fs::File::create(path.join("file")).unwrap();
+        symlink("file", path.join("link")).unwrap();
+
+        let env = Environment::load_all();
+        let colours = Colours::plain();
+        let time_format = TimeFormat::LongISO;
+        let alignments = Alignments::default();
+        let table = Table {
+            columns: vec![ Column::FileSize, Column::Timestamp(TimeType::Modified), Column::Inode ],
+            colours: &colours,
+            env: &env,
+            widths: TableWidths::zero(3),
+            time_format: &time_format,
+            size_format: SizeFormat::DecimalBytes,
+            alignments: &alignments,
+            short_time: None,
+            multiple_links_only: false,
+            blank_symlink_meta: blank_symlink_meta,
+            collapse_owner: false,
+            dir_entries: None,
+            remote_name: DEFAULT_REMOTE,
+            symlink_groups: HashMap::new(),
+            fitted_cells: HashMap::new(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            context_depth: 0,
+            size_total: 0,
+        };
+
+        let cells = [ "file", "link" ].iter().map(|name| {
+            let file = File::new(PathBuf::from(path.join(name)), None, None).unwrap();
+            table.row_for_file(&file, false, false).cells.iter().map(|c| c.contents.strings().to_string()).collect()
+        }).collect();
+
+        cells
+    }
+
+    #[test]
+    fn symlink_meta_shown() {
+        let cells = symlink_cells(false);
+        assert_eq!(cells[1][0], "4");
+        assert!(cells[1][1] != "-");
+    }
+
+    #[test]
+    fn symlink_meta_blanked() {
+        let cells = symlink_cells(true);
+        assert_eq!(cells[0][0], "0");
+        assert!(cells[0][1] != "-");
+
+        assert_eq!(cells[1][0], "-");
+        assert_eq!(cells[1][1], "-");
+        assert!(cells[1][2] != "-");
+    }
+
+    fn size_cells(dir_entries: Option<DotFilter>) -> Vec<String> {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+        use std::path::PathBuf;
+
+        let scratch = ScratchDir::new(&format!("dir-entries-{:?}", dir_entries));
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("one")).unwrap();
+        std_fs::create_dir_all(path.join("three")).unwrap();
+        for name in &[ "one/a", "one/.b", "three/a", "three/b", "three/c", "file" ] {
+            let _ = std_fs::File::create(path.join(name)).unwrap();
+        }
+
+        let env = Environment::load_all();
+        let colours = Colours::plain();
+        let time_format = TimeFormat::LongISO;
+        let alignments = Alignments::default();
+        let table = Table {
+            columns: vec![ Column::FileSize ],
+            colours: &colours,
+            env: &env,
+            widths: TableWidths::zero(1),
+            time_format: &time_format,
+            size_format: SizeFormat::DecimalBytes,
+            alignments: &alignments,
+            short_time: None,
+            multiple_links_only: false,
+            blank_symlink_meta: false,
+            collapse_owner: false,
+            dir_entries: dir_entries,
+            remote_name: DEFAULT_REMOTE,
+            symlink_groups: HashMap::new(),
+            fitted_cells: HashMap::new(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            context_depth: 0,
+            size_total: 0,
+        };
+
+        let cells = [ "one", "three", "file" ].iter().map(|name| {
+            let file = File::new(PathBuf::from(path.join(name)), None, None).unwrap();
+            table.row_for_file(&file, false, false).cells[0].contents.strings().to_string()
+        }).collect();
+
+        cells
+    }
+
+    #[test]
+    fn dir_sizes_dashed() {
+        assert_eq!(size_cells(None), vec![ "-", "-", "0" ]);
+    }
+
+    #[test]
+    fn dir_entries_counted() {
+        assert_eq!(size_cells(Some(DotFilter::JustFiles)), vec![ "1 item", "3 items", "0" ]);
+    }
+
+    #[test]
+    fn dir_entries_with_dotfiles() {
+        assert_eq!(size_cells(Some(DotFilter::Dotfiles)), vec![ "2 items", "3 items", "0" ]);
+    }
+
+    #[test]
+    fn symlinks_grouped_by_target() {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+        use std::os::unix::fs::symlink;
+
+        let scratch = ScratchDir::new("symlink-groups");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("dir")).unwrap();
+        let _ = std_fs::File::create(path.join("one")).unwrap();
+        let _ = std_fs::File::create(path.join("two")).unwrap();
+        symlink("one", path.join("to-one")).unwrap();
+        symlink("../one", path.join("dir/also-to-one")).unwrap();
+        symlink("to-one", path.join("via-to-one")).unwrap();
+        symlink("two", path.join("to-two")).unwrap();
+        symlink("nowhere", path.join("broken")).unwrap();
+        symlink("loop-b", path.join("loop-a")).unwrap();
+        symlink("loop-a", path.join("loop-b")).unwrap();
+
+        let names = [ "one", "to-one", "dir/also-to-one", "via-to-one", "to-two", "broken", "loop-a" ];
+        let files: Vec<File> = names.iter().map(|n| File::new(path.join(n), None, None).unwrap()).collect();
+
+        let env = Environment::load_all();
+        let colours = Colours::plain();
+        let time_format = TimeFormat::LongISO;
+        let alignments = Alignments::default();
+        let mut table = Table {
+            columns: vec![ Column::SymlinkGroup ],
+            colours: &colours,
+            env: &env,
+            widths: TableWidths::zero(1),
+            time_format: &time_format,
+            size_format: SizeFormat::DecimalBytes,
+            alignments: &alignments,
+            short_time: None,
+            multiple_links_only: false,
+            blank_symlink_meta: false,
+            collapse_owner: false,
+            dir_entries: None,
+            remote_name: DEFAULT_REMOTE,
+            symlink_groups: HashMap::new(),
+            fitted_cells: HashMap::new(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            context_depth: 0,
+            size_total: 0,
+        };
+
+        table.add_symlink_targets(&files);
+        let cells: Vec<String> = files.iter().map(|f| table.row_for_file(f, false, false).cell_strings().remove(0)).collect();
+
+        assert_eq!(cells, vec![ "-", "1", "1", "1", "2", "broken", "broken" ]);
+    }
+
+    #[test]
+    fn size_percentages_add_up() {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+
+        let scratch = ScratchDir::new("size-percent");
+        let path = &scratch.path;
+        std_fs::create_dir_all(path.join("dir")).unwrap();
+        for &(name, size) in &[ ("small", 100), ("middle", 250), ("large", 649), ("tiny", 1) ] {
+            std_fs::File::create(path.join(name)).unwrap().set_len(size).unwrap();
+        }
+
+        let names = [ "small", "middle", "large", "tiny", "dir" ];
+        let files: Vec<File> = names.iter().map(|n| File::new(path.join(n), None, None).unwrap()).collect();
+
+        let env = Environment::load_all();
+        let colours = Colours::plain();
+        let time_format = TimeFormat::LongISO;
+        let alignments = Alignments::default();
+        let mut table = Table {
+            columns: vec![ Column::SizePercent ],
+            colours: &colours,
+            env: &env,
+            widths: TableWidths::zero(1),
+            time_format: &time_format,
+            size_format: SizeFormat::DecimalBytes,
+            alignments: &alignments,
+            short_time: None,
+            multiple_links_only: false,
+            blank_symlink_meta: false,
+            collapse_owner: false,
+            dir_entries: None,
+            remote_name: DEFAULT_REMOTE,
+            symlink_groups: HashMap::new(),
+            fitted_cells: HashMap::new(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            context_depth: 0,
+            size_total: 0,
+        };
+
+        table.set_size_total(&files);
+        let cells: Vec<String> = files.iter().map(|f| table.row_for_file(f, false, false).cell_strings().remove(0)).collect();
+
+        assert_eq!(cells[4], "-");
+
+        let total: f64 = cells[.. 4].iter()
+                                    .map(|c| c.trim_right_matches('%').replace(&*env.numeric.decimal_sep, ".").parse::<f64>().unwrap())
+                                    .sum();
+        assert!((total - 100f64).abs() < 0.2, "percentages add up to {}", total);
+    }
+
+    #[test]
+    fn size_and_allocated_side_by_side() {
+        use scratch::ScratchDir;
+        use std::fs as std_fs;
+
+        let scratch = ScratchDir::new("allocated-sparse");
+        let path = scratch.join("sparse");
+        let sparse = std_fs::File::create(&path).unwrap();
+        sparse.set_len(1_048_576).unwrap();
+
+        let env = Environment::load_all();
+        let colours = Colours::plain();
+        let time_format = TimeFormat::LongISO;
+        let alignments = Alignments::default();
+        let table = Table {
+            columns: vec![ Column::FileSize, Column::AllocatedSize ],
+            colours: &colours,
+            env: &env,
+            widths: TableWidths::zero(2),
+            time_format: &time_format,
+            size_format: SizeFormat::DecimalBytes,
+            alignments: &alignments,
+            short_time: None,
+            multiple_links_only: false,
+            blank_symlink_meta: false,
+            collapse_owner: false,
+            dir_entries: None,
+            remote_name: DEFAULT_REMOTE,
+            symlink_groups: HashMap::new(),
+            fitted_cells: HashMap::new(),
+            age_buckets: AgeBuckets { time_type: TimeType::Modified, now: 0, thresholds: DEFAULT_AGE_THRESHOLDS },
+            context_depth: 0,
+            size_total: 0,
+        };
+
+        let file = File::new(path.clone(), None, None).unwrap();
+        let cells: Vec<String> = table.row_for_file(&file, false, false).cells.iter()
+                                      .map(|cell| cell.contents.strings().to_string())
+                                      .collect();
+
+        assert_eq!(cells, vec![ "1.0M", "0" ]);
+    }
+}
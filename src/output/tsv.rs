@@ -0,0 +1,115 @@
+//! The **TSV** view prints the same columns as the details view, but with a
+//! single tab between each one instead of padding, and without any colours,
+//! so its output can be read by other programs such as `awk`.
+//!
+//! The file’s name always goes in the last field. Tabs, newlines, and other
+//! control characters in any field get escaped, as do backslashes, so each
+//! line always holds exactly one file.
+
+use std::io::{Write, Result as IOResult};
+
+use fs::{Dir, File};
+use fs::feature::acl;
+use fs::feature::xattr::FileAttributes;
+use output::colours::Colours;
+use output::table::{Table, Options as TableOptions};
+
+
+#[derive(Debug)]
+pub struct Options {
+
+    /// The columns to show, and how to format their contents.
+    pub table: TableOptions,
+
+    /// Whether to print the columns’ names on the first line.
+    pub header: bool,
+}
+
+
+pub struct Render<'a> {
+    pub dir: Option<&'a Dir>,
+    pub files: Vec<File<'a>>,
+    pub opts: &'a Options,
+
+    /// How many directories down from the arguments the files are, for the
+    /// context column.
+    pub depth: usize,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(&self, w: &mut W) -> IOResult<()> {
+        let colours = Colours::plain();
+        let mut table = Table::new(&self.opts.table, self.dir, &colours);
+        table.add_symlink_targets(&self.files);
+        table.set_context_depth(self.depth);
+        table.set_size_total(&self.files);
+
+        if self.opts.header {
+            let mut fields = table.header_row().cell_strings();
+            fields.push("Name".into());
+            writeln!(w, "{}", fields.join("\t"))?;
+        }
+
+        for file in &self.files {
+            let (xattrs, acl) = match file.path.attributes() {
+                Ok(attrs)  => (attrs.iter().any(|a| !acl::is_acl_attribute(a)), acl::has_extended_acl(&attrs)),
+                Err(_)     => (false, false),
+            };
+
+            let mut fields: Vec<String> = table.row_for_file(file, xattrs, acl).cell_strings()
+                                               .iter().map(|f| escape(f)).collect();
+            fields.push(escape(&file.name));
+            writeln!(w, "{}", fields.join("\t"))?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Escapes the characters that would stop a field from being read back in:
+/// control characters are written the same way the other views show them,
+/// such as `\t` or `\u{1b}`, and backslashes get doubled.
+fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+
+    for c in field.chars() {
+        if c == '\\' {
+            escaped.push_str("\\\\");
+        }
+        else if c < (0x20 as char) {
+            escaped.extend(c.escape_default());
+        }
+        else {
+            escaped.push(c);
+        }
+    }
+
+    escaped
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::escape;
+
+    #[test]
+    fn plain() {
+        assert_eq!(escape("pâté.txt"), "pâté.txt");
+    }
+
+    #[test]
+    fn tabs_and_newlines() {
+        assert_eq!(escape("a\tb\nc"), "a\\tb\\nc");
+    }
+
+    #[test]
+    fn other_controls() {
+        assert_eq!(escape("[\x1b]"), "[\\u{1b}]");
+    }
+
+    #[test]
+    fn backslashes() {
+        assert_eq!(escape("a\\tb"), "a\\\\tb");
+    }
+}
@@ -0,0 +1,54 @@
+//! Scratch directories for tests that need real files to look at.
+//!
+//! Each one gets a name that no other scratch directory has, in this
+//! process or any other exa test run on the same machine, and gets deleted
+//! when it goes out of scope -- even when a failed assertion unwinds past
+//! it -- so tests can neither trip over each other nor leave files behind.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+use libc;
+
+
+/// How many scratch directories this process has made, which keeps their
+/// names apart when two tests use the same name.
+static COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+
+/// A directory under the temporary directory, deleted along with
+/// everything in it when it’s dropped.
+pub struct ScratchDir {
+
+    /// The full path to the directory.
+    pub path: PathBuf,
+}
+
+impl ScratchDir {
+
+    /// Creates a new, empty scratch directory. The name only needs to say
+    /// what the directory is for: the process ID and a count get added to
+    /// it to keep it apart from any others.
+    pub fn new(name: &str) -> ScratchDir {
+        let pid = unsafe { libc::getpid() };
+        let count = COUNT.fetch_add(1, Ordering::SeqCst);
+        let path = env::temp_dir().join(format!("exa-{}-{}-{}", pid, count, name));
+
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir(&path).unwrap();
+        ScratchDir { path }
+    }
+
+    /// Appends a path onto this directory’s path.
+    pub fn join<P: AsRef<Path>>(&self, child: P) -> PathBuf {
+        self.path.join(child)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,107 @@
+//! Timing how long each stage of listing files takes, for `--timings`.
+//!
+//! The stages happen all over the place -- directories get read while
+//! rendering a tree, and on several threads at once -- so rather than pass
+//! a timer around, the totals are kept in global counters. They’re only
+//! touched once timing has been switched on, so a normal run pays for no
+//! more than checking a flag.
+//!
+//! Because of the threads, and because some stages happen during others,
+//! the totals are the time spent in each stage, and can add up to more
+//! than the time exa took to run.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering, ATOMIC_BOOL_INIT, ATOMIC_USIZE_INIT};
+use std::time::{Duration, Instant};
+
+
+/// One of the stages of listing files that gets timed.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Stage {
+
+    /// Reading the names of the files in directories.
+    ReadDir,
+
+    /// Getting each file’s metadata.
+    Stat,
+
+    /// Scanning the Git repositories that directories are in.
+    GitScan,
+
+    /// Sorting lists of files.
+    Sort,
+
+    /// Turning lists of files into output.
+    Render,
+}
+
+/// Every stage, in the order they get reported in.
+const STAGES: &[Stage] = &[ Stage::ReadDir, Stage::Stat, Stage::GitScan, Stage::Sort, Stage::Render ];
+
+impl Stage {
+    fn label(&self) -> &'static str {
+        match *self {
+            Stage::ReadDir  => "read directories",
+            Stage::Stat     => "stat files",
+            Stage::GitScan  => "scan Git repositories",
+            Stage::Sort     => "sort files",
+            Stage::Render   => "render output",
+        }
+    }
+
+    fn total(&self) -> &'static AtomicUsize {
+        match *self {
+            Stage::ReadDir  => &READ_DIR,
+            Stage::Stat     => &STAT,
+            Stage::GitScan  => &GIT_SCAN,
+            Stage::Sort     => &SORT,
+            Stage::Render   => &RENDER,
+        }
+    }
+}
+
+
+static ENABLED: AtomicBool = ATOMIC_BOOL_INIT;
+
+// The total number of microseconds spent in each stage.
+static READ_DIR: AtomicUsize = ATOMIC_USIZE_INIT;
+static STAT:     AtomicUsize = ATOMIC_USIZE_INIT;
+static GIT_SCAN: AtomicUsize = ATOMIC_USIZE_INIT;
+static SORT:     AtomicUsize = ATOMIC_USIZE_INIT;
+static RENDER:   AtomicUsize = ATOMIC_USIZE_INIT;
+
+
+/// Switches timing on for the rest of the run.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Runs the given function, adding how long it took to the total for the
+/// given stage if timing is switched on.
+pub fn time<T, F: FnOnce() -> T>(stage: Stage, function: F) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return function();
+    }
+
+    let start = Instant::now();
+    let result = function();
+    let _ = stage.total().fetch_add(micros(start.elapsed()), Ordering::Relaxed);
+    result
+}
+
+/// Describes the time spent in each stage so far, one per line, such as
+/// `sort files: 1.234ms`.
+pub fn report() -> String {
+    let mut report = String::from("timings:");
+
+    for stage in STAGES {
+        let total = stage.total().load(Ordering::Relaxed);
+        report.push_str(&format!("\n  {}: {}.{:03}ms", stage.label(), total / 1000, total % 1000));
+    }
+
+    report
+}
+
+fn micros(duration: Duration) -> usize {
+    duration.as_secs() as usize * 1_000_000 + duration.subsec_nanos() as usize / 1000
+}
+
@@ -36,6 +36,10 @@ $exa $testcases/attributes/dirs/no-xattrs_empty -lh | diff -q - $results/empty
 
 $exa --color-scale         $testcases/files -l | diff -q - $results/files_l_scale  || exit 1
 
+# TSV view tests
+$exa $testcases/files --tsv                                   | diff -q - $results/files_tsv       || exit 1
+$exa $testcases/file-names --tsv --header --columns=size,name | diff -q - $results/file_names_tsv  || exit 1
+
 
 # Grid view tests
 COLUMNS=40  $exa $testcases/files | diff -q - $results/files_40   || exit 1
@@ -156,6 +160,11 @@ COLUMNS=80 $exa $testcases/links     2>&1 | diff -q - $results/links         ||
 # symlink file was specified on the command-line directly.
 $exa $testcases/links/* -1 | diff -q - $results/links_1_files || exit 1
 
+# Dereferencing links given as arguments
+$exa $testcases/links/current_dir -1                            2>&1 | diff -q - $results/links_arg_link  || exit 1
+$exa $testcases/links/current_dir -1 --dereference-command-line 2>&1 | diff -q - $results/links_1         || exit 1
+$exa $testcases/links/broken $testcases/links/some_file -1 --dereference-command-line 2>&1 | diff -q - $results/links_arg_broken  || exit 1
+
 
 # Colours and terminals
 # Just because COLUMNS is present, doesn’t mean output is to a terminal
@@ -168,6 +177,9 @@ COLUMNS=80 $exa_binary --colour=automatic $testcases/files -l | diff -q - $resul
 # Git
 $exa $testcases/git/additions -l --git 2>&1 | diff -q - $results/git_additions  || exit 1
 $exa $testcases/git/edits     -l --git 2>&1 | diff -q - $results/git_edits      || exit 1
+$exa $testcases/git-worktree/edits -l --git 2>&1 | diff -q - $results/git_worktree  || exit 1
+$exa $testcases/git-worktree/edits -1 --changed-only  2>&1 | diff -q - $results/git_changed_only      || exit 1
+$exa $testcases/git-worktree       -1 --changed-only  2>&1 | diff -q - $results/git_changed_only_dirs || exit 1
 
 
 # Hidden files